@@ -0,0 +1,1270 @@
+//! Analyses of the relations in a description and how they depend on each other
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::hash_map::Entry;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use {Clause, Constant, Description, Diagnostic, Distinct, Function, Literal, Proposition, Relation,
+     Span, Term, TextEdit, Variable};
+use Clause::{RuleClause, SentenceClause, UnknownClause};
+use Literal::{DistinctLit, NotLit, OrLit, PropLit, RelLit};
+use Literal::KnowsLit;
+use Sentence::{PropSentence, RelSentence};
+use Term::{VarTerm, FuncTerm, ConstTerm};
+use canonical::canonical_variables;
+use dialect::atoms;
+use dialect::KNOWS;
+use lint::edit_distance;
+use visitor::{self, Visit};
+
+/// A dependency of one relation on another, because a rule with `from` as its head has `to` in its
+/// body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub from: String,
+    pub to: String,
+
+    /// True if `to` is under an odd number of `not`s
+    pub negative: bool,
+
+    /// The span of the first literal in the body that makes the dependency
+    pub span: Span
+}
+
+/// The dependency graph of a description. There's a node for each relation, and an edge from the
+/// head of each rule to each relation in its body, labeled with whether it's negated. For example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::analysis::DependencyGraph;
+///
+/// let graph = DependencyGraph::new(&parse("(<= p (q ?x) (not (r ?x))) (<= (q ?x) (s ?x))"));
+/// let successors = graph.successors("p");
+/// assert_eq!((&*successors[1].to, successors[1].negative), ("r", true));
+/// assert_eq!(graph.topological_order(), ["s", "q", "r", "p"]);
+/// ```
+///
+/// Under GDL-III, `knows` literals are dependencies on a `knows` relation.
+#[derive(Debug, Clone)]
+pub struct DependencyGraph {
+    relations: Vec<String>,
+    dependencies: Vec<Dependency>,
+
+    // The indexes in `dependencies` of each relation's outgoing and incoming edges
+    successors: HashMap<String, Vec<usize>>,
+    predecessors: HashMap<String, Vec<usize>>,
+
+    // The strongly connected components. Every edge between two components goes to one that's
+    // earlier in this list.
+    components: Vec<Vec<String>>,
+    component: HashMap<String, usize>
+}
+
+impl DependencyGraph {
+    pub fn new(desc: &Description) -> DependencyGraph {
+        let mut graph = DependencyGraph {
+            relations: Vec::new(),
+            dependencies: Vec::new(),
+            successors: HashMap::new(),
+            predecessors: HashMap::new(),
+            components: Vec::new(),
+            component: HashMap::new()
+        };
+        let mut seen = HashSet::new();
+        for c in desc.clauses.iter() {
+            let (head, body): (_, &[Literal]) = match c {
+                &RuleClause(ref r) => (&r.head, &r.body),
+                &SentenceClause(ref s) => (s, &[]),
+                &UnknownClause(_) => continue
+            };
+            let head = head.name().name.clone();
+            graph.add_relation(&head);
+            let mut lits = Vec::new();
+            for l in body.iter() {
+                atoms(l, false, &mut lits);
+            }
+            for (l, negative) in lits {
+                let (name, span) = match l {
+                    &PropLit(ref p) => (p.name.name.clone(), p.span),
+                    &RelLit(ref r) => (r.name.name.clone(), r.span),
+                    &KnowsLit(ref k) => (KNOWS.to_string(), k.span),
+                    _ => continue
+                };
+                graph.add_relation(&name);
+                if seen.insert((head.clone(), name.clone(), negative)) {
+                    let i = graph.dependencies.len();
+                    graph.successors.get_mut(&head).unwrap().push(i);
+                    graph.predecessors.get_mut(&name).unwrap().push(i);
+                    graph.dependencies.push(Dependency { from: head.clone(), to: name,
+                                                         negative: negative, span: span });
+                }
+            }
+        }
+        graph.find_components();
+        graph
+    }
+
+    fn add_relation(&mut self, name: &str) {
+        if !self.successors.contains_key(name) {
+            self.relations.push(name.to_string());
+            self.successors.insert(name.to_string(), Vec::new());
+            self.predecessors.insert(name.to_string(), Vec::new());
+        }
+    }
+
+    /// Returns the relations, in the order they first appear in the description
+    pub fn relations(&self) -> &[String] {
+        &self.relations
+    }
+
+    /// Returns every edge, in the order they first appear in the description. A relation that's
+    /// used both with and without `not` in the same rule has an edge for each.
+    pub fn dependencies(&self) -> &[Dependency] {
+        &self.dependencies
+    }
+
+    /// Returns the edges from `name` to the relations it depends on, which is empty if `name`
+    /// isn't in the graph
+    pub fn successors(&self, name: &str) -> Vec<&Dependency> {
+        self.edges(&self.successors, name)
+    }
+
+    /// Returns the edges to `name` from the relations that depend on it, which is empty if `name`
+    /// isn't in the graph
+    pub fn predecessors(&self, name: &str) -> Vec<&Dependency> {
+        self.edges(&self.predecessors, name)
+    }
+
+    fn edges(&self, edges: &HashMap<String, Vec<usize>>, name: &str) -> Vec<&Dependency> {
+        edges.get(name).map_or(vec![], |e| e.iter().map(|&i| &self.dependencies[i]).collect())
+    }
+
+    /// Returns the relations ordered so that each one comes after the relations it depends on,
+    /// which is the order they can be evaluated in. Relations that depend on each other
+    /// recursively are next to each other, in the order they first appear.
+    pub fn topological_order(&self) -> Vec<&str> {
+        self.components.iter().flat_map(|c| c.iter().map(|name| &name[..])).collect()
+    }
+
+    /// Returns the strongly connected components of the graph, found with Tarjan's algorithm.
+    /// Each component is a set of relations that all depend on each other, or a single relation.
+    /// They're in topological order, so a component only depends on itself and components before
+    /// it.
+    pub fn components(&self) -> &[Vec<String>] {
+        &self.components
+    }
+
+    /// Returns the index in `components` of the component containing `name`, or `None` if `name`
+    /// isn't in the graph
+    pub fn component(&self, name: &str) -> Option<usize> {
+        self.component.get(name).cloned()
+    }
+
+    /// Returns true if `name` depends on itself, directly or through other relations
+    pub fn is_recursive(&self, name: &str) -> bool {
+        match self.component.get(name) {
+            Some(&c) => {
+                self.components[c].len() > 1 || self.successors(name).iter().any(|d| d.to == name)
+            }
+            None => false
+        }
+    }
+
+    /// Returns the components whose relations are recursive, in topological order
+    pub fn recursive_groups(&self) -> Vec<&[String]> {
+        self.components.iter().filter(|c| self.is_recursive(&c[0])).map(|c| &c[..]).collect()
+    }
+
+    /// Returns true if `a` and `b` are the same relation in the graph, or if each depends on the
+    /// other, directly or through other relations
+    pub fn mutually_recursive(&self, a: &str, b: &str) -> bool {
+        match (self.component.get(a), self.component.get(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false
+        }
+    }
+
+    /// Returns the shortest path of relations from `from` to `to`, including both, if `from`
+    /// depends on `to`. The path from a relation to itself is just the relation.
+    pub fn path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let mut previous = HashMap::new();
+        let mut queue = vec![from.to_string()];
+        let mut seen = HashSet::new();
+        seen.insert(from.to_string());
+        let mut i = 0;
+        while i < queue.len() {
+            let node = queue[i].clone();
+            i += 1;
+            if node == to {
+                let mut path = vec![node];
+                while let Some(p) = previous.get(path.last().unwrap()) {
+                    path.push(String::clone(p));
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for d in self.successors(&node) {
+                if seen.insert(d.to.clone()) {
+                    previous.insert(d.to.clone(), node.clone());
+                    queue.push(d.to.clone());
+                }
+            }
+        }
+        None
+    }
+
+    // Finds the strongly connected components with Tarjan's algorithm. It's iterative, so long
+    // chains of rules can't overflow the stack.
+    fn find_components(&mut self) {
+        let mut index = HashMap::new();
+        let mut low = HashMap::new();
+        let mut stack = Vec::new();
+        let mut on_stack = HashSet::new();
+        let mut next = 0;
+        for root in self.relations.iter() {
+            if index.contains_key(root) {
+                continue;
+            }
+            // Each frame is a relation and the number of its edges that have been followed
+            let mut frames = vec![(root.clone(), 0)];
+            index.insert(root.clone(), next);
+            low.insert(root.clone(), next);
+            next += 1;
+            stack.push(root.clone());
+            on_stack.insert(root.clone());
+            while let Some((node, i)) = frames.pop() {
+                if i < self.successors[&node].len() {
+                    let succ = self.dependencies[self.successors[&node][i]].to.clone();
+                    frames.push((node.clone(), i + 1));
+                    if !index.contains_key(&succ) {
+                        index.insert(succ.clone(), next);
+                        low.insert(succ.clone(), next);
+                        next += 1;
+                        stack.push(succ.clone());
+                        on_stack.insert(succ.clone());
+                        frames.push((succ, 0));
+                    } else if on_stack.contains(&succ) {
+                        let l = low[&node].min(index[&succ]);
+                        low.insert(node, l);
+                    }
+                    continue;
+                }
+                if let Some(&(ref parent, _)) = frames.last() {
+                    let l = low[parent].min(low[&node]);
+                    low.insert(parent.clone(), l);
+                }
+                if low[&node] == index[&node] {
+                    let id = self.components.len();
+                    let mut members = Vec::new();
+                    loop {
+                        let member = stack.pop().unwrap();
+                        on_stack.remove(&member);
+                        let done = member == node;
+                        self.component.insert(member.clone(), id);
+                        members.push(member);
+                        if done {
+                            break;
+                        }
+                    }
+                    members.sort_by_key(|m| index[m]);
+                    self.components.push(members);
+                }
+            }
+        }
+    }
+}
+
+/// The values that can appear at an argument position
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Domain {
+    pub constants: BTreeSet<Constant>,
+
+    /// The functions that can appear, by name and number of arguments. The domains of their
+    /// arguments are given by `DomainModel::function_domain`.
+    pub functions: BTreeSet<(String, usize)>
+}
+
+impl Domain {
+    // Adds the values in `other`, returning true if there were any new ones
+    fn extend(&mut self, other: &Domain) -> bool {
+        let len = self.constants.len() + self.functions.len();
+        self.constants.extend(other.constants.iter().cloned());
+        self.functions.extend(other.functions.iter().cloned());
+        len != self.constants.len() + self.functions.len()
+    }
+}
+
+// An argument position of a relation or a function, by name, number of arguments, and index
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Slot {
+    Relation(String, usize, usize),
+    Function(String, usize, usize)
+}
+
+/// The domain of every argument position in a description, like the sentence form model of
+/// ggp-base. For example
+///
+/// ```
+/// use gdl_parser::{parse, Constant};
+/// use gdl_parser::analysis::DomainModel;
+///
+/// let desc = parse("(init (cell 1 b)) (<= (next (cell ?x x)) (true (cell ?x b)))");
+/// let domains = DomainModel::new(&desc);
+/// let cell = domains.function_domain("cell", 2, 1).unwrap();
+/// assert_eq!(cell.constants.iter().collect::<Vec<_>>(),
+///            [&Constant::new("b"), &Constant::new("x")]);
+/// ```
+///
+/// A value can appear at a position if it's written there in a fact or rule head, or if it's in
+/// the domain of a position in a positive literal of a rule's body that shares a variable with it.
+/// `true` gets its values from `init` and `next`, and `does` from `legal`. The arguments of a
+/// function have the same domains wherever the function appears, so the model can include values
+/// that never actually appear together, but never leaves out one that can.
+#[derive(Debug, Clone)]
+pub struct DomainModel {
+    domains: HashMap<Slot, Domain>
+}
+
+impl DomainModel {
+    pub fn new(desc: &Description) -> DomainModel {
+        let mut model = DomainModel { domains: HashMap::new() };
+        // The slots each slot's values flow to
+        let mut edges = HashMap::<Slot, Vec<Slot>>::new();
+        for c in desc.clauses.iter() {
+            let (head, body): (_, &[Literal]) = match c {
+                &RuleClause(ref r) => (&r.head, &r.body),
+                &SentenceClause(ref s) => (s, &[]),
+                &UnknownClause(_) => continue
+            };
+            let mut lits = Vec::new();
+            for l in body.iter() {
+                atoms(l, false, &mut lits);
+            }
+            // The slots in the body that bind each variable
+            let mut bindings = HashMap::new();
+            for (l, negated) in lits {
+                if let &RelLit(ref r) = l {
+                    let mut unused = HashMap::new();
+                    let bindings = if negated { &mut unused } else { &mut bindings };
+                    for (i, arg) in r.args.iter().enumerate() {
+                        let slot = Slot::Relation(r.name.name.clone(), r.args.len(), i);
+                        model.bind(slot, arg, bindings);
+                    }
+                }
+            }
+            if let &RelSentence(ref r) = head {
+                for (i, arg) in r.args.iter().enumerate() {
+                    let slot = Slot::Relation(r.name.name.clone(), r.args.len(), i);
+                    model.add(slot, arg, &bindings, &mut edges);
+                }
+            }
+        }
+
+        let flows = [("init", "true", 1), ("next", "true", 1), ("legal", "does", 2)];
+        for &(from, to, arity) in flows.iter() {
+            for i in 0..arity {
+                let (from, to) = (Slot::Relation(from.to_string(), arity, i),
+                                  Slot::Relation(to.to_string(), arity, i));
+                model.domains.entry(from.clone()).or_insert_with(Domain::default);
+                model.domains.entry(to.clone()).or_insert_with(Domain::default);
+                edges.entry(from).or_insert_with(Vec::new).push(to);
+            }
+        }
+
+        let mut queue = model.domains.keys().cloned().collect::<Vec<_>>();
+        while let Some(from) = queue.pop() {
+            let domain = model.domains[&from].clone();
+            for to in edges.get(&from).into_iter().flat_map(|e| e.iter()) {
+                if model.domains.get_mut(to).unwrap().extend(&domain) {
+                    queue.push(to.clone());
+                }
+            }
+        }
+        model
+    }
+
+    // Records the slots that the variables in `term`, which is at `slot` in a body literal, are
+    // bound by
+    fn bind(&mut self, slot: Slot, term: &Term, bindings: &mut HashMap<String, Vec<Slot>>) {
+        self.domains.entry(slot.clone()).or_insert_with(Domain::default);
+        match term {
+            &VarTerm(ref v) => bindings.entry(v.name.name.clone()).or_insert_with(Vec::new)
+                                       .push(slot),
+            &FuncTerm(ref f) => {
+                for (i, arg) in f.args.iter().enumerate() {
+                    self.bind(Slot::Function(f.name.name.clone(), f.args.len(), i), arg, bindings);
+                }
+            }
+            &ConstTerm(_) => {}
+        }
+    }
+
+    // Adds the values of `term`, which is at `slot` in a rule head, to the slot's domain
+    fn add(&mut self, slot: Slot, term: &Term, bindings: &HashMap<String, Vec<Slot>>,
+           edges: &mut HashMap<Slot, Vec<Slot>>) {
+        let domain = self.domains.entry(slot.clone()).or_insert_with(Domain::default);
+        match term {
+            &VarTerm(ref v) => {
+                for from in bindings.get(&v.name.name).into_iter().flat_map(|b| b.iter()) {
+                    edges.entry(from.clone()).or_insert_with(Vec::new).push(slot.clone());
+                }
+            }
+            &FuncTerm(ref f) => {
+                domain.functions.insert((f.name.name.clone(), f.args.len()));
+                for (i, arg) in f.args.iter().enumerate() {
+                    let slot = Slot::Function(f.name.name.clone(), f.args.len(), i);
+                    self.add(slot, arg, bindings, edges);
+                }
+            }
+            &ConstTerm(ref c) => {
+                domain.constants.insert(Constant::new(c.name.clone()));
+            }
+        }
+    }
+
+    /// Returns the domain of argument `index` of the relation `name` with `arity` arguments, or
+    /// `None` if the relation is never used with that many arguments
+    pub fn domain_of(&self, name: &str, arity: usize, index: usize) -> Option<&Domain> {
+        self.domains.get(&Slot::Relation(name.to_string(), arity, index))
+    }
+
+    /// Returns the domain of argument `index` of the function `name` with `arity` arguments, or
+    /// `None` if the function is never used with that many arguments
+    pub fn function_domain(&self, name: &str, arity: usize, index: usize) -> Option<&Domain> {
+        self.domains.get(&Slot::Function(name.to_string(), arity, index))
+    }
+}
+
+/// Whether a symbol is the name of relations or of functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SymbolKind {
+    Relation,
+    Function
+}
+
+/// A relation or function, told apart from others with the same name by its number of arguments.
+/// Constants in terms are functions without arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol {
+    pub name: String,
+    pub arity: usize,
+    pub kind: SymbolKind
+}
+
+impl Symbol {
+    pub fn new<T: Into<String>>(name: T, arity: usize, kind: SymbolKind) -> Symbol {
+        Symbol { name: name.into(), arity: arity, kind: kind }
+    }
+}
+
+/// A place where the name of a symbol appears
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    /// The span of the name
+    pub span: Span,
+
+    /// The index of the clause the name is in
+    pub clause: usize,
+
+    /// True if the name is in the head of the clause, so the clause defines the relation, or
+    /// builds terms with the function
+    pub definition: bool
+}
+
+/// Every relation and function in a description, along with where each one is defined and used,
+/// for features like go-to-definition and find-references in editors
+#[derive(Debug, Clone)]
+pub struct SymbolTable {
+    symbols: Vec<Symbol>,
+    references: HashMap<Symbol, Vec<Reference>>
+}
+
+impl SymbolTable {
+    /// Constructs the symbol table of `desc`. Clauses that couldn't be parsed are skipped.
+    pub fn new(desc: &Description) -> SymbolTable {
+        let mut references = HashMap::new();
+        for (i, c) in desc.clauses.iter().enumerate() {
+            let (head, body): (_, &[Literal]) = match c {
+                &RuleClause(ref r) => (&r.head, &r.body),
+                &SentenceClause(ref s) => (s, &[]),
+                &UnknownClause(_) => continue
+            };
+            let mut names = Names { found: Vec::new() };
+            visitor::walk_sentence(head, &mut names);
+            let defined = names.found.len();
+            for l in body.iter() {
+                visitor::walk_literal(l, &mut names);
+            }
+            for (j, (symbol, span)) in names.found.into_iter().enumerate() {
+                let reference = Reference { span: span, clause: i, definition: j < defined };
+                references.entry(symbol).or_insert_with(Vec::new).push(reference);
+            }
+        }
+        for refs in references.values_mut() {
+            refs.sort_by_key(|r: &Reference| (r.clause, r.span.lo));
+        }
+        let mut symbols = references.keys().cloned().collect::<Vec<_>>();
+        symbols.sort_by_key(|s| {
+            let first = &references[s][0];
+            (first.clause, first.span.lo)
+        });
+        SymbolTable { symbols: symbols, references: references }
+    }
+
+    /// Returns the symbols in the order they first appear
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Returns every place `symbol` appears, in source order
+    pub fn references(&self, symbol: &Symbol) -> &[Reference] {
+        self.references.get(symbol).map_or(&[], |refs| &refs[..])
+    }
+
+    /// Returns the places `symbol` appears in the head of a clause
+    pub fn definitions(&self, symbol: &Symbol) -> Vec<&Reference> {
+        self.references(symbol).iter().filter(|r| r.definition).collect()
+    }
+
+    /// Returns the places `symbol` appears in the body of a rule
+    pub fn usages(&self, symbol: &Symbol) -> Vec<&Reference> {
+        self.references(symbol).iter().filter(|r| !r.definition).collect()
+    }
+
+    /// Returns the indices of the clauses that define `symbol`, without duplicates
+    pub fn defining_clauses(&self, symbol: &Symbol) -> Vec<usize> {
+        let mut clauses = self.definitions(symbol).into_iter().map(|r| r.clause)
+                              .collect::<Vec<_>>();
+        clauses.dedup();
+        clauses
+    }
+
+    /// Returns the symbol whose name covers the byte at `offset`, along with that reference to it
+    pub fn symbol_at(&self, offset: usize) -> Option<(&Symbol, &Reference)> {
+        self.symbols.iter().filter_map(|s| {
+            self.references(s).iter().find(|r| r.span.lo <= offset && offset < r.span.hi)
+                .map(|r| (s, r))
+        }).next()
+    }
+}
+
+// Collects the names of relations and functions, with their spans
+struct Names {
+    found: Vec<(Symbol, Span)>
+}
+
+impl Visit for Names {
+    fn visit_proposition(&mut self, p: &Proposition) {
+        self.found.push((Symbol::new(&p.name.name[..], 0, SymbolKind::Relation), p.name.span));
+    }
+
+    fn visit_relation(&mut self, r: &Relation) {
+        let symbol = Symbol::new(&r.name.name[..], r.args.len(), SymbolKind::Relation);
+        self.found.push((symbol, r.name.span));
+    }
+
+    fn visit_function(&mut self, f: &Function) {
+        let symbol = Symbol::new(&f.name.name[..], f.args.len(), SymbolKind::Function);
+        self.found.push((symbol, f.name.span));
+    }
+
+    fn visit_term(&mut self, t: &Term) {
+        if let &ConstTerm(ref c) = t {
+            self.found.push((Symbol::new(&c.name[..], 0, SymbolKind::Function), c.span));
+        }
+    }
+}
+
+/// A rule that can build ever larger terms, by wrapping a function around values of a relation
+/// that depends on the rule itself. Grounding a game with one of these might never finish.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnboundedRule {
+    /// The relation the rule defines
+    pub relation: String,
+
+    /// The function that's nested deeper each time the rule is applied, with its number of
+    /// arguments
+    pub function: (String, usize),
+
+    /// The variable inside the function, which is only bound by recursive literals
+    pub variable: String,
+
+    /// The span of the function in the rule's head
+    pub span: Span,
+
+    /// The span of the rule
+    pub rule: Span
+}
+
+impl Display for UnboundedRule {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "the rule for `{}` at byte {} can nest `{}` without limit, since `?{}` is only \
+                   bound by literals that depend on the rule", self.relation, self.rule.lo,
+               self.function.0, self.variable)
+    }
+}
+
+/// Returns the rules that make the domains of `desc` infinite, in source order. A rule is
+/// unbounded if its head has a variable nested in more functions than in any positive literal of
+/// the same relation in its body with the variable in the same argument, and all of the literals
+/// that bind the variable are recursive with the head, like
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::analysis::unbounded_rules;
+///
+/// let desc = parse("(num 0) (<= (num (s ?x)) (num ?x))");
+/// assert_eq!(unbounded_rules(&desc)[0].function, ("s".to_string(), 1));
+/// ```
+///
+/// `true` counts as the same relation as `next`, and `does` as `legal`, since the state and moves
+/// of one step come from the rules of the last one, so a counter that's incremented every step
+/// without a limit is unbounded too. If the variable is also bound by a literal that isn't
+/// recursive, the rule can only produce finitely many terms, so it isn't reported.
+pub fn unbounded_rules(desc: &Description) -> Vec<UnboundedRule> {
+    let graph = DependencyGraph::new(desc);
+    let mut reachable = HashMap::new();
+    let mut recursive = |a: &str, b: &str| {
+        reaches(&graph, &mut reachable, a, b) && reaches(&graph, &mut reachable, b, a)
+    };
+
+    let mut unbounded = Vec::new();
+    for c in desc.clauses.iter() {
+        let r = match c {
+            &RuleClause(ref r) => r,
+            _ => continue
+        };
+        let head = match r.head {
+            RelSentence(ref head) => head,
+            PropSentence(_) => continue
+        };
+        let mut lits = Vec::new();
+        for l in r.body.iter() {
+            atoms(l, false, &mut lits);
+        }
+        // The variables bound by a literal that isn't recursive, and for each variable and
+        // argument the deepest the variable is nested in functions in that argument of a literal
+        // of the head's relation
+        let mut finite = HashSet::new();
+        let mut nested = HashMap::new();
+        for (l, negated) in lits {
+            if let (&RelLit(ref lit), false) = (l, negated) {
+                let rec = recursive(&head.name.name, &lit.name.name);
+                let same = same_relation(&head.name.name, &lit.name.name);
+                for (i, arg) in lit.args.iter().enumerate() {
+                    let mut vars = Vec::new();
+                    nested_variables(arg, 0, None, &mut vars);
+                    for (v, depth, _) in vars {
+                        if !rec {
+                            finite.insert(&v.name.name);
+                        }
+                        if same {
+                            let max = nested.entry((&v.name.name, i)).or_insert(depth);
+                            *max = (*max).max(depth);
+                        }
+                    }
+                }
+            }
+        }
+        let mut vars = Vec::new();
+        for (i, arg) in head.args.iter().enumerate() {
+            let mut arg_vars = Vec::new();
+            nested_variables(arg, 0, None, &mut arg_vars);
+            vars.extend(arg_vars.into_iter().map(|(v, depth, f)| (v, i, depth, f)));
+        }
+        let mut reported = HashSet::new();
+        for (v, i, depth, f) in vars {
+            let deeper = match nested.get(&(&v.name.name, i)) {
+                Some(&max) => depth > max && !finite.contains(&v.name.name),
+                None => false
+            };
+            if deeper && reported.insert(&v.name.name) {
+                let f = f.unwrap();
+                unbounded.push(UnboundedRule {
+                    relation: head.name.name.clone(),
+                    function: (f.name.name.clone(), f.args.len()),
+                    variable: v.name.name.clone(),
+                    span: f.span,
+                    rule: r.span
+                });
+            }
+        }
+    }
+    unbounded
+}
+
+// Adds the variables in `term` to `vars`, with how many functions they're nested in and the
+// function they're directly in
+fn nested_variables<'a>(term: &'a Term, depth: usize, parent: Option<&'a Function>,
+                        vars: &mut Vec<(&'a Variable, usize, Option<&'a Function>)>) {
+    match term {
+        &VarTerm(ref v) => vars.push((v, depth, parent)),
+        &FuncTerm(ref f) => {
+            for arg in f.args.iter() {
+                nested_variables(arg, depth + 1, Some(f), vars);
+            }
+        }
+        &ConstTerm(_) => {}
+    }
+}
+
+// Returns true if a literal of `relation` in the body of a rule for `head` is the same relation,
+// counting `true` as `next` and `does` as `legal`
+fn same_relation(head: &str, relation: &str) -> bool {
+    match (head, relation) {
+        ("next", "true") | ("legal", "does") => true,
+        _ => head == relation
+    }
+}
+
+// Returns true if `from` depends on `to`, directly or through other relations, counting `true` as
+// depending on `next` and `does` as depending on `legal`. `reachable` caches the relations each
+// relation depends on.
+fn reaches(graph: &DependencyGraph, reachable: &mut HashMap<String, HashSet<String>>, from: &str,
+           to: &str) -> bool {
+    if !reachable.contains_key(from) {
+        let mut seen = HashSet::new();
+        let mut stack = vec![from.to_string()];
+        while let Some(name) = stack.pop() {
+            let mut next = graph.successors(&name).into_iter().map(|d| d.to.clone())
+                                .collect::<Vec<_>>();
+            match &name[..] {
+                "true" => next.push("next".to_string()),
+                "does" => next.push("legal".to_string()),
+                _ => {}
+            }
+            for n in next {
+                if seen.insert(n.clone()) {
+                    stack.push(n);
+                }
+            }
+        }
+        reachable.insert(from.to_string(), seen);
+    }
+    reachable[from].contains(to)
+}
+
+/// The relations of a description split into strata. Each relation depends only on relations in
+/// its own stratum or earlier ones, and through `not` only on relations in earlier ones, so the
+/// strata can be evaluated in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stratification {
+    strata: Vec<Vec<String>>,
+    stratum: HashMap<String, usize>
+}
+
+impl Stratification {
+    /// Returns the relations in each stratum, lowest first. The relations in a stratum are in the
+    /// order they first appear in the description.
+    pub fn strata(&self) -> &[Vec<String>] {
+        &self.strata
+    }
+
+    /// Returns the stratum of the relation `name`, or `None` if it isn't in the description
+    pub fn stratum(&self, name: &str) -> Option<usize> {
+        self.stratum.get(name).cloned()
+    }
+}
+
+/// A cycle of dependencies between relations that goes through `not`, which makes a description
+/// invalid GDL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegationCycle {
+    /// The relations in the cycle. The first depends on the negation of the second, each of the
+    /// others depends on the next, and the last depends on the first. A relation that depends on
+    /// its own negation is a cycle of one.
+    pub relations: Vec<String>,
+
+    /// The span of the negated literal
+    pub span: Span
+}
+
+impl Display for NegationCycle {
+    /// Formats the cycle like `p -> not q -> p`
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "cycle through negation at byte {}: {}", self.span.lo, self.relations[0])?;
+        for (i, name) in self.relations.iter().chain(Some(&self.relations[0])).enumerate().skip(1) {
+            write!(f, " -> {}{}", if i == 1 { "not " } else { "" }, name)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for NegationCycle {
+    fn description(&self) -> &str {
+        "cycle through negation"
+    }
+}
+
+/// Splits the relations of `desc` into strata, or returns the first cycle through negation in
+/// source order if there is one. For example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::analysis::stratify;
+///
+/// let desc = parse("(<= p (not q)) (<= q r)");
+/// let strata = stratify(&desc).unwrap();
+/// assert_eq!((strata.stratum("p"), strata.stratum("q")), (Some(1), Some(0)));
+///
+/// let desc = parse("(<= p (not q)) (<= q p)");
+/// assert_eq!(stratify(&desc).unwrap_err().to_string(),
+///            "cycle through negation at byte 11: p -> not q -> p");
+/// ```
+pub fn stratify(desc: &Description) -> Result<Stratification, NegationCycle> {
+    let graph = DependencyGraph::new(desc);
+    for d in graph.dependencies.iter() {
+        if d.negative && graph.mutually_recursive(&d.from, &d.to) {
+            let mut relations = graph.path(&d.to, &d.from).unwrap();
+            relations.pop();
+            relations.insert(0, d.from.clone());
+            if d.from == d.to {
+                relations.truncate(1);
+            }
+            return Err(NegationCycle { relations: relations, span: d.span });
+        }
+    }
+
+    // Every edge out of a component goes to an earlier one, so the components' strata can be
+    // found in order
+    let mut component_strata = Vec::<usize>::with_capacity(graph.components.len());
+    for (id, members) in graph.components.iter().enumerate() {
+        let mut stratum = 0;
+        for name in members.iter() {
+            for d in graph.successors(name) {
+                let other = graph.component[&d.to];
+                if other != id {
+                    stratum = stratum.max(component_strata[other] + d.negative as usize);
+                }
+            }
+        }
+        component_strata.push(stratum);
+    }
+
+    let mut strata = Vec::new();
+    let mut stratum = HashMap::new();
+    for name in graph.relations.iter() {
+        let s = component_strata[graph.component[name]];
+        while strata.len() <= s {
+            strata.push(Vec::new());
+        }
+        strata[s].push(name.clone());
+        stratum.insert(name.clone(), s);
+    }
+    Ok(Stratification { strata: strata, stratum: stratum })
+}
+
+/// Returns warnings for literals in rule bodies that refer to something that's never defined,
+/// which is usually a typo. These are:
+///
+/// * A relation that isn't the head of any rule or fact, with the same number of arguments
+/// * A `true` literal for a fluent that isn't the argument of any `init`, `next`, or `base`
+/// * A `does` literal for a move that isn't the second argument of any `legal` or `input`
+///
+/// Each warning has the code `undefined-relation`. If something that is defined has a similar
+/// name, the warning suggests it and has a fix that renames the literal. For example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::analysis::undefined_relations;
+///
+/// let desc = parse("(init (cell 1 1 b)) (<= open (true (cel 1 1 b)))");
+/// assert_eq!(undefined_relations(&desc)[0].message,
+///            "fluent `cel/3` is never defined by `init`, `next`, or `base`; did you mean \
+///             `cell`?");
+/// ```
+///
+/// Fluents aren't checked if an `init`, `next`, or `base` has a variable as its argument, since
+/// it could be anything. The same goes for moves.
+pub fn undefined_relations(desc: &Description) -> Vec<Diagnostic> {
+    let mut relations = Defined::new();
+    let mut fluents = Defined::new();
+    let mut moves = Defined::new();
+    for c in desc.clauses.iter() {
+        let head = match c {
+            &RuleClause(ref r) => &r.head,
+            &SentenceClause(ref s) => s,
+            &UnknownClause(_) => continue
+        };
+        match head {
+            &PropSentence(ref p) => relations.add(Some(&p.name), 0),
+            &RelSentence(ref r) => {
+                relations.add(Some(&r.name), r.args.len());
+                match (&*r.name.name, r.args.len()) {
+                    ("init", 1) | ("next", 1) | ("base", 1) => fluents.add_term(&r.args[0]),
+                    ("legal", 2) | ("input", 2) => moves.add_term(&r.args[1]),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let mut diagnostics = Vec::new();
+    for c in desc.clauses.iter() {
+        let body = match c {
+            &RuleClause(ref r) => &r.body,
+            _ => continue
+        };
+        let mut lits = Vec::new();
+        for l in body.iter() {
+            atoms(l, false, &mut lits);
+        }
+        for (l, _) in lits {
+            let r = match l {
+                &PropLit(ref p) => {
+                    relations.check("relation", &p.name, 0, p.span, "any rule or fact",
+                                    &mut diagnostics);
+                    continue;
+                }
+                &RelLit(ref r) => r,
+                _ => continue
+            };
+            match (&*r.name.name, r.args.len()) {
+                ("true", 1) => {
+                    fluents.check_term("fluent", &r.args[0], "`init`, `next`, or `base`",
+                                       &mut diagnostics)
+                }
+                ("does", 2) => {
+                    moves.check_term("move", &r.args[1], "`legal` or `input`", &mut diagnostics)
+                }
+                (_, arity) => {
+                    relations.check("relation", &r.name, arity, r.span, "any rule or fact",
+                                    &mut diagnostics)
+                }
+            }
+        }
+    }
+    diagnostics
+}
+
+/// Returns a warning for every use of a relation or function that's also used with a different
+/// number of arguments somewhere else, like `(cell 1 1)` and `(cell 1 1 b)`, in source order.
+/// Each warning has the code `inconsistent-arity`. Relations and functions are checked
+/// separately, and a constant counts as a function with no arguments.
+pub fn inconsistent_arities(desc: &Description) -> Vec<Diagnostic> {
+    let mut uses = Uses { relations: Vec::new(), functions: Vec::new() };
+    visitor::walk(desc, &mut uses);
+
+    let mut diagnostics = Vec::new();
+    for &(kind, ref uses) in [("relation", &uses.relations), ("function", &uses.functions)].iter() {
+        let mut arities = HashMap::new();
+        for &(ref name, arity, _) in uses.iter() {
+            let a = arities.entry(name).or_insert_with(Vec::new);
+            if !a.contains(&arity) {
+                a.push(arity);
+            }
+        }
+        for &(ref name, arity, span) in uses.iter() {
+            let others = arities[name].iter().filter(|&&a| a != arity).map(|a| a.to_string())
+                                      .collect::<Vec<_>>();
+            if others.is_empty() {
+                continue;
+            }
+            let message = format!("{} `{}` has {} arguments here but {} elsewhere", kind, name,
+                                  arity, others.join(" or "));
+            diagnostics.push(Diagnostic::warning("inconsistent-arity", span, message));
+        }
+    }
+    diagnostics.sort_by_key(|d| d.span.lo);
+    diagnostics
+}
+
+/// The relations that make up a game. Everything else only matters if one of these depends on it.
+const GAME_RELATIONS: &'static [&'static str] = &["role", "init", "next", "legal", "goal",
+                                                  "terminal", "sees", "base", "input"];
+
+/// Returns the relations that `role`, `init`, `next`, `legal`, `goal`, or `terminal` depend on,
+/// including themselves, in the order they first appear. The GDL-II `sees` relation and the `base`
+/// and `input` relations count too.
+pub fn reachable_relations(desc: &Description) -> Vec<String> {
+    let graph = DependencyGraph::new(desc);
+    let mut reachable = HashSet::new();
+    let mut stack = graph.relations.iter().filter(|n| GAME_RELATIONS.contains(&&n[..]))
+                                          .collect::<Vec<_>>();
+    while let Some(name) = stack.pop() {
+        if reachable.insert(name) {
+            stack.extend(graph.successors(name).into_iter().map(|d| &d.to));
+        }
+    }
+    graph.relations.iter().filter(|n| reachable.contains(n)).cloned().collect()
+}
+
+/// Returns the relations that don't depend on `true` or `does`, directly or through other
+/// relations, in the order they first appear. They're the same in every state of the game, like
+/// `succ` or `adjacent`. Under GDL-III, relations that depend on `knows` aren't static either.
+pub fn static_relations(desc: &Description) -> Vec<String> {
+    let graph = DependencyGraph::new(desc);
+    #[cfg(feature = "gdl-iii")]
+    let dynamic_roots = ["true", "does", KNOWS];
+    #[cfg(not(feature = "gdl-iii"))]
+    let dynamic_roots = ["true", "does"];
+    let mut dynamic = HashSet::new();
+    let mut stack = dynamic_roots.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    while let Some(name) = stack.pop() {
+        if !dynamic.contains(&name) {
+            stack.extend(graph.predecessors(&name).into_iter().map(|d| d.from.clone()));
+            dynamic.insert(name);
+        }
+    }
+    graph.relations.iter().filter(|n| !dynamic.contains(*n)).cloned().collect()
+}
+
+/// Returns a warning for every rule or fact that defines a relation no relation in
+/// `reachable_relations` depends on, so it can't affect the game and can be removed. Each warning
+/// has the code `unused-rule`.
+pub fn unused_rules(desc: &Description) -> Vec<Diagnostic> {
+    let reachable = reachable_relations(desc).into_iter().collect::<HashSet<_>>();
+    let mut diagnostics = Vec::new();
+    for c in desc.clauses.iter() {
+        let head = match c {
+            &RuleClause(ref r) => &r.head,
+            &SentenceClause(ref s) => s,
+            &UnknownClause(_) => continue
+        };
+        let name = &head.name().name;
+        if !reachable.contains(name) {
+            let message = format!("`{}` is never used by the game's rules, so this can't affect \
+                                   play", name);
+            diagnostics.push(Diagnostic::warning("unused-rule", c.span(), message));
+        }
+    }
+    diagnostics
+}
+
+/// Returns a warning for every clause that's the same as an earlier one, or the same up to the
+/// names of its variables. Duplicates don't change the game, but
+/// they make every clause that uses them be grounded and evaluated again. Each warning has the
+/// code `duplicate-clause` and a fix that removes the clause. `transform::dedup_clauses` removes
+/// all of them at once.
+pub fn duplicate_clauses(desc: &Description) -> Vec<Diagnostic> {
+    let mut seen = HashMap::new();
+    let mut diagnostics = Vec::new();
+    for c in desc.clauses.iter() {
+        if let &UnknownClause(_) = c {
+            continue;
+        }
+        match seen.entry(canonical_variables(c)) {
+            Entry::Occupied(e) => {
+                let first: &&Clause = e.get();
+                let mut message = format!("this clause is the same as the one at byte {}",
+                                          first.span().lo);
+                if first.to_string() != c.to_string() {
+                    message.push_str(" except for its variable names");
+                }
+                diagnostics.push(Diagnostic::warning("duplicate-clause", c.span(), message)
+                                 .with_fix(Some(TextEdit::new(c.span(), ""))));
+            }
+            Entry::Vacant(e) => {
+                e.insert(c);
+            }
+        }
+    }
+    diagnostics
+}
+
+/// Returns a warning for every literal in the body of a rule whose value doesn't depend on the
+/// game, with the code `trivial-literal`. These are `distinct`s, and `not`s of `distinct`s, whose
+/// terms are the same, or are different and ground. A literal that's always false means the rule
+/// never applies, so its fix removes the rule, and one that's always true can be left out, so its
+/// fix removes the literal.
+pub fn trivial_literals(desc: &Description) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for c in desc.clauses.iter() {
+        let r = match c {
+            &RuleClause(ref r) => r,
+            _ => continue
+        };
+        // The end of the head or literal before each literal, so fixes remove the space before it
+        let mut previous = r.head.span().hi;
+        for l in r.body.iter() {
+            let value = match l {
+                &DistinctLit(ref d) => distinct_value(d),
+                &NotLit(ref n) => match *n.lit {
+                    DistinctLit(ref d) => distinct_value(d).map(|v| !v),
+                    _ => None
+                },
+                _ => None
+            };
+            let diagnostic = match value {
+                Some(true) => {
+                    let message = format!("`{}` is always true, so it can be removed", l);
+                    Diagnostic::warning("trivial-literal", l.span(), message)
+                        .with_fix(Some(TextEdit::new(Span::new(previous, l.span().hi), "")))
+                }
+                Some(false) => {
+                    let message = format!("`{}` is always false, so this rule never applies", l);
+                    Diagnostic::warning("trivial-literal", l.span(), message)
+                        .with_fix(Some(TextEdit::new(r.span, "")))
+                }
+                None => {
+                    previous = l.span().hi;
+                    continue;
+                }
+            };
+            diagnostics.push(diagnostic);
+            previous = l.span().hi;
+        }
+    }
+    diagnostics
+}
+
+/// Returns the indices of the rules in `desc` that can never apply, in source order. A rule can't
+/// apply if its body has a literal from `trivial_literals` that's always false, or a positive
+/// literal for a relation whose rules can't apply either. `true` can hold if `init` or `next` can,
+/// and `does` can if `legal` can. If a clause couldn't be parsed, it might define anything, so no
+/// rules are returned.
+pub fn dead_clauses(desc: &Description) -> Vec<usize> {
+    let mut holds = HashSet::new();
+    let mut live = vec![false; desc.clauses.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (i, c) in desc.clauses.iter().enumerate() {
+            if live[i] {
+                continue;
+            }
+            let (head, body): (_, &[Literal]) = match c {
+                &RuleClause(ref r) => (&r.head, &r.body),
+                &SentenceClause(ref s) => (s, &[]),
+                &UnknownClause(_) => return Vec::new()
+            };
+            if body.iter().all(|l| may_hold(l, &holds)) {
+                live[i] = true;
+                changed = true;
+                let name = head.name().name.clone();
+                if name == "init" || name == "next" {
+                    holds.insert("true".to_string());
+                } else if name == "legal" {
+                    holds.insert("does".to_string());
+                }
+                holds.insert(name);
+            }
+        }
+    }
+    (0..live.len()).filter(|&i| !live[i]).collect()
+}
+
+// Returns false if `l` can't hold when only the relations in `holds` can
+fn may_hold(l: &Literal, holds: &HashSet<String>) -> bool {
+    match l {
+        &PropLit(ref p) => holds.contains(&p.name.name),
+        &RelLit(ref r) => holds.contains(&r.name.name),
+        &DistinctLit(ref d) => distinct_value(d) != Some(false),
+        &NotLit(ref n) => match *n.lit {
+            DistinctLit(ref d) => distinct_value(d) != Some(true),
+            _ => true
+        },
+        &OrLit(ref o) => o.lits.iter().any(|l| may_hold(l, holds)),
+        &KnowsLit(_) => true
+    }
+}
+
+// Returns whether `d` is true in every state, false in every state, or `None` if it depends on
+// the values of its variables
+fn distinct_value(d: &Distinct) -> Option<bool> {
+    if d.term1 == d.term2 {
+        Some(false)
+    } else if is_ground(&d.term1) && is_ground(&d.term2) {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+fn is_ground(t: &Term) -> bool {
+    match t {
+        &VarTerm(_) => false,
+        &FuncTerm(ref f) => f.args.iter().all(is_ground),
+        &ConstTerm(_) => true
+    }
+}
+
+// Collects every use of a relation or function, with its number of arguments
+struct Uses {
+    relations: Vec<(String, usize, Span)>,
+    functions: Vec<(String, usize, Span)>
+}
+
+impl Visit for Uses {
+    fn visit_proposition(&mut self, p: &Proposition) {
+        self.relations.push((p.name.name.clone(), 0, p.span));
+    }
+
+    fn visit_relation(&mut self, r: &Relation) {
+        self.relations.push((r.name.name.clone(), r.args.len(), r.span));
+    }
+
+    fn visit_function(&mut self, f: &Function) {
+        self.functions.push((f.name.name.clone(), f.args.len(), f.span));
+    }
+
+    fn visit_term(&mut self, t: &Term) {
+        if let &ConstTerm(ref c) = t {
+            self.functions.push((c.name.clone(), 0, c.span));
+        }
+    }
+}
+
+// Names and arities that are defined somewhere, in the order they're first defined. `None` means
+// anything might be defined.
+struct Defined {
+    names: Option<Vec<(String, usize)>>,
+    seen: HashSet<(String, usize)>
+}
+
+impl Defined {
+    fn new() -> Defined {
+        Defined { names: Some(Vec::new()), seen: HashSet::new() }
+    }
+
+    fn add(&mut self, name: Option<&Constant>, arity: usize) {
+        match (name, &mut self.names) {
+            (Some(name), &mut Some(ref mut names)) => {
+                if self.seen.insert((name.name.clone(), arity)) {
+                    names.push((name.name.clone(), arity));
+                }
+            }
+            (None, names) => *names = None,
+            _ => {}
+        }
+    }
+
+    fn add_term(&mut self, t: &Term) {
+        match t {
+            &VarTerm(_) => self.add(None, 0),
+            &FuncTerm(ref f) => self.add(Some(&f.name), f.args.len()),
+            &ConstTerm(ref c) => self.add(Some(c), 0)
+        }
+    }
+
+    fn check_term(&self, kind: &str, t: &Term, definers: &str, diagnostics: &mut Vec<Diagnostic>) {
+        match t {
+            &VarTerm(_) => {}
+            &FuncTerm(ref f) => self.check(kind, &f.name, f.args.len(), f.span, definers,
+                                           diagnostics),
+            &ConstTerm(ref c) => self.check(kind, c, 0, c.span, definers, diagnostics)
+        }
+    }
+
+    fn check(&self, kind: &str, name: &Constant, arity: usize, span: Span, definers: &str,
+             diagnostics: &mut Vec<Diagnostic>) {
+        let names = match self.names {
+            Some(ref names) => names,
+            None => return
+        };
+        if self.seen.contains(&(name.name.clone(), arity)) {
+            return;
+        }
+        let mut message = format!("{} `{}/{}` is never defined by {}", kind, name, arity, definers);
+        let similar = names.iter().filter(|&&(ref other, a)| a == arity && other != &name.name)
+                           .map(|&(ref other, _)| (edit_distance(&name.name, other), other))
+                           .filter(|&(d, _)| d <= 2)
+                           .min_by_key(|&(d, _)| d);
+        let fix = similar.map(|(_, other)| {
+            message.push_str(&format!("; did you mean `{}`?", other));
+            TextEdit::new(name.span, other.clone())
+        });
+        diagnostics.push(Diagnostic::warning("undefined-relation", span, message).with_fix(fix));
+    }
+}
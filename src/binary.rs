@@ -0,0 +1,459 @@
+//! A compact binary encoding of descriptions, for caching parsed games. Loading a description from
+//! this encoding skips lexing, parsing, and the dialect checks, so it's faster than parsing its
+//! text, although building the AST is still most of the work either way.
+//!
+//! The encoding starts with the magic bytes `GDLB` and a version byte. Then comes a table of
+//! every distinct name in the description, followed by the clauses. Nodes are written as a tag
+//! byte followed by their fields, and spans are kept. A constant is written as twice the index of
+//! its name in the table, plus one if it's a string literal. All numbers are unsigned LEB128
+//! varints, and strings are a length followed by UTF-8 bytes.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str;
+
+use {Clause, Constant, Description, Distinct, Function, Literal, Not, Or, Proposition, Relation,
+     Rule, Sentence, Span, Term, Unknown, Variable, DEFAULT_MAX_DEPTH};
+use Knows;
+use Clause::{RuleClause, SentenceClause, UnknownClause};
+use Literal::{NotLit, OrLit, DistinctLit, PropLit, RelLit};
+use Literal::KnowsLit;
+use Sentence::{PropSentence, RelSentence};
+use Term::{VarTerm, FuncTerm, ConstTerm};
+
+use self::BinaryError::{BadMagic, UnsupportedVersion, UnexpectedEnd, Invalid};
+
+const MAGIC: &'static [u8] = b"GDLB";
+
+/// The version of the encoding written by `to_binary`. It changes whenever the encoding does, and
+/// `from_binary` rejects other versions, so stale caches are detected instead of misread.
+pub const BINARY_VERSION: u8 = 2;
+
+/// An error produced when bytes can't be decoded by `from_binary`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BinaryError {
+    /// The input doesn't start with the magic bytes, so it isn't an encoded description
+    BadMagic,
+
+    /// The input was encoded with a different version of the encoding
+    UnsupportedVersion(u8),
+
+    /// The input ended in the middle of a value
+    UnexpectedEnd,
+
+    /// The input is corrupt. The message says what was wrong and where.
+    Invalid(String)
+}
+
+impl Display for BinaryError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            &BadMagic => write!(f, "not an encoded GDL description"),
+            &UnsupportedVersion(v) => {
+                write!(f, "unsupported encoding version {}, expected {}", v, BINARY_VERSION)
+            }
+            &UnexpectedEnd => write!(f, "unexpected end of encoded description"),
+            &Invalid(ref message) => write!(f, "invalid encoded description: {}", message)
+        }
+    }
+}
+
+impl Error for BinaryError {
+    fn description(&self) -> &str {
+        match self {
+            &BadMagic => "not an encoded GDL description",
+            &UnsupportedVersion(_) => "unsupported encoding version",
+            &UnexpectedEnd => "unexpected end of encoded description",
+            &Invalid(_) => "invalid encoded description"
+        }
+    }
+}
+
+/// Encodes `desc` in the binary encoding described in the module docs
+pub fn to_binary(desc: &Description) -> Vec<u8> {
+    let mut names = Names { indices: HashMap::new(), table: Vec::new() };
+    let mut body = Vec::new();
+    write_usize(&mut body, desc.clauses.len());
+    for c in desc.clauses.iter() {
+        write_clause(&mut body, &mut names, c);
+    }
+
+    let mut out = Vec::with_capacity(body.len() + 16 * names.table.len());
+    out.extend_from_slice(MAGIC);
+    out.push(BINARY_VERSION);
+    write_usize(&mut out, names.table.len());
+    for name in names.table.iter() {
+        write_str(&mut out, name);
+    }
+    out.extend_from_slice(&body);
+    out
+}
+
+/// Decodes a description that was encoded by `to_binary`
+pub fn from_binary(bytes: &[u8]) -> Result<Description, BinaryError> {
+    if !bytes.starts_with(MAGIC) {
+        return Err(BadMagic);
+    }
+    let mut r = Reader { bytes: bytes, pos: MAGIC.len(), names: Vec::new(), depth: 0 };
+    let version = r.byte()?;
+    if version != BINARY_VERSION {
+        return Err(UnsupportedVersion(version));
+    }
+    for _ in 0..r.len()? {
+        let name = r.str()?.to_string();
+        r.names.push(name);
+    }
+    let mut clauses = Vec::with_capacity(r.len()?);
+    for _ in 0..clauses.capacity() {
+        clauses.push(r.clause()?);
+    }
+    if r.pos != bytes.len() {
+        return Err(Invalid(format!("trailing bytes at offset {}", r.pos)));
+    }
+    Ok(Description::new(clauses))
+}
+
+// The table of names used by an encoded description
+struct Names<'a> {
+    indices: HashMap<&'a str, usize>,
+    table: Vec<&'a str>
+}
+
+impl<'a> Names<'a> {
+    fn index(&mut self, name: &'a str) -> usize {
+        let table = &mut self.table;
+        *self.indices.entry(name).or_insert_with(|| {
+            table.push(name);
+            table.len() - 1
+        })
+    }
+}
+
+fn write_clause<'a>(out: &mut Vec<u8>, names: &mut Names<'a>, clause: &'a Clause) {
+    match clause {
+        &RuleClause(ref r) => {
+            out.push(0);
+            write_sentence(out, names, &r.head);
+            write_usize(out, r.body.len());
+            for l in r.body.iter() {
+                write_literal(out, names, l);
+            }
+            write_span(out, r.span);
+        }
+        &SentenceClause(ref s) => {
+            out.push(1);
+            write_sentence(out, names, s);
+        }
+        &UnknownClause(ref u) => {
+            out.push(2);
+            write_str(out, &u.text);
+            write_span(out, u.span);
+        }
+    }
+}
+
+fn write_sentence<'a>(out: &mut Vec<u8>, names: &mut Names<'a>, sentence: &'a Sentence) {
+    match sentence {
+        &PropSentence(ref p) => {
+            out.push(0);
+            write_proposition(out, names, p);
+        }
+        &RelSentence(ref r) => {
+            out.push(1);
+            write_compound(out, names, &r.name, &r.args, r.span);
+        }
+    }
+}
+
+fn write_literal<'a>(out: &mut Vec<u8>, names: &mut Names<'a>, lit: &'a Literal) {
+    match lit {
+        &NotLit(ref n) => {
+            out.push(0);
+            write_literal(out, names, &n.lit);
+            write_span(out, n.span);
+        }
+        &OrLit(ref o) => {
+            out.push(1);
+            write_usize(out, o.lits.len());
+            for l in o.lits.iter() {
+                write_literal(out, names, l);
+            }
+            write_span(out, o.span);
+        }
+        &DistinctLit(ref d) => {
+            out.push(2);
+            write_term(out, names, &d.term1);
+            write_term(out, names, &d.term2);
+            write_span(out, d.span);
+        }
+        &PropLit(ref p) => {
+            out.push(3);
+            write_proposition(out, names, p);
+        }
+        &RelLit(ref r) => {
+            out.push(4);
+            write_compound(out, names, &r.name, &r.args, r.span);
+        }
+        &KnowsLit(ref k) => {
+            out.push(5);
+            match k.role {
+                Some(ref role) => {
+                    out.push(1);
+                    write_term(out, names, role);
+                }
+                None => out.push(0)
+            }
+            write_term(out, names, &k.prop);
+            write_span(out, k.span);
+        }
+    }
+}
+
+fn write_term<'a>(out: &mut Vec<u8>, names: &mut Names<'a>, term: &'a Term) {
+    match term {
+        &VarTerm(ref v) => {
+            out.push(0);
+            write_constant(out, names, &v.name);
+            write_span(out, v.span);
+        }
+        &FuncTerm(ref f) => {
+            out.push(1);
+            write_compound(out, names, &f.name, &f.args, f.span);
+        }
+        &ConstTerm(ref c) => {
+            out.push(2);
+            write_constant(out, names, c);
+        }
+    }
+}
+
+fn write_proposition<'a>(out: &mut Vec<u8>, names: &mut Names<'a>, p: &'a Proposition) {
+    write_constant(out, names, &p.name);
+    write_span(out, p.span);
+}
+
+// Writes a relation or function
+fn write_compound<'a>(out: &mut Vec<u8>, names: &mut Names<'a>, name: &'a Constant,
+                      args: &'a [Term], span: Span) {
+    write_constant(out, names, name);
+    write_usize(out, args.len());
+    for t in args.iter() {
+        write_term(out, names, t);
+    }
+    write_span(out, span);
+}
+
+fn write_constant<'a>(out: &mut Vec<u8>, names: &mut Names<'a>, c: &'a Constant) {
+    let index = names.index(&c.name);
+    write_usize(out, 2 * index + c.string as usize);
+    write_span(out, c.span);
+}
+
+fn write_span(out: &mut Vec<u8>, span: Span) {
+    write_usize(out, span.lo);
+    write_usize(out, span.hi);
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_usize(out, s.len());
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn write_usize(out: &mut Vec<u8>, mut n: usize) {
+    while n >= 0x80 {
+        out.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    names: Vec<String>,
+    // How deeply nested the node being read is, to stop corrupt input from overflowing the stack
+    depth: usize
+}
+
+impl<'a> Reader<'a> {
+    fn clause(&mut self) -> Result<Clause, BinaryError> {
+        match self.tag(2)? {
+            0 => {
+                let head = self.sentence()?;
+                let mut body = Vec::with_capacity(self.len()?);
+                for _ in 0..body.capacity() {
+                    body.push(self.literal()?);
+                }
+                Ok(RuleClause(Rule::new(head, body).with_span(self.span()?)))
+            }
+            1 => Ok(SentenceClause(self.sentence()?)),
+            _ => {
+                let text = self.str()?.to_string();
+                Ok(UnknownClause(Unknown::new(text).with_span(self.span()?)))
+            }
+        }
+    }
+
+    fn sentence(&mut self) -> Result<Sentence, BinaryError> {
+        match self.tag(1)? {
+            0 => Ok(PropSentence(self.proposition()?)),
+            _ => {
+                let (name, args, span) = self.compound()?;
+                Ok(RelSentence(Relation::new(name, args).with_span(span)))
+            }
+        }
+    }
+
+    fn literal(&mut self) -> Result<Literal, BinaryError> {
+        self.enter()?;
+        let lit = match self.tag(5)? {
+            0 => {
+                let lit = self.literal()?;
+                NotLit(Not::new(Box::new(lit)).with_span(self.span()?))
+            }
+            1 => {
+                let mut lits = Vec::with_capacity(self.len()?);
+                for _ in 0..lits.capacity() {
+                    lits.push(self.literal()?);
+                }
+                OrLit(Or::new(lits).with_span(self.span()?))
+            }
+            2 => {
+                let term1 = self.term()?;
+                let term2 = self.term()?;
+                DistinctLit(Distinct::new(term1, term2).with_span(self.span()?))
+            }
+            3 => PropLit(self.proposition()?),
+            4 => {
+                let (name, args, span) = self.compound()?;
+                RelLit(Relation::new(name, args).with_span(span))
+            }
+            _ => {
+                let role = match self.tag(1)? {
+                    0 => None,
+                    _ => Some(self.term()?)
+                };
+                let prop = self.term()?;
+                KnowsLit(Knows::new(role, prop).with_span(self.span()?))
+            }
+        };
+        self.depth -= 1;
+        Ok(lit)
+    }
+
+    fn term(&mut self) -> Result<Term, BinaryError> {
+        self.enter()?;
+        let term = match self.tag(2)? {
+            0 => {
+                let name = self.constant()?;
+                VarTerm(Variable::new(name).with_span(self.span()?))
+            }
+            1 => {
+                let (name, args, span) = self.compound()?;
+                FuncTerm(Function::new(name, args).with_span(span))
+            }
+            _ => ConstTerm(self.constant()?)
+        };
+        self.depth -= 1;
+        Ok(term)
+    }
+
+    fn proposition(&mut self) -> Result<Proposition, BinaryError> {
+        let name = self.constant()?;
+        Ok(Proposition::new(name).with_span(self.span()?))
+    }
+
+    fn compound(&mut self) -> Result<(Constant, Vec<Term>, Span), BinaryError> {
+        let name = self.constant()?;
+        let mut args = Vec::with_capacity(self.len()?);
+        for _ in 0..args.capacity() {
+            args.push(self.term()?);
+        }
+        Ok((name, args, self.span()?))
+    }
+
+    fn constant(&mut self) -> Result<Constant, BinaryError> {
+        let pos = self.pos;
+        let n = self.usize()?;
+        let index = n / 2;
+        let name = match self.names.get(index) {
+            Some(name) => name.clone(),
+            None => return Err(Invalid(format!("unknown name {} at offset {}", index, pos)))
+        };
+        let c = if n % 2 == 1 { Constant::string(name) } else { Constant::new(name) };
+        Ok(c.with_span(self.span()?))
+    }
+
+    fn span(&mut self) -> Result<Span, BinaryError> {
+        let lo = self.usize()?;
+        let hi = self.usize()?;
+        Ok(Span::new(lo, hi))
+    }
+
+    // Reads a tag byte that can be at most `max`
+    fn tag(&mut self, max: u8) -> Result<u8, BinaryError> {
+        let tag = self.byte()?;
+        if tag > max {
+            return Err(Invalid(format!("unknown tag {} at offset {}", tag, self.pos - 1)));
+        }
+        Ok(tag)
+    }
+
+    // Reads the length of a list. Every element takes at least a byte, so a length longer than
+    // the rest of the input is corrupt, and rejecting it keeps it from being allocated.
+    fn len(&mut self) -> Result<usize, BinaryError> {
+        let n = self.usize()?;
+        if n > self.bytes.len() - self.pos {
+            return Err(UnexpectedEnd);
+        }
+        Ok(n)
+    }
+
+    fn str(&mut self) -> Result<&'a str, BinaryError> {
+        let len = self.len()?;
+        let bytes = &self.bytes[self.pos..self.pos + len];
+        match str::from_utf8(bytes) {
+            Ok(s) => {
+                self.pos += len;
+                Ok(s)
+            }
+            Err(_) => Err(Invalid(format!("invalid UTF-8 at offset {}", self.pos)))
+        }
+    }
+
+    fn usize(&mut self) -> Result<usize, BinaryError> {
+        let mut n: usize = 0;
+        let mut shift = 0;
+        loop {
+            let b = self.byte()?;
+            if shift >= 64 || (shift == 63 && b > 1) {
+                return Err(Invalid(format!("number too large at offset {}", self.pos - 1)));
+            }
+            n |= ((b & 0x7f) as usize) << shift;
+            if b < 0x80 {
+                return Ok(n);
+            }
+            shift += 7;
+        }
+    }
+
+    fn byte(&mut self) -> Result<u8, BinaryError> {
+        match self.bytes.get(self.pos) {
+            Some(&b) => {
+                self.pos += 1;
+                Ok(b)
+            }
+            None => Err(UnexpectedEnd)
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), BinaryError> {
+        self.depth += 1;
+        if self.depth > DEFAULT_MAX_DEPTH {
+            return Err(Invalid(format!("nesting deeper than {} levels at offset {}",
+                                       DEFAULT_MAX_DEPTH, self.pos)));
+        }
+        Ok(())
+    }
+}
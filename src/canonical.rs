@@ -0,0 +1,57 @@
+//! A canonical form for descriptions, so that descriptions that differ only in formatting, case,
+//! variable names, or clause order print the same
+
+use std::collections::HashMap;
+
+use {Clause, Constant, Description, Variable};
+use lexer;
+use visitor::{self, Visitor};
+
+/// Prints `desc` in the form described by `Description::to_canonical_string`
+pub fn canonical_string(desc: &Description) -> String {
+    let mut clauses: Vec<String> = desc.clauses.iter().map(canonical_clause).collect();
+    clauses.sort();
+    clauses.join("\n")
+}
+
+/// Prints `c` with its constants lowercased and its variables renamed in the order they appear,
+/// so clauses that are the same up to case and variable names print the same
+pub fn canonical_clause(c: &Clause) -> String {
+    let mut c = c.clone();
+    // Variables are lowercased first, so they're renamed regardless of case too
+    visitor::visit_clause(&mut c, &mut Lowercase);
+    canonical_variables(&c).to_string()
+}
+
+/// Returns `c` with its variables renamed to `?v0`, `?v1`, and so on, in the order they first
+/// appear
+pub fn canonical_variables(c: &Clause) -> Clause {
+    let mut c = c.clone();
+    visitor::visit_clause(&mut c, &mut RenameVariables { vars: HashMap::new() });
+    c
+}
+
+struct Lowercase;
+
+impl Visitor for Lowercase {
+    fn visit_constant(&mut self, c: &mut Constant) {
+        // Strings, and names that have to be quoted, were quoted in the source, so they keep
+        // their case
+        if !c.string && c.name.chars().all(lexer::is_ident_char) {
+            c.name = c.name.to_ascii_lowercase();
+        }
+    }
+}
+
+struct RenameVariables {
+    // The new names of the variables seen so far in the clause
+    vars: HashMap<String, String>
+}
+
+impl Visitor for RenameVariables {
+    fn visit_variable(&mut self, v: &mut Variable) {
+        let n = self.vars.len();
+        v.name.name = self.vars.entry(v.name.name.clone()).or_insert_with(|| format!("v{}", n))
+            .clone();
+    }
+}
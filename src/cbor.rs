@@ -0,0 +1,21 @@
+//! Encoding descriptions as CBOR, using the same representation as the serde impls
+//! (see the `serde` feature).
+
+use std::io;
+
+use ciborium::{de, ser};
+
+use Description;
+
+/// Encodes `desc` as CBOR
+pub fn to_cbor(desc: &Description) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    ser::into_writer(desc, &mut bytes).expect("Encoding a description to memory can't fail");
+    bytes
+}
+
+/// Decodes a description from CBOR written by `to_cbor`, or by anything else that uses the same
+/// representation. Spans can be left out and default to `Span::dummy()`.
+pub fn from_cbor(bytes: &[u8]) -> Result<Description, de::Error<io::Error>> {
+    de::from_reader(bytes)
+}
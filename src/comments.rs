@@ -0,0 +1,84 @@
+use lexer::{Lexer, Token};
+use {Description, Span};
+
+/// A comment in the source text, either a line comment starting with `;` or a `#| ... |#` block
+/// comment, which can nest and span several lines. `text` is the comment as written, including the
+/// leading semicolons of a line comment but not the newline that ends it, and both delimiters of a
+/// block comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    pub text: String,
+    pub span: Span
+}
+
+impl Comment {
+    pub fn new<T: Into<String>>(text: T, span: Span) -> Comment {
+        Comment { text: text.into(), span: span }
+    }
+}
+
+/// The comments attached to a single clause
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Trivia {
+    /// Comments on the lines before the clause
+    pub leading: Vec<Comment>,
+
+    /// Comments inside the clause, between its literals or terms
+    pub inner: Vec<Comment>,
+
+    /// A comment on the same line as the end of the clause
+    pub trailing: Vec<Comment>
+}
+
+/// The comments in a description, grouped by the clause they're attached to
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Comments {
+    /// The comments attached to each clause, indexed the same as `Description::clauses`
+    pub clauses: Vec<Trivia>,
+
+    /// Comments after the last clause that aren't on the same line as it
+    pub dangling: Vec<Comment>
+}
+
+/// Collects the comments in `gdl` and attaches them to the clauses of `desc`, which must have been
+/// parsed from `gdl`. A comment on the same line as the end of a clause is attached to that clause,
+/// otherwise it's attached to the clause that follows it.
+pub fn attach_comments(gdl: &str, desc: &Description) -> Comments {
+    let spans: Vec<Span> = desc.clauses.iter().map(|c| c.span()).collect();
+    let mut comments = Comments {
+        clauses: vec![Trivia::default(); spans.len()],
+        dangling: Vec::new()
+    };
+
+    // Clauses are in source order and each comment comes after the previous one, so `next` only
+    // ever moves forward
+    let mut next = 0;
+    for comment in scan(gdl) {
+        while next < spans.len() && spans[next].hi <= comment.span.lo {
+            next += 1;
+        }
+
+        if next < spans.len() && spans[next].lo <= comment.span.lo {
+            comments.clauses[next].inner.push(comment);
+        } else if next > 0 && !gdl[spans[next - 1].hi..comment.span.lo].contains('\n') {
+            comments.clauses[next - 1].trailing.push(comment);
+        } else if next < spans.len() {
+            comments.clauses[next].leading.push(comment);
+        } else {
+            comments.dangling.push(comment);
+        }
+    }
+    comments
+}
+
+// Semicolons can appear inside quoted atoms, so the lexer is used to find the real comments
+fn scan(gdl: &str) -> Vec<Comment> {
+    let mut comments = Vec::new();
+    for (token, span) in Lexer::new(gdl) {
+        if let Token::Comment(text) = token {
+            let text = text.trim_end_matches('\r');
+            comments.push(Comment::new(text, Span::new(span.lo, span.lo + text.len())));
+        }
+    }
+    comments
+}
@@ -0,0 +1,224 @@
+//! A lossless concrete syntax tree for GDL. Unlike the AST, the syntax tree keeps every token of
+//! the input including whitespace and comments, so printing it reproduces the input byte for byte.
+//! It can also be built for input that doesn't parse, such as unbalanced parentheses.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Display, Formatter};
+use std::mem;
+
+use lexer::{Lexer, Token};
+use {parse_checked, Clause, Description, ParseError, Span};
+
+use self::Node::{Leaf, List};
+
+/// The kind of a token in the syntax tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    LParen,
+    RParen,
+    Atom,
+    Variable,
+    Comment,
+    Whitespace,
+    Unknown
+}
+
+/// A token in the syntax tree, stored with its exact source text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxToken {
+    pub kind: Kind,
+    pub text: String,
+    pub span: Span
+}
+
+impl SyntaxToken {
+    /// Returns true for whitespace and comments
+    pub fn is_trivia(&self) -> bool {
+        self.kind == Kind::Whitespace || self.kind == Kind::Comment
+    }
+}
+
+/// A node in the syntax tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    Leaf(SyntaxToken),
+
+    /// A parenthesized list. Its children start with the `(` token and end with the `)` token,
+    /// unless the list is never closed.
+    List(Vec<Node>, Span)
+}
+
+impl Node {
+    /// Returns the span of the node in the source text
+    pub fn span(&self) -> Span {
+        match self {
+            &Leaf(ref t) => t.span,
+            &List(_, span) => span
+        }
+    }
+
+    /// Returns true for whitespace and comments
+    pub fn is_trivia(&self) -> bool {
+        match self {
+            &Leaf(ref t) => t.is_trivia(),
+            &List(..) => false
+        }
+    }
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        // Printed with an explicit stack like the AST, so deeply nested input can't overflow the
+        // call stack. Children are pushed in reverse, so they're popped and printed in order.
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            match node {
+                &Leaf(ref t) => f.write_str(&t.text)?,
+                &List(ref children, _) => stack.extend(children.iter().rev())
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A lossless syntax tree of a GDL string. The top-level nodes are the clauses of the
+/// description along with the whitespace and comments between them.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SyntaxTree {
+    pub children: Vec<Node>
+}
+
+impl SyntaxTree {
+    /// Builds the syntax tree of a GDL string. This never fails; unclosed lists run to the end of
+    /// the input and stray tokens are kept as leaves.
+    pub fn parse(gdl: &str) -> SyntaxTree {
+        // The bottom of the stack holds the top-level nodes and every other entry is a list that
+        // hasn't been closed yet
+        let mut stack = vec![Vec::new()];
+        for (token, span) in Lexer::new(gdl) {
+            let text = span.snippet(gdl).to_string();
+            let leaf = Leaf(SyntaxToken { kind: kind(&token), text: text, span: span });
+            match token {
+                Token::LParen => stack.push(vec![leaf]),
+                Token::RParen if stack.len() > 1 => {
+                    let mut children = stack.pop().unwrap();
+                    children.push(leaf);
+                    stack.last_mut().unwrap().push(make_list(children));
+                }
+                _ => stack.last_mut().unwrap().push(leaf)
+            }
+        }
+        while stack.len() > 1 {
+            let children = stack.pop().unwrap();
+            stack.last_mut().unwrap().push(make_list(children));
+        }
+        SyntaxTree { children: stack.pop().unwrap() }
+    }
+
+    /// Builds the syntax tree of a description printed with its default formatting
+    pub fn from_description(desc: &Description) -> SyntaxTree {
+        if desc.clauses.is_empty() {
+            return SyntaxTree::default();
+        }
+        SyntaxTree::parse(&desc.to_string())
+    }
+
+    /// Parses the syntax tree into a description
+    pub fn to_description(&self) -> Result<Description, ParseError> {
+        parse_checked(&self.to_string())
+    }
+
+    /// Builds the syntax tree of `desc`, reusing the text of this tree for every clause of `desc`
+    /// that also appears in this tree. Reused clauses keep the comments and whitespace before
+    /// them, and clauses that were changed in place keep the comments before them, so only the
+    /// clauses that actually changed are reformatted.
+    pub fn update(&self, desc: &Description) -> SyntaxTree {
+        // Split the top-level nodes into clauses along with the trivia before each one
+        let mut segments = Vec::new();
+        let mut trivia = String::new();
+        for node in self.children.iter() {
+            if node.is_trivia() {
+                trivia.push_str(&node.to_string());
+            } else {
+                let text = node.to_string();
+                let clause = parse_checked(&text).ok().and_then(|mut d| d.clauses.pop());
+                segments.push((mem::replace(&mut trivia, String::new()), text, clause));
+            }
+        }
+
+        let mut old_positions: HashMap<&Clause, VecDeque<usize>> = HashMap::new();
+        for (i, segment) in segments.iter().enumerate() {
+            if let Some(ref c) = segment.2 {
+                old_positions.entry(c).or_insert_with(VecDeque::new).push_back(i);
+            }
+        }
+        let mut remaining: HashMap<&Clause, usize> = HashMap::new();
+        for c in desc.clauses.iter() {
+            *remaining.entry(c).or_insert(0) += 1;
+        }
+
+        let mut out = String::new();
+        let mut next = 0;
+        for clause in desc.clauses.iter() {
+            *remaining.get_mut(clause).unwrap() -= 1;
+
+            let mut reused = None;
+            if let Some(positions) = old_positions.get_mut(clause) {
+                while positions.front().map_or(false, |&i| i < next) {
+                    positions.pop_front();
+                }
+                reused = positions.pop_front();
+            }
+
+            match reused {
+                Some(i) => {
+                    out.push_str(&segments[i].0);
+                    out.push_str(&segments[i].1);
+                    next = i + 1;
+                }
+                None => {
+                    // If the next old clause isn't needed later, this clause replaces it
+                    let replaces = next < segments.len() && match segments[next].2 {
+                        Some(ref old) => remaining.get(old).map_or(true, |&n| n == 0),
+                        None => true
+                    };
+                    if replaces {
+                        out.push_str(&segments[next].0);
+                        next += 1;
+                    } else if !out.is_empty() {
+                        out.push('\n');
+                    }
+                    out.push_str(&clause.to_string());
+                }
+            }
+        }
+        out.push_str(&trivia);
+        SyntaxTree::parse(&out)
+    }
+}
+
+impl Display for SyntaxTree {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for child in self.children.iter() {
+            Display::fmt(child, f)?;
+        }
+        Ok(())
+    }
+}
+
+fn kind(token: &Token) -> Kind {
+    match token {
+        &Token::LParen => Kind::LParen,
+        &Token::RParen => Kind::RParen,
+        &Token::Atom(_) => Kind::Atom,
+        &Token::Variable(_) => Kind::Variable,
+        &Token::Comment(_) => Kind::Comment,
+        &Token::Whitespace(_) => Kind::Whitespace,
+        &Token::Unknown(_) => Kind::Unknown
+    }
+}
+
+fn make_list(children: Vec<Node>) -> Node {
+    let span = children[0].span().to(children[children.len() - 1].span());
+    List(children, span)
+}
@@ -0,0 +1,143 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde_json::{Map, Value};
+
+use {ParseError, Span, TextEdit};
+use span::line_col;
+
+/// How serious a `Diagnostic` is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Something that is probably a mistake, but doesn't stop the description from being parsed
+    Warning,
+
+    /// Something that stops a clause from being parsed
+    Error
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            &Severity::Warning => write!(f, "warning"),
+            &Severity::Error => write!(f, "error")
+        }
+    }
+}
+
+/// A problem found in a GDL description, along with where it was found. The code is a short,
+/// stable name for the kind of problem, like `singleton-variable`, that tools can filter on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub span: Span,
+
+    /// An edit to the source text that fixes the problem, for editors to offer as a quick fix
+    pub fix: Option<TextEdit>
+}
+
+impl Diagnostic {
+    pub fn new<T: Into<String>>(severity: Severity, code: &'static str, span: Span,
+                                message: T) -> Diagnostic {
+        Diagnostic {
+            severity: severity,
+            code: code,
+            message: message.into(),
+            span: span,
+            fix: None
+        }
+    }
+
+    /// Converts an error from parsing `src` to a diagnostic, with the fix from `ParseError::fix`
+    pub fn from_error(error: ParseError, src: &str) -> Diagnostic {
+        let fix = error.fix(src);
+        let diagnostic: Diagnostic = error.into();
+        diagnostic.with_fix(fix)
+    }
+
+    /// Sets the suggested fix for the problem
+    pub fn with_fix(mut self, fix: Option<TextEdit>) -> Diagnostic {
+        self.fix = fix;
+        self
+    }
+
+    pub fn error<T: Into<String>>(code: &'static str, span: Span, message: T) -> Diagnostic {
+        Diagnostic::new(Severity::Error, code, span, message)
+    }
+
+    pub fn warning<T: Into<String>>(code: &'static str, span: Span, message: T) -> Diagnostic {
+        Diagnostic::new(Severity::Warning, code, span, message)
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Serializes the diagnostic to a JSON object as described in `diagnostics_to_json`, using
+    /// `src`, the text it was found in, for line and column numbers
+    pub fn to_json(&self, src: &str) -> String {
+        diagnostic_json(self, src).to_string()
+    }
+}
+
+/// Serializes `diagnostics`, which were found in `src`, to a JSON array for other tools to read.
+/// Each diagnostic is an object with these fields, and the format won't change except by adding
+/// fields:
+///
+/// * `severity`: `"error"` or `"warning"`
+/// * `code`: the diagnostic's code, like `"singleton-variable"`
+/// * `message`: the message shown to people
+/// * `range`: `{"start": position, "end": position}`, where a position is
+///   `{"offset": n, "line": n, "column": n}` with a byte offset and a 1-based line and column
+/// * `fix`: `{"range": range, "text": replacement}` for the suggested fix, or `null`
+pub fn diagnostics_to_json(diagnostics: &[Diagnostic], src: &str) -> String {
+    Value::Array(diagnostics.iter().map(|d| diagnostic_json(d, src)).collect()).to_string()
+}
+
+fn diagnostic_json(d: &Diagnostic, src: &str) -> Value {
+    let mut obj = Map::new();
+    obj.insert("severity".to_string(), Value::String(d.severity.to_string()));
+    obj.insert("code".to_string(), Value::String(d.code.to_string()));
+    obj.insert("message".to_string(), Value::String(d.message.clone()));
+    obj.insert("range".to_string(), range_json(d.span, src));
+    let fix = match d.fix {
+        Some(ref fix) => {
+            let mut obj = Map::new();
+            obj.insert("range".to_string(), range_json(fix.span, src));
+            obj.insert("text".to_string(), Value::String(fix.text.clone()));
+            Value::Object(obj)
+        }
+        None => Value::Null
+    };
+    obj.insert("fix".to_string(), fix);
+    Value::Object(obj)
+}
+
+fn range_json(span: Span, src: &str) -> Value {
+    let mut obj = Map::new();
+    obj.insert("start".to_string(), position_json(span.lo, src));
+    obj.insert("end".to_string(), position_json(span.hi, src));
+    Value::Object(obj)
+}
+
+fn position_json(offset: usize, src: &str) -> Value {
+    let (line, column) = line_col(src, offset);
+    let mut obj = Map::new();
+    obj.insert("offset".to_string(), Value::from(offset));
+    obj.insert("line".to_string(), Value::from(line));
+    obj.insert("column".to_string(), Value::from(column));
+    Value::Object(obj)
+}
+
+impl Into<Diagnostic> for ParseError {
+    fn into(self) -> Diagnostic {
+        Diagnostic::error(self.code(), self.span(), self.to_string())
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)
+    }
+}
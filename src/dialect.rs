@@ -0,0 +1,217 @@
+//! Support for the keywords added by GDL-II and GDL-III, which the grammar treats as ordinary
+//! relations
+
+use {Description, Literal, ParseError, Sentence, Span};
+#[cfg(feature = "gdl-iii")]
+use {Knows, Node};
+use Literal::KnowsLit;
+#[cfg(feature = "gdl-iii")]
+use visitor::Visitor;
+use Clause::{RuleClause, SentenceClause, UnknownClause};
+use Literal::{NotLit, OrLit, DistinctLit, PropLit, RelLit};
+use Sentence::{PropSentence, RelSentence};
+
+/// The GDL-II relation telling a role what it perceives, `(sees ?role ?percept)`
+pub const SEES: &'static str = "sees";
+
+/// The GDL-II role that makes moves at random
+pub const RANDOM: &'static str = "random";
+
+/// The GDL-III relation for what a role knows, `(knows ?role ?p)`, or what is common knowledge,
+/// `(knows ?p)`
+pub const KNOWS: &'static str = "knows";
+
+/// The relations that GDL defines itself, so a description can't define them with its own clauses
+pub const RESERVED: &'static [&'static str] = &["true", "does", "distinct", "not", "or"];
+
+/// Returns true if `name` is in `RESERVED`, or is `knows` when GDL-III is enabled
+pub fn is_reserved(name: &str) -> bool {
+    #[cfg(feature = "gdl-iii")]
+    let knows = name == KNOWS;
+    #[cfg(not(feature = "gdl-iii"))]
+    let knows = false;
+    knows || RESERVED.contains(&name)
+}
+
+/// The relations that GDL gives a meaning to, along with their number of arguments
+pub const KEYWORD_ARITIES: &'static [(&'static str, usize)] = &[
+    ("role", 1), ("init", 1), ("true", 1), ("next", 1), ("legal", 2), ("does", 2), ("goal", 2),
+    ("terminal", 0), ("base", 1), ("input", 2)
+];
+
+/// Returns true if `name` is one of the relations in `KEYWORD_ARITIES` or a literal keyword like
+/// `not`
+pub fn is_keyword(name: &str) -> bool {
+    name == "not" || name == "or" || name == "distinct" ||
+        KEYWORD_ARITIES.iter().any(|&(keyword, _)| keyword == name)
+}
+
+/// Returns true if the description uses `sees` or declares the `random` role
+pub fn is_gdl_ii(desc: &Description) -> bool {
+    sentences(desc).into_iter().any(|s| {
+        match s {
+            &PropSentence(ref p) => p.name.name == SEES,
+            &RelSentence(ref r) => {
+                r.name.name == SEES || (r.name.name == "role" && r.args.len() == 1 &&
+                                        r.args[0].name().name == RANDOM)
+            }
+        }
+    }) || literals(desc).into_iter().any(|l| match l {
+        &PropLit(ref p) => p.name.name == SEES,
+        &RelLit(ref r) => r.name.name == SEES,
+        _ => false
+    })
+}
+
+/// Returns an error for the first `sees` relation that doesn't have two arguments
+pub fn check_gdl_ii(desc: &Description) -> Result<(), ParseError> {
+    check_arities(desc, &[(SEES, 2)])
+}
+
+/// Returns an error for the first relation in `desc` named by one of `keywords` that doesn't have
+/// the number of arguments it's paired with
+pub fn check_arities(desc: &Description, keywords: &[(&str, usize)]) -> Result<(), ParseError> {
+    let check = |name: &str, found: usize, span: Span| {
+        for &(keyword, expected) in keywords.iter() {
+            check_arity(keyword, expected, name, found, span)?;
+        }
+        Ok(())
+    };
+    for c in desc.clauses.iter() {
+        let (head, body): (_, &[Literal]) = match c {
+            &RuleClause(ref r) => (&r.head, &r.body),
+            &SentenceClause(ref s) => (s, &[]),
+            &UnknownClause(_) => continue
+        };
+        match head {
+            &PropSentence(ref p) => check(&p.name.name, 0, p.span)?,
+            &RelSentence(ref r) => check(&r.name.name, r.args.len(), r.span)?
+        }
+        let mut lits = Vec::new();
+        for l in body.iter() {
+            push_literal(&mut lits, l);
+        }
+        for l in lits {
+            match l {
+                &PropLit(ref p) => check(&p.name.name, 0, p.span)?,
+                &RelLit(ref r) => check(&r.name.name, r.args.len(), r.span)?,
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the `knows` relations in rule bodies with `Knows` literals, returning an error for the
+/// first one that doesn't have one or two arguments
+#[cfg(feature = "gdl-iii")]
+pub fn parse_knows<T: Node>(mut node: T) -> Result<T, ParseError> {
+    let mut visitor = ParseKnows { error: None };
+    node.accept(&mut visitor);
+    match visitor.error {
+        Some(e) => Err(e),
+        None => Ok(node)
+    }
+}
+
+#[cfg(feature = "gdl-iii")]
+struct ParseKnows {
+    error: Option<ParseError>
+}
+
+#[cfg(feature = "gdl-iii")]
+impl Visitor for ParseKnows {
+    fn visit_literal(&mut self, lit: &mut Literal) {
+        let knows = match lit {
+            &mut PropLit(ref p) if p.name.name == KNOWS => {
+                Err(arity_error(KNOWS, vec![1, 2], 0, p.span))
+            }
+            &mut RelLit(ref r) if r.name.name == KNOWS => {
+                let mut args = r.args.clone();
+                match args.len() {
+                    1 | 2 => {
+                        let prop = args.pop().unwrap();
+                        Ok(Knows::new(args.pop(), prop).with_span(r.span))
+                    }
+                    n => Err(arity_error(KNOWS, vec![1, 2], n, r.span))
+                }
+            }
+            _ => return
+        };
+        match knows {
+            Ok(k) => *lit = KnowsLit(k),
+            Err(e) => {
+                if self.error.is_none() {
+                    self.error = Some(e);
+                }
+            }
+        }
+    }
+}
+
+fn check_arity(keyword: &str, expected: usize, name: &str, found: usize,
+               span: Span) -> Result<(), ParseError> {
+    if name == keyword && found != expected {
+        return Err(arity_error(name, vec![expected], found, span));
+    }
+    Ok(())
+}
+
+fn arity_error(name: &str, expected: Vec<usize>, found: usize, span: Span) -> ParseError {
+    ParseError::InvalidArity {
+        span: span,
+        name: name.to_string(),
+        expected: expected,
+        found: found
+    }
+}
+
+// Returns the heads of every clause
+fn sentences(desc: &Description) -> Vec<&Sentence> {
+    desc.clauses.iter().filter_map(|c| match c {
+        &RuleClause(ref r) => Some(&r.head),
+        &SentenceClause(ref s) => Some(s),
+        &UnknownClause(_) => None
+    }).collect()
+}
+
+// Returns every literal in the bodies of rules, including the ones inside `not` and `or`
+fn literals(desc: &Description) -> Vec<&Literal> {
+    let mut lits = Vec::new();
+    for c in desc.clauses.iter() {
+        if let &RuleClause(ref r) = c {
+            for l in r.body.iter() {
+                push_literal(&mut lits, l);
+            }
+        }
+    }
+    lits
+}
+
+fn push_literal<'a>(lits: &mut Vec<&'a Literal>, lit: &'a Literal) {
+    match lit {
+        &NotLit(ref n) => push_literal(lits, &n.lit),
+        &OrLit(ref o) => {
+            for l in o.lits.iter() {
+                push_literal(lits, l);
+            }
+        }
+        &DistinctLit(_) | &PropLit(_) | &RelLit(_) => {}
+        &KnowsLit(_) => {}
+    }
+    lits.push(lit);
+}
+
+/// Adds the literals in `lit` that aren't `not` or `or` to `lits`, each paired with whether it's
+/// negated
+pub fn atoms<'a>(lit: &'a Literal, negated: bool, lits: &mut Vec<(&'a Literal, bool)>) {
+    match lit {
+        &NotLit(ref n) => atoms(&n.lit, !negated, lits),
+        &OrLit(ref o) => {
+            for l in o.lits.iter() {
+                atoms(l, negated, lits);
+            }
+        }
+        _ => lits.push((lit, negated))
+    }
+}
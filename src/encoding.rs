@@ -0,0 +1,78 @@
+//! Decoding GDL from bytes. Game files are usually UTF-8 or ASCII, but files from older
+//! repositories can be Latin-1 or start with a byte order mark.
+
+use std::borrow::Cow;
+use std::str;
+
+use {parse_checked, Description, Diagnostic, ParseError, Span};
+
+/// Decodes `bytes` as text. A UTF-8 or UTF-16 byte order mark is removed and picks the encoding,
+/// otherwise the bytes are read as UTF-8, falling back to Latin-1 if they aren't valid UTF-8.
+/// Returns a warning `Diagnostic` if the bytes had to be guessed at or couldn't all be decoded,
+/// whose span is where the problem starts in the decoded text.
+pub fn decode_bytes<'a>(bytes: &'a [u8]) -> (Cow<'a, str>, Option<Diagnostic>) {
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return decode_utf8(&bytes[3..]);
+    }
+    if bytes.starts_with(&[0xFF, 0xFE]) {
+        return decode_utf16(&bytes[2..], u16::from_le_bytes);
+    }
+    if bytes.starts_with(&[0xFE, 0xFF]) {
+        return decode_utf16(&bytes[2..], u16::from_be_bytes);
+    }
+    decode_utf8(bytes)
+}
+
+/// Parse a GDL description from bytes, decoding them with `decode_bytes`. Spans are relative to
+/// the decoded text rather than the bytes. Returns the description along with a `Diagnostic` if
+/// the decoding was lossy or guessed.
+pub fn parse_bytes(bytes: &[u8]) -> Result<(Description, Vec<Diagnostic>), ParseError> {
+    let (gdl, diagnostic) = decode_bytes(bytes);
+    let desc = parse_checked(&gdl)?;
+    Ok((desc, diagnostic.into_iter().collect()))
+}
+
+fn decode_utf8<'a>(bytes: &'a [u8]) -> (Cow<'a, str>, Option<Diagnostic>) {
+    match str::from_utf8(bytes) {
+        Ok(s) => (Cow::Borrowed(s), None),
+        Err(e) => {
+            // Every byte before the first invalid one is ASCII or part of a valid character, and
+            // the valid characters are decoded as Latin-1 too, so the text before it grows by one
+            // byte for each byte over 0x7F
+            let valid = e.valid_up_to();
+            let lo = valid + bytes[..valid].iter().filter(|&&b| b > 0x7F).count();
+            let text: String = bytes.iter().map(|&b| b as char).collect();
+            let hi = lo + text[lo..].chars().next().map_or(0, |c| c.len_utf8());
+            let message = format!("invalid UTF-8 at byte {}, decoded as Latin-1", valid);
+            let span = Span::new(lo, hi);
+            (Cow::Owned(text), Some(Diagnostic::warning("invalid-utf8", span, message)))
+        }
+    }
+}
+
+fn decode_utf16<'a>(bytes: &'a [u8],
+                    unit: fn([u8; 2]) -> u16) -> (Cow<'a, str>, Option<Diagnostic>) {
+    let units: Vec<u16> = bytes.chunks(2).filter(|c| c.len() == 2).map(|c| unit([c[0], c[1]]))
+        .collect();
+    let mut text = String::with_capacity(units.len());
+    let mut diagnostic = None;
+    for c in ::std::char::decode_utf16(units.iter().cloned()) {
+        match c {
+            Ok(c) => text.push(c),
+            Err(_) => {
+                if diagnostic.is_none() {
+                    let span = Span::new(text.len(), text.len() + 3);
+                    let message = "invalid UTF-16, replaced with U+FFFD";
+                    diagnostic = Some(Diagnostic::warning("invalid-utf16", span, message));
+                }
+                text.push(::std::char::REPLACEMENT_CHARACTER);
+            }
+        }
+    }
+    if bytes.len() % 2 != 0 && diagnostic.is_none() {
+        let span = Span::new(text.len(), text.len());
+        let message = "odd number of bytes in UTF-16 input";
+        diagnostic = Some(Diagnostic::warning("invalid-utf16", span, message));
+    }
+    (Cow::Owned(text), diagnostic)
+}
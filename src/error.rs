@@ -0,0 +1,283 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use lexer::{Lexer, Token};
+use span::Span;
+use TextEdit;
+
+use self::ParseError::{UnbalancedParen, UnexpectedToken, UnexpectedEof, EmptyClause,
+                       InvalidVariable, InvalidArity, UnsafeVariable, RecursionRestriction, TooDeep,
+                       LimitExceeded, Io};
+
+/// An error produced when a GDL description can't be parsed. Every variant carries the span of
+/// the offending text so callers can point at it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A `(` that is never closed, or a `)` without a matching `(`
+    UnbalancedParen { span: Span },
+
+    /// A token that isn't allowed at this position, along with the tokens that would have been
+    UnexpectedToken { span: Span, found: String, expected: Vec<&'static str> },
+
+    /// The input ended while more tokens were expected
+    UnexpectedEof { span: Span, expected: Vec<&'static str> },
+
+    /// A pair of parentheses with nothing between them, like `()`
+    EmptyClause { span: Span },
+
+    /// A `?` that isn't followed by a variable name
+    InvalidVariable { span: Span },
+
+    /// A keyword relation with the wrong number of arguments, like `(sees ?r)`. `expected` lists
+    /// every number of arguments the keyword can have, in increasing order.
+    InvalidArity { span: Span, name: String, expected: Vec<usize>, found: usize },
+
+    /// A variable in the head of a rule, in a negated literal, or in a `distinct` that doesn't
+    /// appear in a positive literal in the rule's body. The span is the variable and `rule` is the
+    /// clause it's in.
+    UnsafeVariable { span: Span, name: String, rule: Span },
+
+    /// An argument of a literal that's recursive with the head of its rule that isn't ground, an
+    /// argument of the head, or a variable from a literal that isn't recursive. The span is the
+    /// argument, `name` is the literal's relation, `position` is the index of the argument
+    /// starting at 0, and `rule` is the rule it's in.
+    RecursionRestriction { span: Span, name: String, position: usize, rule: Span },
+
+    /// Parentheses nested more than `max_depth` levels deep. The span is the first `(` past the
+    /// limit.
+    TooDeep { span: Span, max_depth: usize },
+
+    /// Input that is bigger than one of the limits in `ParserOptions`. The span is the whole input
+    /// for `Limit::InputSize`, the first token of the first clause past the limit for
+    /// `Limit::Clauses`, and the atom for `Limit::AtomLength`.
+    LimitExceeded { span: Span, limit: Limit, max: usize },
+
+    /// Reading the input failed. The span is the position in the input where reading stopped.
+    Io { span: Span, kind: io::ErrorKind, message: String }
+}
+
+/// A limit on the size of the input, which is reported by `ParseError::LimitExceeded`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Limit {
+    /// The length of the input in bytes
+    InputSize,
+
+    /// The number of top-level clauses
+    Clauses,
+
+    /// The length in bytes of a single atom or variable
+    AtomLength
+}
+
+impl ParseError {
+    /// Returns the span of the text that caused the error
+    pub fn span(&self) -> Span {
+        match self {
+            &UnbalancedParen { span } => span,
+            &UnexpectedToken { span, .. } => span,
+            &UnexpectedEof { span, .. } => span,
+            &EmptyClause { span } => span,
+            &InvalidVariable { span } => span,
+            &InvalidArity { span, .. } => span,
+            &UnsafeVariable { span, .. } => span,
+            &RecursionRestriction { span, .. } => span,
+            &TooDeep { span, .. } => span,
+            &LimitExceeded { span, .. } => span,
+            &Io { span, .. } => span
+        }
+    }
+
+    /// Returns a short name for the kind of error, used as the code of its `Diagnostic`
+    pub fn code(&self) -> &'static str {
+        match self {
+            &UnbalancedParen { .. } => "unbalanced-paren",
+            &UnexpectedToken { .. } => "unexpected-token",
+            &UnexpectedEof { .. } => "unexpected-eof",
+            &EmptyClause { .. } => "empty-clause",
+            &InvalidVariable { .. } => "invalid-variable",
+            &InvalidArity { .. } => "invalid-arity",
+            &UnsafeVariable { .. } => "unsafe-variable",
+            &RecursionRestriction { .. } => "recursion-restriction",
+            &TooDeep { .. } => "too-deep",
+            &LimitExceeded { .. } => "limit-exceeded",
+            &Io { .. } => "io"
+        }
+    }
+
+    /// Returns the tokens that would have been accepted where the error occurred. This is empty
+    /// for errors where the problem isn't a single bad token.
+    pub fn expected(&self) -> &[&'static str] {
+        match self {
+            &UnexpectedToken { ref expected, .. } => expected,
+            &UnexpectedEof { ref expected, .. } => expected,
+            _ => &[]
+        }
+    }
+
+    /// Returns an edit to `src`, the text that failed to parse, that fixes the error, if there's an
+    /// obvious one. A stray `)` or a `?` without a name is removed, and `)`s are added after the
+    /// last token to close any `(`s that are still open.
+    pub fn fix(&self, src: &str) -> Option<TextEdit> {
+        match self {
+            &UnbalancedParen { span } if span.snippet(src) == ")" => Some(TextEdit::new(span, "")),
+            &UnbalancedParen { .. } => {
+                let mut open = 0;
+                let mut end = 0;
+                for (token, span) in Lexer::new(src).filter(|&(t, _)| !t.is_trivia()) {
+                    match token {
+                        Token::LParen => open += 1,
+                        Token::RParen if open > 0 => open -= 1,
+                        _ => {}
+                    }
+                    end = span.hi;
+                }
+                if open == 0 {
+                    return None;
+                }
+                Some(TextEdit::new(Span::new(end, end), ")".repeat(open)))
+            }
+            &InvalidVariable { span } if span.len() == 1 => Some(TextEdit::new(span, "")),
+            _ => None
+        }
+    }
+
+    /// Returns the 1-based line and column where the error starts in `src`
+    pub fn line_col(&self, src: &str) -> (usize, usize) {
+        self.span().start_line_col(src)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let span = self.span();
+        match self {
+            &UnbalancedParen { .. } => write!(f, "unbalanced parenthesis at byte {}", span.lo)?,
+            &UnexpectedToken { ref found, .. } => {
+                write!(f, "unexpected `{}` at byte {}", found, span.lo)?
+            }
+            &UnexpectedEof { .. } => write!(f, "unexpected end of input")?,
+            &EmptyClause { .. } => write!(f, "empty parentheses at byte {}", span.lo)?,
+            &InvalidVariable { .. } => write!(f, "invalid variable at byte {}", span.lo)?,
+            &InvalidArity { ref name, ref expected, found, .. } => {
+                let expected = expected.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+                write!(f, "`{}` takes {} arguments but has {} at byte {}", name,
+                       expected.join(" or "), found, span.lo)?
+            }
+            &UnsafeVariable { ref name, rule, .. } => {
+                write!(f, "unsafe variable `?{}` at byte {} in the rule at byte {}", name, span.lo,
+                       rule.lo)?
+            }
+            &RecursionRestriction { ref name, position, rule, .. } => {
+                write!(f, "argument {} of recursive `{}` at byte {} breaks the recursion \
+                           restriction in the rule at byte {}", position + 1, name, span.lo,
+                       rule.lo)?
+            }
+            &TooDeep { max_depth, .. } => {
+                write!(f, "nesting deeper than {} levels at byte {}", max_depth, span.lo)?
+            }
+            &LimitExceeded { limit, max, .. } => match limit {
+                Limit::InputSize => write!(f, "input is larger than {} bytes", max)?,
+                Limit::Clauses => write!(f, "more than {} clauses at byte {}", max, span.lo)?,
+                Limit::AtomLength => {
+                    write!(f, "atom longer than {} bytes at byte {}", max, span.lo)?
+                }
+            },
+            &Io { ref message, .. } => write!(f, "error reading input at byte {}: {}", span.lo,
+                                             message)?
+        }
+        let expected = self.expected();
+        if !expected.is_empty() {
+            write!(f, ", expected ")?;
+            if expected.len() > 1 {
+                write!(f, "one of ")?;
+            }
+            for (i, e) in expected.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "`{}`", e)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {
+    fn description(&self) -> &str {
+        match self {
+            &UnbalancedParen { .. } => "unbalanced parenthesis",
+            &UnexpectedToken { .. } => "unexpected token",
+            &UnexpectedEof { .. } => "unexpected end of input",
+            &EmptyClause { .. } => "empty parentheses",
+            &InvalidVariable { .. } => "invalid variable",
+            &InvalidArity { .. } => "wrong number of arguments",
+            &UnsafeVariable { .. } => "unsafe variable",
+            &RecursionRestriction { .. } => "recursion restriction broken",
+            &TooDeep { .. } => "nesting too deep",
+            &LimitExceeded { .. } => "limit exceeded",
+            &Io { .. } => "error reading input"
+        }
+    }
+}
+
+/// An error produced by `parse_file`, which can fail either reading the file or parsing it. Both
+/// cases keep the path so the error can be reported on its own.
+#[derive(Debug)]
+pub enum GdlError {
+    /// The file couldn't be read
+    Io { path: PathBuf, error: io::Error },
+
+    /// The file isn't a valid GDL description. `line` and `column` are the 1-based position of
+    /// the error in the file.
+    Parse { path: PathBuf, line: usize, column: usize, error: ParseError }
+}
+
+impl GdlError {
+    /// Returns the path of the file that caused the error
+    pub fn path(&self) -> &PathBuf {
+        match self {
+            &GdlError::Io { ref path, .. } => path,
+            &GdlError::Parse { ref path, .. } => path
+        }
+    }
+}
+
+impl Display for GdlError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            &GdlError::Io { ref path, ref error } => write!(f, "{}: {}", path.display(), error),
+            &GdlError::Parse { ref path, line, column, ref error } => {
+                write!(f, "{}:{}:{}: {}", path.display(), line, column, error)
+            }
+        }
+    }
+}
+
+impl Error for GdlError {
+    fn description(&self) -> &str {
+        match self {
+            &GdlError::Io { .. } => "error reading file",
+            &GdlError::Parse { .. } => "invalid GDL description"
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            &GdlError::Io { ref error, .. } => Some(error),
+            &GdlError::Parse { ref error, .. } => Some(error)
+        }
+    }
+}
+
+/// Converts an error from reading the input, which failed `offset` bytes into the input
+pub fn from_io_error(e: &io::Error, offset: usize) -> ParseError {
+    Io { span: Span::new(offset, offset), kind: e.kind(), message: e.to_string() }
+}
+
+/// Converts an error from parsing `gdl`, the contents of the file at `path`
+pub fn in_file(path: &Path, gdl: &str, e: ParseError) -> GdlError {
+    let (line, column) = e.line_col(gdl);
+    GdlError::Parse { path: path.to_path_buf(), line: line, column: column, error: e }
+}
@@ -0,0 +1,855 @@
+//! Exports descriptions to the syntax of other logic programming systems, so they can be analyzed
+//! with those systems' tools.
+
+use std::collections::{HashMap, HashSet};
+
+use {Clause, Constant, Description, Literal, Sentence, Term, Variable};
+use Clause::{RuleClause, SentenceClause, UnknownClause};
+use Literal::{NotLit, OrLit, DistinctLit, PropLit, RelLit};
+use Literal::KnowsLit;
+use Sentence::{PropSentence, RelSentence};
+use Term::{VarTerm, FuncTerm, ConstTerm};
+use analysis::DependencyGraph;
+use visitor::{self, Visit};
+
+/// Translates a description to answer set programming syntax accepted by clingo, with one rule or
+/// fact per line. For example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::export::to_asp;
+///
+/// let desc = parse("(<= (legal ?r noop) (role ?r) (not (true (control ?r))) (distinct ?r x))");
+/// assert_eq!(to_asp(&desc), "legal(R,noop) :- role(R), not true(control(R)), R != x.");
+/// ```
+///
+/// Disjunctions can't appear in the body of an ASP rule, so a rule with a disjunction is split
+/// into one rule for each of its cases. A negated disjunction becomes a conjunction of negations.
+///
+/// Variables are capitalized. Names that aren't valid ASP identifiers are quoted as strings where
+/// they're used as terms, and are escaped with a `c_` prefix where they name a relation or
+/// function, with `_` doubled and other characters that can't be in an identifier replaced by
+/// their code point in hex between `_`s. Variables that don't start with a lowercase letter are
+/// escaped the same way with a `V_` prefix, so different names are never translated to the same
+/// one.
+/// Clauses that couldn't be parsed are kept as comments.
+pub fn to_asp(desc: &Description) -> String {
+    let mut lines = Vec::new();
+    for clause in desc.clauses.iter() {
+        match clause {
+            &RuleClause(ref r) => {
+                let head = sentence(&r.head);
+                for body in body_cases(&r.body) {
+                    if body.is_empty() {
+                        lines.push(format!("{}.", head));
+                    } else {
+                        let body = body.iter().map(|&(l, n)| asp_literal(l, n)).collect::<Vec<_>>();
+                        lines.push(format!("{} :- {}.", head, body.join(", ")));
+                    }
+                }
+            }
+            &SentenceClause(ref s) => lines.push(format!("{}.", sentence(s))),
+            &UnknownClause(ref u) => {
+                lines.extend(u.text.lines().map(|line| format!("% {}", line)))
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+fn sentence(s: &Sentence) -> String {
+    match s {
+        &PropSentence(ref p) => name(&p.name),
+        &RelSentence(ref r) => compound(&r.name, &r.args)
+    }
+}
+
+// Prints a literal returned by `cases`, negated if `negated` is set
+fn asp_literal(lit: &Literal, negated: bool) -> String {
+    let not = if negated { "not " } else { "" };
+    match lit {
+        &DistinctLit(ref d) => {
+            let op = if negated { "=" } else { "!=" };
+            format!("{} {} {}", term(&d.term1), op, term(&d.term2))
+        }
+        &PropLit(ref p) => format!("{}{}", not, name(&p.name)),
+        &RelLit(ref r) => format!("{}{}", not, compound(&r.name, &r.args)),
+        &KnowsLit(ref k) => {
+            let mut args = k.role.iter().map(term).collect::<Vec<_>>();
+            args.push(term(&k.prop));
+            format!("{}knows({})", not, args.join(","))
+        }
+        _ => unreachable!()
+    }
+}
+
+// Splits a rule body into the cases it's true in, so it can be printed without disjunctions
+fn body_cases(body: &[Literal]) -> Vec<Vec<(&Literal, bool)>> {
+    let mut cases = vec![vec![]];
+    for lit in body.iter() {
+        cases = conjoin(&cases, &literal_cases(lit, false));
+    }
+    cases
+}
+
+// Translates `lit`, or its negation if `negated` is set, to the cases it's true in. Each case is a
+// conjunction of literals that aren't `not` or `or`, each paired with whether it's negated.
+fn literal_cases(lit: &Literal, negated: bool) -> Vec<Vec<(&Literal, bool)>> {
+    match lit {
+        &NotLit(ref n) => literal_cases(&n.lit, !negated),
+        &OrLit(ref o) if negated => {
+            let mut cases = vec![vec![]];
+            for l in o.lits.iter() {
+                cases = conjoin(&cases, &literal_cases(l, true));
+            }
+            cases
+        }
+        &OrLit(ref o) => o.lits.iter().flat_map(|l| literal_cases(l, false)).collect(),
+        _ => vec![vec![(lit, negated)]]
+    }
+}
+
+// Returns the cases where both a case of `a` and a case of `b` are true
+fn conjoin<T: Clone>(a: &[Vec<T>], b: &[Vec<T>]) -> Vec<Vec<T>> {
+    let mut cases = Vec::with_capacity(a.len() * b.len());
+    for x in a.iter() {
+        for y in b.iter() {
+            cases.push(x.iter().chain(y.iter()).cloned().collect());
+        }
+    }
+    cases
+}
+
+fn term(t: &Term) -> String {
+    match t {
+        &VarTerm(ref v) => variable(&v.name.name),
+        &FuncTerm(ref f) => compound(&f.name, &f.args),
+        &ConstTerm(ref c) => {
+            let n = &c.name;
+            let is_int = !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) &&
+                         (n.len() == 1 || !n.starts_with('0'));
+            if is_int || is_identifier(n) {
+                n.clone()
+            } else {
+                quote(n)
+            }
+        }
+    }
+}
+
+fn compound(n: &Constant, args: &[Term]) -> String {
+    let args = args.iter().map(term).collect::<Vec<_>>();
+    format!("{}({})", name(n), args.join(","))
+}
+
+fn name(c: &Constant) -> String {
+    name_str(&c.name)
+}
+
+// Translates the name of a relation or function, which has to be an identifier. Names that
+// start with `c_` are escaped too, so no other name can be translated to the same identifier.
+fn name_str(name: &str) -> String {
+    if is_identifier(name) && !name.starts_with("c_") {
+        name.to_string()
+    } else {
+        format!("c_{}", escape(name))
+    }
+}
+
+// Translates the name of a variable, which has to start with an uppercase letter. Like with
+// `name_str`, names that start with `v_` are escaped so they can't collide with escaped names.
+fn variable(name: &str) -> String {
+    if is_identifier(name) && !name.starts_with("v_") {
+        name[..1].to_ascii_uppercase() + &name[1..]
+    } else {
+        format!("V_{}", escape(name))
+    }
+}
+
+fn quote(name: &str) -> String {
+    let mut s = String::from("\"");
+    for c in name.chars() {
+        match c {
+            '"' | '\\' => {
+                s.push('\\');
+                s.push(c);
+            }
+            '\n' => s.push_str("\\n"),
+            _ => s.push(c)
+        }
+    }
+    s.push('"');
+    s
+}
+
+fn is_identifier(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_lowercase()) &&
+    s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// Replaces the characters of `s` that can't be in an identifier with their code point in hex
+// between `_`s, and doubles `_`, so that different strings are always escaped differently
+fn escape(s: &str) -> String {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        match c {
+            '_' => escaped.push_str("__"),
+            c if c.is_ascii_alphanumeric() => escaped.push(c),
+            c => escaped.push_str(&format!("_{:x}_", c as u32))
+        }
+    }
+    escaped
+}
+
+// Words that can't name a relation in Soufflé, because they're keywords or functors
+const SOUFFLE_RESERVED: &'static [&'static str] =
+    &["true", "false", "nil", "as", "autoinc", "cat", "contains", "count", "match", "max", "mean",
+      "min", "ord", "range", "strlen", "substr", "sum", "to_number", "to_string"];
+
+/// Translates a description to Soufflé Datalog, with a declaration for every relation followed
+/// by one rule or fact per line. For example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::export::to_souffle;
+///
+/// let desc = parse("(role white) (<= (legal ?r noop) (role ?r))");
+/// assert_eq!(to_souffle(&desc),
+///            ".decl role(name:symbol)\n.decl legal(role:symbol, move:symbol)\n\n\
+///             role(\"white\").\nlegal(R, \"noop\") :- role(R).");
+/// ```
+///
+/// The type of each argument of a relation or function is inferred from the terms that can appear
+/// there. Arguments that only ever hold constants are `symbol`s. Arguments that can hold function
+/// terms, including through a variable shared with another argument, are of the type `Term`, which
+/// is declared with a branch for each function and a `Sym` branch for constants. Arguments of the
+/// relations that GDL defines are named after what they hold, and other arguments are named
+/// `a0`, `a1`, and so on.
+///
+/// Disjunctions are split into separate rules like in `to_asp`, and names are translated the same
+/// way. Relations whose names are Soufflé keywords, like `true`, get a `_` appended, and a
+/// relation or function used with more than one arity gets the arity appended for each of them.
+pub fn to_souffle(desc: &Description) -> String {
+    let mut types = Types::new();
+    let clause_vars = desc.clauses.iter().map(|c| types.clause(c)).collect::<Vec<_>>();
+
+    let mut lines = Vec::new();
+    if !types.funcs.is_empty() {
+        let mut branches = vec!["Sym {s: symbol}".to_string()];
+        for (i, &(ref name, arity)) in types.funcs.iter().enumerate() {
+            let fields = (0..arity).map(|pos| {
+                let ty = if types.is_term(types.func_slots[&(i, pos)]) { "Term" } else { "symbol" };
+                format!("a{}: {}", pos, ty)
+            });
+            let fields = fields.collect::<Vec<_>>();
+            branches.push(format!("{} {{{}}}", types.func_name(name, arity), fields.join(", ")));
+        }
+        lines.push(format!(".type Term = {}", branches.join(" | ")));
+        lines.push(String::new());
+    }
+    for (i, &(ref name, arity)) in types.rels.iter().enumerate() {
+        let attrs = (0..arity).map(|pos| {
+            let ty = if types.is_term(types.slots[&(i, pos)]) { "Term" } else { "symbol" };
+            format!("{}:{}", attr_name(name, arity, pos), ty)
+        });
+        lines.push(format!(".decl {}({})", types.rel_name(name, arity),
+                           attrs.collect::<Vec<_>>().join(", ")));
+    }
+    if !types.rels.is_empty() {
+        lines.push(String::new());
+    }
+
+    for (clause, vars) in desc.clauses.iter().zip(clause_vars.iter()) {
+        let p = SoufflePrinter { types: &types, vars: vars };
+        match clause {
+            &RuleClause(ref r) => {
+                let head = p.sentence(&r.head);
+                for body in body_cases(&r.body) {
+                    if body.is_empty() {
+                        lines.push(format!("{}.", head));
+                    } else {
+                        let body = body.iter().map(|&(l, n)| p.literal(l, n)).collect::<Vec<_>>();
+                        lines.push(format!("{} :- {}.", head, body.join(", ")));
+                    }
+                }
+            }
+            &SentenceClause(ref s) => lines.push(format!("{}.", p.sentence(s))),
+            &UnknownClause(ref u) => {
+                lines.extend(u.text.lines().map(|line| format!("// {}", line)))
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+// Names the arguments of the relations that GDL defines
+fn attr_name(name: &str, arity: usize, pos: usize) -> String {
+    let names: &[&str] = match (name, arity) {
+        ("role", 1) => &["name"],
+        ("init", 1) | ("true", 1) | ("next", 1) | ("base", 1) => &["prop"],
+        ("legal", 2) | ("does", 2) | ("input", 2) => &["role", "move"],
+        ("goal", 2) => &["role", "value"],
+        ("sees", 2) => &["role", "percept"],
+        ("knows", 1) => &["prop"],
+        ("knows", 2) => &["role", "prop"],
+        _ => &[]
+    };
+    match names.get(pos) {
+        Some(name) => name.to_string(),
+        None => format!("a{}", pos)
+    }
+}
+
+// The relations and functions in a description, and the inferred types of their arguments. Every
+// argument and every variable has a slot, and slots whose terms can flow into each other
+// are unioned. A slot holds `Term`s if it's in the same set as `FUNC`.
+struct Types {
+    rels: Vec<(String, usize)>,
+    rel_indices: HashMap<(String, usize), usize>,
+    funcs: Vec<(String, usize)>,
+    func_indices: HashMap<(String, usize), usize>,
+    // The slot of each relation argument, keyed by the relation's index and the position
+    slots: HashMap<(usize, usize), usize>,
+    // The slot of each function argument, keyed the same way
+    func_slots: HashMap<(usize, usize), usize>,
+    parents: Vec<usize>
+}
+
+// The slot that every slot holding `Term`s is unioned with
+const FUNC: usize = 0;
+
+impl Types {
+    fn new() -> Types {
+        Types {
+            rels: Vec::new(),
+            rel_indices: HashMap::new(),
+            funcs: Vec::new(),
+            func_indices: HashMap::new(),
+            slots: HashMap::new(),
+            func_slots: HashMap::new(),
+            parents: vec![FUNC]
+        }
+    }
+
+    // Adds the relations, functions, and variables of a clause, and returns the slots of its
+    // variables
+    fn clause(&mut self, clause: &Clause) -> HashMap<String, usize> {
+        let mut vars = HashMap::new();
+        match clause {
+            &RuleClause(ref r) => {
+                self.sentence(&r.head, &mut vars);
+                for lit in r.body.iter() {
+                    self.literal(lit, &mut vars);
+                }
+            }
+            &SentenceClause(ref s) => self.sentence(s, &mut vars),
+            &UnknownClause(_) => {}
+        }
+        vars
+    }
+
+    fn sentence(&mut self, s: &Sentence, vars: &mut HashMap<String, usize>) {
+        match s {
+            &PropSentence(ref p) => self.relation(&p.name.name, &[], vars),
+            &RelSentence(ref r) => self.relation(&r.name.name, &r.args, vars)
+        }
+    }
+
+    fn literal(&mut self, lit: &Literal, vars: &mut HashMap<String, usize>) {
+        match lit {
+            &NotLit(ref n) => self.literal(&n.lit, vars),
+            &OrLit(ref o) => {
+                for l in o.lits.iter() {
+                    self.literal(l, vars);
+                }
+            }
+            &DistinctLit(ref d) => {
+                let slot = self.new_slot();
+                self.term(&d.term1, slot, vars);
+                self.term(&d.term2, slot, vars);
+            }
+            &PropLit(ref p) => self.relation(&p.name.name, &[], vars),
+            &RelLit(ref r) => self.relation(&r.name.name, &r.args, vars),
+            &KnowsLit(ref k) => {
+                let args = k.role.iter().chain(Some(&k.prop)).cloned().collect::<Vec<_>>();
+                self.relation("knows", &args, vars)
+            }
+        }
+    }
+
+    fn relation(&mut self, name: &str, args: &[Term], vars: &mut HashMap<String, usize>) {
+        let key = (name.to_string(), args.len());
+        let index = match self.rel_indices.get(&key) {
+            Some(&index) => index,
+            None => {
+                self.rels.push(key.clone());
+                self.rel_indices.insert(key, self.rels.len() - 1);
+                self.rels.len() - 1
+            }
+        };
+        for (pos, arg) in args.iter().enumerate() {
+            let slot = match self.slots.get(&(index, pos)) {
+                Some(&slot) => slot,
+                None => self.new_slot()
+            };
+            self.slots.insert((index, pos), slot);
+            self.term(arg, slot, vars);
+        }
+    }
+
+    fn term(&mut self, t: &Term, slot: usize, vars: &mut HashMap<String, usize>) {
+        match t {
+            &VarTerm(ref v) => {
+                match vars.get(&v.name.name) {
+                    Some(&var_slot) => self.union(var_slot, slot),
+                    None => {}
+                }
+                vars.entry(v.name.name.clone()).or_insert(slot);
+            }
+            &FuncTerm(ref f) => {
+                let key = (f.name.name.clone(), f.args.len());
+                let index = match self.func_indices.get(&key) {
+                    Some(&index) => index,
+                    None => {
+                        self.funcs.push(key.clone());
+                        self.func_indices.insert(key, self.funcs.len() - 1);
+                        self.funcs.len() - 1
+                    }
+                };
+                self.union(slot, FUNC);
+                for (pos, arg) in f.args.iter().enumerate() {
+                    let slot = match self.func_slots.get(&(index, pos)) {
+                        Some(&slot) => slot,
+                        None => self.new_slot()
+                    };
+                    self.func_slots.insert((index, pos), slot);
+                    self.term(arg, slot, vars);
+                }
+            }
+            &ConstTerm(_) => {}
+        }
+    }
+
+    fn new_slot(&mut self) -> usize {
+        self.parents.push(self.parents.len());
+        self.parents.len() - 1
+    }
+
+    fn find(&self, mut slot: usize) -> usize {
+        while self.parents[slot] != slot {
+            slot = self.parents[slot];
+        }
+        slot
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        // Keep `FUNC` as the root of its set, so `is_term` only has to compare roots
+        if a == FUNC {
+            self.parents[b] = a;
+        } else {
+            self.parents[a] = b;
+        }
+    }
+
+    fn is_term(&self, slot: usize) -> bool {
+        self.find(slot) == FUNC
+    }
+
+    fn rel_name(&self, name: &str, arity: usize) -> String {
+        let mut s = name_str(name);
+        if SOUFFLE_RESERVED.contains(&&s[..]) {
+            s.push('_');
+        }
+        if self.rels.iter().any(|&(ref n, a)| n == name && a != arity) {
+            s = format!("{}_{}", s, arity);
+        }
+        s
+    }
+
+    fn func_name(&self, name: &str, arity: usize) -> String {
+        let s = name_str(name);
+        if self.funcs.iter().any(|&(ref n, a)| n == name && a != arity) {
+            format!("{}_{}", s, arity)
+        } else {
+            s
+        }
+    }
+}
+
+// Prints the clauses of a description once their types are known
+struct SoufflePrinter<'a> {
+    types: &'a Types,
+    // The slots of the variables in the clause being printed
+    vars: &'a HashMap<String, usize>
+}
+
+impl<'a> SoufflePrinter<'a> {
+    fn sentence(&self, s: &Sentence) -> String {
+        match s {
+            &PropSentence(ref p) => self.relation(&p.name.name, &[]),
+            &RelSentence(ref r) => self.relation(&r.name.name, &r.args)
+        }
+    }
+
+    // Prints a literal returned by `cases`, negated if `negated` is set
+    fn literal(&self, lit: &Literal, negated: bool) -> String {
+        let not = if negated { "!" } else { "" };
+        match lit {
+            &DistinctLit(ref d) => {
+                let as_term = self.holds_terms(&d.term1) || self.holds_terms(&d.term2);
+                let op = if negated { "=" } else { "!=" };
+                format!("{} {} {}", self.term(&d.term1, as_term), op, self.term(&d.term2, as_term))
+            }
+            &PropLit(ref p) => format!("{}{}", not, self.relation(&p.name.name, &[])),
+            &RelLit(ref r) => format!("{}{}", not, self.relation(&r.name.name, &r.args)),
+            &KnowsLit(ref k) => {
+                let args = k.role.iter().chain(Some(&k.prop)).cloned().collect::<Vec<_>>();
+                format!("{}{}", not, self.relation("knows", &args))
+            }
+            _ => unreachable!()
+        }
+    }
+
+    fn relation(&self, name: &str, args: &[Term]) -> String {
+        let index = self.types.rel_indices[&(name.to_string(), args.len())];
+        let name = self.types.rel_name(name, args.len());
+        let args = args.iter().enumerate().map(|(pos, arg)| {
+            self.term(arg, self.types.is_term(self.types.slots[&(index, pos)]))
+        });
+        format!("{}({})", name, args.collect::<Vec<_>>().join(", "))
+    }
+
+    // Returns true if `t` is a `Term` rather than a `symbol`
+    fn holds_terms(&self, t: &Term) -> bool {
+        match t {
+            &VarTerm(ref v) => self.types.is_term(self.vars[&v.name.name]),
+            &FuncTerm(_) => true,
+            &ConstTerm(_) => false
+        }
+    }
+
+    // Prints a term, wrapping constants in `$Sym` if it's a `Term`
+    fn term(&self, t: &Term, as_term: bool) -> String {
+        match t {
+            &VarTerm(_) => term(t),
+            &FuncTerm(ref f) => {
+                let index = self.types.func_indices[&(f.name.name.clone(), f.args.len())];
+                let args = f.args.iter().enumerate().map(|(pos, arg)| {
+                    self.term(arg, self.types.is_term(self.types.func_slots[&(index, pos)]))
+                });
+                format!("${}({})", self.types.func_name(&f.name.name, f.args.len()),
+                        args.collect::<Vec<_>>().join(", "))
+            }
+            &ConstTerm(ref c) if as_term => format!("$Sym({})", quote(&c.name)),
+            &ConstTerm(ref c) => quote(&c.name)
+        }
+    }
+}
+
+/// Translates a description to the first-order form (FOF) of TPTP, so theorem provers can be used
+/// to check its properties. Each clause becomes an axiom named after its position, like
+/// `clause_1`. For example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::export::to_tptp;
+///
+/// let desc = parse("(role white) (<= (legal ?r noop) (role ?r) (not (true (control ?r))))");
+/// assert_eq!(to_tptp(&desc),
+///            "fof(clause_1, axiom, role(white)).\n\
+///             fof(clause_2, axiom, ![R]: ((role(R) & ~true(control(R))) => legal(R,noop))).");
+/// ```
+///
+/// Rules become implications from their body to their head, with their variables universally
+/// quantified, `or` becomes `|`, `not` becomes `~`, and `distinct` becomes `!=`. Note that this
+/// reads `not` as classical negation, so properties that depend on GDL's negation as failure need
+/// extra axioms, like the completion of the relations involved.
+///
+/// Variables are capitalized like in `to_asp`, and names that aren't TPTP lower words are quoted
+/// with single quotes. Clauses that couldn't be parsed are kept as comments.
+pub fn to_tptp(desc: &Description) -> String {
+    let mut lines = Vec::new();
+    for (i, clause) in desc.clauses.iter().enumerate() {
+        let formula = match clause {
+            &RuleClause(ref r) if r.body.is_empty() => tptp_sentence(&r.head),
+            &RuleClause(ref r) => {
+                let body = r.body.iter().map(tptp_literal).collect::<Vec<_>>();
+                let body = if body.len() == 1 {
+                    body.join("")
+                } else {
+                    format!("({})", body.join(" & "))
+                };
+                format!("({} => {})", body, tptp_sentence(&r.head))
+            }
+            &SentenceClause(ref s) => tptp_sentence(s),
+            &UnknownClause(ref u) => {
+                lines.extend(u.text.lines().map(|line| format!("% {}", line)));
+                continue;
+            }
+        };
+        let mut vars = Vec::new();
+        clause_vars(clause, &mut vars);
+        let formula = if vars.is_empty() {
+            formula
+        } else {
+            format!("![{}]: {}", vars.join(","), formula)
+        };
+        lines.push(format!("fof(clause_{}, axiom, {}).", i + 1, formula));
+    }
+    lines.join("\n")
+}
+
+fn tptp_sentence(s: &Sentence) -> String {
+    match s {
+        &PropSentence(ref p) => tptp_name(&p.name.name),
+        &RelSentence(ref r) => tptp_compound(&r.name, &r.args)
+    }
+}
+
+fn tptp_literal(lit: &Literal) -> String {
+    match lit {
+        &NotLit(ref n) => match *n.lit {
+            DistinctLit(_) => format!("~({})", tptp_literal(&n.lit)),
+            _ => format!("~{}", tptp_literal(&n.lit))
+        },
+        &OrLit(ref o) if o.lits.is_empty() => "$false".to_string(),
+        &OrLit(ref o) => {
+            format!("({})", o.lits.iter().map(tptp_literal).collect::<Vec<_>>().join(" | "))
+        }
+        &DistinctLit(ref d) => format!("{} != {}", tptp_term(&d.term1), tptp_term(&d.term2)),
+        &PropLit(ref p) => tptp_name(&p.name.name),
+        &RelLit(ref r) => tptp_compound(&r.name, &r.args),
+        &KnowsLit(ref k) => {
+            let mut args = k.role.iter().map(tptp_term).collect::<Vec<_>>();
+            args.push(tptp_term(&k.prop));
+            format!("knows({})", args.join(","))
+        }
+    }
+}
+
+fn tptp_term(t: &Term) -> String {
+    match t {
+        &VarTerm(_) => term(t),
+        &FuncTerm(ref f) => tptp_compound(&f.name, &f.args),
+        &ConstTerm(ref c) => tptp_name(&c.name)
+    }
+}
+
+fn tptp_compound(name: &Constant, args: &[Term]) -> String {
+    let args = args.iter().map(tptp_term).collect::<Vec<_>>();
+    format!("{}({})", tptp_name(&name.name), args.join(","))
+}
+
+fn tptp_name(name: &str) -> String {
+    if is_identifier(name) {
+        return name.to_string();
+    }
+    let mut s = String::from("'");
+    for c in name.chars() {
+        if c == '\'' || c == '\\' {
+            s.push('\\');
+        }
+        s.push(c);
+    }
+    s.push('\'');
+    s
+}
+
+// Adds the variables in `clause` to `vars` as they're printed, in the order they first appear
+fn clause_vars(clause: &Clause, vars: &mut Vec<String>) {
+    visitor::walk_clause(clause, &mut VarCollector { vars: vars });
+}
+
+struct VarCollector<'a> {
+    vars: &'a mut Vec<String>
+}
+
+impl<'a> Visit for VarCollector<'a> {
+    fn visit_variable(&mut self, var: &Variable) {
+        let name = term(&VarTerm(var.clone()));
+        if !self.vars.contains(&name) {
+            self.vars.push(name);
+        }
+    }
+}
+
+/// Prints the dependency graph of a description as GraphML, for exploring in tools like Gephi or
+/// yEd. There's a node for each relation, labeled with its name and with a `kind` attribute of
+/// `relation`, and an edge from the head of each rule to each relation in its body. Edges through
+/// an odd number of `not`s have their `negative` attribute set.
+pub fn dependency_graphml(desc: &Description) -> String {
+    let dependencies = DependencyGraph::new(desc);
+    let mut graph = Graph::new("dependencies");
+    for name in dependencies.relations() {
+        graph.node(name, "relation");
+    }
+    for d in dependencies.dependencies() {
+        let (from, to) = (graph.node(&d.from, "relation"), graph.node(&d.to, "relation"));
+        graph.edge(from, to, d.negative);
+    }
+    graph.to_graphml()
+}
+
+/// Prints the domain graph of a description as GraphML. There's a node for each argument position
+/// of each relation and function, labeled like `cell[0]`, and for each constant and function that
+/// appears in a rule head or fact. An edge into an argument position comes from each constant or
+/// function that's written there in a head, and from each argument position in a rule's body
+/// that shares a variable with it, so the domain of an argument is everything that can reach it.
+/// Nodes have a `kind` attribute of `argument`, `constant`, or `function`.
+pub fn domain_graphml(desc: &Description) -> String {
+    let mut graph = Graph::new("domains");
+    for clause in desc.clauses.iter() {
+        let (head, body) = match clause {
+            &RuleClause(ref r) => (&r.head, &r.body[..]),
+            &SentenceClause(ref s) => (s, &[][..]),
+            &UnknownClause(_) => continue
+        };
+        // The argument positions in the body that each variable appears at
+        let mut sources = HashMap::new();
+        for lit in body.iter() {
+            domain_sources(&mut graph, lit, &mut sources);
+        }
+        if let &RelSentence(ref r) = head {
+            for (i, arg) in r.args.iter().enumerate() {
+                let slot = graph.node(&format!("{}[{}]", r.name.name, i), "argument");
+                domain_targets(&mut graph, arg, slot, &sources);
+            }
+        }
+    }
+    graph.to_graphml()
+}
+
+fn domain_sources(graph: &mut Graph, lit: &Literal, sources: &mut HashMap<String, Vec<usize>>) {
+    match lit {
+        // Negated literals and `distinct` can't bind variables
+        &NotLit(_) | &DistinctLit(_) | &PropLit(_) => {}
+        &OrLit(ref o) => {
+            for l in o.lits.iter() {
+                domain_sources(graph, l, sources);
+            }
+        }
+        &RelLit(ref r) => {
+            for (i, arg) in r.args.iter().enumerate() {
+                let slot = graph.node(&format!("{}[{}]", r.name.name, i), "argument");
+                term_sources(graph, arg, slot, sources);
+            }
+        }
+        &KnowsLit(_) => {}
+    }
+}
+
+fn term_sources(graph: &mut Graph, t: &Term, slot: usize,
+                sources: &mut HashMap<String, Vec<usize>>) {
+    match t {
+        &VarTerm(ref v) => sources.entry(v.name.name.clone()).or_insert_with(Vec::new).push(slot),
+        &FuncTerm(ref f) => {
+            for (i, arg) in f.args.iter().enumerate() {
+                let slot = graph.node(&format!("{}[{}]", f.name.name, i), "argument");
+                term_sources(graph, arg, slot, sources);
+            }
+        }
+        &ConstTerm(_) => {}
+    }
+}
+
+fn domain_targets(graph: &mut Graph, t: &Term, slot: usize, sources: &HashMap<String, Vec<usize>>) {
+    match t {
+        &VarTerm(ref v) => {
+            for &source in sources.get(&v.name.name).into_iter().flat_map(|s| s.iter()) {
+                graph.edge(source, slot, false);
+            }
+        }
+        &FuncTerm(ref f) => {
+            let func = graph.node(&format!("{}/{}", f.name.name, f.args.len()), "function");
+            graph.edge(func, slot, false);
+            for (i, arg) in f.args.iter().enumerate() {
+                let arg_slot = graph.node(&format!("{}[{}]", f.name.name, i), "argument");
+                domain_targets(graph, arg, arg_slot, sources);
+            }
+        }
+        &ConstTerm(ref c) => {
+            let constant = graph.node(&c.name, "constant");
+            graph.edge(constant, slot, false);
+        }
+    }
+}
+
+// A directed graph with labeled nodes, which is printed as GraphML
+struct Graph {
+    id: &'static str,
+    // The label and kind of each node
+    nodes: Vec<(String, &'static str)>,
+    indices: HashMap<(String, &'static str), usize>,
+    // The source, target, and whether the edge is negative. Edges aren't repeated.
+    edges: Vec<(usize, usize, bool)>,
+    edge_set: HashSet<(usize, usize, bool)>
+}
+
+impl Graph {
+    fn new(id: &'static str) -> Graph {
+        Graph {
+            id: id,
+            nodes: Vec::new(),
+            indices: HashMap::new(),
+            edges: Vec::new(),
+            edge_set: HashSet::new()
+        }
+    }
+
+    // Returns the index of the node with this label and kind, adding it if it's new
+    fn node(&mut self, label: &str, kind: &'static str) -> usize {
+        let key = (label.to_string(), kind);
+        if let Some(&index) = self.indices.get(&key) {
+            return index;
+        }
+        self.nodes.push(key.clone());
+        self.indices.insert(key, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    fn edge(&mut self, source: usize, target: usize, negative: bool) {
+        if self.edge_set.insert((source, target, negative)) {
+            self.edges.push((source, target, negative));
+        }
+    }
+
+    fn to_graphml(&self) -> String {
+        let mut s = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                                  <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n\
+                                  \x20 <key id=\"label\" for=\"node\" attr.name=\"label\" \
+                                  attr.type=\"string\"/>\n\
+                                  \x20 <key id=\"kind\" for=\"node\" attr.name=\"kind\" \
+                                  attr.type=\"string\"/>\n\
+                                  \x20 <key id=\"negative\" for=\"edge\" attr.name=\"negative\" \
+                                  attr.type=\"boolean\"/>\n");
+        s.push_str(&format!("  <graph id=\"{}\" edgedefault=\"directed\">\n", self.id));
+        for (i, &(ref label, kind)) in self.nodes.iter().enumerate() {
+            s.push_str(&format!("    <node id=\"n{}\"><data key=\"label\">{}</data>\
+                                 <data key=\"kind\">{}</data></node>\n",
+                                i, escape_xml(label), kind));
+        }
+        for &(source, target, negative) in self.edges.iter() {
+            s.push_str(&format!("    <edge source=\"n{}\" target=\"n{}\">\
+                                 <data key=\"negative\">{}</data></edge>\n",
+                                source, target, negative));
+        }
+        s.push_str("  </graph>\n</graphml>\n");
+        s
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut s = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => s.push_str("&amp;"),
+            '<' => s.push_str("&lt;"),
+            '>' => s.push_str("&gt;"),
+            '"' => s.push_str("&quot;"),
+            '\'' => s.push_str("&apos;"),
+            c => s.push(c)
+        }
+    }
+    s
+}
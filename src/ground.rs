@@ -0,0 +1,438 @@
+//! Grounding, which replaces each rule of a description with its instances that don't have
+//! variables, for backends like propositional networks and SAT solvers that can't handle them
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use {Clause, Description, Function, Literal, Relation, Rule, Span, Substitution, Term, Variable};
+use Clause::{RuleClause, SentenceClause, UnknownClause};
+use Literal::{DistinctLit, NotLit, OrLit, PropLit, RelLit};
+use Literal::KnowsLit;
+use Sentence::RelSentence;
+use Term::{ConstTerm, FuncTerm, VarTerm};
+use analysis::{unbounded_rules, Domain, DomainModel, UnboundedRule};
+use dialect::atoms;
+use transform::dedup_clauses;
+use visitor::{self, Visit};
+
+/// Options for `ground_with`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroundOptions {
+    /// Only keep the instances whose positive literals can all be true, ignoring `not`s, starting
+    /// from the facts. `true` can be true if `init` or `next` can, and `does` if `legal` can. The
+    /// domains of the arguments can include values that never appear together, so this removes
+    /// instances that can never apply. Off by default.
+    pub relevant_only: bool,
+
+    /// The most rule instances that will be made. Grounding stops with
+    /// `GroundError::TooManyInstances` past the limit. Unlimited by default.
+    pub max_instances: Option<usize>
+}
+
+impl Default for GroundOptions {
+    fn default() -> GroundOptions {
+        GroundOptions { relevant_only: false, max_instances: None }
+    }
+}
+
+impl GroundOptions {
+    pub fn new() -> GroundOptions {
+        GroundOptions::default()
+    }
+}
+
+/// The reasons a description can't be grounded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroundError {
+    /// A rule that can build ever larger terms, so it has infinitely many instances
+    Unbounded(UnboundedRule),
+
+    /// A function whose arguments can be terms built with the function itself, so it can be
+    /// nested without limit
+    InfiniteDomain { function: (String, usize) },
+
+    /// A variable that isn't in a positive literal of its rule, so it doesn't have a domain
+    UnsafeVariable { name: String, span: Span, clause: Span },
+
+    /// Grounding would make more than `limit` rule instances
+    TooManyInstances { limit: usize }
+}
+
+impl Display for GroundError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            &GroundError::Unbounded(ref u) => write!(f, "{}", u),
+            &GroundError::InfiniteDomain { function: (ref name, arity) } => {
+                write!(f, "the function `{}/{}` can be nested in itself without limit", name,
+                       arity)
+            }
+            &GroundError::UnsafeVariable { ref name, span, clause } => {
+                write!(f, "variable `?{}` at byte {} in the clause at byte {} isn't in a positive \
+                           literal, so it can't be grounded", name, span.lo, clause.lo)
+            }
+            &GroundError::TooManyInstances { limit } => {
+                write!(f, "grounding makes more than {} rule instances", limit)
+            }
+        }
+    }
+}
+
+impl Error for GroundError {
+    fn description(&self) -> &str {
+        match self {
+            &GroundError::Unbounded(_) => "unbounded rule",
+            &GroundError::InfiniteDomain { .. } => "infinite domain",
+            &GroundError::UnsafeVariable { .. } => "unsafe variable",
+            &GroundError::TooManyInstances { .. } => "too many rule instances"
+        }
+    }
+}
+
+/// Grounds `desc` with the default options. Panics if it can't be grounded.
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::ground::ground;
+///
+/// let desc = parse("(index 1) (index 2) \
+///                   (<= (pair ?x ?y) (index ?x) (index ?y) (distinct ?x ?y))");
+/// assert_eq!(ground(&desc).to_string(),
+///            "(index 1) (index 2) (<= (pair 1 2) (index 1) (index 2)) \
+///             (<= (pair 2 1) (index 2) (index 1))");
+/// ```
+pub fn ground(desc: &Description) -> Description {
+    match ground_checked(desc) {
+        Ok(d) => d,
+        Err(e) => panic!("{}", e)
+    }
+}
+
+/// Grounds `desc` with the default options, returning an error if it can't be grounded
+pub fn ground_checked(desc: &Description) -> Result<Description, GroundError> {
+    ground_with(desc, &GroundOptions::default())
+}
+
+/// Returns `desc` with each rule replaced by its instances, in order. The values of each variable
+/// come from the domains of `analysis::DomainModel` for the positions it's at in the rule's
+/// positive literals. `distinct`s between ground terms are evaluated, so instances where one is
+/// false are left out, and ones that are true are removed from the bodies. Duplicate clauses are
+/// removed with `transform::dedup_clauses`. Clauses that couldn't be parsed are kept as they are.
+pub fn ground_with(desc: &Description, options: &GroundOptions)
+                   -> Result<Description, GroundError> {
+    if let Some(u) = unbounded_rules(desc).into_iter().next() {
+        return Err(GroundError::Unbounded(u));
+    }
+    let model = DomainModel::new(desc);
+    let mut clauses = Vec::new();
+    let mut count = 0;
+    for c in desc.clauses.iter() {
+        match c {
+            &RuleClause(ref r) => {
+                for instance in instances(r, &model)? {
+                    count += 1;
+                    if let Some(limit) = options.max_instances {
+                        if count > limit {
+                            return Err(GroundError::TooManyInstances { limit: limit });
+                        }
+                    }
+                    clauses.push(RuleClause(instance));
+                }
+            }
+            &SentenceClause(_) => {
+                if let Some(v) = variables(c).into_iter().next() {
+                    return Err(GroundError::UnsafeVariable { name: v.name.name, span: v.span,
+                                                             clause: c.span() });
+                }
+                clauses.push(c.clone());
+            }
+            &UnknownClause(_) => clauses.push(c.clone())
+        }
+    }
+    if options.relevant_only {
+        clauses = relevant(clauses);
+    }
+    Ok(dedup_clauses(&Description::new(clauses)))
+}
+
+/// Returns `desc` with `base` facts for every fluent that can be true and `input` facts for every
+/// move each role might make, for backends like propositional networks that need them. For
+/// example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::ground::add_base_and_input;
+///
+/// let desc = parse("(role white) (init (step 1)) (<= (next (step 2)) (true (step 1)))\n\
+///                   (<= (legal white noop) (true (step 1)))");
+/// assert_eq!(add_base_and_input(&desc).unwrap().to_string(),
+///            "(role white) (init (step 1)) (<= (next (step 2)) (true (step 1))) \
+///             (<= (legal white noop) (true (step 1))) (base (step 1)) (base (step 2)) \
+///             (input white noop)");
+/// ```
+///
+/// The fluents and moves come from the domains of `true` and of the second argument of `legal`
+/// in `analysis::DomainModel`, so there can be some that never actually happen, and every role
+/// gets every move. If `desc` already has `base` or `input` clauses, it's assumed they're
+/// complete and no more of that kind are added. Returns `GroundError::InfiniteDomain` if there
+/// are infinitely many fluents or moves.
+pub fn add_base_and_input(desc: &Description) -> Result<Description, GroundError> {
+    let model = DomainModel::new(desc);
+    let defines = |name: &str| {
+        desc.clauses.iter().any(|c| {
+            match c {
+                &RuleClause(ref r) => r.head.name().name == name,
+                &SentenceClause(ref s) => s.name().name == name,
+                &UnknownClause(_) => false
+            }
+        })
+    };
+
+    let mut clauses = desc.clauses.clone();
+    if !defines("base") {
+        for fluent in terms(model.domain_of("true", 1, 0), &model, &mut Vec::new())? {
+            clauses.push(SentenceClause(RelSentence(Relation::new("base", vec![fluent]))));
+        }
+    }
+    if !defines("input") {
+        let roles = terms(model.domain_of("role", 1, 0), &model, &mut Vec::new())?;
+        let moves = terms(model.domain_of("legal", 2, 1), &model, &mut Vec::new())?;
+        for role in roles.iter() {
+            for m in moves.iter() {
+                let input = Relation::new("input", vec![role.clone(), m.clone()]);
+                clauses.push(SentenceClause(RelSentence(input)));
+            }
+        }
+    }
+    Ok(Description::new(clauses))
+}
+
+// Returns the instances of `rule` where every ground `distinct` is true
+fn instances(rule: &Rule, model: &DomainModel) -> Result<Vec<Rule>, GroundError> {
+    // Variables in the positive literals of the body can only have the values that are in the
+    // domains of all of their positions, and ones that are only in `or`s can have the values from
+    // any of theirs
+    let mut domains = Vec::new();
+    for l in rule.body.iter() {
+        if let &RelLit(ref r) = l {
+            bind_relation(r, model, false, &mut domains)?;
+        }
+    }
+    let bound = domains.len();
+    for l in rule.body.iter() {
+        let mut lits = Vec::new();
+        if let &OrLit(_) = l {
+            atoms(l, false, &mut lits);
+        }
+        for (l, negated) in lits {
+            if let (&RelLit(ref r), false) = (l, negated) {
+                let mut values = domains.split_off(bound);
+                bind_relation(r, model, true, &mut values)?;
+                values.retain(|&(ref name, _)| {
+                    !domains.iter().any(|&(ref n, _): &(String, Vec<Term>)| n == name)
+                });
+                domains.extend(values);
+            }
+        }
+    }
+    for v in variables(&RuleClause(rule.clone())) {
+        if !domains.iter().any(|&(ref name, _)| *name == v.name.name) {
+            return Err(GroundError::UnsafeVariable { name: v.name.name, span: v.span,
+                                                     clause: rule.span });
+        }
+    }
+
+    let mut instances = Vec::new();
+    let mut choice = vec![0; domains.len()];
+    if domains.iter().any(|&(_, ref values)| values.is_empty()) {
+        return Ok(instances);
+    }
+    loop {
+        let subst = domains.iter().zip(choice.iter()).map(|(&(ref name, ref values), &i)| {
+            (Variable::new(&name[..]), values[i].clone())
+        }).collect::<Substitution>();
+        let instance = subst.apply_rule(rule);
+        if let Some(body) = simplify(&instance.body) {
+            instances.push(Rule::new(instance.head, body).with_span(rule.span));
+        }
+
+        // Moves to the next combination of values, with the last variable changing fastest
+        let mut i = domains.len();
+        loop {
+            if i == 0 {
+                return Ok(instances);
+            }
+            i -= 1;
+            choice[i] += 1;
+            if choice[i] < domains[i].1.len() {
+                break;
+            }
+            choice[i] = 0;
+        }
+    }
+}
+
+fn bind_relation(r: &Relation, model: &DomainModel, union: bool,
+                 domains: &mut Vec<(String, Vec<Term>)>) -> Result<(), GroundError> {
+    for (i, arg) in r.args.iter().enumerate() {
+        bind(arg, model.domain_of(&r.name.name, r.args.len(), i), model, union, domains)?;
+    }
+    Ok(())
+}
+
+// Adds the values in `domain` to the variables in `term`, or narrows them to the ones in `domain`
+// if `union` is false. Variables that haven't been seen before get all the values in `domain`.
+fn bind(term: &Term, domain: Option<&Domain>, model: &DomainModel, union: bool,
+        domains: &mut Vec<(String, Vec<Term>)>) -> Result<(), GroundError> {
+    match term {
+        &VarTerm(ref v) => {
+            let values = terms(domain, model, &mut Vec::new())?;
+            match domains.iter().position(|&(ref name, _)| *name == v.name.name) {
+                Some(i) if union => {
+                    let existing = domains[i].1.iter().cloned().collect::<HashSet<_>>();
+                    domains[i].1.extend(values.into_iter().filter(|t| !existing.contains(t)));
+                }
+                Some(i) => {
+                    let values = values.into_iter().collect::<HashSet<_>>();
+                    domains[i].1.retain(|t| values.contains(t));
+                }
+                None => domains.push((v.name.name.clone(), values))
+            }
+        }
+        &FuncTerm(ref f) => {
+            for (i, arg) in f.args.iter().enumerate() {
+                let domain = model.function_domain(&f.name.name, f.args.len(), i);
+                bind(arg, domain, model, union, domains)?;
+            }
+        }
+        &ConstTerm(_) => {}
+    }
+    Ok(())
+}
+
+// Returns the ground terms in `domain`. `functions` are the functions whose arguments are being
+// found, to catch functions nested in themselves.
+fn terms(domain: Option<&Domain>, model: &DomainModel, functions: &mut Vec<(String, usize)>)
+         -> Result<Vec<Term>, GroundError> {
+    let domain = match domain {
+        Some(d) => d,
+        None => return Ok(Vec::new())
+    };
+    let mut values = domain.constants.iter().map(|c| ConstTerm(c.clone())).collect::<Vec<_>>();
+    for &(ref name, arity) in domain.functions.iter() {
+        let function = (name.clone(), arity);
+        if functions.contains(&function) {
+            return Err(GroundError::InfiniteDomain { function: function });
+        }
+        functions.push(function);
+        let mut args = vec![vec![]];
+        for i in 0..arity {
+            let values = terms(model.function_domain(name, arity, i), model, functions)?;
+            args = args.iter().flat_map(|a: &Vec<Term>| {
+                values.iter().map(move |v| a.iter().chain(Some(v)).cloned().collect())
+            }).collect();
+        }
+        functions.pop();
+        values.extend(args.into_iter().map(|a| FuncTerm(Function::new(&name[..], a))));
+    }
+    Ok(values)
+}
+
+// Removes the `distinct`s between ground terms that are true, or returns `None` if one is false
+fn simplify(body: &[Literal]) -> Option<Vec<Literal>> {
+    let mut simplified = Vec::new();
+    for l in body.iter() {
+        let value = match l {
+            &DistinctLit(ref d) if is_ground(&d.term1) && is_ground(&d.term2) => {
+                Some(d.term1 != d.term2)
+            }
+            &NotLit(ref n) => match *n.lit {
+                DistinctLit(ref d) if is_ground(&d.term1) && is_ground(&d.term2) => {
+                    Some(d.term1 == d.term2)
+                }
+                _ => None
+            },
+            _ => None
+        };
+        match value {
+            Some(true) => {}
+            Some(false) => return None,
+            None => simplified.push(l.clone())
+        }
+    }
+    Some(simplified)
+}
+
+fn is_ground(t: &Term) -> bool {
+    match t {
+        &VarTerm(_) => false,
+        &FuncTerm(ref f) => f.args.iter().all(is_ground),
+        &ConstTerm(_) => true
+    }
+}
+
+// Keeps the clauses whose positive literals can all be true, as described by
+// `GroundOptions::relevant_only`
+fn relevant(clauses: Vec<Clause>) -> Vec<Clause> {
+    let mut produced = HashSet::new();
+    let mut kept = vec![false; clauses.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (i, c) in clauses.iter().enumerate() {
+            let keep = match c {
+                _ if kept[i] => continue,
+                &RuleClause(ref r) => r.body.iter().all(|l| may_hold(l, &produced)),
+                _ => true
+            };
+            if keep {
+                kept[i] = true;
+                changed = true;
+                match c {
+                    &RuleClause(ref r) => produced.insert(r.head.to_string()),
+                    &SentenceClause(ref s) => produced.insert(s.to_string()),
+                    &UnknownClause(_) => false
+                };
+            }
+        }
+    }
+    clauses.into_iter().zip(kept).filter(|&(_, k)| k).map(|(c, _)| c).collect()
+}
+
+// Returns false if `l` can't hold when only the sentences in `produced` can
+fn may_hold(l: &Literal, produced: &HashSet<String>) -> bool {
+    match l {
+        &PropLit(ref p) => produced.contains(&p.to_string()),
+        &RelLit(ref r) => {
+            let args = r.args.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(" ");
+            match (&r.name.name[..], r.args.len()) {
+                ("true", 1) => {
+                    produced.contains(&format!("(init {})", args)) ||
+                        produced.contains(&format!("(next {})", args))
+                }
+                ("does", 2) => produced.contains(&format!("(legal {})", args)),
+                _ => produced.contains(&r.to_string())
+            }
+        }
+        &OrLit(ref o) => o.lits.iter().any(|l| may_hold(l, produced)),
+        &NotLit(_) | &DistinctLit(_) => true,
+        &KnowsLit(_) => true
+    }
+}
+
+fn variables(clause: &Clause) -> Vec<Variable> {
+    let mut vars = Variables { vars: Vec::new() };
+    visitor::walk_clause(clause, &mut vars);
+    vars.vars
+}
+
+// Collects the variables in a clause, in the order they're visited
+struct Variables {
+    vars: Vec<Variable>
+}
+
+impl Visit for Variables {
+    fn visit_variable(&mut self, var: &Variable) {
+        self.vars.push(var.clone());
+    }
+}
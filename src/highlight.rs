@@ -0,0 +1,82 @@
+//! Syntax highlighting for descriptions, for terminals with ANSI colors and for HTML. Keywords,
+//! variables, and constants are each colored differently. Descriptions are laid out by
+//! `printer::PrettyPrinter` before they're highlighted.
+
+use Description;
+use dialect;
+use lexer::{Lexer, Token};
+use printer::PrettyPrinter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Keyword,
+    Variable,
+    Constant,
+    Plain
+}
+
+/// Prints `desc` with ANSI escape codes that color keywords in bold magenta, variables in cyan, and
+/// constants in green
+pub fn to_ansi(desc: &Description) -> String {
+    highlight(&PrettyPrinter::new().print(desc), |s, class, text| {
+        let color = match class {
+            Class::Keyword => "1;35",
+            Class::Variable => "36",
+            Class::Constant => "32",
+            Class::Plain => return s.push_str(text)
+        };
+        s.push_str(&format!("\x1b[{}m{}\x1b[0m", color, text));
+    })
+}
+
+/// Prints `desc` as HTML, with keywords, variables, and constants wrapped in `<span>`s with the
+/// classes `gdl-keyword`, `gdl-variable`, and `gdl-constant`. The output should be put in a
+/// `<pre>` element to keep its line breaks and indentation.
+pub fn to_html(desc: &Description) -> String {
+    highlight(&PrettyPrinter::new().print(desc), |s, class, text| {
+        let class = match class {
+            Class::Keyword => "gdl-keyword",
+            Class::Variable => "gdl-variable",
+            Class::Constant => "gdl-constant",
+            Class::Plain => return push_escaped(s, text)
+        };
+        s.push_str(&format!("<span class=\"{}\">", class));
+        push_escaped(s, text);
+        s.push_str("</span>");
+    })
+}
+
+// Classifies the tokens of `gdl` and passes each one to `write` along with the output. Keywords are
+// the atoms that name a relation or literal, as in `printer::format`, and `<=`.
+fn highlight<F>(gdl: &str, mut write: F) -> String where F: FnMut(&mut String, Class, &str) {
+    let mut s = String::with_capacity(gdl.len() * 2);
+    let mut after_paren = false;
+    for (token, span) in Lexer::new(gdl) {
+        let class = match token {
+            Token::Atom("<=") => Class::Keyword,
+            Token::Atom(a) if (after_paren || a == "terminal") && dialect::is_keyword(a) => {
+                Class::Keyword
+            }
+            Token::Atom(_) => Class::Constant,
+            Token::Variable(_) => Class::Variable,
+            _ => Class::Plain
+        };
+        write(&mut s, class, span.snippet(gdl));
+        if !token.is_trivia() {
+            after_paren = token == Token::LParen;
+        }
+    }
+    s
+}
+
+fn push_escaped(s: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '<' => s.push_str("&lt;"),
+            '>' => s.push_str("&gt;"),
+            '&' => s.push_str("&amp;"),
+            '"' => s.push_str("&quot;"),
+            c => s.push(c)
+        }
+    }
+}
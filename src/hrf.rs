@@ -0,0 +1,684 @@
+//! Support for the infix syntax used by the Stanford GGP course, sometimes called HRF (human
+//! readable format). In this syntax relations and functions are written as `name(arg1,arg2)`,
+//! variables start with an uppercase letter, rules are written `head :- lit1 & lit2`, negation is
+//! written `~lit`, and a disjunction is written `(lit1 | lit2)`. For example,
+//!
+//! ```text
+//! legal(W,mark(X,Y)) :- true(cell(X,Y,b)) & true(control(W))
+//! ```
+//!
+//! is the same rule as `(<= (legal ?w (mark ?x ?y)) (true (cell ?x ?y b)) (true (control ?w)))`.
+//! Body literals can also be separated by commas, and `%` starts a comment.
+//!
+//! Variable names are converted by changing the case of their first letter, so `?w` is printed as
+//! `W` and `W` is parsed as `?w`. A variable can also be written like in GDL, so `?W` is the
+//! variable `?W`, which is how variables that don't start with a lowercase letter are printed.
+//! Constants can be quoted with `'`, like `'White'` or `'a b'`, which is how constants that
+//! aren't identifiers or that start with an uppercase letter are printed. There are no string
+//! literals, so GDL strings are printed as quoted constants too. Rules without a body are printed
+//! as facts.
+
+use std::collections::HashSet;
+
+use {Clause, Constant, Description, Distinct, Function, Literal, Not, Or, ParseError, Proposition,
+     Relation, Rule, Sentence, Span, Term, Variable, DEFAULT_MAX_DEPTH};
+use Clause::{RuleClause, SentenceClause, UnknownClause};
+use Literal::{NotLit, OrLit, DistinctLit, PropLit, RelLit};
+use Sentence::{PropSentence, RelSentence};
+use Term::{VarTerm, FuncTerm, ConstTerm};
+
+use self::Tok::{Ident, Quoted, Var, LParen, RParen, Comma, Amp, Bar, Tilde, Implies, Neq, Dot,
+                Eof};
+
+/// The infix syntaxes that share this parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    Hrf,
+    Prolog
+}
+
+/// Parse a description written in the infix syntax
+pub fn parse_hrf(src: &str) -> Result<Description, ParseError> {
+    parse_infix(src, Syntax::Hrf)
+}
+
+/// Parse a description written in either of the infix syntaxes
+pub fn parse_infix(src: &str, syntax: Syntax) -> Result<Description, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(src, syntax)?,
+        pos: 0,
+        depth: 0,
+        anonymous: 0,
+        names: HashSet::new(),
+        syntax: syntax,
+        src: src
+    };
+    let mut clauses = Vec::new();
+    while parser.peek() != Eof {
+        clauses.push(parser.clause()?);
+    }
+    #[cfg(feature = "gdl-iii")]
+    let clauses = clauses.into_iter().map(::dialect::parse_knows).collect::<Result<_, _>>()?;
+    Ok(Description::new(clauses))
+}
+
+/// Print a description in the infix syntax, with one clause per line
+pub fn to_hrf(desc: &Description) -> String {
+    let mut s = String::new();
+    for (i, clause) in desc.clauses.iter().enumerate() {
+        if i > 0 {
+            s.push('\n');
+        }
+        write_clause(&mut s, clause);
+    }
+    s
+}
+
+fn write_clause(s: &mut String, clause: &Clause) {
+    match clause {
+        &RuleClause(ref r) => {
+            write_sentence(s, &r.head);
+            for (i, lit) in r.body.iter().enumerate() {
+                s.push_str(if i == 0 { " :- " } else { " & " });
+                write_literal(s, lit);
+            }
+        }
+        &SentenceClause(ref sentence) => write_sentence(s, sentence),
+        &UnknownClause(ref u) => s.push_str(&u.text)
+    }
+}
+
+fn write_sentence(s: &mut String, sentence: &Sentence) {
+    match sentence {
+        &PropSentence(ref p) => write_constant(s, &p.name),
+        &RelSentence(ref r) => write_compound(s, &r.name, &r.args)
+    }
+}
+
+fn write_literal(s: &mut String, lit: &Literal) {
+    match lit {
+        &NotLit(ref n) => {
+            s.push('~');
+            write_literal(s, &n.lit);
+        }
+        &OrLit(ref o) => {
+            s.push('(');
+            for (i, l) in o.lits.iter().enumerate() {
+                if i > 0 {
+                    s.push_str(" | ");
+                }
+                write_literal(s, l);
+            }
+            s.push(')');
+        }
+        &DistinctLit(ref d) => {
+            s.push_str("distinct(");
+            write_term(s, &d.term1);
+            s.push(',');
+            write_term(s, &d.term2);
+            s.push(')');
+        }
+        &PropLit(ref p) => write_constant(s, &p.name),
+        &RelLit(ref r) => write_compound(s, &r.name, &r.args),
+        &::Literal::KnowsLit(ref k) => {
+            s.push_str("knows(");
+            if let Some(ref role) = k.role {
+                write_term(s, role);
+                s.push(',');
+            }
+            write_term(s, &k.prop);
+            s.push(')');
+        }
+    }
+}
+
+fn write_term(s: &mut String, term: &Term) {
+    match term {
+        &VarTerm(ref v) => {
+            let name = &v.name.name;
+            if name.starts_with(|c: char| c.is_ascii_lowercase()) && is_identifier(name) {
+                let mut chars = name.chars();
+                s.extend(chars.next().unwrap().to_uppercase());
+                s.push_str(chars.as_str());
+            } else {
+                s.push('?');
+                s.push_str(name);
+            }
+        }
+        &FuncTerm(ref f) => write_compound(s, &f.name, &f.args),
+        &ConstTerm(ref c) => write_constant(s, c)
+    }
+}
+
+fn write_compound(s: &mut String, name: &Constant, args: &[Term]) {
+    write_constant(s, name);
+    s.push('(');
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            s.push(',');
+        }
+        write_term(s, arg);
+    }
+    s.push(')');
+}
+
+// Writes a constant, quoting it if it would be read back as something else
+fn write_constant(s: &mut String, c: &Constant) {
+    let name = &c.name;
+    if is_identifier(name) && !name.starts_with(|c: char| c.is_uppercase()) {
+        s.push_str(name);
+        return;
+    }
+    s.push('\'');
+    for c in name.chars() {
+        if c == '\'' || c == '\\' {
+            s.push('\\');
+        }
+        s.push(c);
+    }
+    s.push('\'');
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii() && (c.is_alphanumeric() || c == '_'))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tok {
+    Ident,
+    Quoted,
+    Var,
+    LParen,
+    RParen,
+    Comma,
+    Amp,
+    Bar,
+    Tilde,
+    Implies,
+    Neq,
+    Dot,
+    Eof
+}
+
+fn describe(tok: Tok, syntax: Syntax) -> &'static str {
+    match tok {
+        Ident | Quoted => "identifier",
+        Var => "variable",
+        LParen => "(",
+        RParen => ")",
+        Comma => ",",
+        Amp => "&",
+        Bar if syntax == Syntax::Prolog => ";",
+        Bar => "|",
+        Tilde if syntax == Syntax::Prolog => "\\+",
+        Tilde => "~",
+        Implies => ":-",
+        Neq => "\\=",
+        Dot => ".",
+        Eof => "end of input"
+    }
+}
+
+fn tokenize(src: &str, syntax: Syntax) -> Result<Vec<(Tok, Span)>, ParseError> {
+    let prolog = syntax == Syntax::Prolog;
+    let mut tokens = Vec::new();
+    let mut chars = src.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, c)| c);
+        let tok = match c {
+            ' ' | '\t' | '\n' | '\r' => continue,
+            '%' => {
+                while chars.peek().map_or(false, |&(_, c)| c != '\n') {
+                    chars.next();
+                }
+                continue;
+            }
+            '/' if prolog && next == Some('*') => {
+                let end = match src[i + 2..].find("*/") {
+                    Some(n) => i + 2 + n + 2,
+                    None => {
+                        let span = Span::new(src.len(), src.len());
+                        return Err(ParseError::UnexpectedEof { span: span, expected: vec!["*/"] });
+                    }
+                };
+                while chars.peek().map_or(false, |&(j, _)| j < end) {
+                    chars.next();
+                }
+                continue;
+            }
+            '(' => LParen,
+            ')' => RParen,
+            ',' => Comma,
+            '&' if !prolog => Amp,
+            '|' if !prolog => Bar,
+            ';' if prolog => Bar,
+            '~' if !prolog => Tilde,
+            '.' if prolog => Dot,
+            ':' if next == Some('-') => {
+                chars.next();
+                if prolog && tokens.last().map_or(true, |&(t, _)| t == Dot) {
+                    // A directive like `:- dynamic cell/3.` is where a clause would start. It
+                    // means nothing in GDL, so it's skipped up to the `.` that ends it.
+                    let mut closed = false;
+                    while let Some((_, c)) = chars.next() {
+                        let at_end = chars.peek().map_or(true, |&(_, c)| c.is_whitespace());
+                        if c == '.' && at_end {
+                            closed = true;
+                            break;
+                        }
+                    }
+                    if !closed {
+                        let span = Span::new(src.len(), src.len());
+                        return Err(ParseError::UnexpectedEof { span: span, expected: vec!["."] });
+                    }
+                    continue;
+                }
+                tokens.push((Implies, Span::new(i, i + 2)));
+                continue;
+            }
+            '\\' if prolog && (next == Some('+') || next == Some('=')) => {
+                chars.next();
+                tokens.push((if next == Some('+') { Tilde } else { Neq }, Span::new(i, i + 2)));
+                continue;
+            }
+            '\'' => {
+                // A quote is escaped by doubling it or with a backslash
+                let mut end = None;
+                while let Some((j, c)) = chars.next() {
+                    if c == '\\' {
+                        chars.next();
+                    } else if c == '\'' {
+                        if chars.peek().map_or(false, |&(_, c)| c == '\'') {
+                            chars.next();
+                        } else {
+                            end = Some(j + 1);
+                            break;
+                        }
+                    }
+                }
+                match end {
+                    Some(end) => {
+                        tokens.push((Quoted, Span::new(i, end)));
+                        continue;
+                    }
+                    None => {
+                        let span = Span::new(src.len(), src.len());
+                        return Err(ParseError::UnexpectedEof { span: span, expected: vec!["'"] });
+                    }
+                }
+            }
+            '?' if next.map_or(false, |c| c.is_ascii() && (c.is_alphanumeric() || c == '_')) => {
+                // A variable written like in GDL, whose name is kept as it is
+                let mut end = i + 1;
+                while let Some(&(j, c)) = chars.peek() {
+                    if !(c.is_ascii() && (c.is_alphanumeric() || c == '_')) {
+                        break;
+                    }
+                    end = j + 1;
+                    chars.next();
+                }
+                tokens.push((Var, Span::new(i, end)));
+                continue;
+            }
+            c if c.is_ascii() && (c.is_alphanumeric() || c == '_') => {
+                let mut end = i + 1;
+                while let Some(&(j, c)) = chars.peek() {
+                    if !(c.is_ascii() && (c.is_alphanumeric() || c == '_')) {
+                        break;
+                    }
+                    end = j + 1;
+                    chars.next();
+                }
+                tokens.push((Ident, Span::new(i, end)));
+                continue;
+            }
+            c => {
+                let span = Span::new(i, i + c.len_utf8());
+                return Err(ParseError::UnexpectedToken {
+                    span: span,
+                    found: span.snippet(src).to_string(),
+                    expected: Vec::new()
+                });
+            }
+        };
+        tokens.push((tok, Span::new(i, i + 1)));
+    }
+    tokens.push((Eof, Span::new(src.len(), src.len())));
+    Ok(tokens)
+}
+
+// Removes the quotes and escapes from a Prolog quoted atom
+fn unquote(text: &str) -> String {
+    let mut s = String::new();
+    let mut chars = text[1..text.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' | '\'' => s.extend(chars.next()),
+            c => s.push(c)
+        }
+    }
+    s
+}
+
+// Converts a variable written with an uppercase letter or an underscore to its name in GDL
+fn variable_name(text: &str) -> String {
+    let mut chars = text.chars();
+    let mut name: String = chars.next().unwrap().to_lowercase().collect();
+    name.push_str(chars.as_str());
+    name
+}
+
+struct Parser<'a> {
+    tokens: Vec<(Tok, Span)>,
+    pos: usize,
+    depth: usize,
+    // The number of `_` variables seen so far in the clause, used to give each one a different name
+    anonymous: usize,
+    // The names of the other variables in the clause, which `_` variables can't be named
+    names: HashSet<String>,
+    syntax: Syntax,
+    src: &'a str
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Tok {
+        self.tokens[self.pos].0
+    }
+
+    // Returns the token after the next one
+    fn peek2(&self) -> Tok {
+        self.tokens.get(self.pos + 1).map_or(Eof, |t| t.0)
+    }
+
+    fn span(&self) -> Span {
+        self.tokens[self.pos].1
+    }
+
+    fn next(&mut self) -> (Tok, Span) {
+        let t = self.tokens[self.pos];
+        if t.0 != Eof {
+            self.pos += 1;
+        }
+        t
+    }
+
+    fn error(&self, expected: Vec<&'static str>) -> ParseError {
+        let span = self.span();
+        if self.peek() == Eof {
+            ParseError::UnexpectedEof { span: span, expected: expected }
+        } else {
+            ParseError::UnexpectedToken {
+                span: span,
+                found: span.snippet(self.src).to_string(),
+                expected: expected
+            }
+        }
+    }
+
+    fn expect(&mut self, tok: Tok) -> Result<Span, ParseError> {
+        if self.peek() == tok {
+            Ok(self.next().1)
+        } else {
+            Err(self.error(vec![describe(tok, self.syntax)]))
+        }
+    }
+
+    // Called before parsing something nested, so deeply nested input fails cleanly instead of
+    // overflowing the stack. The caller decrements `depth` when it's done.
+    fn enter(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > DEFAULT_MAX_DEPTH {
+            return Err(ParseError::TooDeep { span: self.span(), max_depth: DEFAULT_MAX_DEPTH });
+        }
+        Ok(())
+    }
+
+    // The end of the previous token, for computing the span of a node
+    fn prev_end(&self) -> usize {
+        self.tokens[self.pos - 1].1.hi
+    }
+
+    // Returns true if the next token is a variable. Variables start with an uppercase letter, or
+    // in Prolog also with an underscore, or are written with a `?`.
+    fn at_variable(&self) -> bool {
+        let text = self.span().snippet(self.src);
+        self.peek() == Var ||
+            self.peek() == Ident && (text.starts_with(|c: char| c.is_uppercase()) ||
+                                     self.syntax == Syntax::Prolog && text.starts_with('_'))
+    }
+
+    // Returns true if the next tokens are `name(`
+    fn at_call(&self, name: &str) -> bool {
+        self.peek() == Ident && self.span().snippet(self.src) == name && self.peek2() == LParen
+    }
+
+    fn clause(&mut self) -> Result<Clause, ParseError> {
+        if self.syntax == Syntax::Prolog {
+            self.scan_variable_names();
+        }
+        let head = self.sentence()?;
+        let clause = if self.peek() != Implies {
+            SentenceClause(head)
+        } else {
+            self.next();
+            let mut body = vec![self.literal()?];
+            while self.peek() == Amp || self.peek() == Comma {
+                self.next();
+                body.push(self.literal()?);
+            }
+            let span = Span::new(head.span().lo, self.prev_end());
+            RuleClause(Rule::new(head, body).with_span(span))
+        };
+        if self.syntax == Syntax::Prolog {
+            self.expect(Dot)?;
+        }
+        Ok(clause)
+    }
+
+    // Collects the names of the variables in the clause that starts at the next token, which ends
+    // at the next `.`. Any identifier that could be a variable is included, since it only matters
+    // that `_` variables get names that aren't among them.
+    fn scan_variable_names(&mut self) {
+        self.anonymous = 0;
+        self.names.clear();
+        for &(tok, span) in self.tokens[self.pos..].iter().take_while(|t| t.0 != Dot) {
+            let text = span.snippet(self.src);
+            match tok {
+                Var => {
+                    self.names.insert(text[1..].to_string());
+                }
+                Ident if text.starts_with(|c: char| c.is_uppercase() || c == '_') => {
+                    self.names.insert(variable_name(text));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn sentence(&mut self) -> Result<Sentence, ParseError> {
+        match self.relation()? {
+            PropLit(p) => Ok(PropSentence(p)),
+            RelLit(r) => Ok(RelSentence(r)),
+            _ => unreachable!()
+        }
+    }
+
+    // Negations and disjunctions nest through this, so it only picks the method that parses the
+    // literal to keep the stack used per level small
+    fn literal(&mut self) -> Result<Literal, ParseError> {
+        let prolog = self.syntax == Syntax::Prolog;
+        match self.peek() {
+            Tilde => self.negation(),
+            LParen => self.disjunction(),
+            Ident if prolog && self.at_call("not") => self.negation(),
+            _ if prolog && (self.at_variable() || self.peek2() == Neq) => self.inequality(),
+            _ => self.atomic_literal()
+        }
+    }
+
+    // Parses `~lit`, or `not(lit)` in Prolog
+    fn negation(&mut self) -> Result<Literal, ParseError> {
+        let lo = self.span().lo;
+        self.enter()?;
+        let call = self.next().0 == Ident;
+        if call {
+            self.next();
+        }
+        let lit = self.literal()?;
+        if call {
+            self.expect(RParen)?;
+        }
+        self.depth -= 1;
+        let span = Span::new(lo, self.prev_end());
+        Ok(NotLit(Not::new(Box::new(lit)).with_span(span)))
+    }
+
+    fn disjunction(&mut self) -> Result<Literal, ParseError> {
+        let lo = self.span().lo;
+        self.enter()?;
+        self.next();
+        let mut lits = Vec::new();
+        if self.peek() != RParen {
+            lits.push(self.literal()?);
+            while self.peek() == Bar {
+                self.next();
+                lits.push(self.literal()?);
+            }
+        }
+        self.expect(RParen)?;
+        self.depth -= 1;
+        let span = Span::new(lo, self.prev_end());
+        Ok(OrLit(Or::new(lits).with_span(span)))
+    }
+
+    // Parses `X \= Y`, the only literal in Prolog that can start with a variable
+    fn inequality(&mut self) -> Result<Literal, ParseError> {
+        let lo = self.span().lo;
+        let term1 = self.term()?;
+        self.expect(Neq)?;
+        let term2 = self.term()?;
+        let span = Span::new(lo, self.prev_end());
+        Ok(DistinctLit(Distinct::new(term1, term2).with_span(span)))
+    }
+
+    // Parses a proposition, relation, or `distinct(a,b)`
+    fn atomic_literal(&mut self) -> Result<Literal, ParseError> {
+        let lit = self.relation()?;
+        match lit {
+            RelLit(ref r) if r.name.name == "distinct" && r.args.len() == 2 => {
+                let span = r.span;
+                let d = Distinct::new(r.args[0].clone(), r.args[1].clone());
+                Ok(DistinctLit(d.with_span(span)))
+            }
+            lit => {
+                if self.syntax == Syntax::Prolog && self.peek() == Neq {
+                    return Err(self.error(vec!["constant", "variable"]));
+                }
+                Ok(lit)
+            }
+        }
+    }
+
+    // Parses a proposition or relation, returned as a literal
+    fn relation(&mut self) -> Result<Literal, ParseError> {
+        let name = self.constant()?;
+        if self.peek() != LParen {
+            let span = name.span;
+            return Ok(PropLit(Proposition::new(name).with_span(span)));
+        }
+        let lo = name.span.lo;
+        let args = self.args()?;
+        let span = Span::new(lo, self.prev_end());
+        Ok(RelLit(Relation::new(name, args).with_span(span)))
+    }
+
+    fn args(&mut self) -> Result<Vec<Term>, ParseError> {
+        self.enter()?;
+        self.expect(LParen)?;
+        let mut args = vec![self.term()?];
+        while self.peek() == Comma {
+            self.next();
+            args.push(self.term()?);
+        }
+        self.expect(RParen)?;
+        self.depth -= 1;
+        Ok(args)
+    }
+
+    // Functions are parsed with a stack of the ones that are open instead of by recursing, so
+    // deeply nested terms don't use up the thread's stack before `enter` rejects them
+    fn term(&mut self) -> Result<Term, ParseError> {
+        let mut open: Vec<(Constant, Vec<Term>)> = Vec::new();
+        loop {
+            if self.peek() != Ident && self.peek() != Quoted && self.peek() != Var {
+                return Err(self.error(vec!["constant", "variable"]));
+            }
+            let mut term = if self.at_variable() {
+                self.variable()
+            } else {
+                let name = self.constant()?;
+                if self.peek() == LParen {
+                    self.enter()?;
+                    self.next();
+                    open.push((name, Vec::new()));
+                    continue;
+                }
+                ConstTerm(name)
+            };
+
+            // Add the term to the function it's in, closing every function that ends after it
+            loop {
+                let (name, mut args) = match open.pop() {
+                    Some(f) => f,
+                    None => return Ok(term)
+                };
+                args.push(term);
+                if self.peek() == Comma {
+                    self.next();
+                    open.push((name, args));
+                    break;
+                }
+                self.expect(RParen)?;
+                self.depth -= 1;
+                let span = Span::new(name.span.lo, self.prev_end());
+                term = FuncTerm(Function::new(name, args).with_span(span));
+            }
+        }
+    }
+
+    fn variable(&mut self) -> Term {
+        let (tok, span) = self.next();
+        let text = span.snippet(self.src);
+        if tok == Var {
+            let name = Constant::new(&text[1..]).with_span(Span::new(span.lo + 1, span.hi));
+            return VarTerm(Variable::new(name).with_span(span));
+        }
+        let name = if text == "_" {
+            // Every `_` is a different variable
+            loop {
+                self.anonymous += 1;
+                let name = format!("_{}", self.anonymous);
+                if !self.names.contains(&name) {
+                    break name;
+                }
+            }
+        } else {
+            variable_name(text)
+        };
+        let name = Constant::new(name).with_span(span);
+        VarTerm(Variable::new(name).with_span(span))
+    }
+
+    // Parses an identifier that isn't a variable
+    fn constant(&mut self) -> Result<Constant, ParseError> {
+        if self.peek() == Quoted {
+            let span = self.next().1;
+            return Ok(Constant::new(unquote(span.snippet(self.src))).with_span(span));
+        }
+        if self.peek() != Ident || self.at_variable() {
+            return Err(self.error(vec!["constant"]));
+        }
+        let span = self.next().1;
+        Ok(Constant::new(span.snippet(self.src)).with_span(span))
+    }
+}
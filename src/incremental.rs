@@ -0,0 +1,72 @@
+use {parse_clause_at, split, visitor, Description, ParseError, ShiftSpans, Span};
+
+/// A change to a GDL string that replaces the bytes covered by `span` with `text`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub span: Span,
+    pub text: String
+}
+
+impl TextEdit {
+    pub fn new<T: Into<String>>(span: Span, text: T) -> TextEdit {
+        TextEdit { span: span, text: text.into() }
+    }
+
+    /// Returns the result of applying this edit to `src`
+    pub fn apply(&self, src: &str) -> String {
+        let mut s = String::with_capacity(src.len() + self.text.len());
+        s.push_str(&src[..self.span.lo]);
+        s.push_str(&self.text);
+        s.push_str(&src[self.span.hi..]);
+        s
+    }
+}
+
+/// Updates `desc`, which was parsed from the text before `edit` was applied, to match `new_src`,
+/// the text after the edit. Only the clauses touched by the edit are parsed again; the clauses
+/// after it are kept and have their spans moved. If the edited clauses fail to parse, the error is
+/// returned and `desc` is left unchanged.
+pub fn reparse(desc: &mut Description, edit: &TextEdit, new_src: &str)
+               -> Result<(), ParseError> {
+    let delta = edit.text.len() as isize - edit.span.len() as isize;
+    let edit_end = edit.span.lo + edit.text.len();
+
+    // A clause ending right at the edit can still be changed by it, e.g. by appending to an atom
+    let first = desc.clauses.iter().position(|c| c.span().hi >= edit.span.lo)
+        .unwrap_or(desc.clauses.len());
+    let start = if first == 0 { 0 } else { desc.clauses[first - 1].span().hi };
+
+    // Reparse from the end of the last unchanged clause until a clause starts after the edit at
+    // the same place an old clause started. The text from there on is the same as before the edit
+    // and both clauses start outside of any comment, so it splits into the same clauses even if the
+    // edit opened or closed a block comment.
+    let mut new_clauses = Vec::new();
+    let mut resume = desc.clauses.len();
+    let mut old = first;
+    for (offset, text) in split::forms(&new_src[start..]) {
+        let offset = start + offset;
+        if offset >= edit_end {
+            while old < desc.clauses.len() &&
+                  (desc.clauses[old].span().lo as isize + delta) < offset as isize {
+                old += 1;
+            }
+            if old < desc.clauses.len() &&
+               desc.clauses[old].span().lo as isize + delta == offset as isize {
+                resume = old;
+                break;
+            }
+        }
+        new_clauses.push(parse_clause_at(text, offset)?);
+    }
+
+    let mut rest = desc.clauses.split_off(resume);
+    if delta != 0 {
+        for c in rest.iter_mut() {
+            visitor::visit_clause(c, &mut ShiftSpans(delta));
+        }
+    }
+    desc.clauses.truncate(first);
+    desc.clauses.extend(new_clauses);
+    desc.clauses.extend(rest);
+    Ok(())
+}
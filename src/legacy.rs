@@ -0,0 +1,218 @@
+//! Reading the JSON that `rustc_serialize::json` wrote for the AST before it was replaced by serde.
+//! Structs are objects with a key for each field, and enum variants are objects like
+//! `{"variant": "RelSentence", "fields": [..]}`. JSON written before spans existed has no `span`
+//! fields, so missing spans are read as dummy spans.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use serde_json::{self, Map, Value};
+
+use {Constant, Description, Distinct, Function, Literal, Not, Or, Proposition, Relation, Rule,
+     Sentence, Span, Term, Unknown, Variable};
+use Clause::{self, RuleClause, SentenceClause, UnknownClause};
+use Literal::{NotLit, OrLit, DistinctLit, PropLit, RelLit};
+use Sentence::{PropSentence, RelSentence};
+use Term::{VarTerm, FuncTerm, ConstTerm};
+
+use self::LegacyJsonError::{InvalidJson, Malformed};
+
+/// An error produced when JSON can't be read by `from_legacy_json`
+#[derive(Debug, Clone, PartialEq)]
+pub enum LegacyJsonError {
+    /// The input isn't valid JSON
+    InvalidJson(String),
+
+    /// The JSON doesn't have the layout `rustc_serialize` used for the AST. The message says what
+    /// was expected.
+    Malformed(String)
+}
+
+impl Display for LegacyJsonError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            &InvalidJson(ref message) => write!(f, "invalid JSON: {}", message),
+            &Malformed(ref message) => write!(f, "invalid legacy AST JSON: {}", message)
+        }
+    }
+}
+
+impl Error for LegacyJsonError {
+    fn description(&self) -> &str {
+        match self {
+            &InvalidJson(_) => "invalid JSON",
+            &Malformed(_) => "invalid legacy AST JSON"
+        }
+    }
+}
+
+type Object = Map<String, Value>;
+
+/// Reads a description from JSON written by `rustc_serialize::json::encode`, which is how ASTs were
+/// serialized before the `serde` feature. The result can be written back out with serde to convert
+/// old fixtures.
+pub fn from_legacy_json(json: &str) -> Result<Description, LegacyJsonError> {
+    let json: Value = match serde_json::from_str(json) {
+        Ok(json) => json,
+        Err(e) => return Err(InvalidJson(e.to_string()))
+    };
+    let obj = object(&json, "Description")?;
+    let clauses = array(field(obj, "clauses")?, "clauses")?;
+    Ok(Description::new(clauses.iter().map(clause).collect::<Result<_, _>>()?))
+}
+
+fn clause(json: &Value) -> Result<Clause, LegacyJsonError> {
+    let (name, fields) = variant(json, "Clause")?;
+    match name {
+        "RuleClause" => Ok(RuleClause(rule(only(fields, name)?)?)),
+        "SentenceClause" => Ok(SentenceClause(sentence(only(fields, name)?)?)),
+        "UnknownClause" => {
+            let obj = object(only(fields, name)?, "Unknown")?;
+            let text = string(field(obj, "text")?, "text")?;
+            Ok(UnknownClause(Unknown::new(text).with_span(span(obj)?)))
+        }
+        _ => Err(unknown_variant("Clause", name))
+    }
+}
+
+fn rule(json: &Value) -> Result<Rule, LegacyJsonError> {
+    let obj = object(json, "Rule")?;
+    let head = sentence(field(obj, "head")?)?;
+    let body = array(field(obj, "body")?, "body")?.iter().map(literal).collect::<Result<_, _>>()?;
+    Ok(Rule::new(head, body).with_span(span(obj)?))
+}
+
+fn sentence(json: &Value) -> Result<Sentence, LegacyJsonError> {
+    let (name, fields) = variant(json, "Sentence")?;
+    match name {
+        "PropSentence" => Ok(PropSentence(proposition(only(fields, name)?)?)),
+        "RelSentence" => Ok(RelSentence(relation(only(fields, name)?)?)),
+        _ => Err(unknown_variant("Sentence", name))
+    }
+}
+
+fn literal(json: &Value) -> Result<Literal, LegacyJsonError> {
+    let (name, fields) = variant(json, "Literal")?;
+    let json = only(fields, name)?;
+    match name {
+        "NotLit" => {
+            let obj = object(json, "Not")?;
+            let lit = literal(field(obj, "lit")?)?;
+            Ok(NotLit(Not::new(Box::new(lit)).with_span(span(obj)?)))
+        }
+        "OrLit" => {
+            let obj = object(json, "Or")?;
+            let lits = array(field(obj, "lits")?, "lits")?;
+            let lits = lits.iter().map(literal).collect::<Result<_, _>>()?;
+            Ok(OrLit(Or::new(lits).with_span(span(obj)?)))
+        }
+        "DistinctLit" => {
+            let obj = object(json, "Distinct")?;
+            let term1 = term(field(obj, "term1")?)?;
+            let term2 = term(field(obj, "term2")?)?;
+            Ok(DistinctLit(Distinct::new(term1, term2).with_span(span(obj)?)))
+        }
+        "PropLit" => Ok(PropLit(proposition(json)?)),
+        "RelLit" => Ok(RelLit(relation(json)?)),
+        "KnowsLit" => {
+            let obj = object(json, "Knows")?;
+            let role = match field(obj, "role")? {
+                &Value::Null => None,
+                role => Some(term(role)?)
+            };
+            let prop = term(field(obj, "prop")?)?;
+            Ok(::Literal::KnowsLit(::Knows::new(role, prop).with_span(span(obj)?)))
+        }
+        _ => Err(unknown_variant("Literal", name))
+    }
+}
+
+fn term(json: &Value) -> Result<Term, LegacyJsonError> {
+    let (name, fields) = variant(json, "Term")?;
+    let json = only(fields, name)?;
+    match name {
+        "VarTerm" => {
+            let obj = object(json, "Variable")?;
+            Ok(VarTerm(Variable::new(constant(field(obj, "name")?)?).with_span(span(obj)?)))
+        }
+        "FuncTerm" => {
+            let obj = object(json, "Function")?;
+            let name = constant(field(obj, "name")?)?;
+            let args = array(field(obj, "args")?, "args")?.iter().map(term)
+                .collect::<Result<_, _>>()?;
+            Ok(FuncTerm(Function::new(name, args).with_span(span(obj)?)))
+        }
+        "ConstTerm" => Ok(ConstTerm(constant(json)?)),
+        _ => Err(unknown_variant("Term", name))
+    }
+}
+
+fn proposition(json: &Value) -> Result<Proposition, LegacyJsonError> {
+    let obj = object(json, "Proposition")?;
+    Ok(Proposition::new(constant(field(obj, "name")?)?).with_span(span(obj)?))
+}
+
+fn relation(json: &Value) -> Result<Relation, LegacyJsonError> {
+    let obj = object(json, "Relation")?;
+    let name = constant(field(obj, "name")?)?;
+    let args = array(field(obj, "args")?, "args")?.iter().map(term).collect::<Result<_, _>>()?;
+    Ok(Relation::new(name, args).with_span(span(obj)?))
+}
+
+fn constant(json: &Value) -> Result<Constant, LegacyJsonError> {
+    let obj = object(json, "Constant")?;
+    Ok(Constant::new(string(field(obj, "name")?, "name")?).with_span(span(obj)?))
+}
+
+// Reads the optional `span` field of a struct
+fn span(obj: &Object) -> Result<Span, LegacyJsonError> {
+    match obj.get("span") {
+        None | Some(&Value::Null) => Ok(Span::dummy()),
+        Some(json) => {
+            let span = object(json, "Span")?;
+            let offset = |name| field(span, name)?.as_u64()
+                .ok_or_else(|| Malformed(format!("expected `{}` to be an offset", name)));
+            Ok(Span::new(offset("lo")? as usize, offset("hi")? as usize))
+        }
+    }
+}
+
+// Returns the name and fields of an enum variant
+fn variant<'a>(json: &'a Value, ty: &str) -> Result<(&'a str, &'a [Value]), LegacyJsonError> {
+    let obj = object(json, ty)?;
+    let name = string(field(obj, "variant")?, "variant")?;
+    Ok((name, array(field(obj, "fields")?, "fields")?))
+}
+
+// Returns the field of a variant that has exactly one
+fn only<'a>(fields: &'a [Value], variant: &str) -> Result<&'a Value, LegacyJsonError> {
+    match fields {
+        [json] => Ok(json),
+        _ => Err(Malformed(format!("expected `{}` to have one field, found {}", variant,
+                                   fields.len())))
+    }
+}
+
+fn object<'a>(json: &'a Value, ty: &str) -> Result<&'a Object, LegacyJsonError> {
+    json.as_object().ok_or_else(|| Malformed(format!("expected a {} object, found {}", ty, json)))
+}
+
+fn field<'a>(obj: &'a Object, name: &str) -> Result<&'a Value, LegacyJsonError> {
+    obj.get(name).ok_or_else(|| Malformed(format!("missing field `{}`", name)))
+}
+
+fn array<'a>(json: &'a Value, name: &str) -> Result<&'a [Value], LegacyJsonError> {
+    match json.as_array() {
+        Some(array) => Ok(array),
+        None => Err(Malformed(format!("expected `{}` to be an array, found {}", name, json)))
+    }
+}
+
+fn string<'a>(json: &'a Value, name: &str) -> Result<&'a str, LegacyJsonError> {
+    json.as_str()
+        .ok_or_else(|| Malformed(format!("expected `{}` to be a string, found {}", name, json)))
+}
+
+fn unknown_variant(ty: &str, name: &str) -> LegacyJsonError {
+    Malformed(format!("unknown {} variant `{}`", ty, name))
+}
@@ -0,0 +1,133 @@
+//! The tokenizer used by the parser and the syntax tree. Every byte of the input belongs to exactly
+//! one token, so this can be used for syntax highlighting even when the input doesn't parse.
+//!
+//! ```
+//! use gdl_parser::lexer::{Lexer, Token};
+//!
+//! let tokens: Vec<_> = Lexer::new("(role ?r)").map(|(t, _)| t).collect();
+//! assert_eq!(tokens, vec![Token::LParen, Token::Atom("role"), Token::Whitespace(" "),
+//!                         Token::Variable("r"), Token::RParen]);
+//! ```
+
+use span::Span;
+use split;
+
+use self::Token::{LParen, RParen, Atom, Variable, Comment, Whitespace, Unknown};
+
+/// A GDL token. Whitespace and comments are tokens too, so concatenating the text of every token
+/// reproduces the input exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Token<'a> {
+    LParen,
+    RParen,
+
+    /// A constant or keyword, including `<=`. Quoted atoms like `"foo bar"` and `|foo bar|` keep
+    /// their quotes and escapes.
+    Atom(&'a str),
+
+    /// A variable, without the leading `?`
+    Variable(&'a str),
+
+    /// A `;` comment, including the semicolons but not the newline that ends it, or a KIF
+    /// `#| ... |#` block comment, including the delimiters
+    Comment(&'a str),
+
+    Whitespace(&'a str),
+
+    /// A character that can't start any other token, or a quote or block comment that's never
+    /// closed
+    Unknown(&'a str)
+}
+
+impl<'a> Token<'a> {
+    /// Returns true for tokens that have no meaning to the parser, i.e. whitespace and comments
+    pub fn is_trivia(&self) -> bool {
+        match self {
+            &Comment(_) | &Whitespace(_) => true,
+            _ => false
+        }
+    }
+}
+
+/// An iterator over the tokens in a GDL string, along with their spans
+pub struct Lexer<'a> {
+    input: &'a str,
+    pos: usize
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str) -> Lexer<'a> {
+        Lexer { input: input, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = (Token<'a>, Span);
+
+    fn next(&mut self) -> Option<(Token<'a>, Span)> {
+        let input = self.input;
+        let start = self.pos;
+        let c = match input[start..].chars().next() {
+            Some(c) => c,
+            None => return None
+        };
+
+        let (token, end) = match c {
+            '(' => (LParen, start + 1),
+            ')' => (RParen, start + 1),
+            ';' => {
+                let end = find_from(input, start, |c| c == '\n');
+                (Comment(&input[start..end]), end)
+            }
+            '?' => {
+                let end = find_from(input, start + 1, |c| !is_ident_char(c));
+                if end == start + 1 {
+                    (Unknown(&input[start..end]), end)
+                } else {
+                    (Variable(&input[start + 1..end]), end)
+                }
+            }
+            '#' if input[start..].starts_with("#|") => {
+                match split::block_comment_end(input, start) {
+                    Some(end) => (Comment(&input[start..end]), end),
+                    None => (Unknown(&input[start..start + 2]), start + 2)
+                }
+            }
+            '<' if input[start..].starts_with("<=") => (Atom(&input[start..start + 2]), start + 2),
+            '"' | '|' => {
+                match split::quote_end(input, start) {
+                    Some(end) => (Atom(&input[start..end]), end),
+                    None => (Unknown(&input[start..start + 1]), start + 1)
+                }
+            }
+            c if is_whitespace(c) => {
+                let end = find_from(input, start, |c| !is_whitespace(c));
+                (Whitespace(&input[start..end]), end)
+            }
+            c if is_ident_char(c) => {
+                let end = find_from(input, start, |c| !is_ident_char(c));
+                (Atom(&input[start..end]), end)
+            }
+            c => (Unknown(&input[start..start + c.len_utf8()]), start + c.len_utf8())
+        };
+        self.pos = end;
+        Some((token, Span::new(start, end)))
+    }
+}
+
+/// Returns true if `c` can appear in an atom or variable name
+pub fn is_ident_char(c: char) -> bool {
+    c.is_ascii() && (c.is_alphanumeric() || c == '_')
+}
+
+fn is_whitespace(c: char) -> bool {
+    c == ' ' || c == '\t' || c == '\n' || c == '\r'
+}
+
+// Returns the offset of the first character at or after `pos` matching `f`, or the end of the input
+fn find_from<F: Fn(char) -> bool>(input: &str, pos: usize, f: F) -> usize {
+    match input[pos..].find(f) {
+        Some(n) => pos + n,
+        None => input.len()
+    }
+}
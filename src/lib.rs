@@ -3,31 +3,455 @@
 //! [AST](http://www.ggp.org/developers/gdl.html) used in
 //! [GGP Base](https://github.com/ggp-org/ggp-base).
 
-extern crate rustc_serialize;
-
-mod gdl;
-
+extern crate serde_json;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+#[cfg(feature = "mmap")]
+extern crate memmap2;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "cbor")]
+extern crate ciborium;
+#[cfg(feature = "msgpack")]
+extern crate rmp_serde;
+
+mod binary;
+mod canonical;
+#[cfg(feature = "cbor")]
+mod cbor;
+mod comments;
+mod diagnostic;
+mod dialect;
+mod encoding;
+mod error;
+#[cfg(feature = "highlight")]
+mod highlight;
+mod hrf;
+mod incremental;
+#[cfg(feature = "legacy-json")]
+mod legacy;
+mod lint;
+mod merge;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod options;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod parser;
+mod print;
+mod prolog;
+#[cfg(feature = "pretty")]
+mod render;
+mod repository;
+mod sexpr;
+mod span;
+mod split;
+mod substitution;
+mod validate;
+
+use std::cmp::Ordering;
 use std::fmt::{Display, Formatter, Error};
-
-use gdl::description;
-use self::Clause::{RuleClause, SentenceClause};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::str::{self, FromStr};
+
+use lexer::Lexer;
+use parser::Parser;
+use print::Item;
+use visitor::Visitor;
+pub use binary::{from_binary, to_binary, BinaryError, BINARY_VERSION};
+#[cfg(feature = "cbor")]
+pub use cbor::{from_cbor, to_cbor};
+pub use comments::{attach_comments, Comment, Comments, Trivia};
+pub use diagnostic::{diagnostics_to_json, Diagnostic, Severity};
+pub use dialect::KEYWORD_ARITIES;
+pub use encoding::{decode_bytes, parse_bytes};
+pub use error::{GdlError, Limit, ParseError};
+#[cfg(feature = "highlight")]
+pub use highlight::{to_ansi, to_html};
+pub use hrf::{parse_hrf, to_hrf};
+pub use incremental::{reparse, TextEdit};
+#[cfg(feature = "legacy-json")]
+pub use legacy::{from_legacy_json, LegacyJsonError};
+pub use lint::{lint, Lint, LintLevel, LintRegistry, NestedMoves};
+pub use merge::{MergeError, MergePolicy};
+#[cfg(feature = "mmap")]
+pub use mmap::parse_mmap;
+pub use options::{ParserOptions, DEFAULT_MAX_DEPTH};
+#[cfg(feature = "msgpack")]
+pub use msgpack::{from_msgpack, to_msgpack};
+#[cfg(feature = "parallel")]
+pub use parallel::parse_parallel;
+pub use prolog::{parse_prolog, parse_prolog_file};
+#[cfg(feature = "pretty")]
+pub use render::render;
+pub use repository::{from_ggp_json, parse_game_json, to_ggp_json, Game, GameMetadata,
+                     RepositoryError};
+pub use sexpr::{parse_sexprs, SExpr};
+pub use span::Span;
+pub use substitution::Substitution;
+pub use validate::{check_recursion, check_roles, check_safety, validate, ValidationReport};
+use self::Clause::{RuleClause, SentenceClause, UnknownClause};
 use self::Sentence::{PropSentence, RelSentence};
 use self::Literal::{NotLit, DistinctLit, OrLit, PropLit, RelLit};
+use self::Literal::KnowsLit;
 use self::Term::{VarTerm, FuncTerm, ConstTerm};
 
+pub mod analysis;
+pub mod cst;
+pub mod export;
+pub mod ground;
+pub mod lexer;
+pub mod printer;
+pub mod refactor;
+pub mod rewrite;
+pub mod scramble;
+pub mod transform;
 pub mod visitor;
 
 /// Parse a GDL string to a `Description`. Panics if the description is invalid.
 pub fn parse(gdl: &str) -> Description {
-    match description(gdl) {
+    match parse_checked(gdl) {
         Ok(d) => d,
         Err(e) => panic!("{}", e)
     }
 }
 
+/// Parse a GDL string to a `Description`, returning a `ParseError` describing what went wrong and
+/// where if the description is invalid.
+pub fn parse_checked(gdl: &str) -> Result<Description, ParseError> {
+    parse_with(gdl, &ParserOptions::new())
+}
+
+/// Parse a GDL string to a `Description` using the dialect and validation settings in `options`
+pub fn parse_with(gdl: &str, options: &ParserOptions) -> Result<Description, ParseError> {
+    if let Some(max) = options.max_input_size {
+        if gdl.len() > max {
+            let span = Span::new(0, gdl.len());
+            return Err(ParseError::LimitExceeded { span: span, limit: Limit::InputSize, max: max });
+        }
+    }
+    let folded;
+    let text = if options.fold_case {
+        folded = options::fold_case(gdl);
+        &folded
+    } else {
+        gdl
+    };
+    let desc = if options.keep_unknown {
+        parse_keeping_unknown(text, gdl, options)?
+    } else {
+        parse_at(text, 0, options, Parser::description)?
+    };
+    if options.allow_gdl_ii {
+        dialect::check_gdl_ii(&desc)?;
+    }
+    if options.strict_arity {
+        dialect::check_arities(&desc, KEYWORD_ARITIES)?;
+    }
+    if options.strict_safety {
+        validate::check_safety(&desc)?;
+    }
+    if options.strict_recursion {
+        validate::check_recursion(&desc)?;
+    }
+    Ok(desc)
+}
+
+// Parses each top-level form of `gdl` on its own, keeping the ones that aren't valid clauses as
+// `Unknown` clauses. The rest of the line after an invalid form is kept with it, so a directive
+// like `#stylesheet foo.xsl` is a single clause. The text of unknown clauses is taken from `src`,
+// which is `gdl` before case folding.
+fn parse_keeping_unknown(gdl: &str, src: &str,
+                         options: &ParserOptions) -> Result<Description, ParseError> {
+    let mut clauses = Vec::new();
+    for (start, form) in split::forms(gdl) {
+        // An unclosed `(` runs to the end of the input, including any trailing whitespace
+        let end = start + form.trim_end().len();
+        if let Some(&mut UnknownClause(ref mut u)) = clauses.last_mut() {
+            if !src[u.span.hi..start].contains('\n') {
+                u.span.hi = end;
+                u.text = src[u.span.lo..end].to_string();
+                continue;
+            }
+        }
+        if let Some(max) = options.max_clauses {
+            if clauses.len() == max {
+                let first = Lexer::new(form).next().map_or(Span::new(0, 0), |(_, span)| span);
+                let span = Span::new(start + first.lo, start + first.hi);
+                let limit = Limit::Clauses;
+                return Err(ParseError::LimitExceeded { span: span, limit: limit, max: max });
+            }
+        }
+        match parse_at(form, start, options, Parser::clause) {
+            Ok(clause) => clauses.push(clause),
+            Err(_) => {
+                let unknown = Unknown::new(&src[start..end]).with_span(Span::new(start, end));
+                clauses.push(UnknownClause(unknown));
+            }
+        }
+    }
+    Ok(Description::new(clauses))
+}
+
+/// Parse a GDL description from a reader. Clauses are parsed as soon as they've been read, so the
+/// whole description is never held in memory as a single string. Spans are relative to the start
+/// of the reader's input.
+pub fn parse_reader<R: Read>(mut reader: R) -> Result<Description, ParseError> {
+    let mut clauses = Vec::new();
+    let mut buf = Vec::new();
+    let mut chunk = [0; 64 * 1024];
+    // The offset of the start of `buf` in the reader's input
+    let mut offset = 0;
+    loop {
+        let n = match reader.read(&mut chunk) {
+            Ok(n) => n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(ref e) => return Err(error::from_io_error(e, offset + buf.len()))
+        };
+        buf.extend_from_slice(&chunk[..n]);
+        let eof = n == 0;
+
+        // A read can end partway through a multi-byte character, which is only an error if the
+        // rest of it never arrives
+        let text = match str::from_utf8(&buf) {
+            Ok(text) => text,
+            Err(e) => {
+                if eof || e.error_len().is_some() {
+                    let offset = offset + e.valid_up_to();
+                    let e = io::Error::new(io::ErrorKind::InvalidData, e);
+                    return Err(error::from_io_error(&e, offset));
+                }
+                str::from_utf8(&buf[..e.valid_up_to()]).unwrap()
+            }
+        };
+
+        // The last form in the buffer may continue in the next read, so it's only parsed once
+        // something follows it or there's nothing left to read
+        let mut consumed = 0;
+        for (start, form) in split::forms(text) {
+            let end = start + form.len();
+            if end == text.len() && !eof {
+                break;
+            }
+            clauses.push(parse_clause_at(form, offset + start)?);
+            consumed = end;
+        }
+
+        if eof {
+            return Ok(Description::new(clauses));
+        }
+        buf.drain(..consumed);
+        offset += consumed;
+    }
+}
+
+/// Parse the GDL description in the file at `path`. Errors include the path, and parse errors the
+/// line and column they occurred at.
+pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<Description, GdlError> {
+    let path = path.as_ref();
+    let mut gdl = String::new();
+    if let Err(e) = File::open(path).and_then(|mut f| f.read_to_string(&mut gdl)) {
+        return Err(GdlError::Io { path: path.to_path_buf(), error: e });
+    }
+    parse_checked(&gdl).map_err(|e| error::in_file(path, &gdl, e))
+}
+
+/// Parse a GDL string to a `Description` along with its comments, which are otherwise discarded.
+/// See `attach_comments` for how comments are assigned to clauses.
+pub fn parse_with_comments(gdl: &str) -> Result<(Description, Comments), ParseError> {
+    parse_with_trivia(gdl, &ParserOptions { keep_comments: true, ..ParserOptions::new() })
+}
+
+/// Parse a GDL string to a `Description` with `options`, like `parse_with`, along with its
+/// comments if `options.keep_comments` is set. Otherwise every clause has empty `Trivia`.
+pub fn parse_with_trivia(gdl: &str,
+                         options: &ParserOptions) -> Result<(Description, Comments), ParseError> {
+    let desc = parse_with(gdl, options)?;
+    let comments = if options.keep_comments {
+        attach_comments(gdl, &desc)
+    } else {
+        Comments { clauses: vec![Trivia::default(); desc.clauses.len()], dangling: Vec::new() }
+    };
+    Ok((desc, comments))
+}
+
+/// Parse a GDL string to a `Description`, skipping over any top-level clauses that are invalid
+/// instead of stopping at the first one. Returns the clauses that could be parsed along with a
+/// `Diagnostic` for each clause that was skipped, with a suggested fix where there is one.
+pub fn parse_lenient(gdl: &str) -> (Description, Vec<Diagnostic>) {
+    let mut clauses = Vec::new();
+    let mut diagnostics = Vec::new();
+    for result in parse_iter(gdl) {
+        match result {
+            Ok(c) => clauses.push(c),
+            Err(e) => diagnostics.push(Diagnostic::from_error(e, gdl))
+        }
+    }
+    (Description::new(clauses), diagnostics)
+}
+
+/// Parse a GDL string like `parse_lenient`, and also check the clauses that could be parsed with
+/// `lint`. Returns the errors and warnings together, ordered by where they start.
+pub fn parse_with_diagnostics(gdl: &str) -> (Description, Vec<Diagnostic>) {
+    let (desc, mut diagnostics) = parse_lenient(gdl);
+    diagnostics.extend(lint(&desc));
+    diagnostics.sort_by_key(|d| d.span.lo);
+    (desc, diagnostics)
+}
+
+/// Returns an iterator that parses the clauses of a GDL string one at a time, so callers that only
+/// need some of the clauses can stop early. A clause that fails to parse yields an error and the
+/// iterator continues with the next clause.
+pub fn parse_iter<'a>(gdl: &'a str) -> ClauseIter<'a> {
+    ClauseIter { forms: split::forms(gdl) }
+}
+
+/// An iterator over the clauses of a GDL string, returned by `parse_iter`
+pub struct ClauseIter<'a> {
+    forms: split::Forms<'a>
+}
+
+impl<'a> Iterator for ClauseIter<'a> {
+    type Item = Result<Clause, ParseError>;
+
+    fn next(&mut self) -> Option<Result<Clause, ParseError>> {
+        self.forms.next().map(|(offset, text)| parse_clause_at(text, offset))
+    }
+}
+
+/// Parse a single GDL rule, such as `(<= (legal ?r noop) (role ?r))`
+pub fn parse_rule(gdl: &str) -> Result<Rule, ParseError> {
+    parse_fragment(gdl, Parser::rule)
+}
+
+/// Parse a single GDL sentence, such as `(cell 1 1 b)`
+pub fn parse_sentence(gdl: &str) -> Result<Sentence, ParseError> {
+    parse_fragment(gdl, Parser::sentence)
+}
+
+/// Parse a single GDL literal, such as `(not (true (control white)))`
+pub fn parse_literal(gdl: &str) -> Result<Literal, ParseError> {
+    parse_fragment(gdl, Parser::literal)
+}
+
+/// Parse a single GDL term, such as `(move 2 3)`
+pub fn parse_term(gdl: &str) -> Result<Term, ParseError> {
+    parse_fragment(gdl, Parser::term)
+}
+
+// Parses a string containing a single node, which may be surrounded by whitespace and comments
+fn parse_fragment<'a, T, P>(gdl: &'a str, parse: P) -> Result<T, ParseError>
+    where T: Node, P: FnOnce(&mut Parser<'a>) -> Result<T, ParseError>
+{
+    let start = split::skip_trivia(gdl, 0);
+    parse_at(&gdl[start..], start, &ParserOptions::new(), parse)
+}
+
+// Parses a single clause that starts `offset` bytes into the full description
+fn parse_clause_at(text: &str, offset: usize) -> Result<Clause, ParseError> {
+    parse_at(text, offset, &ParserOptions::new(), Parser::clause)
+}
+
+// Parses `text`, which starts `offset` bytes into a larger input, so spans in the result and in
+// any error are relative to the larger input. The nesting limit in `options` keeps deep input from
+// overflowing the stack.
+fn parse_at<'a, T, P>(text: &'a str, offset: usize, options: &ParserOptions,
+                      parse: P) -> Result<T, ParseError>
+    where T: Node, P: FnOnce(&mut Parser<'a>) -> Result<T, ParseError>
+{
+    let mut parser = Parser::new(text, offset, options);
+    let node = parse(&mut parser)?;
+    parser.finish()?;
+    #[cfg(feature = "gdl-iii")]
+    let node = dialect::parse_knows(node)?;
+    Ok(node)
+}
+
+// The AST nodes returned by the parser's entry points
+#[cfg_attr(not(feature = "gdl-iii"), allow(dead_code))]
+trait Node {
+    fn accept<V: Visitor>(&mut self, visitor: &mut V);
+}
+
+impl Node for Description {
+    fn accept<V: Visitor>(&mut self, v: &mut V) { visitor::visit(self, v); }
+}
+
+impl Node for Clause {
+    fn accept<V: Visitor>(&mut self, v: &mut V) { visitor::visit_clause(self, v); }
+}
+
+impl Node for Rule {
+    fn accept<V: Visitor>(&mut self, v: &mut V) { visitor::visit_rule(self, v); }
+}
+
+impl Node for Sentence {
+    fn accept<V: Visitor>(&mut self, v: &mut V) { visitor::visit_sentence(self, v); }
+}
+
+impl Node for Literal {
+    fn accept<V: Visitor>(&mut self, v: &mut V) { visitor::visit_literal(self, v); }
+}
+
+impl Node for Term {
+    fn accept<V: Visitor>(&mut self, v: &mut V) { visitor::visit_term(self, v); }
+}
+
+// Moves every span in the visited nodes by a number of bytes
+struct ShiftSpans(isize);
+
+impl ShiftSpans {
+    fn shift(&self, span: &mut Span) {
+        span.lo = (span.lo as isize + self.0) as usize;
+        span.hi = (span.hi as isize + self.0) as usize;
+    }
+}
+
+impl Visitor for ShiftSpans {
+    fn visit_rule(&mut self, r: &mut Rule) { self.shift(&mut r.span) }
+    fn visit_proposition(&mut self, p: &mut Proposition) { self.shift(&mut p.span) }
+    fn visit_relation(&mut self, r: &mut Relation) { self.shift(&mut r.span) }
+    fn visit_constant(&mut self, c: &mut Constant) { self.shift(&mut c.span) }
+    fn visit_or(&mut self, o: &mut Or) { self.shift(&mut o.span) }
+    fn visit_not(&mut self, n: &mut Not) { self.shift(&mut n.span) }
+    fn visit_distinct(&mut self, d: &mut Distinct) { self.shift(&mut d.span) }
+    fn visit_variable(&mut self, v: &mut Variable) { self.shift(&mut v.span) }
+    fn visit_function(&mut self, f: &mut Function) { self.shift(&mut f.span) }
+    fn visit_unknown(&mut self, u: &mut Unknown) { self.shift(&mut u.span) }
+    fn visit_knows(&mut self, k: &mut Knows) { self.shift(&mut k.span) }
+}
+
+// Replaces every constant in the visited nodes that isn't the name of a variable
+struct MapConstants<F>(F);
+
+impl<F: FnMut(&Constant) -> Constant> Visitor for MapConstants<F> {
+    fn visit_proposition(&mut self, p: &mut Proposition) { p.name = (self.0)(&p.name) }
+    fn visit_relation(&mut self, r: &mut Relation) { r.name = (self.0)(&r.name) }
+    fn visit_function(&mut self, f: &mut Function) { f.name = (self.0)(&f.name) }
+
+    fn visit_term(&mut self, t: &mut Term) {
+        if let &mut ConstTerm(ref mut c) = t {
+            *c = (self.0)(c);
+        }
+    }
+}
+
+// Replaces every variable in the visited nodes
+struct MapVariables<F>(F);
+
+impl<F: FnMut(&Variable) -> Variable> Visitor for MapVariables<F> {
+    fn visit_variable(&mut self, v: &mut Variable) { *v = (self.0)(v) }
+}
+
 /// A GDL description. Contains a vector of `Clause`s, which are the top-level statements in
 /// a GDL description.
-#[derive(Debug, Clone, Hash, Eq, PartialEq, RustcDecodable, RustcEncodable, Ord, PartialOrd)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Description {
     pub clauses: Vec<Clause>
 }
@@ -37,51 +461,189 @@ impl Description {
     pub fn new(clauses: Vec<Clause>) -> Description {
         Description { clauses: clauses }
     }
+
+    /// Returns true if the description uses GDL-II keywords, i.e. it has `sees` relations or a
+    /// `random` role
+    pub fn is_gdl_ii(&self) -> bool {
+        dialect::is_gdl_ii(self)
+    }
+
+    /// Prints the description in a canonical form, so that descriptions of the same game can be
+    /// compared or hashed however they were written. Clauses are printed one per line in sorted
+    /// order with single spaces between tokens, unquoted atoms are lowercased, and the variables
+    /// in each clause are renamed to `?v0`, `?v1`, and so on in the order they first appear.
+    pub fn to_canonical_string(&self) -> String {
+        canonical::canonical_string(self)
+    }
+
+    /// Prints the description with no whitespace except the single spaces needed between atoms and
+    /// variables, for sending rulesheets in GGP messages where size matters. For example
+    /// `(<= (legal ?r noop) (role ?r))` is printed as `(<=(legal ?r noop)(role ?r))`.
+    pub fn to_compact_string(&self) -> String {
+        print::compact(&self.to_string())
+    }
+
+    /// Writes the description to `w` as it's printed by `Display`, without building the whole
+    /// string in memory first. The output is written in many small pieces, so `w` should usually
+    /// be buffered. Descriptions can be written to a `fmt::Write` with `write!`.
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write!(w, "{}", self)
+    }
+
+    /// Returns the clauses of this description followed by the clauses of `other`, for composing
+    /// games from libraries of rules. Returns an error if a relation has a different number of
+    /// arguments in each description, after `policy` is applied. For example
+    ///
+    /// ```
+    /// use gdl_parser::{parse, MergePolicy};
+    ///
+    /// let game = parse("(<= (next (step ?y)) (true (step ?x)) (succ ?x ?y))");
+    /// let library = parse("(succ 1 2) (succ 2 3)");
+    /// assert_eq!(game.merge(&library, &MergePolicy::Strict).unwrap().to_string(),
+    ///            "(<= (next (step ?y)) (true (step ?x)) (succ ?x ?y)) (succ 1 2) (succ 2 3)");
+    ///
+    /// let library = parse("(succ 1 2 3)");
+    /// let prefixed = game.merge(&library, &MergePolicy::PrefixOther("lib_".to_string()));
+    /// assert_eq!(prefixed.unwrap().clauses[1].to_string(), "(lib_succ 1 2 3)");
+    /// ```
+    ///
+    /// The spans of the clauses from `other` are still offsets into its own source.
+    pub fn merge(&self, other: &Description, policy: &MergePolicy)
+                 -> Result<Description, MergeError> {
+        merge::merge(self, other, policy)
+    }
+
+    /// Returns the description with every constant replaced by what `f` returns for it, including
+    /// the names of relations and functions and keywords like `role`, but not the names of
+    /// variables. For example
+    ///
+    /// ```
+    /// use gdl_parser::{parse, Constant};
+    ///
+    /// let desc = parse("(ROLE White) (<= (Legal ?Role noop) (role ?Role))");
+    /// let lowercased = desc.map_constants(|c| {
+    ///     Constant::new(c.name.to_lowercase()).with_span(c.span)
+    /// });
+    /// assert_eq!(lowercased.to_string(), "(role white) (<= (legal ?Role noop) (role ?Role))");
+    /// ```
+    pub fn map_constants<F: FnMut(&Constant) -> Constant>(&self, f: F) -> Description {
+        let mut desc = self.clone();
+        visitor::visit(&mut desc, &mut MapConstants(f));
+        desc
+    }
+
+    /// Returns the description with every variable replaced by what `f` returns for it
+    pub fn map_variables<F: FnMut(&Variable) -> Variable>(&self, f: F) -> Description {
+        let mut desc = self.clone();
+        visitor::visit(&mut desc, &mut MapVariables(f));
+        desc
+    }
+}
+
+impl FromStr for Description {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Description, ParseError> {
+        parse_checked(s)
+    }
 }
 
 impl Display for Description {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        let mut s = String::new();
-        for clause in self.clauses.iter() {
-            s.push_str(&clause.to_string());
-            s.push(' ');
-        }
-        let len = s.len();
-        s.remove(len - 1);
-        write!(f, "{}", s)
+        print::write(f, Item::Description(self))
     }
 }
 
 /// A top level statement in GDL. The only types of top level statements are `Rule`s and
-/// `Sentence`s
-#[derive(Debug, Clone, Hash, Eq, PartialEq, RustcDecodable, RustcEncodable, Ord, PartialOrd)]
+/// `Sentence`s, but text that isn't either can be kept as an `Unknown` clause.
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Clause {
     // A rule clause
     RuleClause(Rule),
 
     // A sentence clause
-    SentenceClause(Sentence)
+    SentenceClause(Sentence),
+
+    // Text that isn't a valid clause, only produced with `ParserOptions::keep_unknown`
+    UnknownClause(Unknown)
 }
 
-impl Display for Clause {
-    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+impl Clause {
+    /// Returns the span of the clause in the source text
+    pub fn span(&self) -> Span {
         match self {
-            &RuleClause(ref r) => Display::fmt(r, f),
-            &SentenceClause(ref s) => Display::fmt(s, f)
+            &RuleClause(ref r) => r.span,
+            &SentenceClause(ref s) => s.span(),
+            &UnknownClause(ref u) => u.span
         }
     }
+
+    /// Writes the clause to `w` as it's printed by `Display`
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write!(w, "{}", self)
+    }
+}
+
+impl Display for Clause {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        print::write(f, Item::Clause(self))
+    }
+}
+
+/// Top-level text that isn't a valid clause, such as a stylesheet directive embedded in a
+/// rulesheet. It's kept exactly as written and printed unchanged.
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Unknown {
+    pub text: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span
+}
+
+impl Unknown {
+    pub fn new<T: Into<String>>(text: T) -> Unknown {
+        Unknown { text: text.into(), span: Span::dummy() }
+    }
+
+    /// Sets the span of the text in the source
+    pub fn with_span(mut self, span: Span) -> Unknown {
+        self.span = span;
+        self
+    }
+}
+
+impl Into<Clause> for Unknown {
+    fn into(self) -> Clause {
+        UnknownClause(self)
+    }
+}
+
+impl Display for Unknown {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}", self.text)
+    }
 }
 
 /// A GDL rule contains a head `Sentence` that is implied by all the `Literal`s in the body
-#[derive(Debug, Clone, Hash, Eq, PartialEq, RustcDecodable, RustcEncodable, Ord, PartialOrd)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Rule {
     pub head: Sentence,
-    pub body: Vec<Literal>
+    pub body: Vec<Literal>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span
 }
 
 impl Rule {
     pub fn new(head: Sentence, body: Vec<Literal>) -> Rule {
-        Rule { head: head, body: body }
+        Rule { head: head, body: body, span: Span::dummy() }
+    }
+
+    /// Sets the span of the rule in the source text
+    pub fn with_span(mut self, span: Span) -> Rule {
+        self.span = span;
+        self
     }
 }
 
@@ -91,20 +653,24 @@ impl Into<Clause> for Rule {
     }
 }
 
+impl FromStr for Rule {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Rule, ParseError> {
+        parse_rule(s)
+    }
+}
+
 impl Display for Rule {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        let mut s = String::from(&*self.head.to_string());
-        for arg in self.body.iter() {
-            s.push(' ');
-            s.push_str(&arg.to_string());
-        }
-        write!(f, "(<= {})", s)
+        print::write(f, Item::Rule(self))
     }
 }
 
 /// A GDL sentence is like a `Rule` without a body. The two types of `Sentence`s are `Relation`s
 /// and `Proposition`s
-#[derive(Debug, Clone, Hash, Eq, PartialEq, RustcDecodable, RustcEncodable, Ord, PartialOrd)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Sentence {
     /// A proposition sentence
     PropSentence(Proposition),
@@ -121,6 +687,14 @@ impl Sentence {
             &RelSentence(ref r) => &r.name
         }
     }
+
+    /// Returns the span of the sentence in the source text
+    pub fn span(&self) -> Span {
+        match self {
+            &PropSentence(ref p) => p.span,
+            &RelSentence(ref r) => r.span
+        }
+    }
 }
 
 impl Into<Literal> for Sentence {
@@ -144,39 +718,68 @@ impl Into<Clause> for Sentence {
     }
 }
 
+impl FromStr for Sentence {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Sentence, ParseError> {
+        parse_sentence(s)
+    }
+}
+
 impl Display for Sentence {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        match self {
-            &PropSentence(ref p) => Display::fmt(p, f),
-            &RelSentence(ref r) => Display::fmt(r, f)
-        }
+        print::write(f, Item::Sentence(self))
     }
 }
 
-/// A GDL literal
-#[derive(Debug, Clone, Hash, Eq, PartialEq, RustcDecodable, RustcEncodable, Ord, PartialOrd)]
+/// A GDL literal. More kinds of literals may be added for other GDL dialects, so matches on a
+/// literal from outside this crate need a wildcard arm.
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
 pub enum Literal {
     NotLit(Not),
     OrLit(Or),
     DistinctLit(Distinct),
     PropLit(Proposition),
-    RelLit(Relation)
+    RelLit(Relation),
+
+    /// A GDL-III knows literal. The parser only produces these with the `gdl-iii` feature;
+    /// otherwise `knows` is an ordinary relation.
+    KnowsLit(Knows)
 }
 
-impl Display for Literal {
-    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+impl Literal {
+    /// Returns the span of the literal in the source text
+    pub fn span(&self) -> Span {
         match self {
-            &NotLit(ref n) => Display::fmt(n, f),
-            &OrLit(ref o) => Display::fmt(o, f),
-            &DistinctLit(ref d) => Display::fmt(d, f),
-            &PropLit(ref p) => Display::fmt(p, f),
-            &RelLit(ref r) => Display::fmt(r, f),
+            &NotLit(ref n) => n.span,
+            &OrLit(ref o) => o.span,
+            &DistinctLit(ref d) => d.span,
+            &PropLit(ref p) => p.span,
+            &RelLit(ref r) => r.span,
+            &KnowsLit(ref k) => k.span
         }
     }
 }
 
+impl FromStr for Literal {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Literal, ParseError> {
+        parse_literal(s)
+    }
+}
+
+impl Display for Literal {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        print::write(f, Item::Literal(self))
+    }
+}
+
 /// A GDL term is either a variable, a function, or a constant
-#[derive(Debug, Clone, Hash, Eq, PartialEq, RustcDecodable, RustcEncodable, Ord, PartialOrd)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Term {
     VarTerm(Variable),
     FuncTerm(Function),
@@ -192,27 +795,49 @@ impl Term {
             &ConstTerm(ref c) => c
         }
     }
+
+    /// Returns the span of the term in the source text
+    pub fn span(&self) -> Span {
+        match self {
+            &VarTerm(ref v) => v.span,
+            &FuncTerm(ref f) => f.span,
+            &ConstTerm(ref c) => c.span
+        }
+    }
+}
+
+impl FromStr for Term {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Term, ParseError> {
+        parse_term(s)
+    }
 }
 
 impl Display for Term {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        match self {
-            &VarTerm(ref v) => Display::fmt(v, f),
-            &FuncTerm(ref func) => Display::fmt(func, f),
-            &ConstTerm(ref c) => Display::fmt(c, f)
-        }
+        print::write(f, Item::Term(self))
     }
 }
 
 /// A proposition is a `Relation` with no body; it only has a name.
-#[derive(Debug, Clone, Hash, Eq, PartialEq, RustcDecodable, RustcEncodable, Ord, PartialOrd)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Proposition {
-    pub name: Constant
+    pub name: Constant,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span
 }
 
 impl Proposition {
     pub fn new<T: Into<Constant>>(name: T) -> Proposition {
-        Proposition { name: name.into() }
+        Proposition { name: name.into(), span: Span::dummy() }
+    }
+
+    /// Sets the span of the proposition in the source text
+    pub fn with_span(mut self, span: Span) -> Proposition {
+        self.span = span;
+        self
     }
 }
 
@@ -230,7 +855,7 @@ impl Into<Sentence> for Proposition {
 
 impl Into<Relation> for Proposition {
     fn into(self) -> Relation {
-        Relation::new(self.name, Vec::new())
+        Relation::new(self.name, Vec::new()).with_span(self.span)
     }
 }
 
@@ -241,16 +866,25 @@ impl Display for Proposition {
 }
 
 /// A GDL relation
-#[derive(Debug, Clone, Hash, Eq, PartialEq, RustcDecodable, RustcEncodable, Ord, PartialOrd)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Relation {
     pub name: Constant,
-    pub args: Vec<Term>
+    pub args: Vec<Term>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span
 }
 
 impl Relation {
     /// Consruct a new `Relation` given a `name` and a list of `args`
     pub fn new<T: Into<Constant>>(name: T, args: Vec<Term>) -> Relation {
-        Relation { name: name.into(), args: args }
+        Relation { name: name.into(), args: args, span: Span::dummy() }
+    }
+
+    /// Sets the span of the relation in the source text
+    pub fn with_span(mut self, span: Span) -> Relation {
+        self.span = span;
+        self
     }
 }
 
@@ -268,24 +902,28 @@ impl Into<Sentence> for Relation {
 
 impl Display for Relation {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        let mut s = String::from(&*self.name.name);
-        for arg in self.args.iter() {
-            s.push(' ');
-            s.push_str(&arg.to_string());
-        }
-        write!(f, "({})", s)
+        print::write(f, Item::Relation(self))
     }
 }
 
 /// A not literal
-#[derive(Debug, Clone, Hash, Eq, PartialEq, RustcDecodable, RustcEncodable, Ord, PartialOrd)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Not {
-    pub lit: Box<Literal>
+    pub lit: Box<Literal>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span
 }
 
 impl Not {
     pub fn new(lit: Box<Literal>) -> Not {
-        Not { lit: lit }
+        Not { lit: lit, span: Span::dummy() }
+    }
+
+    /// Sets the span of the not literal in the source text
+    pub fn with_span(mut self, span: Span) -> Not {
+        self.span = span;
+        self
     }
 }
 
@@ -297,19 +935,28 @@ impl Into<Literal> for Not {
 
 impl Display for Not {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        write!(f, "(not {})", self.lit)
+        print::write(f, Item::Not(self))
     }
 }
 
 /// An or literal
-#[derive(Debug, Clone, Hash, Eq, PartialEq, RustcDecodable, RustcEncodable, Ord, PartialOrd)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Or {
-    pub lits: Vec<Literal>
+    pub lits: Vec<Literal>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span
 }
 
 impl Or {
     pub fn new(lits: Vec<Literal>) -> Or {
-        Or { lits: lits }
+        Or { lits: lits, span: Span::dummy() }
+    }
+
+    /// Sets the span of the or literal in the source text
+    pub fn with_span(mut self, span: Span) -> Or {
+        self.span = span;
+        self
     }
 }
 
@@ -321,25 +968,29 @@ impl Into<Literal> for Or {
 
 impl Display for Or {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        let mut s = String::new();
-        for l in self.lits.iter() {
-            s.push(' ');
-            s.push_str(&l.to_string());
-        }
-        write!(f, "(or {})", s)
+        print::write(f, Item::Or(self))
     }
 }
 
 /// A distinct literal
-#[derive(Debug, Clone, Hash, Eq, PartialEq, RustcDecodable, RustcEncodable, Ord, PartialOrd)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Distinct {
     pub term1: Term,
-    pub term2: Term
+    pub term2: Term,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span
 }
 
 impl Distinct {
     pub fn new(term1: Term, term2: Term) -> Distinct {
-        Distinct { term1: term1, term2: term2 }
+        Distinct { term1: term1, term2: term2, span: Span::dummy() }
+    }
+
+    /// Sets the span of the distinct literal in the source text
+    pub fn with_span(mut self, span: Span) -> Distinct {
+        self.span = span;
+        self
     }
 }
 
@@ -351,19 +1002,63 @@ impl Into<Literal> for Distinct {
 
 impl Display for Distinct {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        write!(f, "(distinct {} {})", self.term1, self.term2)
+        print::write(f, Item::Distinct(self))
+    }
+}
+
+/// A GDL-III knows literal. `(knows ?r ?p)` holds when role `?r` knows that `?p` is true, and
+/// `(knows ?p)` holds when `?p` is common knowledge.
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Knows {
+    pub role: Option<Term>,
+    pub prop: Term,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span
+}
+
+impl Knows {
+    pub fn new(role: Option<Term>, prop: Term) -> Knows {
+        Knows { role: role, prop: prop, span: Span::dummy() }
+    }
+
+    /// Sets the span of the knows literal in the source text
+    pub fn with_span(mut self, span: Span) -> Knows {
+        self.span = span;
+        self
+    }
+}
+
+impl Into<Literal> for Knows {
+    fn into(self) -> Literal {
+        KnowsLit(self)
+    }
+}
+
+impl Display for Knows {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        print::write(f, Item::Knows(self))
     }
 }
 
 /// A variable term
-#[derive(Debug, Clone, Hash, Eq, PartialEq, RustcDecodable, RustcEncodable, Ord, PartialOrd)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Variable {
-    pub name: Constant
+    pub name: Constant,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span
 }
 
 impl Variable {
     pub fn new<T: Into<Constant>>(name: T) -> Variable {
-        Variable { name: name.into() }
+        Variable { name: name.into(), span: Span::dummy() }
+    }
+
+    /// Sets the span of the variable in the source text
+    pub fn with_span(mut self, span: Span) -> Variable {
+        self.span = span;
+        self
     }
 }
 
@@ -375,22 +1070,29 @@ impl Into<Term> for Variable {
 
 impl Display for Variable {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        let mut s = self.name.to_string();
-        s.insert(0, '?');
         write!(f, "?{}", self.name)
     }
 }
 
 /// A function term
-#[derive(Debug, Clone, Hash, Eq, PartialEq, RustcDecodable, RustcEncodable, Ord, PartialOrd)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Function {
     pub name: Constant,
-    pub args: Vec<Term>
+    pub args: Vec<Term>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span
 }
 
 impl Function {
     pub fn new<T: Into<Constant>>(name: T, args: Vec<Term>) -> Function {
-        Function { name: name.into(), args: args }
+        Function { name: name.into(), args: args, span: Span::dummy() }
+    }
+
+    /// Sets the span of the function in the source text
+    pub fn with_span(mut self, span: Span) -> Function {
+        self.span = span;
+        self
     }
 }
 
@@ -402,24 +1104,76 @@ impl Into<Term> for Function {
 
 impl Display for Function {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        let mut s = String::from(&*self.name.name);
-        for arg in self.args.iter() {
-            s.push(' ');
-            s.push_str(&arg.to_string());
-        }
-        write!(f, "({})", s)
+        print::write(f, Item::Function(self))
     }
 }
 
-/// A GDL constant
-#[derive(Debug, Clone, Hash, Eq, PartialEq, RustcDecodable, RustcEncodable, Ord, PartialOrd)]
+/// A GDL constant. Constants made up of digits, like goal values and board coordinates, are
+/// ordered by their numeric value, so `9` comes before `10`.
+///
+/// A string literal like `"foo bar"` is a different constant from the quoted atom `|foo bar|`, and
+/// each is printed the way it was written.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Constant {
-    pub name: String
+    pub name: String,
+    /// True if the constant is a string literal rather than an atom
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub string: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span
 }
 
 impl Constant {
     pub fn new<T: Into<String>>(name: T) -> Constant {
-        Constant { name: name.into() }
+        Constant { name: name.into(), string: false, span: Span::dummy() }
+    }
+
+    /// Constructs a string literal, which is printed in double quotes
+    pub fn string<T: Into<String>>(name: T) -> Constant {
+        Constant { name: name.into(), string: true, span: Span::dummy() }
+    }
+
+    /// Sets the span of the constant in the source text
+    pub fn with_span(mut self, span: Span) -> Constant {
+        self.span = span;
+        self
+    }
+
+    /// Returns the value of the constant if it's a non-negative integer that fits in an `i64`.
+    /// String literals aren't integers, even if they're made up of digits.
+    pub fn as_int(&self) -> Option<i64> {
+        if self.string || self.name.is_empty() || !self.name.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        self.name.parse().ok()
+    }
+
+    /// Returns true if the constant is an integer, see `as_int`
+    pub fn is_int(&self) -> bool {
+        self.as_int().is_some()
+    }
+}
+
+// Integers come before other constants and are compared by value. Integers with the same value
+// but different names, like `7` and `07`, are compared by name so the order agrees with `Eq`, and
+// an atom comes before a string literal with the same name.
+impl Ord for Constant {
+    fn cmp(&self, other: &Constant) -> Ordering {
+        match (self.as_int(), other.as_int()) {
+            (Some(a), Some(b)) => a.cmp(&b).then_with(|| self.name.cmp(&other.name)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => {
+                self.name.cmp(&other.name).then_with(|| self.string.cmp(&other.string))
+            }
+        }
+    }
+}
+
+impl PartialOrd for Constant {
+    fn partial_cmp(&self, other: &Constant) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
@@ -429,9 +1183,40 @@ impl Into<Term> for Constant {
     }
 }
 
+impl FromStr for Constant {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Constant, ParseError> {
+        match parse_term(s)? {
+            ConstTerm(c) => Ok(c),
+            t => {
+                let span = t.span();
+                Err(ParseError::UnexpectedToken {
+                    span: span,
+                    found: span.snippet(s).to_string(),
+                    expected: vec!["constant"]
+                })
+            }
+        }
+    }
+}
+
+// String literals are printed in double quotes, and names that aren't plain atoms are printed as
+// quoted atoms, escaping the quote and backslash
 impl Display for Constant {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        write!(f, "{}", self.name)
+        if !self.string && !self.name.is_empty() && self.name.chars().all(lexer::is_ident_char) {
+            return write!(f, "{}", self.name);
+        }
+        let quote = if self.string { '"' } else { '|' };
+        write!(f, "{}", quote)?;
+        for c in self.name.chars() {
+            if c == quote || c == '\\' {
+                write!(f, "\\")?;
+            }
+            write!(f, "{}", c)?;
+        }
+        write!(f, "{}", quote)
     }
 }
 
@@ -0,0 +1,348 @@
+//! Warnings for descriptions that parse but are probably wrong
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use {analysis, Description, Diagnostic, Relation, Severity, Term, TextEdit, Variable};
+use Clause::{RuleClause, SentenceClause, UnknownClause};
+use Term::FuncTerm;
+use dialect::is_reserved;
+use visitor::{self, Visit};
+
+/// Returns warnings for the clauses in `desc`, in source order. These are:
+///
+/// * `singleton-variable`: a variable that only appears once in its clause, which is usually a
+///   typo. Variables whose names start with `_` are skipped. If another variable in the clause
+///   has a similar name, like `?piece` for `?peice`, the warning suggests it and has a fix that
+///   renames the variable.
+/// * `shadowed-keyword`: a clause whose head is a relation that GDL reserves, like `true` or
+///   `does`.
+///
+/// `LintRegistry` runs these along with the rest of the built-in lints and any custom ones.
+pub fn lint(desc: &Description) -> Vec<Diagnostic> {
+    let mut diagnostics = shadowed_keywords(desc);
+    diagnostics.extend(singleton_variables(desc));
+    diagnostics.sort_by_key(|d| d.span.lo);
+    diagnostics
+}
+
+fn shadowed_keywords(desc: &Description) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for c in desc.clauses.iter() {
+        let head = match c {
+            &RuleClause(ref r) => &r.head,
+            &SentenceClause(ref s) => s,
+            &UnknownClause(_) => continue
+        };
+        let name = &head.name().name;
+        if is_reserved(name) {
+            let message = format!("`{}` is reserved by GDL and can't be defined", name);
+            diagnostics.push(Diagnostic::warning("shadowed-keyword", head.span(), message));
+        }
+    }
+    diagnostics
+}
+
+fn singleton_variables(desc: &Description) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for c in desc.clauses.iter() {
+        let mut vars = Variables { vars: Vec::new() };
+        visitor::walk_clause(c, &mut vars);
+        let mut counts = HashMap::new();
+        for v in vars.vars.iter() {
+            *counts.entry(&v.name.name).or_insert(0) += 1;
+        }
+        for v in vars.vars.iter() {
+            if counts[&v.name.name] == 1 && !v.name.name.starts_with('_') {
+                let mut message = format!("variable `?{}` only appears once", v.name.name);
+                // Names are compared ignoring case, since `?x` and `?X` are easily mixed up
+                let name = v.name.name.to_lowercase();
+                let similar = vars.vars.iter().filter(|o| counts[&o.name.name] > 1)
+                                  .map(|o| (edit_distance(&name, &o.name.name.to_lowercase()), o))
+                                  .filter(|&(d, _)| d <= 2 && d < name.chars().count())
+                                  .min_by_key(|&(d, _)| d);
+                let fix = similar.map(|(_, o)| {
+                    message.push_str(&format!("; did you mean `?{}`?", o.name.name));
+                    TextEdit::new(v.span, format!("?{}", o.name.name))
+                });
+                diagnostics.push(Diagnostic::warning("singleton-variable", v.span, message)
+                                 .with_fix(fix));
+            }
+        }
+    }
+    diagnostics
+}
+
+/// How the diagnostics of a lint are reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintLevel {
+    /// The lint isn't run
+    Allow,
+
+    /// The lint's diagnostics are warnings
+    Warn,
+
+    /// The lint's diagnostics are errors
+    Deny
+}
+
+impl Display for LintLevel {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            &LintLevel::Allow => write!(f, "allow"),
+            &LintLevel::Warn => write!(f, "warn"),
+            &LintLevel::Deny => write!(f, "deny")
+        }
+    }
+}
+
+impl FromStr for LintLevel {
+    type Err = String;
+
+    /// Parses `allow`, `warn`, or `deny`, so levels can be read from configuration files and
+    /// command line flags
+    fn from_str(s: &str) -> Result<LintLevel, String> {
+        match s {
+            "allow" => Ok(LintLevel::Allow),
+            "warn" => Ok(LintLevel::Warn),
+            "deny" => Ok(LintLevel::Deny),
+            _ => Err(format!("unknown lint level `{}`, expected `allow`, `warn`, or `deny`", s))
+        }
+    }
+}
+
+/// A check for things in a description that are probably mistakes. For example
+///
+/// ```
+/// use gdl_parser::{parse, Diagnostic, Description, Lint, LintRegistry};
+///
+/// struct NoRandom;
+///
+/// impl Lint for NoRandom {
+///     fn code(&self) -> &'static str {
+///         "no-random"
+///     }
+///
+///     fn check(&self, desc: &Description) -> Vec<Diagnostic> {
+///         desc.clauses.iter().filter(|c| c.to_string() == "(role random)").map(|c| {
+///             Diagnostic::warning(self.code(), c.span(), "this game isn't deterministic")
+///         }).collect()
+///     }
+/// }
+///
+/// let mut registry = LintRegistry::new();
+/// registry.register(Box::new(NoRandom));
+/// let diagnostics = registry.run(&parse("(role random)"));
+/// assert_eq!(diagnostics[0].code, "no-random");
+/// ```
+pub trait Lint {
+    /// The code of the lint's diagnostics, like `singleton-variable`, which is also the name its
+    /// level is configured by
+    fn code(&self) -> &'static str;
+
+    /// The level of the lint if it isn't configured
+    fn default_level(&self) -> LintLevel {
+        LintLevel::Warn
+    }
+
+    /// Returns the problems found in `desc`. Their severity is replaced based on the lint's level.
+    fn check(&self, desc: &Description) -> Vec<Diagnostic>;
+}
+
+// A lint that runs a function
+struct FnLint {
+    code: &'static str,
+    check: fn(&Description) -> Vec<Diagnostic>
+}
+
+impl Lint for FnLint {
+    fn code(&self) -> &'static str {
+        self.code
+    }
+
+    fn check(&self, desc: &Description) -> Vec<Diagnostic> {
+        (self.check)(desc)
+    }
+}
+
+/// Warns about moves nested in more than `max_depth` functions, in the second argument of `does`,
+/// `legal`, or `input`, since some older players can only handle flat moves. For example
+/// `(move (cell 1 1) (cell 2 2))` is nested two deep and `(mark 1 1)` is nested one deep. The
+/// registry from `LintRegistry::new` has one with a `max_depth` of 1, which can be replaced by
+/// registering another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NestedMoves {
+    pub max_depth: usize
+}
+
+impl NestedMoves {
+    pub fn new(max_depth: usize) -> NestedMoves {
+        NestedMoves { max_depth: max_depth }
+    }
+}
+
+impl Default for NestedMoves {
+    fn default() -> NestedMoves {
+        NestedMoves::new(1)
+    }
+}
+
+impl Lint for NestedMoves {
+    fn code(&self) -> &'static str {
+        "nested-move"
+    }
+
+    fn check(&self, desc: &Description) -> Vec<Diagnostic> {
+        let mut moves = Moves { moves: Vec::new() };
+        visitor::walk(desc, &mut moves);
+        let mut diagnostics = moves.moves.into_iter().filter_map(|m| {
+            let depth = term_depth(&m);
+            if depth > self.max_depth {
+                let message = format!("the move `{}` is nested {} functions deep, but some \
+                                       players only handle moves nested {} deep", m, depth,
+                                      self.max_depth);
+                Some(Diagnostic::warning(self.code(), m.span(), message))
+            } else {
+                None
+            }
+        }).collect::<Vec<_>>();
+        diagnostics.sort_by_key(|d| d.span.lo);
+        diagnostics
+    }
+}
+
+// Collects the moves in `does`, `legal`, and `input` relations
+struct Moves {
+    moves: Vec<Term>
+}
+
+impl Visit for Moves {
+    fn visit_relation(&mut self, r: &Relation) {
+        let name = &r.name.name[..];
+        if (name == "does" || name == "legal" || name == "input") && r.args.len() == 2 {
+            self.moves.push(r.args[1].clone());
+        }
+    }
+}
+
+// Returns the number of functions `t` is nested in, counting itself
+fn term_depth(t: &Term) -> usize {
+    match t {
+        &FuncTerm(ref f) => 1 + f.args.iter().map(term_depth).max().unwrap_or(0),
+        _ => 0
+    }
+}
+
+/// A set of lints along with the level each one is configured at
+pub struct LintRegistry {
+    lints: Vec<Box<dyn Lint>>,
+    levels: HashMap<String, LintLevel>
+}
+
+impl LintRegistry {
+    /// Returns a registry with the built-in lints, all at their default level of warn. These are
+    /// `singleton-variable` and `shadowed-keyword` from `lint`, and `undefined-relation`,
+    /// `inconsistent-arity`, `unused-rule`, `duplicate-clause`, and `trivial-literal` from the
+    /// `analysis` module, and `nested-move` from `NestedMoves`.
+    pub fn new() -> LintRegistry {
+        let mut registry = LintRegistry::empty();
+        let builtin: [(&'static str, fn(&Description) -> Vec<Diagnostic>); 7] = [
+            ("singleton-variable", singleton_variables),
+            ("shadowed-keyword", shadowed_keywords),
+            ("undefined-relation", analysis::undefined_relations),
+            ("inconsistent-arity", analysis::inconsistent_arities),
+            ("unused-rule", analysis::unused_rules),
+            ("duplicate-clause", analysis::duplicate_clauses),
+            ("trivial-literal", analysis::trivial_literals)
+        ];
+        for &(code, check) in builtin.iter() {
+            registry.register(Box::new(FnLint { code: code, check: check }));
+        }
+        registry.register(Box::new(NestedMoves::default()));
+        registry
+    }
+
+    /// Returns a registry without any lints
+    pub fn empty() -> LintRegistry {
+        LintRegistry { lints: Vec::new(), levels: HashMap::new() }
+    }
+
+    /// Adds a lint to the registry. It replaces any lint that's already registered with the same
+    /// code.
+    pub fn register(&mut self, lint: Box<dyn Lint>) {
+        self.lints.retain(|l| l.code() != lint.code());
+        self.lints.push(lint);
+    }
+
+    /// Returns the codes of the registered lints, in the order they were registered
+    pub fn codes(&self) -> Vec<&'static str> {
+        self.lints.iter().map(|l| l.code()).collect()
+    }
+
+    /// Sets the level of the lint with the code `code`. The level is kept even if the lint isn't
+    /// registered yet.
+    pub fn set_level(&mut self, code: &str, level: LintLevel) {
+        self.levels.insert(code.to_string(), level);
+    }
+
+    /// Returns the level of the lint with the code `code`, or `None` if it isn't registered
+    pub fn level(&self, code: &str) -> Option<LintLevel> {
+        self.lints.iter().find(|l| l.code() == code).map(|l| {
+            self.levels.get(code).cloned().unwrap_or(l.default_level())
+        })
+    }
+
+    /// Runs every lint that isn't allowed over `desc`, returning their diagnostics in source
+    /// order. Diagnostics from denied lints are errors and the rest are warnings.
+    pub fn run(&self, desc: &Description) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for l in self.lints.iter() {
+            let severity = match self.level(l.code()).unwrap() {
+                LintLevel::Allow => continue,
+                LintLevel::Warn => Severity::Warning,
+                LintLevel::Deny => Severity::Error
+            };
+            diagnostics.extend(l.check(desc).into_iter().map(|mut d| {
+                d.severity = severity;
+                d
+            }));
+        }
+        diagnostics.sort_by_key(|d| d.span.lo);
+        diagnostics
+    }
+}
+
+impl Default for LintRegistry {
+    fn default() -> LintRegistry {
+        LintRegistry::new()
+    }
+}
+
+// Collects the variables in a clause
+struct Variables {
+    vars: Vec<Variable>
+}
+
+impl Visit for Variables {
+    fn visit_variable(&mut self, var: &Variable) {
+        self.vars.push(var.clone());
+    }
+}
+
+/// Returns the number of single character insertions, deletions, and substitutions it takes to
+/// turn `a` into `b`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..b.len() + 1).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for j in 0..b.len() {
+            let substitute = previous + if ca == b[j] { 0 } else { 1 };
+            previous = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
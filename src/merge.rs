@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use {Description, Proposition, Relation, Span};
+use analysis::{SymbolKind, SymbolTable};
+use dialect::{is_keyword, is_reserved, SEES};
+use visitor::{self, Visitor};
+
+/// How `Description::merge` handles the relations of the two descriptions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keep every name as it is, so relations with the same name in both descriptions are the
+    /// same relation. Fails if they have different numbers of arguments.
+    Strict,
+
+    /// Put the prefix in front of the name of every relation in the second description that
+    /// isn't a keyword, so its relations can't be confused with the first's. Fails if a prefixed
+    /// name is still used with a different number of arguments in the first description.
+    PrefixOther(String)
+}
+
+/// A relation that has a different number of arguments in each of the descriptions being merged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeError {
+    /// The name of the relation, with the prefix if one was added
+    pub name: String,
+
+    /// The number of arguments it first has in each description
+    pub arities: (usize, usize),
+
+    /// The span of the relation's first use with a different number of arguments in the second
+    /// description
+    pub span: Span
+}
+
+impl Display for MergeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "relation `{}` has {} arguments in the first description, but {} at byte {} of \
+                   the second", self.name, self.arities.0, self.arities.1, self.span.lo)
+    }
+}
+
+impl Error for MergeError {
+    fn description(&self) -> &str {
+        "relation with different arities"
+    }
+}
+
+pub fn merge(first: &Description, second: &Description, policy: &MergePolicy)
+             -> Result<Description, MergeError> {
+    let mut second = second.clone();
+    if let &MergePolicy::PrefixOther(ref prefix) = policy {
+        visitor::visit(&mut second, &mut Prefix { prefix: prefix });
+    }
+
+    // The numbers of arguments each relation in the first description has, in the order they
+    // first appear
+    let mut arities = HashMap::<String, Vec<usize>>::new();
+    for s in SymbolTable::new(first).symbols().iter().filter(|s| s.kind == SymbolKind::Relation) {
+        arities.entry(s.name.clone()).or_insert_with(Vec::new).push(s.arity);
+    }
+    let table = SymbolTable::new(&second);
+    for s in table.symbols().iter().filter(|s| s.kind == SymbolKind::Relation) {
+        match arities.get(&s.name) {
+            Some(a) if !a.contains(&s.arity) => {
+                return Err(MergeError { name: s.name.clone(), arities: (a[0], s.arity),
+                                        span: table.references(s)[0].span });
+            }
+            _ => {}
+        }
+    }
+
+    let clauses = first.clauses.iter().chain(second.clauses.iter()).cloned().collect();
+    Ok(Description::new(clauses))
+}
+
+struct Prefix<'a> {
+    prefix: &'a str
+}
+
+impl<'a> Prefix<'a> {
+    fn rename(&self, name: &mut String) {
+        if !is_keyword(name) && !is_reserved(name) && name != SEES {
+            *name = format!("{}{}", self.prefix, name);
+        }
+    }
+}
+
+impl<'a> Visitor for Prefix<'a> {
+    fn visit_proposition(&mut self, proposition: &mut Proposition) {
+        self.rename(&mut proposition.name.name);
+    }
+
+    fn visit_relation(&mut self, relation: &mut Relation) {
+        self.rename(&mut relation.name.name);
+    }
+}
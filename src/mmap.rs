@@ -0,0 +1,32 @@
+//! Parsing descriptions straight from a memory-mapped file, so a large file is never copied into a
+//! `String` first.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::str;
+
+use memmap2::Mmap;
+
+use {error, parse_checked, Description, GdlError};
+
+/// Parse the GDL description in the file at `path` by memory-mapping it. Errors are the same as
+/// for `parse_file`; a file that isn't valid UTF-8 is an `Io` error.
+///
+/// The file must not be modified while it's being parsed.
+pub fn parse_mmap<P: AsRef<Path>>(path: P) -> Result<Description, GdlError> {
+    let path = path.as_ref();
+    let io_error = |e| GdlError::Io { path: path.to_path_buf(), error: e };
+
+    let file = File::open(path).map_err(&io_error)?;
+    // Mapping an empty file fails on some platforms
+    if file.metadata().map_err(&io_error)?.len() == 0 {
+        return Ok(Description::new(Vec::new()));
+    }
+    let map = unsafe { Mmap::map(&file) }.map_err(&io_error)?;
+    let gdl = match str::from_utf8(&map) {
+        Ok(gdl) => gdl,
+        Err(e) => return Err(io_error(io::Error::new(io::ErrorKind::InvalidData, e)))
+    };
+    parse_checked(gdl).map_err(|e| error::in_file(path, gdl, e))
+}
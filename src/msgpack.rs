@@ -0,0 +1,18 @@
+//! Encoding descriptions as MessagePack, using the same representation as the serde impls
+//! (see the `serde` feature). Structs are written as maps with their field names, so spans can be
+//! left out like in JSON.
+
+use rmp_serde::{self, decode};
+
+use Description;
+
+/// Encodes `desc` as MessagePack
+pub fn to_msgpack(desc: &Description) -> Vec<u8> {
+    rmp_serde::to_vec_named(desc).expect("Encoding a description to memory can't fail")
+}
+
+/// Decodes a description from MessagePack written by `to_msgpack`, or by anything else that uses
+/// the same representation
+pub fn from_msgpack(bytes: &[u8]) -> Result<Description, decode::Error> {
+    rmp_serde::from_slice(bytes)
+}
@@ -0,0 +1,125 @@
+use lexer::{Lexer, Token};
+
+/// The default limit on how deeply parentheses can be nested. The parser is recursive, so this
+/// keeps machine-generated input from overflowing the stack. It's low enough that input nested
+/// this deeply uses less than half of the 2 MiB stack that spawned threads get by default, even in
+/// a debug build, so it doesn't depend on how large the parser's stack frames happen to be.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Options for `parse_with` that choose the dialect of GDL that's accepted and how strictly it's
+/// checked. The default options accept the same descriptions as `parse_checked`.
+///
+/// ```
+/// use gdl_parser::{parse_with, ParserOptions};
+///
+/// let options = ParserOptions { fold_case: true, max_depth: Some(32), ..ParserOptions::new() };
+/// let desc = parse_with("(ROLE White)", &options).unwrap();
+/// assert_eq!(desc.to_string(), "(role white)");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// Treat `sees` and the `random` role as GDL-II keywords, rejecting `sees` relations that
+    /// don't have exactly two arguments
+    pub allow_gdl_ii: bool,
+
+    /// Lowercase every constant, keyword, and variable name, since GDL is case-insensitive. For
+    /// example `(ROLE White)` is parsed as `(role white)` and `(NOT p)` as a not literal. Quoted
+    /// atoms keep their case.
+    pub fold_case: bool,
+
+    /// Reject GDL keywords that have the wrong number of arguments, like `(role)` or
+    /// `(legal ?r)`, with `ParseError::InvalidArity`. The keywords and their arities are listed in
+    /// `KEYWORD_ARITIES`.
+    pub strict_arity: bool,
+
+    /// Reject rules with a variable in the head, in a negated literal, or in a `distinct` that
+    /// doesn't appear in a positive literal in the body, with `ParseError::UnsafeVariable`.
+    /// Provers can't evaluate these rules.
+    pub strict_safety: bool,
+
+    /// Reject rules that break the recursion restriction from the GDL spec, with
+    /// `ParseError::RecursionRestriction`. See `check_recursion`.
+    pub strict_recursion: bool,
+
+    /// Keep top-level text that isn't a valid clause as `Clause::UnknownClause` instead of
+    /// failing, so embedded directives and metadata can be passed through. Each top-level form is
+    /// parsed separately in this mode. The rest of the line after an invalid form is kept with
+    /// it, and an unclosed `(` makes the rest of the input unknown.
+    pub keep_unknown: bool,
+
+    /// Collect the comments in the input and attach them to clauses, for `parse_with_trivia` to
+    /// return alongside the description. `parse_with` ignores this, since a `Description` has
+    /// nowhere to keep comments.
+    pub keep_comments: bool,
+
+    /// The deepest that parentheses can be nested. Input nested deeper than this is rejected with
+    /// `ParseError::TooDeep`. Defaults to `DEFAULT_MAX_DEPTH`; `None` removes the limit, which
+    /// risks overflowing the stack on very deep input.
+    pub max_depth: Option<usize>,
+
+    /// The most bytes of input that will be parsed. Larger input is rejected with
+    /// `ParseError::LimitExceeded` before it's parsed. Unlimited by default.
+    pub max_input_size: Option<usize>,
+
+    /// The most top-level clauses a description can have. Parsing stops with
+    /// `ParseError::LimitExceeded` at the first clause past the limit. Unlimited by default.
+    pub max_clauses: Option<usize>,
+
+    /// The longest an atom or variable can be, in bytes, including the quotes of quoted atoms.
+    /// Longer ones are rejected with `ParseError::LimitExceeded`. Unlimited by default.
+    pub max_atom_len: Option<usize>
+}
+
+impl Default for ParserOptions {
+    fn default() -> ParserOptions {
+        ParserOptions {
+            allow_gdl_ii: false,
+            fold_case: false,
+            strict_arity: false,
+            strict_safety: false,
+            strict_recursion: false,
+            keep_unknown: false,
+            keep_comments: false,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_input_size: None,
+            max_clauses: None,
+            max_atom_len: None
+        }
+    }
+}
+
+impl ParserOptions {
+    pub fn new() -> ParserOptions {
+        ParserOptions::default()
+    }
+
+    /// Options for parsing input from untrusted sources, like descriptions uploaded by players,
+    /// with limits on everything that makes parsing slow or uses a lot of memory. Descriptions
+    /// used in competitions are well within these limits.
+    pub fn untrusted() -> ParserOptions {
+        ParserOptions {
+            max_depth: Some(64),
+            max_input_size: Some(4 * 1024 * 1024),
+            max_clauses: Some(100000),
+            max_atom_len: Some(1024),
+            ..ParserOptions::default()
+        }
+    }
+}
+
+/// Lowercases the unquoted atoms and variables in `gdl`. They're ASCII, so this doesn't move any
+/// spans.
+pub fn fold_case(gdl: &str) -> String {
+    let mut folded = String::with_capacity(gdl.len());
+    for (token, span) in Lexer::new(gdl) {
+        let text = span.snippet(gdl);
+        match token {
+            Token::Atom(a) if !a.starts_with('"') && !a.starts_with('|') => {
+                folded.push_str(&text.to_ascii_lowercase())
+            }
+            Token::Variable(_) => folded.push_str(&text.to_ascii_lowercase()),
+            _ => folded.push_str(text)
+        }
+    }
+    folded
+}
@@ -0,0 +1,20 @@
+//! Parsing large descriptions on multiple threads. Top-level clauses don't depend on each other,
+//! so the input is split at clause boundaries and each clause is parsed on its own.
+
+use rayon::prelude::*;
+
+use {parse_clause_at, split, Description, ParseError};
+
+/// Parse a GDL string to a `Description`, parsing its clauses in parallel. Clauses are kept in the
+/// order they appear in, and if several are invalid the error is for the first one.
+pub fn parse_parallel(gdl: &str) -> Result<Description, ParseError> {
+    let forms: Vec<(usize, &str)> = split::forms(gdl).collect();
+    let results: Vec<_> = forms.par_iter()
+        .map(|&(start, form)| parse_clause_at(form, start))
+        .collect();
+    let mut clauses = Vec::with_capacity(results.len());
+    for r in results {
+        clauses.push(r?);
+    }
+    Ok(Description::new(clauses))
+}
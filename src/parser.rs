@@ -0,0 +1,358 @@
+//! A recursive-descent parser for the grammar in the README. It reads the tokens produced by the
+//! lexer and decides what to parse from the next token alone, so it never backtracks, and the only
+//! allocations are for the strings and vectors in the AST.
+
+use {Clause, Constant, Description, Distinct, Function, Limit, Literal, Not, Or, ParseError,
+     ParserOptions, Proposition, Relation, Rule, Sentence, Span, Term, Variable};
+use Clause::{RuleClause, SentenceClause};
+use Literal::{NotLit, OrLit, DistinctLit, PropLit, RelLit};
+use Sentence::{PropSentence, RelSentence};
+use Term::{VarTerm, FuncTerm, ConstTerm};
+use lexer::{Lexer, Token};
+use lexer::Token::{LParen, RParen, Atom, Variable as Var, Unknown};
+
+pub struct Parser<'a> {
+    input: &'a str,
+    lexer: Lexer<'a>,
+    // The next token that isn't trivia, or `None` at the end of the input
+    next: Option<(Token<'a>, Span)>,
+    // The last token that was consumed
+    prev: Option<(Token<'a>, Span)>,
+    // Added to every span, for input that is part of a larger string
+    offset: usize,
+    // The offsets of the `(`s that haven't been closed yet
+    open: Vec<usize>,
+    max_depth: Option<usize>,
+    max_clauses: Option<usize>,
+    max_atom_len: Option<usize>
+}
+
+impl<'a> Parser<'a> {
+    /// Creates a parser for `input`, which starts `offset` bytes into the full input. The nesting,
+    /// clause, and atom limits in `options` are enforced; the other options aren't used.
+    pub fn new(input: &'a str, offset: usize, options: &ParserOptions) -> Parser<'a> {
+        let mut parser = Parser {
+            input: input,
+            lexer: Lexer::new(input),
+            next: None,
+            prev: None,
+            offset: offset,
+            open: Vec::new(),
+            max_depth: options.max_depth,
+            max_clauses: options.max_clauses,
+            max_atom_len: options.max_atom_len
+        };
+        parser.advance();
+        parser
+    }
+
+    /// Returns an error unless the whole input has been parsed
+    pub fn finish(&self) -> Result<(), ParseError> {
+        match self.next {
+            None => Ok(()),
+            Some(_) => Err(self.error(&[]))
+        }
+    }
+
+    pub fn description(&mut self) -> Result<Description, ParseError> {
+        let mut clauses = Vec::new();
+        while let Some((_, span)) = self.next {
+            if let Some(max) = self.max_clauses {
+                if clauses.len() == max {
+                    return Err(ParseError::LimitExceeded { span: span, limit: Limit::Clauses,
+                                                           max: max });
+                }
+            }
+            clauses.push(self.clause()?);
+        }
+        Ok(Description::new(clauses))
+    }
+
+    pub fn clause(&mut self) -> Result<Clause, ParseError> {
+        match self.peek() {
+            Some(LParen) => {
+                let lo = self.expect_lparen(&["("])?;
+                if self.peek() == Some(Atom("<=")) {
+                    Ok(RuleClause(self.rule_rest(lo)?))
+                } else {
+                    let rel = self.relation_rest(lo, &["<=", "constant"])?;
+                    Ok(SentenceClause(RelSentence(rel)))
+                }
+            }
+            _ => {
+                let name = self.expect_constant(&["(", "constant"])?;
+                let span = name.span;
+                Ok(SentenceClause(PropSentence(Proposition::new(name).with_span(span))))
+            }
+        }
+    }
+
+    pub fn rule(&mut self) -> Result<Rule, ParseError> {
+        let lo = self.expect_lparen(&["("])?;
+        if self.peek() != Some(Atom("<=")) {
+            return Err(self.error(&["<="]));
+        }
+        self.rule_rest(lo)
+    }
+
+    pub fn sentence(&mut self) -> Result<Sentence, ParseError> {
+        match self.peek() {
+            Some(LParen) => {
+                let lo = self.expect_lparen(&["("])?;
+                Ok(RelSentence(self.relation_rest(lo, &["constant"])?))
+            }
+            _ => {
+                let name = self.expect_constant(&["(", "constant"])?;
+                let span = name.span;
+                Ok(PropSentence(Proposition::new(name).with_span(span)))
+            }
+        }
+    }
+
+    // Parses the rest of a rule whose `(` started at `lo`, starting from the `<=`
+    fn rule_rest(&mut self, lo: usize) -> Result<Rule, ParseError> {
+        self.bump();
+        let head = self.sentence()?;
+        let body = self.literals()?;
+        let hi = self.close_paren();
+        Ok(Rule::new(head, body).with_span(Span::new(lo, hi)))
+    }
+
+    // Parses the rest of a relation whose `(` started at `lo`, starting from its name
+    fn relation_rest(&mut self, lo: usize,
+                     expected: &[&'static str]) -> Result<Relation, ParseError> {
+        let name = self.expect_constant(expected)?;
+        let args = self.terms()?;
+        let hi = self.close_paren();
+        Ok(Relation::new(name, args).with_span(Span::new(lo, hi)))
+    }
+
+    pub fn literal(&mut self) -> Result<Literal, ParseError> {
+        if self.peek() != Some(LParen) {
+            let name = self.expect_constant(&["(", "constant"])?;
+            let span = name.span;
+            return Ok(PropLit(Proposition::new(name).with_span(span)));
+        }
+
+        let lo = self.expect_lparen(&["("])?;
+        let lit = match self.peek() {
+            Some(Atom("not")) => {
+                self.bump();
+                let lit = self.literal()?;
+                if self.peek() != Some(RParen) {
+                    return Err(self.error(&[")"]));
+                }
+                NotLit(Not::new(Box::new(lit)))
+            }
+            Some(Atom("or")) => {
+                self.bump();
+                OrLit(Or::new(self.literals()?))
+            }
+            Some(Atom("distinct")) => {
+                self.bump();
+                let term1 = self.term()?;
+                let term2 = self.term()?;
+                if self.peek() != Some(RParen) {
+                    return Err(self.error(&[")"]));
+                }
+                DistinctLit(Distinct::new(term1, term2))
+            }
+            _ => {
+                let rel = self.relation_rest(lo, &["constant", "distinct", "not", "or"])?;
+                return Ok(RelLit(rel));
+            }
+        };
+        let span = Span::new(lo, self.close_paren());
+        Ok(match lit {
+            NotLit(n) => NotLit(n.with_span(span)),
+            OrLit(o) => OrLit(o.with_span(span)),
+            DistinctLit(d) => DistinctLit(d.with_span(span)),
+            lit => lit
+        })
+    }
+
+    pub fn term(&mut self) -> Result<Term, ParseError> {
+        match self.peek() {
+            Some(LParen) => {
+                let lo = self.expect_lparen(&["("])?;
+                let name = self.expect_constant(&["constant"])?;
+                let args = self.terms()?;
+                let hi = self.close_paren();
+                Ok(FuncTerm(Function::new(name, args).with_span(Span::new(lo, hi))))
+            }
+            Some(Var(name)) => self.variable(name),
+            _ => self.constant_term()
+        }
+    }
+
+    // Functions nest through `term`, so the other kinds of terms are parsed here to keep the
+    // stack used per level small
+    fn variable(&mut self, name: &str) -> Result<Term, ParseError> {
+        let span = self.bump();
+        self.check_atom_len(span)?;
+        let name = Constant::new(name).with_span(Span::new(span.lo + 1, span.hi));
+        Ok(VarTerm(Variable::new(name).with_span(span)))
+    }
+
+    fn constant_term(&mut self) -> Result<Term, ParseError> {
+        Ok(ConstTerm(self.expect_constant(&["(", "constant", "variable"])?))
+    }
+
+    // Parses the body of a rule or disjunction, up to but not including the closing `)`
+    fn literals(&mut self) -> Result<Vec<Literal>, ParseError> {
+        let mut lits = Vec::new();
+        loop {
+            match self.peek() {
+                Some(RParen) => return Ok(lits),
+                Some(LParen) | Some(Atom(_)) => lits.push(self.literal()?),
+                _ => return Err(self.error(&["(", ")", "constant"]))
+            }
+        }
+    }
+
+    // Parses the arguments of a relation or function, up to but not including the closing `)`
+    fn terms(&mut self) -> Result<Vec<Term>, ParseError> {
+        let mut args = Vec::new();
+        loop {
+            match self.peek() {
+                Some(RParen) => return Ok(args),
+                Some(LParen) | Some(Var(_)) | Some(Atom(_)) => args.push(self.term()?),
+                _ => return Err(self.error(&["(", ")", "constant", "variable"]))
+            }
+        }
+    }
+
+    fn expect_constant(&mut self, expected: &[&'static str]) -> Result<Constant, ParseError> {
+        match self.peek() {
+            Some(Atom(a)) if a != "<=" => {
+                let span = self.bump();
+                self.check_atom_len(span)?;
+                let name = if a.starts_with('"') {
+                    Constant::string(unquote(a))
+                } else if a.starts_with('|') {
+                    Constant::new(unquote(a))
+                } else {
+                    Constant::new(a)
+                };
+                Ok(name.with_span(span))
+            }
+            _ => Err(self.error(expected))
+        }
+    }
+
+    fn check_atom_len(&self, span: Span) -> Result<(), ParseError> {
+        match self.max_atom_len {
+            Some(max) if span.len() > max => {
+                Err(ParseError::LimitExceeded { span: span, limit: Limit::AtomLength, max: max })
+            }
+            _ => Ok(())
+        }
+    }
+
+    // Consumes a `(`, returning its offset
+    fn expect_lparen(&mut self, expected: &[&'static str]) -> Result<usize, ParseError> {
+        if self.peek() != Some(LParen) {
+            return Err(self.error(expected));
+        }
+        let span = self.bump();
+        self.open.push(span.lo);
+        if let Some(max_depth) = self.max_depth {
+            if self.open.len() > max_depth {
+                return Err(ParseError::TooDeep { span: span, max_depth: max_depth });
+            }
+        }
+        Ok(span.lo)
+    }
+
+    // Consumes the `)` that the caller has already checked is next, returning its end
+    fn close_paren(&mut self) -> usize {
+        self.open.pop();
+        self.bump().hi
+    }
+
+    fn peek(&self) -> Option<Token<'a>> {
+        self.next.map(|(t, _)| t)
+    }
+
+    // Consumes the next token, returning its span
+    fn bump(&mut self) -> Span {
+        let next = self.next;
+        self.prev = next;
+        self.advance();
+        next.unwrap().1
+    }
+
+    fn advance(&mut self) {
+        let offset = self.offset;
+        self.next = self.lexer.by_ref().find(|&(t, _)| !t.is_trivia())
+            .map(|(t, span)| (t, Span::new(span.lo + offset, span.hi + offset)));
+    }
+
+    // Describes the next token, which isn't one of the `expected` tokens
+    fn error(&self, expected: &[&'static str]) -> ParseError {
+        let end = self.offset + self.input.len();
+        let eof = Span::new(end, end);
+        let (token, span) = match self.next {
+            Some(next) => next,
+            None => {
+                return match self.open.last() {
+                    Some(&lo) => ParseError::UnbalancedParen { span: Span::new(lo, lo + 1) },
+                    None => ParseError::UnexpectedEof { span: eof, expected: expected.to_vec() }
+                };
+            }
+        };
+        match token {
+            RParen if self.open.is_empty() => ParseError::UnbalancedParen { span: span },
+            RParen => match self.prev {
+                Some((LParen, lparen)) => {
+                    ParseError::EmptyClause { span: Span::new(lparen.lo, span.hi) }
+                }
+                _ => self.unexpected(token, span, expected)
+            },
+            Unknown("?") => {
+                let hi = token_end(self.input, span.hi - self.offset) + self.offset;
+                ParseError::InvalidVariable { span: Span::new(span.lo, hi) }
+            }
+            // The lexer can't find the end of an unclosed quote or block comment
+            Unknown("\"") => ParseError::UnexpectedEof { span: eof, expected: vec!["\""] },
+            Unknown("|") => ParseError::UnexpectedEof { span: eof, expected: vec!["|"] },
+            Unknown("#|") => ParseError::UnexpectedEof { span: eof, expected: vec!["|#"] },
+            _ => self.unexpected(token, span, expected)
+        }
+    }
+
+    fn unexpected(&self, token: Token<'a>, span: Span, expected: &[&'static str]) -> ParseError {
+        let lo = span.lo - self.offset;
+        let hi = match token {
+            Unknown(_) => token_end(self.input, lo),
+            _ => span.hi - self.offset
+        };
+        ParseError::UnexpectedToken {
+            span: Span::new(span.lo, hi + self.offset),
+            found: self.input[lo..hi].to_string(),
+            expected: expected.to_vec()
+        }
+    }
+}
+
+// Removes the quotes and escapes from a quoted atom
+fn unquote(text: &str) -> String {
+    let mut s = String::with_capacity(text.len() - 2);
+    let mut chars = text[1..text.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => s.extend(chars.next()),
+            c => s.push(c)
+        }
+    }
+    s
+}
+
+// Returns the end of the text starting at `pos` that isn't a token on its own, which runs until the
+// next delimiter
+fn token_end(input: &str, pos: usize) -> usize {
+    match input[pos..].find(|c: char| c.is_whitespace() || c == '(' || c == ')' || c == ';') {
+        Some(n) => pos + n,
+        None => input.len()
+    }
+}
@@ -0,0 +1,146 @@
+//! Printing for the `Display` impls of the AST. Nodes are printed with an explicit stack instead of
+//! by recursing, so printing machine-generated terms can't overflow the stack however deeply
+//! they're nested. Only printing works this way; the derived impls like `Clone` and `Eq`, and
+//! dropping nodes, still recurse once for every level of nesting.
+
+use std::fmt::{self, Formatter};
+
+use {Clause, Description, Distinct, Function, Literal, Not, Or, Relation, Rule, Sentence, Term};
+use Knows;
+use Clause::{RuleClause, SentenceClause, UnknownClause};
+use Literal::{NotLit, OrLit, DistinctLit, PropLit, RelLit};
+use Literal::KnowsLit;
+use Sentence::{PropSentence, RelSentence};
+use Term::{VarTerm, FuncTerm, ConstTerm};
+use lexer::{Lexer, Token};
+
+/// Something that's left to print
+#[derive(Clone, Copy)]
+pub enum Item<'a> {
+    Description(&'a Description),
+    Clause(&'a Clause),
+    Rule(&'a Rule),
+    Sentence(&'a Sentence),
+    Literal(&'a Literal),
+    Term(&'a Term),
+    Relation(&'a Relation),
+    Function(&'a Function),
+    Not(&'a Not),
+    Or(&'a Or),
+    Distinct(&'a Distinct),
+    Knows(&'a Knows),
+    Text(&'static str)
+}
+
+/// Prints `item` and everything inside it
+pub fn write(f: &mut Formatter, item: Item) -> fmt::Result {
+    let mut stack = vec![item];
+    while let Some(item) = stack.pop() {
+        // Children are pushed in reverse, so they're popped and printed in order
+        match item {
+            Item::Description(d) => {
+                for (i, c) in d.clauses.iter().enumerate().rev() {
+                    stack.push(Item::Clause(c));
+                    if i > 0 {
+                        stack.push(Item::Text(" "));
+                    }
+                }
+            }
+            Item::Clause(c) => match c {
+                &RuleClause(ref r) => stack.push(Item::Rule(r)),
+                &SentenceClause(ref s) => stack.push(Item::Sentence(s)),
+                &UnknownClause(ref u) => write!(f, "{}", u.text)?
+            },
+            Item::Rule(r) => {
+                write!(f, "(<= ")?;
+                stack.push(Item::Text(")"));
+                push_all(&mut stack, r.body.iter().map(Item::Literal));
+                stack.push(Item::Sentence(&r.head));
+            }
+            Item::Sentence(s) => match s {
+                &PropSentence(ref p) => write!(f, "{}", p.name)?,
+                &RelSentence(ref r) => stack.push(Item::Relation(r))
+            },
+            Item::Literal(l) => match l {
+                &NotLit(ref n) => stack.push(Item::Not(n)),
+                &OrLit(ref o) => stack.push(Item::Or(o)),
+                &DistinctLit(ref d) => stack.push(Item::Distinct(d)),
+                &PropLit(ref p) => write!(f, "{}", p.name)?,
+                &RelLit(ref r) => stack.push(Item::Relation(r)),
+                &KnowsLit(ref k) => stack.push(Item::Knows(k))
+            },
+            Item::Term(t) => match t {
+                &VarTerm(ref v) => write!(f, "?{}", v.name)?,
+                &FuncTerm(ref func) => stack.push(Item::Function(func)),
+                &ConstTerm(ref c) => write!(f, "{}", c)?
+            },
+            Item::Relation(r) => {
+                write!(f, "({}", r.name)?;
+                stack.push(Item::Text(")"));
+                push_all(&mut stack, r.args.iter().map(Item::Term));
+            }
+            Item::Function(func) => {
+                write!(f, "({}", func.name)?;
+                stack.push(Item::Text(")"));
+                push_all(&mut stack, func.args.iter().map(Item::Term));
+            }
+            Item::Not(n) => {
+                write!(f, "(not ")?;
+                stack.push(Item::Text(")"));
+                stack.push(Item::Literal(&n.lit));
+            }
+            Item::Or(o) => {
+                write!(f, "(or")?;
+                stack.push(Item::Text(")"));
+                push_all(&mut stack, o.lits.iter().map(Item::Literal));
+            }
+            Item::Distinct(d) => {
+                write!(f, "(distinct ")?;
+                stack.push(Item::Text(")"));
+                stack.push(Item::Term(&d.term2));
+                stack.push(Item::Text(" "));
+                stack.push(Item::Term(&d.term1));
+            }
+            Item::Knows(k) => {
+                write!(f, "(knows ")?;
+                stack.push(Item::Text(")"));
+                stack.push(Item::Term(&k.prop));
+                if let Some(ref role) = k.role {
+                    stack.push(Item::Text(" "));
+                    stack.push(Item::Term(role));
+                }
+            }
+            Item::Text(text) => write!(f, "{}", text)?
+        }
+    }
+    Ok(())
+}
+
+// Pushes `items` so that they're printed in order, each preceded by a space
+fn push_all<'a, I>(stack: &mut Vec<Item<'a>>, items: I)
+    where I: DoubleEndedIterator<Item = Item<'a>>
+{
+    for item in items.rev() {
+        stack.push(item);
+        stack.push(Item::Text(" "));
+    }
+}
+
+/// Removes the whitespace from printed GDL that isn't needed to separate tokens, which is all of it
+/// except single spaces between atoms and variables
+pub fn compact(gdl: &str) -> String {
+    let mut s = String::with_capacity(gdl.len());
+    let mut prev_word = false;
+    for (token, span) in Lexer::new(gdl).filter(|&(t, _)| !t.is_trivia()) {
+        let word = match token {
+            Token::Atom(_) | Token::Variable(_) => true,
+            _ => false
+        };
+        if prev_word && word {
+            s.push(' ');
+        }
+        s.push_str(span.snippet(gdl));
+        prev_word = word;
+    }
+    s
+}
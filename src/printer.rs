@@ -0,0 +1,273 @@
+//! A pretty printer that lays out descriptions the way hand-written GDL files usually are. Facts
+//! are printed on one line each, and rules have their head on the first line and each literal of
+//! their body indented on its own line beneath it:
+//!
+//! ```text
+//! (role white)
+//! (role black)
+//!
+//! (<= (legal ?player noop)
+//!     (role ?player)
+//!     (not (true (control ?player))))
+//! ```
+//!
+//! A blank line separates rules from the clauses around them. A disjunction that doesn't fit in the
+//! maximum width is broken up with each of its literals on its own line, indented further. The
+//! layout can be changed by passing a `FormatStyle` to `format`, and `reformat` tidies up source
+//! text while keeping its comments and line breaks.
+
+use std::cmp;
+
+use {parse_checked, Clause, Description, Literal, ParseError, Rule};
+use Clause::{RuleClause, SentenceClause, UnknownClause};
+use Literal::OrLit;
+use dialect;
+use lexer::{Lexer, Token};
+
+/// Options for how `format` lays out a description
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::printer::{format, FormatStyle};
+///
+/// let desc = parse("(<= (legal ?r noop) (role ?r) (not (true (control ?r))))");
+/// let style = FormatStyle { uppercase_keywords: true, align_body: true, ..FormatStyle::new() };
+/// assert_eq!(format(&desc, &style),
+///            "(<= (LEGAL ?r noop)\n    (ROLE ?r)\n    (NOT (TRUE (control ?r))))");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatStyle {
+    /// Print the whole description on one line with a space between clauses, like `Display`.
+    /// Otherwise each clause gets its own line, and rule bodies are broken up as described in the
+    /// module docs. The other layout options are ignored when this is set.
+    pub compact: bool,
+
+    /// Print the keywords that GDL gives a meaning to, like `role`, `true`, and `not`, in
+    /// uppercase
+    pub uppercase_keywords: bool,
+
+    /// Line body literals up under the head of their rule, and the literals of a broken up
+    /// disjunction under its first literal, instead of indenting them by `indent`
+    pub align_body: bool,
+
+    /// End the output with a newline
+    pub trailing_newline: bool,
+
+    /// The number of spaces that each level of nesting is indented by
+    pub indent: usize,
+
+    /// The width that lines are kept within where possible. Lines can still be wider if a single
+    /// literal doesn't fit.
+    pub max_width: usize
+}
+
+impl Default for FormatStyle {
+    fn default() -> FormatStyle {
+        FormatStyle {
+            compact: false,
+            uppercase_keywords: false,
+            align_body: false,
+            trailing_newline: false,
+            indent: 4,
+            max_width: 80
+        }
+    }
+}
+
+impl FormatStyle {
+    pub fn new() -> FormatStyle {
+        FormatStyle::default()
+    }
+}
+
+/// Prints `desc` laid out according to `style`
+pub fn format(desc: &Description, style: &FormatStyle) -> String {
+    let mut s = if style.compact {
+        desc.to_string()
+    } else {
+        let mut s = String::new();
+        let mut prev: Option<&Clause> = None;
+        for clause in desc.clauses.iter() {
+            if let Some(prev) = prev {
+                s.push('\n');
+                if is_rule(prev) || is_rule(clause) {
+                    s.push('\n');
+                }
+            }
+            match clause {
+                &RuleClause(ref r) => write_rule(&mut s, r, style),
+                &SentenceClause(ref sentence) => s.push_str(&sentence.to_string()),
+                &UnknownClause(ref u) => s.push_str(&u.text)
+            }
+            prev = Some(clause);
+        }
+        s
+    };
+    if style.uppercase_keywords {
+        s = uppercase_keywords(&s);
+    }
+    if style.trailing_newline {
+        s.push('\n');
+    }
+    s
+}
+
+/// Normalizes the spacing of `gdl` without otherwise changing its layout, so reformatting an
+/// existing file gives a small diff. Comments and line breaks are kept where they are, except that
+/// runs of blank lines become a single blank line. Tokens on the same line are separated by single
+/// spaces, with none inside parentheses, and each line is indented by `style.indent` spaces per
+/// open parenthesis. Of the other style options, only `uppercase_keywords` and
+/// `trailing_newline` are used, and the output also ends with a newline if `gdl` did.
+///
+/// Returns an error if `gdl` isn't a valid description, since its layout can't be trusted then.
+pub fn reformat(gdl: &str, style: &FormatStyle) -> Result<String, ParseError> {
+    parse_checked(gdl)?;
+    let mut s = String::with_capacity(gdl.len());
+    let mut depth = 0;
+    // The last token that was written, which decides whether a space is needed before the next
+    let mut prev = None;
+    let mut newlines = 0;
+    for (token, span) in Lexer::new(gdl) {
+        if let Token::Whitespace(ws) = token {
+            newlines += ws.matches('\n').count();
+            continue;
+        }
+        if newlines > 0 && !s.is_empty() {
+            s.extend((0..cmp::min(newlines, 2)).map(|_| '\n'));
+            let level = if token == Token::RParen { depth - 1 } else { depth };
+            push_spaces(&mut s, level * style.indent);
+        } else if let Some(prev) = prev {
+            if prev != Token::LParen && token != Token::RParen {
+                s.push(' ');
+            }
+        }
+        newlines = 0;
+        s.push_str(span.snippet(gdl));
+        match token {
+            Token::LParen => depth += 1,
+            Token::RParen => depth -= 1,
+            _ => {}
+        }
+        prev = Some(token);
+    }
+    if style.uppercase_keywords {
+        s = uppercase_keywords(&s);
+    }
+    if style.trailing_newline || gdl.ends_with('\n') {
+        s.push('\n');
+    }
+    Ok(s)
+}
+
+/// Prints descriptions with indentation and a maximum line width. This is a builder for the
+/// layout options of `FormatStyle`.
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::printer::PrettyPrinter;
+///
+/// let desc = parse("(<= (legal ?r noop) (role ?r) (not (true (control ?r))))");
+/// assert_eq!(PrettyPrinter::new().indent(2).max_width(80).print(&desc),
+///            "(<= (legal ?r noop)\n  (role ?r)\n  (not (true (control ?r))))");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PrettyPrinter {
+    style: FormatStyle
+}
+
+impl PrettyPrinter {
+    /// Creates a printer that indents by 4 spaces and breaks disjunctions wider than 80 columns
+    pub fn new() -> PrettyPrinter {
+        PrettyPrinter::default()
+    }
+
+    /// Sets the number of spaces that each level of nesting is indented by
+    pub fn indent(mut self, indent: usize) -> PrettyPrinter {
+        self.style.indent = indent;
+        self
+    }
+
+    /// Sets the width that lines are kept within where possible. Lines can still be wider if a
+    /// single literal doesn't fit.
+    pub fn max_width(mut self, max_width: usize) -> PrettyPrinter {
+        self.style.max_width = max_width;
+        self
+    }
+
+    /// Prints `desc`, with one clause per line
+    pub fn print(&self, desc: &Description) -> String {
+        format(desc, &self.style)
+    }
+}
+
+fn write_rule(s: &mut String, rule: &Rule, style: &FormatStyle) {
+    s.push_str("(<= ");
+    s.push_str(&rule.head.to_string());
+    let col = if style.align_body { 4 } else { style.indent };
+    for (i, lit) in rule.body.iter().enumerate() {
+        s.push('\n');
+        push_spaces(s, col);
+        let closing = if i == rule.body.len() - 1 { 1 } else { 0 };
+        write_literal(s, lit, col, closing, style);
+    }
+    s.push(')');
+}
+
+// Writes `lit`, which starts at column `col` of the current line. It's followed by `closing`
+// parentheses, which have to fit on the line too.
+fn write_literal(s: &mut String, lit: &Literal, col: usize, closing: usize, style: &FormatStyle) {
+    let text = lit.to_string();
+    match lit {
+        &OrLit(ref o) if !o.lits.is_empty() && col + text.len() + closing > style.max_width => {
+            let col = if style.align_body {
+                s.push_str("(or ");
+                col + 4
+            } else {
+                s.push_str("(or");
+                col + style.indent
+            };
+            for (i, l) in o.lits.iter().enumerate() {
+                if i > 0 || !style.align_body {
+                    s.push('\n');
+                    push_spaces(s, col);
+                }
+                let closing = if i == o.lits.len() - 1 { closing + 1 } else { 0 };
+                write_literal(s, l, col, closing, style);
+            }
+            s.push(')');
+        }
+        _ => s.push_str(&text)
+    }
+}
+
+fn push_spaces(s: &mut String, n: usize) {
+    s.extend((0..n).map(|_| ' '));
+}
+
+// Uppercases the keywords in printed GDL. A keyword is only uppercased where it names a relation
+// or literal, right after a `(`, so constants that happen to share a keyword's name keep their
+// case. `terminal` has no arguments, so it's uppercased wherever it appears.
+fn uppercase_keywords(gdl: &str) -> String {
+    let mut s = String::with_capacity(gdl.len());
+    let mut after_paren = false;
+    for (token, span) in Lexer::new(gdl) {
+        let text = span.snippet(gdl);
+        match token {
+            Token::Atom(a) if (after_paren || a == "terminal") && dialect::is_keyword(a) => {
+                s.push_str(&text.to_ascii_uppercase())
+            }
+            _ => s.push_str(text)
+        }
+        if !token.is_trivia() {
+            after_paren = token == Token::LParen;
+        }
+    }
+    s
+}
+
+fn is_rule(clause: &Clause) -> bool {
+    match clause {
+        &RuleClause(_) => true,
+        _ => false
+    }
+}
@@ -0,0 +1,37 @@
+//! A front-end for clauses written in Prolog syntax, as emitted by many logic programming tools.
+//! Each clause ends with a `.`, and its body literals are separated by commas:
+//!
+//! ```text
+//! next(cell(X,Y,o)) :- does(o,mark(X,Y)).
+//! ```
+//!
+//! Negation as failure is written `\+ lit` or `not(lit)`, a disjunction is written
+//! `(lit1 ; lit2)`, and `X \= Y` is translated to `(distinct ?x ?y)`. Variables start with an
+//! uppercase letter or an underscore and are converted like in the HRF syntax; every `_` is a
+//! separate variable, named `?_1`, `?_2`, and so on, skipping names used elsewhere in the clause.
+//! Atoms can be quoted with `'`, and `%` and `/* */` start comments. Directives like
+//! `:- dynamic cell/3.` are skipped, so files written for Prolog GGP engines can be read as they
+//! are.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use hrf::{self, Syntax};
+use {error, Description, GdlError, ParseError};
+
+/// Parse a description written in Prolog syntax
+pub fn parse_prolog(src: &str) -> Result<Description, ParseError> {
+    hrf::parse_infix(src, Syntax::Prolog)
+}
+
+/// Parse the description in the Prolog file at `path`. Errors include the path, and parse errors
+/// the line and column they occurred at, like for `parse_file`.
+pub fn parse_prolog_file<P: AsRef<Path>>(path: P) -> Result<Description, GdlError> {
+    let path = path.as_ref();
+    let mut src = String::new();
+    if let Err(e) = File::open(path).and_then(|mut f| f.read_to_string(&mut src)) {
+        return Err(GdlError::Io { path: path.to_path_buf(), error: e });
+    }
+    parse_prolog(&src).map_err(|e| error::in_file(path, &src, e))
+}
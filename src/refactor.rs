@@ -0,0 +1,123 @@
+//! Refactorings, which change names in a description everywhere they're defined and used
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use {Description, Proposition, Relation, Span};
+use analysis::{SymbolKind, SymbolTable};
+use dialect::{is_keyword, is_reserved, SEES};
+use lexer::{Lexer, Token};
+use visitor::{self, Visitor};
+
+/// The reasons a relation can't be renamed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    /// The old or new name is a GDL keyword, which would change what the game means
+    Keyword { name: String },
+
+    /// There's no relation with the old name
+    Undefined { name: String },
+
+    /// The new name isn't a single atom, so it can't be the name of a relation
+    InvalidName { name: String },
+
+    /// There's already a relation with the new name, first appearing at `span`
+    Conflict { name: String, span: Span }
+}
+
+impl Display for RenameError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            &RenameError::Keyword { ref name } => {
+                write!(f, "`{}` is a GDL keyword, so it can't be renamed or be a new name", name)
+            }
+            &RenameError::Undefined { ref name } => {
+                write!(f, "there's no relation named `{}`", name)
+            }
+            &RenameError::InvalidName { ref name } => {
+                write!(f, "`{}` isn't a valid relation name", name)
+            }
+            &RenameError::Conflict { ref name, span } => {
+                write!(f, "there's already a relation named `{}` at byte {}", name, span.lo)
+            }
+        }
+    }
+}
+
+impl Error for RenameError {
+    fn description(&self) -> &str {
+        match self {
+            &RenameError::Keyword { .. } => "keyword can't be renamed",
+            &RenameError::Undefined { .. } => "undefined relation",
+            &RenameError::InvalidName { .. } => "invalid relation name",
+            &RenameError::Conflict { .. } => "relation name already used"
+        }
+    }
+}
+
+/// Renames the relation `old` to `new` in every head and body of `desc`, for any number of
+/// arguments. For example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::refactor::rename_relation;
+///
+/// let mut desc = parse("(succ 1 2) (<= (next (step ?y)) (true (step ?x)) (succ ?x ?y))");
+/// rename_relation(&mut desc, "succ", "plus_one").unwrap();
+/// assert_eq!(desc.to_string(),
+///            "(plus_one 1 2) (<= (next (step ?y)) (true (step ?x)) (plus_one ?x ?y))");
+/// ```
+///
+/// Functions with the same name, like `step` in `(true (step ?x))`, aren't renamed. The
+/// description isn't changed if `old` or `new` is a keyword like `legal` or `sees`, if there's no
+/// relation named `old`, if `new` isn't an atom, or if there's already a relation named `new`.
+/// Clauses that couldn't be parsed are left as they are.
+pub fn rename_relation(desc: &mut Description, old: &str, new: &str) -> Result<(), RenameError> {
+    for name in [old, new].iter() {
+        if is_keyword(name) || is_reserved(name) || *name == SEES {
+            return Err(RenameError::Keyword { name: name.to_string() });
+        }
+    }
+    let table = SymbolTable::new(desc);
+    let first = |name: &str| {
+        table.symbols().iter().filter(|s| s.kind == SymbolKind::Relation && s.name == name)
+             .filter_map(|s| table.references(s).first()).min_by_key(|r| (r.clause, r.span.lo))
+             .map(|r| r.span)
+    };
+    if first(old).is_none() {
+        return Err(RenameError::Undefined { name: old.to_string() });
+    }
+    let mut tokens = Lexer::new(new);
+    match (tokens.next(), tokens.next()) {
+        (Some((Token::Atom(a), _)), None) if a != "<=" => {}
+        _ => return Err(RenameError::InvalidName { name: new.to_string() })
+    }
+    if old == new {
+        return Ok(());
+    }
+    if let Some(span) = first(new) {
+        return Err(RenameError::Conflict { name: new.to_string(), span: span });
+    }
+
+    visitor::visit(desc, &mut Rename { old: old, new: new });
+    Ok(())
+}
+
+struct Rename<'a> {
+    old: &'a str,
+    new: &'a str
+}
+
+impl<'a> Visitor for Rename<'a> {
+    fn visit_proposition(&mut self, proposition: &mut Proposition) {
+        if proposition.name.name == self.old {
+            proposition.name.name = self.new.to_string();
+        }
+    }
+
+    fn visit_relation(&mut self, relation: &mut Relation) {
+        if relation.name.name == self.old {
+            relation.name.name = self.new.to_string();
+        }
+    }
+}
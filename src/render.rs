@@ -0,0 +1,65 @@
+//! Renders diagnostics for people reading them in a terminal, along with the source they point at
+
+use std::cmp;
+
+use Diagnostic;
+use span::line_col;
+use split;
+
+// The most lines of the clause before the diagnostic's line that are shown with it
+const CONTEXT_LINES: usize = 3;
+
+/// Renders `diagnostic` with the lines of `src` that it points at and a caret under its span,
+/// preceded by up to three earlier lines from the same clause. `name` is the file name shown with
+/// the line and column. For example:
+///
+/// ```text
+/// warning[singleton-variable]: variable `?player` only appears once
+///  --> game.gdl:3:11
+///   |
+/// 2 | (<= (legal ?r noop)
+/// 3 |     (role ?player))
+///   |           ^^^^^^^
+/// ```
+pub fn render(diagnostic: &Diagnostic, src: &str, name: &str) -> String {
+    let span = diagnostic.span;
+    let (line, col) = line_col(src, span.lo);
+    let clause_lo = split::forms(src).map(|(offset, text)| (offset, offset + text.len()))
+        .take_while(|&(lo, _)| lo <= span.lo)
+        .last()
+        .and_then(|(lo, hi)| if span.lo <= hi { Some(lo) } else { None })
+        .unwrap_or(span.lo);
+    let first = cmp::max(line_col(src, clause_lo).0, line.saturating_sub(CONTEXT_LINES));
+    let lines: Vec<&str> = src.lines().collect();
+    let text = |n: usize| lines.get(n - 1).cloned().unwrap_or("");
+    let width = line.to_string().len();
+
+    let mut s = format!("{}\n", diagnostic);
+    s.push_str(&format!("{:w$}--> {}:{}:{}\n", "", name, line, col, w = width));
+    s.push_str(&format!("{:w$} |\n", "", w = width));
+    for n in first..line + 1 {
+        s.push_str(&format!("{:>w$} | {}\n", n, text(n), w = width));
+    }
+
+    // Line the caret up with the span, keeping tabs so it lines up however they're displayed, and
+    // underline the part of the span on its first line
+    let before = &text(line)[..cmp::min(col - 1, text(line).len())];
+    let indent: String = before.chars().map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+    let rest = &text(line)[before.len()..];
+    let underline = rest[..cmp::min(span.len(), rest.len())].chars().count();
+    let carets = "^".repeat(cmp::max(underline, 1));
+    s.push_str(&format!("{:w$} | {}{}\n", "", indent, carets, w = width));
+
+    if let Some(ref fix) = diagnostic.fix {
+        let old = fix.span.snippet(src);
+        let help = if fix.text.is_empty() {
+            format!("remove `{}`", old)
+        } else if old.is_empty() {
+            format!("insert `{}`", fix.text)
+        } else {
+            format!("replace `{}` with `{}`", old, fix.text)
+        };
+        s.push_str(&format!("{:w$} = help: {}\n", "", help, w = width));
+    }
+    s
+}
@@ -0,0 +1,248 @@
+//! Support for the JSON served by [ggp.org](http://games.ggp.org) game repositories. A game's
+//! JSON holds its GDL in a `rulesheet` field (`theRulesheet` in the format GGP Base serializes
+//! games to) along with metadata about the game, such as its name and the roles in it. A
+//! repository's `METADATA` file names the rulesheet file instead of holding its text, so a
+//! `rulesheet` field is only used if it contains GDL.
+//!
+//! `to_ggp_json` and `from_ggp_json` convert descriptions to and from the symbol lists that GGP
+//! Base uses for GDL in JSON, where each parenthesized list is a JSON array and each atom or
+//! variable is a string, so `(<= (legal ?r noop) (role ?r))` is
+//! `["<=",["legal","?r","noop"],["role","?r"]]`.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use serde_json::{self, Value};
+
+use {parse_checked, Constant, Description, Literal, ParseError, Sentence, Term};
+use Clause::{RuleClause, SentenceClause, UnknownClause};
+use Literal::{NotLit, OrLit, DistinctLit, PropLit, RelLit};
+use Sentence::{PropSentence, RelSentence};
+use Term::{VarTerm, FuncTerm, ConstTerm};
+
+use self::RepositoryError::{InvalidJson, MissingRulesheet, Parse};
+
+/// A game read from a repository: the parsed rulesheet and the metadata that came with it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Game {
+    pub desc: Description,
+    pub metadata: GameMetadata
+}
+
+impl Game {
+    /// Returns the number of roles in the game. This is the `numRoles` field if the JSON had one,
+    /// otherwise the number of `role` facts in the rulesheet.
+    pub fn num_roles(&self) -> usize {
+        match self.metadata.num_roles {
+            Some(n) => n,
+            None => self.role_names().len()
+        }
+    }
+
+    /// Returns the names of the roles in the game. This is the `roleNames` field if the JSON had
+    /// one, otherwise the arguments of the `role` facts in the rulesheet.
+    pub fn role_names(&self) -> Vec<String> {
+        if !self.metadata.role_names.is_empty() {
+            return self.metadata.role_names.clone();
+        }
+        let mut roles = Vec::new();
+        for clause in self.desc.clauses.iter() {
+            if let &SentenceClause(RelSentence(ref r)) = clause {
+                if r.name.name == "role" && r.args.len() == 1 {
+                    roles.push(r.args[0].to_string());
+                }
+            }
+        }
+        roles
+    }
+}
+
+/// The metadata a repository stores alongside a game's rulesheet. Every field is optional since
+/// repositories differ in what they include.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GameMetadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub version: Option<u64>,
+    pub num_roles: Option<usize>,
+    pub role_names: Vec<String>
+}
+
+impl GameMetadata {
+    fn from_json(json: &Value) -> GameMetadata {
+        GameMetadata {
+            name: string_field(json, &["gameName", "theName"]),
+            description: string_field(json, &["description", "theDescription"]),
+            version: json.get("version").and_then(|v| v.as_u64()),
+            num_roles: json.get("numRoles").and_then(|n| n.as_u64()).map(|n| n as usize),
+            role_names: match json.get("roleNames").and_then(|r| r.as_array()) {
+                Some(names) => names.iter().filter_map(|n| n.as_str())
+                                    .map(|n| n.to_string()).collect(),
+                None => Vec::new()
+            }
+        }
+    }
+}
+
+/// An error produced when a repository's JSON can't be turned into a `Game`
+#[derive(Debug, Clone, PartialEq)]
+pub enum RepositoryError {
+    /// The input isn't valid JSON, or doesn't have the expected structure
+    InvalidJson(String),
+
+    /// The JSON doesn't have a rulesheet containing GDL
+    MissingRulesheet,
+
+    /// The rulesheet isn't a valid GDL description
+    Parse(ParseError)
+}
+
+impl From<ParseError> for RepositoryError {
+    fn from(e: ParseError) -> RepositoryError {
+        Parse(e)
+    }
+}
+
+impl Display for RepositoryError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            &InvalidJson(ref message) => write!(f, "invalid game JSON: {}", message),
+            &MissingRulesheet => write!(f, "game JSON has no rulesheet"),
+            &Parse(ref e) => write!(f, "invalid rulesheet: {}", e)
+        }
+    }
+}
+
+impl Error for RepositoryError {
+    fn description(&self) -> &str {
+        match self {
+            &InvalidJson(_) => "invalid game JSON",
+            &MissingRulesheet => "missing rulesheet",
+            &Parse(_) => "invalid rulesheet"
+        }
+    }
+}
+
+/// Parse a game from the JSON served by a game repository. Spans in the description are relative
+/// to the start of the rulesheet string.
+pub fn parse_game_json(json: &str) -> Result<Game, RepositoryError> {
+    let json: Value = match serde_json::from_str(json) {
+        Ok(json) => json,
+        Err(e) => return Err(InvalidJson(e.to_string()))
+    };
+    if json.as_object().is_none() {
+        return Err(InvalidJson("expected an object".to_string()));
+    }
+    let rulesheet = ["rulesheet", "theRulesheet"].iter()
+        .filter_map(|k| json.get(k).and_then(|v| v.as_str()))
+        .find(|r| r.contains('('));
+    let rulesheet = match rulesheet {
+        Some(r) => r,
+        None => return Err(MissingRulesheet)
+    };
+    let desc = parse_checked(rulesheet)?;
+    Ok(Game { desc: desc, metadata: GameMetadata::from_json(&json) })
+}
+
+// Returns the first of `keys` that is a string field of `json`
+fn string_field(json: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter().filter_map(|k| json.get(k).and_then(|v| v.as_str()))
+        .next().map(|s| s.to_string())
+}
+
+/// Converts a description to the symbol list JSON used by GGP Base, with one array element per
+/// clause. Unknown clauses are left out, since they can't be represented as symbols.
+pub fn to_ggp_json(desc: &Description) -> String {
+    let clauses = desc.clauses.iter().filter_map(|c| match c {
+        &RuleClause(ref r) => {
+            let mut list = vec![Value::String("<=".to_string()), sentence_json(&r.head)];
+            list.extend(r.body.iter().map(literal_json));
+            Some(Value::Array(list))
+        }
+        &SentenceClause(ref s) => Some(sentence_json(s)),
+        &UnknownClause(_) => None
+    });
+    Value::Array(clauses.collect()).to_string()
+}
+
+/// Parse a description from the symbol list JSON used by GGP Base, which must be an array with
+/// one element per clause. Spans in the description are relative to the GDL text that the symbols
+/// are converted to, not the JSON.
+pub fn from_ggp_json(json: &str) -> Result<Description, RepositoryError> {
+    let json: Value = match serde_json::from_str(json) {
+        Ok(json) => json,
+        Err(e) => return Err(InvalidJson(e.to_string()))
+    };
+    let clauses = match json.as_array() {
+        Some(clauses) => clauses,
+        None => return Err(InvalidJson("expected an array of clauses".to_string()))
+    };
+    let mut gdl = String::new();
+    for clause in clauses.iter() {
+        if !gdl.is_empty() {
+            gdl.push(' ');
+        }
+        write_symbol(&mut gdl, clause)?;
+    }
+    Ok(parse_checked(&gdl)?)
+}
+
+fn sentence_json(sentence: &Sentence) -> Value {
+    match sentence {
+        &PropSentence(ref p) => Value::String(p.name.name.clone()),
+        &RelSentence(ref r) => list_json(&r.name.name, r.args.iter().map(term_json))
+    }
+}
+
+fn literal_json(lit: &Literal) -> Value {
+    match lit {
+        &NotLit(ref n) => list_json("not", Some(literal_json(&n.lit)).into_iter()),
+        &OrLit(ref o) => list_json("or", o.lits.iter().map(literal_json)),
+        &DistinctLit(ref d) => {
+            list_json("distinct", vec![term_json(&d.term1), term_json(&d.term2)].into_iter())
+        }
+        &PropLit(ref p) => Value::String(p.name.name.clone()),
+        &RelLit(ref r) => list_json(&r.name.name, r.args.iter().map(term_json)),
+        &::Literal::KnowsLit(ref k) => {
+            list_json("knows", k.role.iter().chain(Some(&k.prop)).map(term_json))
+        }
+    }
+}
+
+fn term_json(term: &Term) -> Value {
+    match term {
+        &VarTerm(ref v) => Value::String(format!("?{}", v.name.name)),
+        &FuncTerm(ref f) => list_json(&f.name.name, f.args.iter().map(term_json)),
+        &ConstTerm(ref c) => Value::String(c.name.clone())
+    }
+}
+
+fn list_json<I: Iterator<Item = Value>>(name: &str, args: I) -> Value {
+    let mut list = vec![Value::String(name.to_string())];
+    list.extend(args);
+    Value::Array(list)
+}
+
+// Writes a symbol as GDL. Atoms that aren't valid identifiers are quoted, except for `<=`.
+fn write_symbol(gdl: &mut String, symbol: &Value) -> Result<(), RepositoryError> {
+    match symbol {
+        &Value::String(ref s) if s == "<=" => gdl.push_str(s),
+        &Value::String(ref s) if s.starts_with('?') => {
+            gdl.push('?');
+            gdl.push_str(&Constant::new(&s[1..]).to_string());
+        }
+        &Value::String(ref s) => gdl.push_str(&Constant::new(&s[..]).to_string()),
+        &Value::Array(ref list) => {
+            gdl.push('(');
+            for (i, s) in list.iter().enumerate() {
+                if i > 0 {
+                    gdl.push(' ');
+                }
+                write_symbol(gdl, s)?;
+            }
+            gdl.push(')');
+        }
+        _ => return Err(InvalidJson(format!("expected a string or an array, found {}", symbol)))
+    }
+    Ok(())
+}
@@ -0,0 +1,306 @@
+//! Rewriting, which replaces the terms and atoms of a description that match patterns until none
+//! of them match anymore
+
+use std::error::Error;
+use std::mem;
+use std::fmt::{self, Display, Formatter};
+
+use {Constant, Description, Function, Literal, Proposition, Relation, Sentence, Span,
+     Substitution, Term, Variable};
+use Literal::{PropLit, RelLit};
+use Sentence::{PropSentence, RelSentence};
+use Term::{ConstTerm, FuncTerm, VarTerm};
+use visitor::{self, Folder, Visit};
+
+/// The default limit on the number of rewrites `Rewriter::rewrite` makes
+pub const DEFAULT_MAX_STEPS: usize = 100000;
+
+/// The reasons rewriting can fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewriteError {
+    /// A replacement has a variable that isn't in its pattern, so there's nothing to replace it
+    /// with
+    UnboundVariable { name: String },
+
+    /// More than `limit` rewrites were made without reaching a term that no pattern matches,
+    /// which usually means the rules rewrite some terms back and forth forever
+    NoFixpoint { limit: usize },
+
+    /// A relation or proposition was rewritten to a variable, which can't be a sentence
+    NotASentence { span: Span }
+}
+
+impl Display for RewriteError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            &RewriteError::UnboundVariable { ref name } => {
+                write!(f, "variable `?{}` is in the replacement but not the pattern", name)
+            }
+            &RewriteError::NoFixpoint { limit } => {
+                write!(f, "rewriting didn't finish after {} rewrites", limit)
+            }
+            &RewriteError::NotASentence { span } => {
+                write!(f, "the sentence at byte {} was rewritten to a variable", span.lo)
+            }
+        }
+    }
+}
+
+impl Error for RewriteError {
+    fn description(&self) -> &str {
+        match self {
+            &RewriteError::UnboundVariable { .. } => "unbound variable in replacement",
+            &RewriteError::NoFixpoint { .. } => "rewriting didn't finish",
+            &RewriteError::NotASentence { .. } => "sentence rewritten to a variable"
+        }
+    }
+}
+
+/// A set of rules that each rewrite the terms matching a pattern to a replacement, with the
+/// pattern's variables standing for whatever they matched. For example
+///
+/// ```
+/// use gdl_parser::{parse, parse_term};
+/// use gdl_parser::rewrite::Rewriter;
+///
+/// let mut rewriter = Rewriter::new();
+/// rewriter.register(parse_term("(succ ?x ?y)").unwrap(),
+///                   parse_term("(plus_one ?x ?y)").unwrap()).unwrap();
+/// rewriter.register(parse_term("(cell ?x ?y ?v)").unwrap(),
+///                   parse_term("(cell (at ?x ?y) ?v)").unwrap()).unwrap();
+///
+/// let desc = parse("(succ 1 2) (<= (next (cell ?x 1 b)) (true (cell ?y 1 b)) (succ ?y ?x))");
+/// assert_eq!(rewriter.rewrite(&desc).unwrap().to_string(),
+///            "(plus_one 1 2) (<= (next (cell (at ?x 1) b)) (true (cell (at ?y 1) b)) \
+///             (plus_one ?y ?x))");
+/// ```
+///
+/// Relations and propositions are matched as if they were functions and constants, so patterns
+/// can rewrite whole sentences and literals as well as the terms in them. Terms are rewritten
+/// innermost first, with the rules tried in the order they were registered, until no pattern
+/// matches anywhere. A variable in a description only matches pattern variables.
+#[derive(Debug, Clone)]
+pub struct Rewriter {
+    rules: Vec<(Term, Term)>,
+    max_steps: usize
+}
+
+impl Default for Rewriter {
+    fn default() -> Rewriter {
+        Rewriter::new()
+    }
+}
+
+impl Rewriter {
+    /// Constructs a rewriter without any rules, that makes at most `DEFAULT_MAX_STEPS` rewrites
+    pub fn new() -> Rewriter {
+        Rewriter { rules: Vec::new(), max_steps: DEFAULT_MAX_STEPS }
+    }
+
+    /// Sets the most rewrites that rewriting a description or term can make before it fails with
+    /// `RewriteError::NoFixpoint`
+    pub fn with_max_steps(mut self, max_steps: usize) -> Rewriter {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Adds a rule that rewrites terms matching `pattern` to `replacement`. Returns an error if
+    /// `replacement` has a variable that isn't in `pattern`.
+    pub fn register(&mut self, pattern: Term, replacement: Term) -> Result<(), RewriteError> {
+        let mut vars = Variables { vars: Vec::new() };
+        visitor::walk_term(&pattern, &mut vars);
+        let bound = vars.vars;
+        let mut vars = Variables { vars: Vec::new() };
+        visitor::walk_term(&replacement, &mut vars);
+        if let Some(v) = vars.vars.into_iter().find(|v| !bound.contains(v)) {
+            return Err(RewriteError::UnboundVariable { name: v });
+        }
+        self.rules.push((pattern, replacement));
+        Ok(())
+    }
+
+    /// Returns `desc` with its terms, sentences, and literals rewritten until no pattern matches
+    pub fn rewrite(&self, desc: &Description) -> Result<Description, RewriteError> {
+        let mut rewrite = Rewrite { rewriter: self, steps: 0, error: None };
+        let desc = visitor::fold(desc.clone(), &mut rewrite);
+        match rewrite.error {
+            Some(e) => Err(e),
+            None => Ok(desc)
+        }
+    }
+
+    /// Returns `term` rewritten until no pattern matches it or any term in it
+    pub fn rewrite_term(&self, term: &Term) -> Result<Term, RewriteError> {
+        self.normalize(term, &mut 0)
+    }
+
+    // Rewrites the arguments of `term` and then `term` itself until no pattern matches. Rules
+    // that make terms bigger can nest them deeper with every rewrite until the limit is reached,
+    // so the functions whose arguments are being rewritten are kept in `open` instead of on the
+    // stack, each with the arguments rewritten so far and the rest in reverse order.
+    fn normalize(&self, term: &Term, steps: &mut usize) -> Result<Term, RewriteError> {
+        let mut open: Vec<(Function, Vec<Term>)> = Vec::new();
+        let mut term = term.clone();
+        // Whether the arguments of `term` still have to be rewritten
+        let mut descend = true;
+        loop {
+            if descend {
+                term = match term {
+                    FuncTerm(mut f) => {
+                        let mut rest = mem::replace(&mut f.args, Vec::new());
+                        rest.reverse();
+                        match rest.pop() {
+                            Some(arg) => {
+                                open.push((f, rest));
+                                term = arg;
+                                continue;
+                            }
+                            None => FuncTerm(f)
+                        }
+                    }
+                    term => term
+                };
+            }
+
+            if let Some(t) = self.step(&term) {
+                *steps += 1;
+                if *steps > self.max_steps {
+                    return Err(RewriteError::NoFixpoint { limit: self.max_steps });
+                }
+                term = t;
+                descend = true;
+                continue;
+            }
+
+            // `term` can't be rewritten anymore, so it's done unless it's an argument
+            let (mut f, mut rest) = match open.pop() {
+                Some(parent) => parent,
+                None => return Ok(term)
+            };
+            f.args.push(term);
+            match rest.pop() {
+                Some(arg) => {
+                    open.push((f, rest));
+                    term = arg;
+                    descend = true;
+                }
+                None => {
+                    term = FuncTerm(f);
+                    descend = false;
+                }
+            }
+        }
+    }
+
+    // Rewrites `term` with the first rule whose pattern matches it
+    fn step(&self, term: &Term) -> Option<Term> {
+        self.rules.iter().filter_map(|&(ref pattern, ref replacement)| {
+            let mut subst = Substitution::new();
+            if matches(pattern, term, &mut subst) {
+                Some(subst.apply(replacement))
+            } else {
+                None
+            }
+        }).next()
+    }
+}
+
+// Extends `subst` so it makes `pattern` into `term`, returning false if it can't
+fn matches(pattern: &Term, term: &Term, subst: &mut Substitution) -> bool {
+    match (pattern, term) {
+        (&VarTerm(ref v), _) => {
+            if let Some(t) = subst.get(v) {
+                return t == term;
+            }
+            subst.insert(v.clone(), term.clone());
+            true
+        }
+        (&FuncTerm(ref f), &FuncTerm(ref g)) => {
+            f.name == g.name && f.args.len() == g.args.len() &&
+                f.args.iter().zip(g.args.iter()).all(|(p, t)| matches(p, t, subst))
+        }
+        (&ConstTerm(ref a), &ConstTerm(ref b)) => a == b,
+        _ => false
+    }
+}
+
+struct Rewrite<'a> {
+    rewriter: &'a Rewriter,
+    steps: usize,
+    error: Option<RewriteError>
+}
+
+impl<'a> Rewrite<'a> {
+    // Rewrites a relation or proposition as a term, returning the sentence it becomes
+    fn sentence(&mut self, name: &Constant, args: Option<&[Term]>, span: Span)
+                -> Option<Sentence> {
+        if self.error.is_some() {
+            return None;
+        }
+        let term = match args {
+            Some(args) => FuncTerm(Function::new(name.clone(), args.to_vec()).with_span(span)),
+            None => ConstTerm(name.clone())
+        };
+        let result = self.rewriter.normalize(&term, &mut self.steps).and_then(|t| {
+            match t {
+                FuncTerm(f) => Ok(RelSentence(Relation::new(f.name, f.args).with_span(span))),
+                ConstTerm(c) => Ok(PropSentence(Proposition::new(c).with_span(span))),
+                VarTerm(_) => Err(RewriteError::NotASentence { span: span })
+            }
+        });
+        match result {
+            Ok(s) => Some(s),
+            Err(e) => {
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+}
+
+impl<'a> Folder for Rewrite<'a> {
+    fn fold_sentence(&mut self, sentence: Sentence) -> Sentence {
+        let rewritten = match sentence {
+            PropSentence(ref p) => self.sentence(&p.name, None, p.span),
+            RelSentence(ref r) => self.sentence(&r.name, Some(&r.args), r.span)
+        };
+        rewritten.unwrap_or(sentence)
+    }
+
+    fn fold_literal(&mut self, literal: Literal) -> Literal {
+        let rewritten = match literal {
+            PropLit(ref p) => self.sentence(&p.name, None, p.span),
+            RelLit(ref r) => self.sentence(&r.name, Some(&r.args), r.span),
+            _ => None
+        };
+        match rewritten {
+            Some(PropSentence(p)) => PropLit(p),
+            Some(RelSentence(r)) => RelLit(r),
+            None => literal
+        }
+    }
+
+    fn fold_term(&mut self, term: Term) -> Term {
+        if self.error.is_some() {
+            return term;
+        }
+        match self.rewriter.normalize(&term, &mut self.steps) {
+            Ok(t) => t,
+            Err(e) => {
+                self.error = Some(e);
+                term
+            }
+        }
+    }
+}
+
+// Collects the names of the variables in a term
+struct Variables {
+    vars: Vec<String>
+}
+
+impl Visit for Variables {
+    fn visit_variable(&mut self, var: &Variable) {
+        self.vars.push(var.name.name.clone());
+    }
+}
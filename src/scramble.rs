@@ -0,0 +1,159 @@
+//! Scrambling, which renames the constants and variables of a description to random words so
+//! players can't tell which game they're playing, like GGP Base's `GdlScrambler`
+
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use {Constant, Description, Term};
+use dialect::{is_keyword, is_reserved, RANDOM, SEES};
+use visitor::{self, Visitor};
+
+const CONSONANTS: &'static [u8] = b"bdfghjklmnprstvz";
+const VOWELS: &'static [u8] = b"aeiou";
+
+/// Renames the constants and variables of descriptions to random words, and back again. A name
+/// always becomes the same word, so moves and other terms can be scrambled and unscrambled on
+/// their own with the scrambler that scrambled the description. For example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::scramble::Scrambler;
+///
+/// let desc = parse("(role white) (<= (legal white noop) (true (control white)))");
+/// let mut scrambler = Scrambler::new();
+/// let scrambled = scrambler.scramble(&desc);
+/// assert!(!scrambled.to_string().contains("white"));
+/// assert_eq!(scrambler.unscramble(&scrambled), desc);
+/// ```
+///
+/// Keywords like `role` and `true`, the GDL-II `sees` relation and `random` role, and numbers,
+/// like goal values, keep their names. Clauses that couldn't be parsed are left as they are.
+#[derive(Debug, Clone)]
+pub struct Scrambler {
+    words: HashMap<String, String>,
+    names: HashMap<String, String>,
+
+    // The state of the splitmix64 generator the words are picked with
+    state: u64
+}
+
+impl Default for Scrambler {
+    fn default() -> Scrambler {
+        Scrambler::new()
+    }
+}
+
+impl Scrambler {
+    /// Constructs a scrambler with a random seed
+    pub fn new() -> Scrambler {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(0);
+        Scrambler::with_seed(hasher.finish())
+    }
+
+    /// Constructs a scrambler that picks the same words as any other with the same seed, as long
+    /// as they're given the same names in the same order
+    pub fn with_seed(seed: u64) -> Scrambler {
+        Scrambler { words: HashMap::new(), names: HashMap::new(), state: seed }
+    }
+
+    /// Returns `desc` with each name replaced by its word, picking words for the names that don't
+    /// have one yet
+    pub fn scramble(&mut self, desc: &Description) -> Description {
+        let mut desc = desc.clone();
+        visitor::visit(&mut desc, &mut Scramble { scrambler: self });
+        desc
+    }
+
+    /// Returns `term` with each name replaced by its word, like a move being sent to a player
+    pub fn scramble_term(&mut self, term: &Term) -> Term {
+        let mut term = term.clone();
+        visitor::visit_term(&mut term, &mut Scramble { scrambler: self });
+        term
+    }
+
+    /// Returns `desc` with each word replaced by the name it was picked for. Words the scrambler
+    /// didn't pick are left as they are.
+    pub fn unscramble(&self, desc: &Description) -> Description {
+        let mut desc = desc.clone();
+        visitor::visit(&mut desc, &mut Unscramble { scrambler: self });
+        desc
+    }
+
+    /// Returns `term` with each word replaced by the name it was picked for, like a move sent by a
+    /// player
+    pub fn unscramble_term(&self, term: &Term) -> Term {
+        let mut term = term.clone();
+        visitor::visit_term(&mut term, &mut Unscramble { scrambler: self });
+        term
+    }
+
+    /// Returns the word picked for `name`, if there is one
+    pub fn word(&self, name: &str) -> Option<&str> {
+        self.words.get(name).map(|w| &w[..])
+    }
+
+    /// Returns the name that `word` was picked for, if it was picked
+    pub fn name(&self, word: &str) -> Option<&str> {
+        self.names.get(word).map(|n| &n[..])
+    }
+
+    fn pick(&mut self, name: &str) -> String {
+        if let Some(word) = self.words.get(name) {
+            return word.clone();
+        }
+        let word = loop {
+            let syllables = 2 + self.next() % 3;
+            let mut word = String::new();
+            for _ in 0..syllables {
+                word.push(CONSONANTS[(self.next() % CONSONANTS.len() as u64) as usize] as char);
+                word.push(VOWELS[(self.next() % VOWELS.len() as u64) as usize] as char);
+            }
+            if !kept(&word) && !self.names.contains_key(&word) {
+                break word;
+            }
+        };
+        self.words.insert(name.to_string(), word.clone());
+        self.names.insert(word.clone(), name.to_string());
+        word
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+// Returns true if `name` means something in GDL, so it can't be scrambled
+fn kept(name: &str) -> bool {
+    is_keyword(name) || is_reserved(name) || name == SEES || name == RANDOM ||
+        (!name.is_empty() && name.bytes().all(|b| b.is_ascii_digit()))
+}
+
+struct Scramble<'a> {
+    scrambler: &'a mut Scrambler
+}
+
+impl<'a> Visitor for Scramble<'a> {
+    fn visit_constant(&mut self, constant: &mut Constant) {
+        if !kept(&constant.name) {
+            constant.name = self.scrambler.pick(&constant.name);
+        }
+    }
+}
+
+struct Unscramble<'a> {
+    scrambler: &'a Scrambler
+}
+
+impl<'a> Visitor for Unscramble<'a> {
+    fn visit_constant(&mut self, constant: &mut Constant) {
+        if let Some(name) = self.scrambler.name(&constant.name) {
+            constant.name = name.to_string();
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! A generic s-expression type, for manipulating descriptions as plain lists of atoms or reading
+//! KIF content that isn't GDL. Descriptions convert to s-expressions with `Into` and back with
+//! `TryFrom`, which checks that the s-expressions are valid GDL.
+
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use {parse_checked, Clause, Description, Literal, ParseError, Sentence, Span, Term};
+use Clause::{RuleClause, SentenceClause, UnknownClause};
+use Literal::{NotLit, OrLit, DistinctLit, PropLit, RelLit};
+use Literal::KnowsLit;
+use Sentence::{PropSentence, RelSentence};
+use Term::{VarTerm, FuncTerm, ConstTerm};
+use lexer::{Lexer, Token};
+
+use self::SExpr::{Atom, List};
+
+/// An s-expression. Atoms are stored as they're written in source text, so variables keep their
+/// leading `?` and quoted atoms keep their quotes and escapes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SExpr {
+    Atom(String),
+    List(Vec<SExpr>)
+}
+
+impl SExpr {
+    /// Creates an atom
+    pub fn atom<T: Into<String>>(text: T) -> SExpr {
+        Atom(text.into())
+    }
+
+    /// Returns the text of an atom, or `None` for a list
+    pub fn as_atom(&self) -> Option<&str> {
+        match self {
+            &Atom(ref text) => Some(text),
+            &List(_) => None
+        }
+    }
+
+    /// Returns the elements of a list, or `None` for an atom
+    pub fn as_list(&self) -> Option<&[SExpr]> {
+        match self {
+            &Atom(_) => None,
+            &List(ref elems) => Some(elems)
+        }
+    }
+}
+
+/// Parses `text` as a sequence of s-expressions. Any atoms the lexer accepts are allowed, so this
+/// can read KIF content that isn't GDL. Comments are skipped.
+pub fn parse_sexprs(text: &str) -> Result<Vec<SExpr>, ParseError> {
+    // The lists that are still open, with the span of their `(`. Using a stack instead of
+    // recursing means deeply nested input can't overflow the stack.
+    let mut open: Vec<(Vec<SExpr>, Span)> = Vec::new();
+    let mut top = Vec::new();
+    for (token, span) in Lexer::new(text).filter(|&(t, _)| !t.is_trivia()) {
+        let expr = match token {
+            Token::LParen => {
+                open.push((Vec::new(), span));
+                continue;
+            }
+            Token::RParen => match open.pop() {
+                Some((elems, _)) => List(elems),
+                None => return Err(ParseError::UnbalancedParen { span: span })
+            },
+            Token::Atom(_) | Token::Variable(_) => Atom(span.snippet(text).to_string()),
+            Token::Unknown(found) => {
+                return Err(ParseError::UnexpectedToken { span: span,
+                                                         found: found.to_string(),
+                                                         expected: vec!["(", ")", "atom"] });
+            }
+            Token::Comment(_) | Token::Whitespace(_) => unreachable!()
+        };
+        match open.last_mut() {
+            Some(&mut (ref mut elems, _)) => elems.push(expr),
+            None => top.push(expr)
+        }
+    }
+    match open.pop() {
+        Some((_, span)) => Err(ParseError::UnbalancedParen { span: span }),
+        None => Ok(top)
+    }
+}
+
+impl FromStr for SExpr {
+    type Err = ParseError;
+
+    /// Parses exactly one s-expression
+    fn from_str(s: &str) -> Result<SExpr, ParseError> {
+        let mut exprs = parse_sexprs(s)?;
+        match exprs.len() {
+            1 => Ok(exprs.pop().unwrap()),
+            0 => Err(ParseError::UnexpectedEof { span: Span::new(s.len(), s.len()),
+                                                 expected: vec!["(", "atom"] }),
+            _ => {
+                // Skip the tokens of the first s-expression to find where the second starts
+                let mut tokens = Lexer::new(s).filter(|&(t, _)| !t.is_trivia());
+                let mut depth = 0;
+                for (token, _) in tokens.by_ref() {
+                    match token {
+                        Token::LParen => depth += 1,
+                        Token::RParen => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                let span = tokens.next().unwrap().1;
+                Err(ParseError::UnexpectedToken { span: span,
+                                                  found: span.snippet(s).to_string(),
+                                                  expected: vec!["end of input"] })
+            }
+        }
+    }
+}
+
+impl Display for SExpr {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        // Elements that are left to print, or `None` for the `)` that closes a list
+        let mut stack = vec![Some(self)];
+        let mut first = true;
+        while let Some(expr) = stack.pop() {
+            match expr {
+                Some(expr) => {
+                    if !first {
+                        write!(f, " ")?;
+                    }
+                    match expr {
+                        &Atom(ref text) => {
+                            write!(f, "{}", text)?;
+                            first = false;
+                        }
+                        &List(ref elems) => {
+                            write!(f, "(")?;
+                            stack.push(None);
+                            stack.extend(elems.iter().rev().map(Some));
+                            first = true;
+                        }
+                    }
+                }
+                None => {
+                    write!(f, ")")?;
+                    first = false;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Into<SExpr> for Description {
+    /// Converts the description to a list with an element for each clause
+    fn into(self) -> SExpr {
+        List(self.clauses.into_iter().map(Into::into).collect())
+    }
+}
+
+impl Into<SExpr> for Clause {
+    /// Converts the clause to an s-expression. An unknown clause is parsed from its text, or
+    /// becomes a single atom of its text if it isn't a single s-expression.
+    fn into(self) -> SExpr {
+        match self {
+            RuleClause(r) => {
+                let mut elems = vec![SExpr::atom("<="), r.head.into()];
+                elems.extend(r.body.into_iter().map(Into::into));
+                List(elems)
+            }
+            SentenceClause(s) => s.into(),
+            UnknownClause(u) => u.text.parse().unwrap_or(Atom(u.text))
+        }
+    }
+}
+
+impl Into<SExpr> for Sentence {
+    fn into(self) -> SExpr {
+        match self {
+            PropSentence(p) => Atom(p.name.to_string()),
+            RelSentence(r) => compound(r.name.to_string(), r.args)
+        }
+    }
+}
+
+impl Into<SExpr> for Literal {
+    fn into(self) -> SExpr {
+        match self {
+            NotLit(n) => List(vec![SExpr::atom("not"), (*n.lit).into()]),
+            OrLit(o) => {
+                let mut elems = vec![SExpr::atom("or")];
+                elems.extend(o.lits.into_iter().map(Into::into));
+                List(elems)
+            }
+            DistinctLit(d) => {
+                List(vec![SExpr::atom("distinct"), d.term1.into(), d.term2.into()])
+            }
+            PropLit(p) => Atom(p.name.to_string()),
+            RelLit(r) => compound(r.name.to_string(), r.args),
+            KnowsLit(k) => {
+                let mut elems = vec![SExpr::atom("knows")];
+                elems.extend(k.role.map(Into::into));
+                elems.push(k.prop.into());
+                List(elems)
+            }
+        }
+    }
+}
+
+impl Into<SExpr> for Term {
+    fn into(self) -> SExpr {
+        match self {
+            VarTerm(v) => Atom(format!("?{}", v.name)),
+            FuncTerm(f) => compound(f.name.to_string(), f.args),
+            ConstTerm(c) => Atom(c.to_string())
+        }
+    }
+}
+
+fn compound(name: String, args: Vec<Term>) -> SExpr {
+    let mut elems = vec![Atom(name)];
+    elems.extend(args.into_iter().map(Into::into));
+    List(elems)
+}
+
+impl TryFrom<SExpr> for Description {
+    type Error = ParseError;
+
+    /// Converts a list of clauses to a description. The s-expressions are printed and parsed as
+    /// GDL, so an error's span is a position in their printed text.
+    fn try_from(expr: SExpr) -> Result<Description, ParseError> {
+        match expr {
+            List(clauses) => {
+                let gdl = clauses.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(" ");
+                parse_checked(&gdl)
+            }
+            Atom(text) => Err(ParseError::UnexpectedToken { span: Span::new(0, text.len()),
+                                                            found: text,
+                                                            expected: vec!["("] })
+        }
+    }
+}
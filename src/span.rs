@@ -0,0 +1,91 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+/// A region of the source text, stored as a half-open range of byte offsets. Nodes that weren't
+/// produced by the parser have a dummy span.
+///
+/// Spans are ignored when comparing, ordering, and hashing AST nodes, so a parsed description is
+/// equal to the same description constructed by hand.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Span {
+    pub lo: usize,
+    pub hi: usize
+}
+
+impl Span {
+    /// Constructs a new span covering the bytes from `lo` up to but not including `hi`
+    pub fn new(lo: usize, hi: usize) -> Span {
+        Span { lo: lo, hi: hi }
+    }
+
+    /// Constructs a span for a node that doesn't come from any source text
+    pub fn dummy() -> Span {
+        Span::new(0, 0)
+    }
+
+    /// Returns true if this span doesn't cover any source text
+    pub fn is_dummy(&self) -> bool {
+        self.lo == 0 && self.hi == 0
+    }
+
+    /// Returns the number of bytes covered by this span
+    pub fn len(&self) -> usize {
+        self.hi - self.lo
+    }
+
+    /// Returns the smallest span covering both this span and `other`
+    pub fn to(&self, other: Span) -> Span {
+        Span::new(::std::cmp::min(self.lo, other.lo), ::std::cmp::max(self.hi, other.hi))
+    }
+
+    /// Returns the source text covered by this span
+    pub fn snippet<'a>(&self, src: &'a str) -> &'a str {
+        &src[self.lo..self.hi]
+    }
+
+    /// Returns the 1-based line and column of the start of this span
+    pub fn start_line_col(&self, src: &str) -> (usize, usize) {
+        line_col(src, self.lo)
+    }
+
+    /// Returns the 1-based line and column of the end of this span
+    pub fn end_line_col(&self, src: &str) -> (usize, usize) {
+        line_col(src, self.hi)
+    }
+}
+
+/// Returns the 1-based line and column of the byte `offset` in `src`
+pub fn line_col(src: &str, offset: usize) -> (usize, usize) {
+    let before = &src[..offset];
+    let line = before.matches('\n').count() + 1;
+    let col = match before.rfind('\n') {
+        Some(i) => offset - i,
+        None => offset + 1
+    };
+    (line, col)
+}
+
+impl PartialEq for Span {
+    fn eq(&self, _: &Span) -> bool {
+        true
+    }
+}
+
+impl Eq for Span {}
+
+impl PartialOrd for Span {
+    fn partial_cmp(&self, other: &Span) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Span {
+    fn cmp(&self, _: &Span) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+impl Hash for Span {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
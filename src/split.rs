@@ -0,0 +1,160 @@
+/// An iterator over the top-level forms of a GDL description, without parsing them. Each item is
+/// the byte offset of the form along with its text, which doesn't include any surrounding
+/// whitespace or comments.
+///
+/// A form is either a balanced parenthesized expression or a bare atom. An unclosed `(` or block
+/// comment runs to the end of the input and a stray `)` is a form on its own, so every non-trivia
+/// byte of the input ends up in exactly one form and parsing an unclosed comment fails.
+pub struct Forms<'a> {
+    input: &'a str,
+    pos: usize
+}
+
+/// Returns an iterator over the top-level forms in `input`
+pub fn forms<'a>(input: &'a str) -> Forms<'a> {
+    Forms { input: input, pos: 0 }
+}
+
+impl<'a> Iterator for Forms<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<(usize, &'a str)> {
+        let bytes = self.input.as_bytes();
+        self.pos = skip_trivia(self.input, self.pos);
+        if self.pos >= bytes.len() {
+            return None;
+        }
+
+        let start = self.pos;
+        let end = match bytes[start] {
+            b'(' => paren_end(self.input, start),
+            b')' => start + 1,
+            b'"' | b'|' => quote_end(self.input, start).unwrap_or(bytes.len()),
+            // `skip_trivia` stops at a block comment that's never closed
+            b'#' if self.input[start..].starts_with("#|") => bytes.len(),
+            _ => atom_end(self.input, start)
+        };
+        self.pos = end;
+        Some((start, &self.input[start..end]))
+    }
+}
+
+/// Returns the offset of the first byte at or after `pos` that isn't whitespace or part of a
+/// comment. A block comment that's never closed isn't skipped, so the error is reported when it's
+/// parsed.
+pub fn skip_trivia(input: &str, mut pos: usize) -> usize {
+    let bytes = input.as_bytes();
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b' ' | b'\t' | b'\n' | b'\r' => pos += 1,
+            b';' => pos = line_end(input, pos),
+            b'#' if input[pos..].starts_with("#|") => {
+                match block_comment_end(input, pos) {
+                    Some(end) => pos = end,
+                    None => break
+                }
+            }
+            _ => break
+        }
+    }
+    pos
+}
+
+/// Returns the offset just past the closing quote of the quoted atom starting at `pos`, or `None`
+/// if it's never closed. A `\` escapes the character after it.
+pub fn quote_end(input: &str, pos: usize) -> Option<usize> {
+    let bytes = input.as_bytes();
+    let quote = bytes[pos];
+    let mut i = pos + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b if b == quote => return Some(i + 1),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Returns the offset just past the `|#` closing the KIF block comment starting at `pos`, or `None`
+/// if it's never closed. Block comments nest, so each `#|` inside the comment needs its own `|#`.
+pub fn block_comment_end(input: &str, pos: usize) -> Option<usize> {
+    let bytes = input.as_bytes();
+    let mut depth = 0;
+    let mut i = pos;
+    while i + 1 < bytes.len() {
+        match (bytes[i], bytes[i + 1]) {
+            (b'#', b'|') => {
+                depth += 1;
+                i += 2;
+            }
+            (b'|', b'#') => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => i += 1
+        }
+    }
+    None
+}
+
+fn line_end(input: &str, pos: usize) -> usize {
+    match input[pos..].find('\n') {
+        Some(n) => pos + n,
+        None => input.len()
+    }
+}
+
+// Returns the offset of the end of the atom starting at `pos`. Quotes and block comments end an
+// atom like they do in the lexer, so `x#| .. |#` is the atom `x` followed by a comment.
+fn atom_end(input: &str, pos: usize) -> usize {
+    for (n, c) in input[pos..].char_indices() {
+        let end = match c {
+            '(' | ')' | ';' | '"' | '|' => true,
+            '#' => input[pos + n..].starts_with("#|"),
+            c => c.is_whitespace()
+        };
+        if end {
+            return pos + n;
+        }
+    }
+    input.len()
+}
+
+// Returns the offset just past the `)` matching the `(` at `pos`, or the end of the input if it's
+// never closed
+fn paren_end(input: &str, pos: usize) -> usize {
+    let bytes = input.as_bytes();
+    let mut depth = 0;
+    let mut i = pos;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            b';' => {
+                i = line_end(input, i);
+                continue;
+            }
+            b'#' if input[i..].starts_with("#|") => {
+                i = block_comment_end(input, i).unwrap_or(bytes.len());
+                continue;
+            }
+            b'"' | b'|' => {
+                i = quote_end(input, i).unwrap_or(bytes.len());
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    input.len()
+}
@@ -0,0 +1,113 @@
+//! Substitutions of terms for variables
+
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+use {Clause, Literal, Rule, Sentence, Term, Variable};
+use Term::VarTerm;
+use visitor::{self, Visitor};
+
+/// A mapping from variables to the terms they stand for. Variables are identified by their names,
+/// so `?x` maps to the same term wherever it appears.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Substitution {
+    bindings: HashMap<String, Term>
+}
+
+impl Substitution {
+    /// Constructs a substitution that doesn't replace any variables
+    pub fn new() -> Substitution {
+        Substitution { bindings: HashMap::new() }
+    }
+
+    /// Maps `var` to `term`, returning the term it was mapped to before, if there was one
+    pub fn insert(&mut self, var: Variable, term: Term) -> Option<Term> {
+        self.bindings.insert(var.name.name, term)
+    }
+
+    /// Returns the term `var` is mapped to
+    pub fn get(&self, var: &Variable) -> Option<&Term> {
+        self.bindings.get(&var.name.name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.bindings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    /// Returns `term` with each of its variables that the substitution maps replaced by its term.
+    /// The replacements aren't substituted into again.
+    pub fn apply(&self, term: &Term) -> Term {
+        let mut term = term.clone();
+        visitor::visit_term(&mut term, &mut Apply { subst: self });
+        term
+    }
+
+    /// Returns `sentence` with the substitution applied to its arguments
+    pub fn apply_sentence(&self, sentence: &Sentence) -> Sentence {
+        let mut sentence = sentence.clone();
+        visitor::visit_sentence(&mut sentence, &mut Apply { subst: self });
+        sentence
+    }
+
+    /// Returns `literal` with the substitution applied to its terms
+    pub fn apply_literal(&self, literal: &Literal) -> Literal {
+        let mut literal = literal.clone();
+        visitor::visit_literal(&mut literal, &mut Apply { subst: self });
+        literal
+    }
+
+    /// Returns `rule` with the substitution applied to its head and body
+    pub fn apply_rule(&self, rule: &Rule) -> Rule {
+        let mut rule = rule.clone();
+        visitor::visit_rule(&mut rule, &mut Apply { subst: self });
+        rule
+    }
+
+    /// Returns `clause` with the substitution applied to it
+    pub fn apply_clause(&self, clause: &Clause) -> Clause {
+        let mut clause = clause.clone();
+        visitor::visit_clause(&mut clause, &mut Apply { subst: self });
+        clause
+    }
+
+    /// Returns the substitution that's the same as applying `self` and then `other`. Each term in
+    /// `self` has `other` applied to it, and the variables that only `other` maps are added.
+    pub fn compose(&self, other: &Substitution) -> Substitution {
+        let mut bindings = self.bindings.iter().map(|(v, t)| (v.clone(), other.apply(t)))
+                               .collect::<HashMap<_, _>>();
+        for (v, t) in other.bindings.iter() {
+            bindings.entry(v.clone()).or_insert_with(|| t.clone());
+        }
+        Substitution { bindings: bindings }
+    }
+}
+
+impl FromIterator<(Variable, Term)> for Substitution {
+    fn from_iter<I: IntoIterator<Item = (Variable, Term)>>(iter: I) -> Substitution {
+        let mut subst = Substitution::new();
+        for (v, t) in iter {
+            subst.insert(v, t);
+        }
+        subst
+    }
+}
+
+struct Apply<'a> {
+    subst: &'a Substitution
+}
+
+impl<'a> Visitor for Apply<'a> {
+    fn visit_term(&mut self, t: &mut Term) {
+        let replacement = match t {
+            &mut VarTerm(ref v) => self.subst.get(v).cloned(),
+            _ => None
+        };
+        if let Some(replacement) = replacement {
+            *t = replacement;
+        }
+    }
+}
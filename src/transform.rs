@@ -0,0 +1,926 @@
+//! Transformations of descriptions that keep the game they describe the same
+
+use std::collections::{HashMap, HashSet};
+
+use {Clause, Constant, Description, Literal, Not, Or, Proposition, Relation, Rule, Sentence, Span,
+     Substitution, Term, Variable};
+use Clause::{RuleClause, SentenceClause, UnknownClause};
+use Literal::{DistinctLit, NotLit, OrLit, PropLit, RelLit};
+use Sentence::{PropSentence, RelSentence};
+use Term::{ConstTerm, FuncTerm, VarTerm};
+use analysis::{reachable_relations, static_relations, stratify, unbounded_rules, DependencyGraph};
+use canonical::canonical_variables;
+use dialect::{atoms, is_keyword, is_reserved, SEES};
+use visitor::{self, Visit};
+
+/// Returns `desc` without the clauses reported by `analysis::duplicate_clauses`, keeping the first
+/// clause of each group of duplicates in its place
+pub fn dedup_clauses(desc: &Description) -> Description {
+    let mut seen = HashSet::new();
+    let clauses = desc.clauses.iter().filter(|c| {
+        match *c {
+            &UnknownClause(_) => true,
+            c => seen.insert(canonical_variables(c))
+        }
+    }).cloned().collect();
+    Description::new(clauses)
+}
+
+/// Returns `desc` without the rules and facts reported by `analysis::unused_rules`, which define
+/// relations that can't affect the game, along with the clauses that were removed, in order. For
+/// example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::transform::eliminate_dead_rules;
+///
+/// let desc = parse("(role white) (helper 1) (<= (old ?x) (helper ?x)) (<= terminal (true done))");
+/// let (pruned, removed) = eliminate_dead_rules(&desc);
+/// assert_eq!(pruned.to_string(), "(role white) (<= terminal (true done))");
+/// assert_eq!(removed.len(), 2);
+/// ```
+///
+/// Clauses that couldn't be parsed are kept.
+pub fn eliminate_dead_rules(desc: &Description) -> (Description, Vec<Clause>) {
+    let reachable = reachable_relations(desc).into_iter().collect::<HashSet<_>>();
+    let (kept, removed) = desc.clauses.iter().cloned().partition(|c| {
+        head_name(c).map_or(true, |name| reachable.contains(name))
+    });
+    (Description::new(kept), removed)
+}
+
+/// Returns `desc` with its clauses reordered so that each relation's clauses come after the
+/// clauses of the relations it depends on, which is the order they can be evaluated bottom-up in.
+/// For example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::transform::sort_by_dependencies;
+///
+/// let desc = parse("(<= terminal (not open)) (<= open (cell ?x b)) (cell 1 b) (role white)");
+/// assert_eq!(sort_by_dependencies(&desc).to_string(),
+///            "(cell 1 b) (<= open (cell ?x b)) (role white) (<= terminal (not open))");
+/// ```
+///
+/// Relations are grouped by stratum from `analysis::stratify`, lowest first, and then follow
+/// `analysis::DependencyGraph::topological_order`, so the result only depends on the order of
+/// `desc` where the dependencies don't decide it. A relation's clauses keep their order, and
+/// clauses that couldn't be parsed are moved to the end. If `desc` has a cycle through negation,
+/// it's returned unchanged.
+pub fn sort_by_dependencies(desc: &Description) -> Description {
+    let strata = match stratify(desc) {
+        Ok(strata) => strata,
+        Err(_) => return desc.clone()
+    };
+    let graph = DependencyGraph::new(desc);
+    let order = graph.topological_order().into_iter().enumerate().map(|(i, name)| {
+        (name, (strata.stratum(name).unwrap(), i))
+    }).collect::<HashMap<_, _>>();
+    let mut clauses = desc.clauses.clone();
+    clauses.sort_by_key(|c| head_name(c).map_or((usize::max_value(), 0), |name| order[name]));
+    Description::new(clauses)
+}
+
+/// Returns `desc` without any `or` literals, by replacing each rule that has them with a rule for
+/// every way its body can be true. For example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::transform::deor;
+///
+/// let desc = parse("(<= p (q ?x) (or (r ?x) (not (or (s ?x) (t ?x)))))");
+/// assert_eq!(deor(&desc).to_string(),
+///            "(<= p (q ?x) (r ?x)) (<= p (q ?x) (not (s ?x)) (not (t ?x)))");
+/// ```
+///
+/// `or`s under a `not` are removed with De Morgan's laws, and `(not (not x))` becomes `x`. Rules
+/// without `or`s are left as they are.
+pub fn deor(desc: &Description) -> Description {
+    let mut clauses = Vec::new();
+    for c in desc.clauses.iter() {
+        match c {
+            &RuleClause(ref r) if r.body.iter().any(has_or) => {
+                let bodies = r.body.iter().fold(vec![vec![]], |bodies, l| {
+                    product(&bodies, &alternatives(l))
+                });
+                clauses.extend(bodies.into_iter().map(|body| {
+                    RuleClause(Rule::new(r.head.clone(), body).with_span(r.span))
+                }));
+            }
+            c => clauses.push(c.clone())
+        }
+    }
+    Description::new(clauses)
+}
+
+fn has_or(l: &Literal) -> bool {
+    match l {
+        &OrLit(_) => true,
+        &NotLit(ref n) => has_or(&n.lit),
+        _ => false
+    }
+}
+
+// Returns conjunctions of literals without `or`s, such that `l` is true exactly when one of them is
+fn alternatives(l: &Literal) -> Vec<Vec<Literal>> {
+    match l {
+        &OrLit(ref o) => o.lits.iter().flat_map(|l| alternatives(l)).collect(),
+        &NotLit(ref n) => negated_alternatives(&n.lit),
+        l => vec![vec![l.clone()]]
+    }
+}
+
+// Returns the alternatives of `(not l)`
+fn negated_alternatives(l: &Literal) -> Vec<Vec<Literal>> {
+    match l {
+        &OrLit(ref o) => o.lits.iter().fold(vec![vec![]], |bodies, l| {
+            product(&bodies, &negated_alternatives(l))
+        }),
+        &NotLit(ref n) => alternatives(&n.lit),
+        l => vec![vec![Not::new(Box::new(l.clone())).with_span(l.span()).into()]]
+    }
+}
+
+// Returns every conjunction of one of `a` followed by one of `b`
+fn product(a: &[Vec<Literal>], b: &[Vec<Literal>]) -> Vec<Vec<Literal>> {
+    a.iter().flat_map(|x| {
+        b.iter().map(move |y| x.iter().chain(y.iter()).cloned().collect())
+    }).collect()
+}
+
+/// Returns `desc` with the `or`s in its rule bodies simplified. Nested `or`s are merged into the
+/// `or` they're in, disjuncts that are the same as an earlier one are removed, and an `or` with
+/// one disjunct is replaced by it. For example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::transform::flatten_ors;
+///
+/// let desc = parse("(<= p (or (or a (q ?x)) a (r ?x)) (not (or b)))");
+/// assert_eq!(flatten_ors(&desc).to_string(), "(<= p (or a (q ?x) (r ?x)) (not b))");
+/// ```
+pub fn flatten_ors(desc: &Description) -> Description {
+    let clauses = desc.clauses.iter().map(|c| {
+        match c {
+            &RuleClause(ref r) => {
+                let body = r.body.iter().map(flatten_or).collect();
+                RuleClause(Rule::new(r.head.clone(), body).with_span(r.span))
+            }
+            c => c.clone()
+        }
+    }).collect();
+    Description::new(clauses)
+}
+
+fn flatten_or(l: &Literal) -> Literal {
+    match l {
+        &OrLit(ref o) => {
+            let mut lits = Vec::new();
+            for l in o.lits.iter().map(flatten_or) {
+                let disjuncts = match l {
+                    OrLit(o) => o.lits,
+                    l => vec![l]
+                };
+                for l in disjuncts {
+                    if !lits.contains(&l) {
+                        lits.push(l);
+                    }
+                }
+            }
+            if lits.len() == 1 {
+                lits.remove(0)
+            } else {
+                Or::new(lits).with_span(o.span).into()
+            }
+        }
+        &NotLit(ref n) => Not::new(Box::new(flatten_or(&n.lit))).with_span(n.span).into(),
+        l => l.clone()
+    }
+}
+
+/// Returns `desc` with the `not`s in its rule bodies moved inward, so they're only around
+/// relations, propositions, and `distinct`s. For example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::transform::negation_normal_form;
+///
+/// let desc = parse("(<= p (not (or (q ?x) (not (r ?x)))))");
+/// assert_eq!(negation_normal_form(&desc).to_string(), "(<= p (not (q ?x)) (r ?x))");
+/// ```
+///
+/// A `not` around an `or` becomes a `not` around each of its literals, and `(not (not x))` becomes
+/// `x`. Since an `or` can't hold several literals that all have to be true, an `or` with a `not`
+/// of an `or` in it is split into several `or`s, like `(or a (not (or b c)))` into
+/// `(or a (not b)) (or a (not c))`. Nested `or`s are flattened too.
+pub fn negation_normal_form(desc: &Description) -> Description {
+    let clauses = desc.clauses.iter().map(|c| {
+        match c {
+            &RuleClause(ref r) => {
+                let body = r.body.iter().flat_map(|l| {
+                    conjuncts(l).into_iter().map(move |mut lits| {
+                        if lits.len() == 1 {
+                            lits.remove(0)
+                        } else {
+                            Or::new(lits).with_span(l.span()).into()
+                        }
+                    })
+                }).collect();
+                RuleClause(Rule::new(r.head.clone(), body).with_span(r.span))
+            }
+            c => c.clone()
+        }
+    }).collect();
+    Description::new(clauses)
+}
+
+// Returns disjunctions of literals with `not`s only around atoms, such that `l` is true exactly
+// when all of them are
+fn conjuncts(l: &Literal) -> Vec<Vec<Literal>> {
+    match l {
+        &OrLit(ref o) => o.lits.iter().fold(vec![vec![]], |ors, l| product(&ors, &conjuncts(l))),
+        &NotLit(ref n) => negated_conjuncts(&n.lit),
+        l => vec![vec![l.clone()]]
+    }
+}
+
+// Returns the conjuncts of `(not l)`
+fn negated_conjuncts(l: &Literal) -> Vec<Vec<Literal>> {
+    match l {
+        &OrLit(ref o) => o.lits.iter().flat_map(|l| negated_conjuncts(l)).collect(),
+        &NotLit(ref n) => conjuncts(&n.lit),
+        l => vec![vec![Not::new(Box::new(l.clone())).with_span(l.span()).into()]]
+    }
+}
+
+/// Returns `desc` with its rules condensed, so they have fewer literals and variables for grounding
+/// to instantiate. For example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::transform::condense;
+///
+/// let desc = parse("(<= (p ?x ?y) (q ?x) (r ?x ?z) (r ?x 1) (not (distinct ?y ?x)) (q ?x))");
+/// assert_eq!(condense(&desc).to_string(), "(<= (p ?x ?x) (q ?x) (r ?x 1))");
+/// ```
+///
+/// A `(not (distinct ?x t))` in a body is removed, and `?x` is replaced by `t` in the rest of the
+/// rule. A literal that's the same as an earlier one is removed. So is a positive literal that
+/// becomes another one in the body when its variables that aren't anywhere else in the rule are
+/// replaced, since the other literal being true makes it true too.
+pub fn condense(desc: &Description) -> Description {
+    let clauses = desc.clauses.iter().map(|c| {
+        match c {
+            &RuleClause(ref r) => RuleClause(condense_rule(r)),
+            c => c.clone()
+        }
+    }).collect();
+    Description::new(clauses)
+}
+
+fn condense_rule(rule: &Rule) -> Rule {
+    let mut rule = rule.clone();
+    while let Some(i) = rule.body.iter().position(|l| equality(l).is_some()) {
+        let (var, term) = equality(&rule.body[i]).unwrap();
+        let subst = Some((var, term)).into_iter().collect::<Substitution>();
+        rule.body.remove(i);
+        rule = subst.apply_rule(&rule);
+    }
+
+    let mut body = Vec::<Literal>::new();
+    for l in rule.body.iter() {
+        if !body.contains(l) {
+            body.push(l.clone());
+        }
+    }
+    while let Some(i) = (0..body.len()).find(|&i| subsumed(i, &rule.head, &body)) {
+        body.remove(i);
+    }
+    Rule::new(rule.head, body).with_span(rule.span)
+}
+
+// Returns the variable and the term that `l` says are equal, if it's a `(not (distinct ...))`
+// with a variable that isn't in the other term
+fn equality(l: &Literal) -> Option<(Variable, Term)> {
+    let d = match l {
+        &NotLit(ref n) => match *n.lit {
+            DistinctLit(ref d) => d,
+            _ => return None
+        },
+        _ => return None
+    };
+    match (&d.term1, &d.term2) {
+        (&VarTerm(ref v), t) | (t, &VarTerm(ref v)) if !term_has_variable(t, v) => {
+            Some((v.clone(), t.clone()))
+        }
+        _ => None
+    }
+}
+
+fn term_has_variable(t: &Term, var: &Variable) -> bool {
+    match t {
+        &VarTerm(ref v) => v.name.name == var.name.name,
+        &FuncTerm(ref f) => f.args.iter().any(|a| term_has_variable(a, var)),
+        &ConstTerm(_) => false
+    }
+}
+
+// Returns true if the positive literal `body[i]` becomes another literal in `body` when the
+// variables that are only in it are replaced
+fn subsumed(i: usize, head: &Sentence, body: &[Literal]) -> bool {
+    let r = match body[i] {
+        RelLit(ref r) => r,
+        _ => return false
+    };
+    let mut vars = Variables { vars: Vec::new() };
+    visitor::walk_sentence(head, &mut vars);
+    for l in body[..i].iter().chain(body[i + 1..].iter()) {
+        visitor::walk_literal(l, &mut vars);
+    }
+    let elsewhere = vars.vars.into_iter().map(|v| v.name.name).collect::<HashSet<_>>();
+
+    body.iter().enumerate().any(|(j, l)| {
+        match l {
+            &RelLit(ref other) if j != i && other.name == r.name &&
+                                  other.args.len() == r.args.len() => {
+                let mut subst = Substitution::new();
+                r.args.iter().zip(other.args.iter()).all(|(a, b)| {
+                    subsumes(a, b, &elsewhere, &mut subst)
+                })
+            }
+            _ => false
+        }
+    })
+}
+
+// Extends `subst` so it makes `general` into `specific`, only replacing the variables that aren't
+// in `fixed`, and returns false if it can't
+fn subsumes(general: &Term, specific: &Term, fixed: &HashSet<String>,
+            subst: &mut Substitution) -> bool {
+    match (general, specific) {
+        (&VarTerm(ref v), _) if !fixed.contains(&v.name.name) => {
+            if let Some(t) = subst.get(v) {
+                return t == specific;
+            }
+            subst.insert(v.clone(), specific.clone());
+            true
+        }
+        (&FuncTerm(ref f), &FuncTerm(ref g)) => {
+            f.name == g.name && f.args.len() == g.args.len() &&
+                f.args.iter().zip(g.args.iter()).all(|(a, b)| subsumes(a, b, fixed, subst))
+        }
+        (a, b) => a == b
+    }
+}
+
+/// Returns `desc` with the relations that are defined by a single rule that isn't recursive
+/// inlined into the rules that use them, so there are fewer relations to ground. For example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::transform::inline_relations;
+///
+/// let desc = parse("(<= (adjacent ?x ?y) (succ ?x ?y) (index ?y))\n\
+///                   (<= (legal ?r (move ?x ?y)) (true (at ?r ?x)) (adjacent ?x ?y))");
+/// assert_eq!(inline_relations(&desc).to_string(),
+///            "(<= (legal ?r (move ?x ?y)) (true (at ?r ?x)) (succ ?x ?y) (index ?y))");
+/// ```
+///
+/// Each literal for one of these relations that isn't in a `not` or an `or` is replaced by the
+/// body of its rule, with the rule's variables renamed apart with a `VariableGenerator` and
+/// unified with the literal's arguments. A rule with a literal that can't be unified with the head
+/// of its relation's rule can never apply, so it's removed. The rule of a relation is removed if
+/// it was used before inlining, but nothing uses it anymore. Keyword relations like `legal` aren't
+/// inlined, and if `desc` has a clause that couldn't be parsed, it's returned unchanged.
+pub fn inline_relations(desc: &Description) -> Description {
+    if desc.clauses.iter().any(|c| head_name(c).is_none()) {
+        return desc.clone();
+    }
+    let graph = DependencyGraph::new(desc);
+    let mut definitions = HashMap::<&str, Vec<&Clause>>::new();
+    for c in desc.clauses.iter() {
+        definitions.entry(head_name(c).unwrap()).or_insert_with(Vec::new).push(c);
+    }
+    let inlined = definitions.into_iter().filter_map(|(name, clauses)| {
+        match clauses[..] {
+            [&RuleClause(ref r)] if !is_keyword(name) && !is_reserved(name) && name != SEES &&
+                                    !graph.is_recursive(name) => {
+                Some((name.to_string(), r.clone()))
+            }
+            _ => None
+        }
+    }).collect::<HashMap<_, _>>();
+
+    let mut generator = VariableGenerator::for_description(desc);
+    let mut clauses = Vec::new();
+    for c in desc.clauses.iter() {
+        match c {
+            &RuleClause(ref r) => {
+                if let Some(r) = inline_rule(r, &inlined, &mut generator) {
+                    clauses.push(RuleClause(r));
+                }
+            }
+            c => clauses.push(c.clone())
+        }
+    }
+
+    let (before, after) = (used_relations(&desc.clauses), used_relations(&clauses));
+    clauses.retain(|c| {
+        head_name(c).map_or(true, |n| !inlined.contains_key(n) || !before.contains(n) ||
+                                      after.contains(n))
+    });
+    Description::new(clauses)
+}
+
+// Returns the names of the relations in the bodies of `clauses`
+fn used_relations(clauses: &[Clause]) -> HashSet<String> {
+    let mut used = HashSet::new();
+    for c in clauses.iter() {
+        if let &RuleClause(ref r) = c {
+            let mut lits = Vec::new();
+            for l in r.body.iter() {
+                atoms(l, false, &mut lits);
+            }
+            used.extend(lits.into_iter().filter_map(|(l, _)| atom(l)).map(|(n, _)| n.to_string()));
+        }
+    }
+    used
+}
+
+// Returns `rule` with its positive literals for the relations in `inlined` replaced by the bodies
+// of their rules, or `None` if one of them can't be true
+fn inline_rule(rule: &Rule, inlined: &HashMap<String, Rule>, generator: &mut VariableGenerator)
+               -> Option<Rule> {
+    let mut rule = rule.clone();
+    loop {
+        let i = match rule.body.iter().position(|l| {
+            atom(l).map_or(false, |(name, _)| inlined.contains_key(name))
+        }) {
+            Some(i) => i,
+            None => return Some(rule)
+        };
+        let (name, args) = atom(&rule.body[i]).map(|(n, a)| (n.to_string(), a.to_vec())).unwrap();
+        let callee = rename_apart(&inlined[&name], generator);
+        let params = sentence_args(&callee.head);
+        let mut subst = Substitution::new();
+        if params.len() != args.len() ||
+           !params.iter().zip(args.iter()).all(|(p, a)| unify(p, a, &mut subst)) {
+            return None;
+        }
+        let mut body = rule.body;
+        body.splice(i..i + 1, callee.body);
+        rule = subst.apply_rule(&Rule::new(rule.head, body).with_span(rule.span));
+    }
+}
+
+// Extends `subst` so it makes `a` and `b` the same term, binding the variables of `a` first, and
+// returns false if it can't. `subst` is kept so that none of its terms have variables it maps.
+fn unify(a: &Term, b: &Term, subst: &mut Substitution) -> bool {
+    let (a, b) = (subst.apply(a), subst.apply(b));
+    match (&a, &b) {
+        (&VarTerm(ref v), &VarTerm(ref w)) if v.name.name == w.name.name => true,
+        (&VarTerm(ref v), t) | (t, &VarTerm(ref v)) => {
+            if term_has_variable(t, v) {
+                return false;
+            }
+            let binding = Some((v.clone(), t.clone())).into_iter().collect::<Substitution>();
+            *subst = subst.compose(&binding);
+            true
+        }
+        (&FuncTerm(ref f), &FuncTerm(ref g)) => {
+            f.name == g.name && f.args.len() == g.args.len() &&
+                f.args.iter().zip(g.args.iter()).all(|(x, y)| unify(x, y, subst))
+        }
+        (&ConstTerm(ref x), &ConstTerm(ref y)) => x == y,
+        _ => false
+    }
+}
+
+/// Makes variables with names that haven't been used before, like `?_v0` and `?_v1`. Names
+/// starting with `_` are skipped by the `singleton-variable` lint.
+#[derive(Debug, Clone, Default)]
+pub struct VariableGenerator {
+    used: HashSet<String>,
+    next: usize
+}
+
+impl VariableGenerator {
+    /// Constructs a generator that hasn't seen any names
+    pub fn new() -> VariableGenerator {
+        VariableGenerator { used: HashSet::new(), next: 0 }
+    }
+
+    /// Constructs a generator that won't make any of the variable names in `desc`
+    pub fn for_description(desc: &Description) -> VariableGenerator {
+        let mut generator = VariableGenerator::new();
+        for c in desc.clauses.iter() {
+            generator.reserve_clause(c);
+        }
+        generator
+    }
+
+    /// Stops the generator from making the name `name`
+    pub fn reserve<T: Into<String>>(&mut self, name: T) {
+        self.used.insert(name.into());
+    }
+
+    /// Stops the generator from making any of the variable names in `clause`
+    pub fn reserve_clause(&mut self, clause: &Clause) {
+        for v in clause_variables(clause) {
+            self.reserve(v.name.name);
+        }
+    }
+
+    /// Returns a variable whose name hasn't been made or reserved before
+    pub fn fresh(&mut self) -> Variable {
+        loop {
+            let name = format!("_v{}", self.next);
+            self.next += 1;
+            if self.used.insert(name.clone()) {
+                return Variable::new(Constant::new(name));
+            }
+        }
+    }
+}
+
+/// Returns `rule` with each of its variables renamed to a fresh one from `generator`, so it doesn't
+/// share variables with any other rule renamed with the same generator
+pub fn rename_apart(rule: &Rule, generator: &mut VariableGenerator) -> Rule {
+    match rename_clause(&RuleClause(rule.clone()), generator) {
+        RuleClause(r) => r,
+        _ => unreachable!()
+    }
+}
+
+/// Returns `desc` with the variables of every clause renamed with `rename_apart`, so no two clauses
+/// share a variable name, and none of the new names were in `desc` before
+pub fn standardize_apart(desc: &Description) -> Description {
+    let mut generator = VariableGenerator::for_description(desc);
+    Description::new(desc.clauses.iter().map(|c| rename_clause(c, &mut generator)).collect())
+}
+
+/// Returns `rule` with its variables renamed to `?v0`, `?v1`, and so on, in the order they first
+/// appear, so rules that only differ in the names of their variables are equal and hash the same.
+/// For example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::Clause::RuleClause;
+/// use gdl_parser::transform::rename_canonically;
+///
+/// let desc = parse("(<= (p ?x) (q ?x ?y)) (<= (p ?a) (q ?a ?b))");
+/// match (&desc.clauses[0], &desc.clauses[1]) {
+///     (&RuleClause(ref r1), &RuleClause(ref r2)) => {
+///         assert_eq!(rename_canonically(r1).to_string(), "(<= (p ?v0) (q ?v0 ?v1))");
+///         assert_eq!(rename_canonically(r1), rename_canonically(r2));
+///     }
+///     _ => unreachable!()
+/// }
+/// ```
+pub fn rename_canonically(rule: &Rule) -> Rule {
+    match canonical_variables(&RuleClause(rule.clone())) {
+        RuleClause(r) => r,
+        _ => unreachable!()
+    }
+}
+
+/// Returns `desc` with the variables of every clause renamed with `rename_canonically`
+pub fn canonicalize_variables(desc: &Description) -> Description {
+    Description::new(desc.clauses.iter().map(canonical_variables).collect())
+}
+
+fn rename_clause(clause: &Clause, generator: &mut VariableGenerator) -> Clause {
+    let mut subst = Substitution::new();
+    for v in clause_variables(clause) {
+        if subst.get(&v).is_none() {
+            let fresh = generator.fresh();
+            subst.insert(v, fresh.into());
+        }
+    }
+    subst.apply_clause(clause)
+}
+
+fn clause_variables(clause: &Clause) -> Vec<Variable> {
+    let mut vars = Variables { vars: Vec::new() };
+    visitor::walk_clause(clause, &mut vars);
+    vars.vars
+}
+
+// Collects the variables in a clause, in the order they're visited
+struct Variables {
+    vars: Vec<Variable>
+}
+
+impl Visit for Variables {
+    fn visit_variable(&mut self, var: &Variable) {
+        self.vars.push(var.clone());
+    }
+}
+
+/// Returns `desc` with its static relations evaluated ahead of time and inlined into the rules
+/// that use them, so reasoners don't have to prove them again in every state. For example
+///
+/// ```
+/// use gdl_parser::parse;
+/// use gdl_parser::transform::evaluate_static;
+///
+/// let desc = parse("(succ 1 2) (succ 2 3) (<= (next (step ?y)) (true (step ?x)) (succ ?x ?y))");
+/// assert_eq!(evaluate_static(&desc).to_string(),
+///            "(<= (next (step 2)) (true (step 1))) (<= (next (step 3)) (true (step 2)))");
+/// ```
+///
+/// The relations from `analysis::static_relations` are evaluated to a fixpoint, one stratum at a
+/// time, and their rules are replaced by the facts they produce. A rule that has a positive
+/// literal for one of them is replaced by an instance for each fact the literal matches, and
+/// other literals for them, like ones under a `not`, are evaluated once their arguments are
+/// ground. Only the first literal of a rule that matches more than one fact adds instances, and
+/// the literals after it that would too are kept, so rules don't grow with the product of the
+/// facts. Literals in an `or` that are false are removed from it. The facts of a relation are
+/// left out if no rule uses it anymore. Keyword relations like `role` and `init` are evaluated
+/// too, but they're never inlined or left out. `distinct`s between ground terms are evaluated as
+/// well.
+///
+/// Relations with a rule from `analysis::unbounded_rules`, with a rule that has a variable that
+/// isn't in a positive literal, or that depend on one of these can't be evaluated, so they're
+/// left as they are. If `desc` has a cycle through negation or a clause that couldn't be parsed,
+/// it's returned unchanged.
+pub fn evaluate_static(desc: &Description) -> Description {
+    let strata = match stratify(desc) {
+        Ok(strata) => strata,
+        Err(_) => return desc.clone()
+    };
+    let rules = deor(desc);
+    let mut unevaluable = unbounded_rules(desc).into_iter().map(|u| u.relation)
+                                               .collect::<HashSet<_>>();
+    for c in rules.clauses.iter() {
+        match c {
+            &UnknownClause(_) => return desc.clone(),
+            &RuleClause(ref r) if !is_safe(r) => {
+                unevaluable.insert(r.head.name().name.clone());
+            }
+            &SentenceClause(ref s) if !sentence_args(s).iter().all(is_ground) => {
+                unevaluable.insert(s.name().name.clone());
+            }
+            _ => {}
+        }
+    }
+    let graph = DependencyGraph::new(desc);
+    let mut stack = unevaluable.iter().cloned().collect::<Vec<_>>();
+    while let Some(name) = stack.pop() {
+        for d in graph.predecessors(&name) {
+            if unevaluable.insert(d.from.clone()) {
+                stack.push(d.from.clone());
+            }
+        }
+    }
+    let evaluable = static_relations(desc).into_iter().filter(|n| !unevaluable.contains(n))
+                                          .collect::<HashSet<_>>();
+
+    let mut facts = Facts::default();
+    for i in 0..strata.strata().len() {
+        let clauses = rules.clauses.iter().filter(|c| {
+            head_name(c).map_or(false, |n| evaluable.contains(n) && strata.stratum(n) == Some(i))
+        }).collect::<Vec<_>>();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for c in clauses.iter() {
+                match *c {
+                    &SentenceClause(ref s) => changed |= facts.insert(s, s.span()),
+                    &RuleClause(ref r) => {
+                        for instance in inline(r, &evaluable, &facts, false) {
+                            if instance.body.is_empty() {
+                                changed |= facts.insert(&instance.head, r.span);
+                            }
+                        }
+                    }
+                    &UnknownClause(_) => {}
+                }
+            }
+        }
+    }
+
+    let inlined = evaluable.iter().filter(|n| !is_keyword(n)).cloned().collect::<HashSet<_>>();
+    let mut clauses = Vec::new();
+    let mut evaluated = HashSet::new();
+    for c in desc.clauses.iter() {
+        match (c, head_name(c)) {
+            (_, Some(name)) if evaluable.contains(name) => {
+                if evaluated.insert(name) {
+                    clauses.extend(facts.clauses(name));
+                }
+            }
+            (&RuleClause(ref r), _) => {
+                clauses.extend(inline(r, &inlined, &facts, true).into_iter().map(|r| {
+                    if r.body.is_empty() { SentenceClause(r.head) } else { RuleClause(r) }
+                }));
+            }
+            (c, _) => clauses.push(c.clone())
+        }
+    }
+
+    let used = used_relations(&clauses);
+    clauses.retain(|c| head_name(c).map_or(true, |n| !inlined.contains(n) || used.contains(n)));
+    Description::new(clauses)
+}
+
+// The facts of the evaluated relations, in the order they were found, with the span of the clause
+// that first produced each one
+#[derive(Default)]
+struct Facts {
+    rows: HashMap<String, Vec<(Vec<Term>, Span)>>,
+    set: HashSet<(String, Vec<Term>)>
+}
+
+impl Facts {
+    fn insert(&mut self, sentence: &Sentence, span: Span) -> bool {
+        let name = sentence.name().name.clone();
+        let args = sentence_args(sentence).to_vec();
+        if !self.set.insert((name.clone(), args.clone())) {
+            return false;
+        }
+        self.rows.entry(name).or_insert_with(Vec::new).push((args, span));
+        true
+    }
+
+    fn contains(&self, name: &str, args: &[Term]) -> bool {
+        self.set.contains(&(name.to_string(), args.to_vec()))
+    }
+
+    fn get(&self, name: &str) -> &[(Vec<Term>, Span)] {
+        self.rows.get(name).map_or(&[], |rows| &rows[..])
+    }
+
+    fn clauses(&self, name: &str) -> Vec<Clause> {
+        self.get(name).iter().map(|&(ref args, span)| {
+            if args.is_empty() {
+                SentenceClause(PropSentence(Proposition::new(name).with_span(span)))
+            } else {
+                SentenceClause(RelSentence(Relation::new(name, args.clone()).with_span(span)))
+            }
+        }).collect()
+    }
+}
+
+// Returns the instances of `rule` with the positive literals for the relations in `inlined`
+// replaced by each combination of facts they match, and the literals that can be evaluated
+// removed. Instances with a literal that's false are left out. If `bounded` is true, only one
+// literal can match more than one fact for an instance, and the literals after it that would
+// make more instances are kept, so the number of instances is at most the number of facts of one
+// relation instead of growing with the product of them.
+fn inline(rule: &Rule, inlined: &HashSet<String>, facts: &Facts, bounded: bool) -> Vec<Rule> {
+    let mut substs = vec![Substitution::new()];
+    let mut expanded = false;
+    for l in rule.body.iter() {
+        let (name, args) = match atom(l) {
+            Some((name, args)) if inlined.contains(name) => (name, args),
+            _ => continue
+        };
+        let next = substs.iter().flat_map(|subst| {
+            facts.get(name).iter().filter_map(move |&(ref values, _)| {
+                let mut subst = subst.clone();
+                let matched = args.len() == values.len() &&
+                    args.iter().zip(values.iter()).all(|(a, v)| matches(a, v, &mut subst));
+                if matched { Some(subst) } else { None }
+            })
+        });
+        if bounded && expanded {
+            // Stop as soon as it's clear the literal is kept
+            let next = next.take(substs.len() + 1).collect::<Vec<_>>();
+            if next.len() <= substs.len() {
+                substs = next;
+            }
+            continue;
+        }
+        let next = next.collect::<Vec<_>>();
+        expanded |= next.len() > substs.len();
+        substs = next;
+    }
+
+    let mut instances = Vec::new();
+    let mut seen = HashSet::new();
+    'substs: for subst in substs {
+        let mut body = Vec::new();
+        for l in rule.body.iter() {
+            let l = subst.apply_literal(l);
+            match evaluate(&l, inlined, facts) {
+                Some(true) => {}
+                Some(false) => continue 'substs,
+                None => body.push(prune(&l, inlined, facts))
+            }
+        }
+        let instance = Rule::new(subst.apply_sentence(&rule.head), body).with_span(rule.span);
+        if seen.insert(instance.clone()) {
+            instances.push(instance);
+        }
+    }
+    instances
+}
+
+// Extends `subst` so it makes `pattern` into the ground term `value`, returning false if it can't
+fn matches(pattern: &Term, value: &Term, subst: &mut Substitution) -> bool {
+    match (pattern, value) {
+        (&VarTerm(ref v), _) => {
+            if let Some(t) = subst.get(v) {
+                return t == value;
+            }
+            subst.insert(v.clone(), value.clone());
+            true
+        }
+        (&FuncTerm(ref f), &FuncTerm(ref g)) => {
+            f.name == g.name && f.args.len() == g.args.len() &&
+                f.args.iter().zip(g.args.iter()).all(|(a, v)| matches(a, v, subst))
+        }
+        (&ConstTerm(ref a), &ConstTerm(ref b)) => a == b,
+        _ => false
+    }
+}
+
+// Returns the value of `l` if it only depends on the relations in `inlined` and has no variables
+fn evaluate(l: &Literal, inlined: &HashSet<String>, facts: &Facts) -> Option<bool> {
+    match l {
+        &DistinctLit(ref d) if is_ground(&d.term1) && is_ground(&d.term2) => {
+            Some(d.term1 != d.term2)
+        }
+        &NotLit(ref n) => evaluate(&n.lit, inlined, facts).map(|v| !v),
+        &OrLit(ref o) => {
+            let values = o.lits.iter().map(|l| evaluate(l, inlined, facts)).collect::<Vec<_>>();
+            if values.contains(&Some(true)) {
+                Some(true)
+            } else if values.iter().all(|v| *v == Some(false)) {
+                Some(false)
+            } else {
+                None
+            }
+        }
+        l => match atom(l) {
+            Some((name, args)) if inlined.contains(name) && args.iter().all(is_ground) => {
+                Some(facts.contains(name, args))
+            }
+            _ => None
+        }
+    }
+}
+
+// Removes the literals in the `or`s in `l` that are false, for an `l` that can't be evaluated
+fn prune(l: &Literal, inlined: &HashSet<String>, facts: &Facts) -> Literal {
+    match l {
+        &OrLit(ref o) => {
+            let mut lits = o.lits.iter().filter(|l| evaluate(l, inlined, facts) != Some(false))
+                                        .map(|l| prune(l, inlined, facts)).collect::<Vec<_>>();
+            if lits.len() == 1 {
+                lits.remove(0)
+            } else {
+                Or::new(lits).with_span(o.span).into()
+            }
+        }
+        &NotLit(ref n) => {
+            Not::new(Box::new(prune(&n.lit, inlined, facts))).with_span(n.span).into()
+        }
+        l => l.clone()
+    }
+}
+
+// Returns true if every variable in `rule` is in a positive relation in its body
+fn is_safe(rule: &Rule) -> bool {
+    let mut bound = Vec::new();
+    for l in rule.body.iter() {
+        if let &RelLit(ref r) = l {
+            let mut vars = Variables { vars: Vec::new() };
+            visitor::walk_relation(r, &mut vars);
+            bound.extend(vars.vars.into_iter().map(|v| v.name.name));
+        }
+    }
+    clause_variables(&RuleClause(rule.clone())).iter().all(|v| bound.contains(&v.name.name))
+}
+
+fn is_ground(t: &Term) -> bool {
+    match t {
+        &VarTerm(_) => false,
+        &FuncTerm(ref f) => f.args.iter().all(is_ground),
+        &ConstTerm(_) => true
+    }
+}
+
+fn head_name(c: &Clause) -> Option<&str> {
+    match c {
+        &RuleClause(ref r) => Some(&r.head.name().name),
+        &SentenceClause(ref s) => Some(&s.name().name),
+        &UnknownClause(_) => None
+    }
+}
+
+fn atom(l: &Literal) -> Option<(&str, &[Term])> {
+    match l {
+        &PropLit(ref p) => Some((&p.name.name, &[])),
+        &RelLit(ref r) => Some((&r.name.name, &r.args)),
+        _ => None
+    }
+}
+
+fn sentence_args(s: &Sentence) -> &[Term] {
+    match s {
+        &PropSentence(_) => &[],
+        &RelSentence(ref r) => &r.args
+    }
+}
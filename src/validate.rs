@@ -0,0 +1,550 @@
+//! Checks that a description is well-formed GDL, beyond what's needed to parse it
+
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+
+use {lint, Constant, Description, Diagnostic, Literal, ParseError, Relation, Sentence, Span, Term,
+     Variable};
+use Clause::{RuleClause, SentenceClause, UnknownClause};
+use Literal::{DistinctLit, OrLit, PropLit, RelLit};
+use Literal::KnowsLit;
+use Sentence::{PropSentence, RelSentence};
+use Term::{VarTerm, FuncTerm, ConstTerm};
+use analysis::{dead_clauses, DependencyGraph};
+use dialect::{atoms, is_reserved, KEYWORD_ARITIES, RANDOM, SEES};
+
+/// The problems found by `validate`, in source order
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ValidationReport {
+    pub diagnostics: Vec<Diagnostic>
+}
+
+impl ValidationReport {
+    /// Returns true if no errors were found. There may still be warnings.
+    pub fn is_valid(&self) -> bool {
+        !self.diagnostics.iter().any(|d| d.is_error())
+    }
+
+    pub fn errors(&self) -> Vec<&Diagnostic> {
+        self.diagnostics.iter().filter(|d| d.is_error()).collect()
+    }
+
+    pub fn warnings(&self) -> Vec<&Diagnostic> {
+        self.diagnostics.iter().filter(|d| !d.is_error()).collect()
+    }
+}
+
+impl Display for ValidationReport {
+    /// Prints each diagnostic on its own line
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (i, d) in self.diagnostics.iter().enumerate() {
+            if i > 0 {
+                write!(f, "\n")?;
+            }
+            write!(f, "{}", d)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that `desc` is well-formed GDL, returning every problem found. These errors make the
+/// description invalid:
+///
+/// * `invalid-clause`: a clause that couldn't be parsed, kept by `ParserOptions::keep_unknown`
+/// * `invalid-arity`: a keyword relation like `legal` or `sees` with the wrong number of arguments.
+///   The parser already rejects these, but descriptions built in code might have them.
+/// * `unsafe-variable`: a variable in the head of a rule, in a negated literal, or in a `distinct`
+///   that doesn't appear in a positive literal in the rule's body, or in every disjunct of an `or`
+/// * `unstratified`: a relation that depends on itself through `not`
+/// * `keyword-redefinition`: a fact or rule whose head is a relation that GDL defines itself, like
+///   `true`, `does`, or `distinct`. Reasoners can't run games that do this.
+/// * `keyword-position`: `init` or `next` in the body of a rule
+/// * `keyword-dependency`: a literal in a rule for `init` that depends on `true`, `does`, `next`,
+///   `legal`, `goal`, or `terminal`, or in a rule for `legal`, `goal`, or `terminal` that depends
+///   on `does`
+/// * `recursion-restriction`: an argument of a literal that's recursive with the rule's head that
+///   isn't ground, an argument of the head, or a variable from a literal that isn't recursive
+///
+/// Descriptions that use GDL-II, as decided by `Description::is_gdl_ii`, are also checked for:
+///
+/// * `sees-role`: a `sees` whose first argument is a constant that isn't declared as a role
+/// * `undeclared-random`: `random` used as the role in `legal`, `does`, `goal`, or `sees` without
+///   `(role random)`
+/// * `percept-dependency`: a literal in a rule for `sees` that depends on `sees`, since the
+///   percepts of a step are only known once every role's have been found
+///
+/// The warnings from `lint` are included too, except for `shadowed-keyword`, which
+/// `keyword-redefinition` replaces.
+pub fn validate(desc: &Description) -> ValidationReport {
+    let mut diagnostics = lint(desc).into_iter().filter(|d| d.code != "shadowed-keyword")
+                                    .collect::<Vec<_>>();
+    let graph = DependencyGraph::new(desc);
+    for c in desc.clauses.iter() {
+        let (head, body): (_, &[Literal]) = match c {
+            &RuleClause(ref r) => (&r.head, &r.body),
+            &SentenceClause(ref s) => (s, &[]),
+            &UnknownClause(ref u) => {
+                let message = "this clause couldn't be parsed";
+                diagnostics.push(Diagnostic::error("invalid-clause", u.span, message));
+                continue;
+            }
+        };
+        let mut lits = Vec::new();
+        for l in body.iter() {
+            atoms(l, false, &mut lits);
+        }
+        check_arity(&head.name().name, head_arity(head), head.span(), &mut diagnostics);
+        for &(l, _) in lits.iter() {
+            match l {
+                &PropLit(ref p) => check_arity(&p.name.name, 0, p.span, &mut diagnostics),
+                &RelLit(ref r) => {
+                    check_arity(&r.name.name, r.args.len(), r.span, &mut diagnostics)
+                }
+                _ => {}
+            }
+        }
+        check_keywords(head, &lits, &graph, &mut diagnostics);
+        for v in unsafe_variables(head, body, &lits) {
+            let message = format!("variable `?{}` in the rule for `{}` doesn't appear in a \
+                                   positive literal in its body", v.name.name, head.name());
+            diagnostics.push(Diagnostic::error("unsafe-variable", v.span, message));
+        }
+        for (r, i, arg) in recursion_violations(head, &lits, &graph) {
+            let message = format!("argument {} of `{}`, `{}`, must be ground, an argument of the \
+                                   head, or a variable from a literal that isn't recursive, since \
+                                   `{}` is recursive with `{}`", i + 1, r.name, arg, r.name,
+                                  head.name());
+            diagnostics.push(Diagnostic::error("recursion-restriction", arg.span(), message));
+        }
+    }
+    for d in graph.dependencies().iter().filter(|d| d.negative) {
+        let (from, to) = (&d.from, &d.to);
+        if graph.mutually_recursive(from, to) {
+            let message = if from == to {
+                format!("`{}` depends on its own negation", from)
+            } else {
+                format!("`{}` depends on the negation of `{}`, which depends on `{}`", from, to,
+                        from)
+            };
+            diagnostics.push(Diagnostic::error("unstratified", d.span, message));
+        }
+    }
+    if desc.is_gdl_ii() {
+        check_gdl_ii(desc, &graph, &mut diagnostics);
+    }
+    diagnostics.sort_by_key(|d| d.span.lo);
+    ValidationReport { diagnostics: diagnostics }
+}
+
+fn head_arity(head: &Sentence) -> usize {
+    match head {
+        &PropSentence(_) => 0,
+        &RelSentence(ref r) => r.args.len()
+    }
+}
+
+fn check_arity(name: &str, found: usize, span: Span, diagnostics: &mut Vec<Diagnostic>) {
+    let keywords = KEYWORD_ARITIES.iter().chain(Some(&(SEES, 2)));
+    for &(keyword, expected) in keywords {
+        if name == keyword && found != expected {
+            let message = format!("`{}` takes {} arguments but has {}", name, expected, found);
+            diagnostics.push(Diagnostic::error("invalid-arity", span, message));
+        }
+    }
+}
+
+fn check_gdl_ii(desc: &Description, graph: &DependencyGraph, diagnostics: &mut Vec<Diagnostic>) {
+    let mut roles = HashSet::new();
+    for c in desc.clauses.iter() {
+        if let &SentenceClause(RelSentence(ref r)) = c {
+            if let (&"role", Some(&ConstTerm(ref role))) = (&&r.name.name[..], r.args.first()) {
+                roles.insert(&role.name[..]);
+            }
+        }
+    }
+
+    for c in desc.clauses.iter() {
+        let (head, body): (_, &[Literal]) = match c {
+            &RuleClause(ref r) => (&r.head, &r.body),
+            &SentenceClause(ref s) => (s, &[]),
+            &UnknownClause(_) => continue
+        };
+        let mut lits = Vec::new();
+        for l in body.iter() {
+            atoms(l, false, &mut lits);
+        }
+        let mut relations = Vec::new();
+        if let &RelSentence(ref r) = head {
+            relations.push(r);
+        }
+        for &(l, _) in lits.iter() {
+            if let &RelLit(ref r) = l {
+                relations.push(r);
+            }
+        }
+        for r in relations {
+            let name = &r.name.name[..];
+            let role = match r.args.first() {
+                Some(&ConstTerm(ref role)) => role,
+                _ => continue
+            };
+            if name == SEES && role.name != RANDOM && !roles.contains(&role.name[..]) {
+                let message = format!("`sees` is for `{}`, which isn't a role", role.name);
+                diagnostics.push(Diagnostic::error("sees-role", role.span, message));
+            }
+            let role_relation = name == SEES || name == "legal" || name == "does" || name == "goal";
+            if role_relation && role.name == RANDOM && !roles.contains(RANDOM) {
+                let message = format!("`{}` is used as a role, but `(role {})` isn't declared",
+                                      RANDOM, RANDOM);
+                diagnostics.push(Diagnostic::error("undeclared-random", role.span, message));
+            }
+        }
+
+        if head.name().name != SEES {
+            continue;
+        }
+        for &(l, _) in lits.iter() {
+            let (name, span) = match l {
+                &PropLit(ref p) => (&p.name.name, p.span),
+                &RelLit(ref r) => (&r.name.name, r.span),
+                _ => continue
+            };
+            if let Some(path) = graph.path(name, SEES) {
+                let mut message = "percepts can't depend on `sees`".to_string();
+                if path.len() > 1 {
+                    message.push_str(&format!(", but `{}` does through {}", name,
+                                              path.join(" -> ")));
+                }
+                diagnostics.push(Diagnostic::error("percept-dependency", span, message));
+            }
+        }
+    }
+}
+
+// Keywords that can only be in the heads of rules
+const HEAD_KEYWORDS: &'static [&'static str] = &["init", "next"];
+
+// The relations each keyword can't depend on
+const FORBIDDEN_DEPENDENCIES: &'static [(&'static str, &'static [&'static str])] = &[
+    ("init", &["true", "does", "next", "legal", "goal", "terminal"]),
+    ("legal", &["does"]),
+    ("goal", &["does"]),
+    ("terminal", &["does"])
+];
+
+fn check_keywords(head: &Sentence, lits: &[(&Literal, bool)], graph: &DependencyGraph,
+                  diagnostics: &mut Vec<Diagnostic>) {
+    let head_name = &head.name().name;
+    if is_reserved(head_name) {
+        let message = format!("`{}` is defined by GDL, so the game can't define it too",
+                              head_name);
+        diagnostics.push(Diagnostic::error("keyword-redefinition", head.span(), message));
+    }
+    let forbidden = FORBIDDEN_DEPENDENCIES.iter().find(|&&(k, _)| k == head_name)
+                                          .map_or(&[][..], |&(_, f)| f);
+    for &(l, _) in lits.iter() {
+        let (name, span) = match l {
+            &PropLit(ref p) => (&p.name.name, p.span),
+            &RelLit(ref r) => (&r.name.name, r.span),
+            _ => continue
+        };
+        if HEAD_KEYWORDS.contains(&&name[..]) {
+            let message = format!("`{}` can only be in the head of a rule", name);
+            diagnostics.push(Diagnostic::error("keyword-position", span, message));
+        }
+        for f in forbidden.iter() {
+            if let Some(path) = graph.path(name, f) {
+                let mut message = format!("`{}` can't depend on `{}`", head_name, f);
+                if path.len() > 1 {
+                    message.push_str(&format!(", but `{}` does through {}", name,
+                                              path.join(" -> ")));
+                }
+                diagnostics.push(Diagnostic::error("keyword-dependency", span, message));
+                break;
+            }
+        }
+    }
+}
+
+/// Checks the roles of `desc`, returning the problems found in source order. These are all errors:
+///
+/// * `invalid-role`: a `role` defined by a rule, or by a fact whose argument isn't a constant
+/// * `no-roles`: the game doesn't have any roles
+/// * `missing-legal`: a role that isn't the first argument of any `legal` fact or rule head
+/// * `missing-goal`: a role that isn't the first argument of any `goal` fact or rule head
+/// * `missing-terminal`: the game doesn't define `terminal`
+///
+/// Heads whose first argument is a variable, like `(legal ?r noop)`, cover every role. Rules from
+/// `analysis::dead_clauses` don't count, since they can never apply, and the message says so when
+/// they're the only rules for a role. Diagnostics about the game as a whole, rather than a clause,
+/// have a dummy span.
+pub fn check_roles(desc: &Description) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let dead = dead_clauses(desc).into_iter().collect::<HashSet<_>>();
+    let mut roles = Vec::new();
+    let (mut legal, mut goal, mut terminal) = (Coverage::new(), Coverage::new(), Coverage::new());
+    for (i, c) in desc.clauses.iter().enumerate() {
+        let (head, is_rule) = match c {
+            &RuleClause(ref r) => (&r.head, true),
+            &SentenceClause(ref s) => (s, false),
+            &UnknownClause(_) => continue
+        };
+        let first = match head {
+            &RelSentence(ref r) => r.args.first(),
+            &PropSentence(_) => None
+        };
+        let live = !dead.contains(&i);
+        match &head.name().name[..] {
+            "role" => match first {
+                Some(&ConstTerm(ref role)) if !is_rule => {
+                    if !roles.iter().any(|&(r, _): &(&Constant, Span)| r.name == role.name) {
+                        roles.push((role, head.span()));
+                    }
+                }
+                _ => {
+                    let message = if is_rule {
+                        "`role` must be defined by facts, not rules"
+                    } else {
+                        "`role` must be defined by facts like `(role white)`"
+                    };
+                    diagnostics.push(Diagnostic::error("invalid-role", c.span(), message));
+                }
+            },
+            "legal" => legal.add(first.and_then(role_covered), live, c.span()),
+            "goal" => goal.add(first.and_then(role_covered), live, c.span()),
+            "terminal" => terminal.add(Some(None), live, c.span()),
+            _ => {}
+        }
+    }
+
+    if roles.is_empty() {
+        let message = "the game doesn't have any roles";
+        diagnostics.push(Diagnostic::error("no-roles", Span::dummy(), message));
+    }
+    for &(role, span) in roles.iter() {
+        let name = Some(&role.name[..]);
+        if !legal.covers(name) {
+            let message = format!("role `{}` doesn't have any legal moves{}", role.name,
+                                  legal.reason(name));
+            diagnostics.push(Diagnostic::error("missing-legal", span, message));
+        }
+        if !goal.covers(name) {
+            let message = format!("role `{}` doesn't have any goals{}", role.name,
+                                  goal.reason(name));
+            diagnostics.push(Diagnostic::error("missing-goal", span, message));
+        }
+    }
+    if !terminal.covers(None) {
+        let (span, message) = match terminal.dead.first() {
+            Some(&(_, span)) => (span, "`terminal` can never be true, since none of its rules can \
+                                        ever apply, so the game never ends"),
+            None => (Span::dummy(), "the game doesn't define `terminal`, so it never ends")
+        };
+        diagnostics.push(Diagnostic::error("missing-terminal", span, message));
+    }
+    diagnostics.sort_by_key(|d| d.span.lo);
+    diagnostics
+}
+
+// The roles that the `legal`, `goal`, or `terminal` clauses of a game are for, using the values
+// from `role_covered`
+struct Coverage<'a> {
+    live: HashSet<Option<&'a str>>,
+    dead: Vec<(Option<&'a str>, Span)>
+}
+
+impl<'a> Coverage<'a> {
+    fn new() -> Coverage<'a> {
+        Coverage { live: HashSet::new(), dead: Vec::new() }
+    }
+
+    fn add(&mut self, role: Option<Option<&'a str>>, live: bool, span: Span) {
+        match role {
+            Some(role) if live => {
+                self.live.insert(role);
+            }
+            Some(role) => self.dead.push((role, span)),
+            None => {}
+        }
+    }
+
+    // Returns true if a clause that can apply is for `role`. `None` checks for a clause that's for
+    // every role.
+    fn covers(&self, role: Option<&str>) -> bool {
+        self.live.contains(&role) || self.live.contains(&None)
+    }
+
+    // Returns why `role` isn't covered, if it's only because its clauses can't apply
+    fn reason(&self, role: Option<&str>) -> &'static str {
+        if self.dead.iter().any(|&(r, _)| r == role || r.is_none()) {
+            ", since none of its rules can ever apply"
+        } else {
+            ""
+        }
+    }
+}
+
+// Returns the role the first argument of a `legal` or `goal` head is for, `Some(None)` if it could
+// be for any role, or `None` if it can't be a role
+fn role_covered(arg: &Term) -> Option<Option<&str>> {
+    match arg {
+        &ConstTerm(ref c) => Some(Some(&c.name)),
+        &VarTerm(_) => Some(None),
+        &FuncTerm(_) => None
+    }
+}
+
+/// Returns an error for the first variable in `desc` that's in the head of a rule, in a negated
+/// literal, or in a `distinct` but isn't in a positive literal in the rule's body. A variable in an
+/// `or` is only bound if it's in every disjunct.
+pub fn check_safety(desc: &Description) -> Result<(), ParseError> {
+    for c in desc.clauses.iter() {
+        let (head, body): (_, &[Literal]) = match c {
+            &RuleClause(ref r) => (&r.head, &r.body),
+            &SentenceClause(ref s) => (s, &[]),
+            &UnknownClause(_) => continue
+        };
+        let mut lits = Vec::new();
+        for l in body.iter() {
+            atoms(l, false, &mut lits);
+        }
+        if let Some(v) = unsafe_variables(head, body, &lits).into_iter().next() {
+            return Err(ParseError::UnsafeVariable { span: v.span, name: v.name.name.clone(),
+                                                    rule: c.span() });
+        }
+    }
+    Ok(())
+}
+
+// Returns the variables that need to be bound by a positive literal but aren't, without duplicates
+fn unsafe_variables<'a>(head: &'a Sentence, body: &'a [Literal],
+                        lits: &[(&'a Literal, bool)]) -> Vec<&'a Variable> {
+    let mut bound = HashSet::new();
+    for l in body.iter() {
+        bound_variables(l, &mut bound);
+    }
+
+    let mut needed = Vec::new();
+    if let &RelSentence(ref r) = head {
+        needed.extend(r.args.iter().flat_map(term_vars));
+    }
+    for &(l, negated) in lits.iter() {
+        match l {
+            &RelLit(ref r) if negated => needed.extend(r.args.iter().flat_map(term_vars)),
+            &DistinctLit(ref d) => {
+                needed.extend(term_vars(&d.term1));
+                needed.extend(term_vars(&d.term2));
+            }
+            &KnowsLit(ref k) => {
+                needed.extend(k.role.iter().flat_map(term_vars));
+                needed.extend(term_vars(&k.prop));
+            }
+            _ => {}
+        }
+    }
+    let mut reported = HashSet::new();
+    needed.into_iter().filter(|v| {
+        !bound.contains(&v.name.name) && reported.insert(&v.name.name)
+    }).collect()
+}
+
+/// Returns an error for the first argument in `desc` that breaks the recursion restriction from the
+/// GDL spec. In a rule whose body has a positive literal that's recursive with the head, each
+/// argument of that literal must be ground, equal to an argument of the head, or a variable from a
+/// positive literal in the body that isn't recursive with the head. Rules that break this can
+/// build ever larger terms, so the game might never be grounded.
+pub fn check_recursion(desc: &Description) -> Result<(), ParseError> {
+    let graph = DependencyGraph::new(desc);
+    for c in desc.clauses.iter() {
+        let r = match c {
+            &RuleClause(ref r) => r,
+            _ => continue
+        };
+        let mut lits = Vec::new();
+        for l in r.body.iter() {
+            atoms(l, false, &mut lits);
+        }
+        let violations = recursion_violations(&r.head, &lits, &graph);
+        if let Some(&(lit, position, arg)) = violations.first() {
+            return Err(ParseError::RecursionRestriction { span: arg.span(),
+                                                          name: lit.name.name.clone(),
+                                                          position: position, rule: r.span });
+        }
+    }
+    Ok(())
+}
+
+// Returns the arguments of recursive literals that break the recursion restriction, along with the
+// literal and the argument's position in it
+fn recursion_violations<'a>(head: &Sentence, lits: &[(&'a Literal, bool)],
+                            graph: &DependencyGraph) -> Vec<(&'a Relation, usize, &'a Term)> {
+    let head_name = &head.name().name;
+    let recursive = |name: &String| graph.mutually_recursive(name, head_name);
+
+    // Arguments of the head, which a recursive literal's arguments may repeat as they are
+    let head_args: &[Term] = match head {
+        &RelSentence(ref r) => &r.args,
+        &PropSentence(_) => &[]
+    };
+
+    // Variables bound by positive literals that aren't recursive with the head
+    let mut allowed = HashSet::new();
+    for &(l, negated) in lits.iter() {
+        if let (&RelLit(ref r), false) = (l, negated) {
+            if !recursive(&r.name.name) {
+                for arg in r.args.iter() {
+                    allowed.extend(term_vars(arg).into_iter().map(|v| &v.name.name));
+                }
+            }
+        }
+    }
+
+    let mut violations = Vec::new();
+    for &(l, negated) in lits.iter() {
+        let r = match (l, negated) {
+            (&RelLit(ref r), false) if recursive(&r.name.name) => r,
+            _ => continue
+        };
+        for (i, arg) in r.args.iter().enumerate() {
+            let ok = term_vars(arg).is_empty() || head_args.contains(arg) || match arg {
+                &VarTerm(ref v) => allowed.contains(&v.name.name),
+                _ => false
+            };
+            if !ok {
+                violations.push((r, i, arg));
+            }
+        }
+    }
+    violations
+}
+
+// Adds the variables that `lit` binds to `bound`. A positive relation binds all of its variables,
+// and an `or` only binds the ones that every disjunct binds.
+fn bound_variables<'a>(lit: &'a Literal, bound: &mut HashSet<&'a String>) {
+    match lit {
+        &RelLit(ref r) => {
+            for arg in r.args.iter() {
+                bound.extend(term_vars(arg).into_iter().map(|v| &v.name.name));
+            }
+        }
+        &OrLit(ref o) => {
+            let mut disjuncts = o.lits.iter().map(|l| {
+                let mut bound = HashSet::new();
+                bound_variables(l, &mut bound);
+                bound
+            });
+            if let Some(first) = disjuncts.next() {
+                bound.extend(disjuncts.fold(first, |common, b| &common & &b));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn term_vars(t: &Term) -> Vec<&Variable> {
+    match t {
+        &VarTerm(ref v) => vec![v],
+        &FuncTerm(ref f) => f.args.iter().flat_map(term_vars).collect(),
+        &ConstTerm(_) => vec![]
+    }
+}
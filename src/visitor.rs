@@ -1,12 +1,219 @@
 use {Description, Sentence, Proposition, Relation, Literal, Or, Not, Distinct, Function, Rule,
-          Variable, Constant, Clause, Term};
-use Clause::{RuleClause, SentenceClause};
+          Variable, Constant, Clause, Term, Unknown};
+use Clause::{RuleClause, SentenceClause, UnknownClause};
 use Sentence::{PropSentence, RelSentence};
 use Term::{ConstTerm, FuncTerm, VarTerm};
 use Literal::{OrLit, NotLit, DistinctLit, PropLit, RelLit};
+use Knows;
+use Literal::KnowsLit;
 
-/// A visitor for the AST. Each function will get called when that respective AST node is visited
+/// What a traversal does after a visitor enters a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// Walk the node's children and then visit the node
+    Continue,
+
+    /// Leave out the node and its children, and carry on with the node after it
+    Skip,
+
+    /// Stop the traversal, so nothing else is entered or visited
+    Stop
+}
+
+/// The kinds of nodes in the AST, for describing where a node is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    Description,
+    Clause,
+    Rule,
+    Sentence,
+    Proposition,
+    Relation,
+    Literal,
+    Term,
+    Constant,
+    Or,
+    Not,
+    Distinct,
+    Variable,
+    Function,
+    Unknown,
+    Knows
+}
+
+/// A step from a node down to one of its children
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Step {
+    /// The kind of the node the step is from
+    pub kind: NodeKind,
+
+    /// Which of the node's children the step is to. For a description it's the index of the
+    /// clause. For a rule the head is 0 and the body's literals are 1 onwards, and for a relation
+    /// or function the name is 0 and the arguments are 1 onwards. The literals of an `or` start
+    /// at 0, the terms of a `distinct` are 0 and 1, and the role and proposition of a GDL-III
+    /// `knows` are 0 and 1. Every other node has just one child, which is 0.
+    pub index: usize
+}
+
+/// The steps from where a traversal started down to the node being entered or visited, which
+/// tells a visitor what the node is inside of. For example
+///
+/// ```
+/// use gdl_parser::{parse, Relation};
+/// use gdl_parser::visitor::{self, NodeKind, Path, Visit};
+///
+/// // Finds the relations that are used under `not`
+/// #[derive(Default)]
+/// struct Negated {
+///     path: Path,
+///     found: Vec<String>
+/// }
+///
+/// impl Visit for Negated {
+///     fn path_mut(&mut self) -> Option<&mut Path> {
+///         Some(&mut self.path)
+///     }
+///
+///     fn visit_relation(&mut self, relation: &Relation) {
+///         if self.path.is_within(NodeKind::Not) {
+///             self.found.push(relation.name.name.clone());
+///         }
+///     }
+/// }
+///
+/// let mut negated = Negated::default();
+/// visitor::walk(&parse("(<= (p ?x) (q ?x) (not (r ?x)) (not (or (s ?x) (t ?x))))"), &mut negated);
+/// assert_eq!(negated.found, ["r", "s", "t"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Path {
+    steps: Vec<Step>
+}
+
+impl Path {
+    /// Constructs an empty path
+    pub fn new() -> Path {
+        Path { steps: Vec::new() }
+    }
+
+    /// Returns the steps, starting from where the traversal started
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Returns the step from the node's parent, or `None` if the node is where the traversal
+    /// started
+    pub fn parent(&self) -> Option<&Step> {
+        self.steps.last()
+    }
+
+    /// Returns true if the node is inside a node of kind `kind`
+    pub fn is_within(&self, kind: NodeKind) -> bool {
+        self.steps.iter().any(|s| s.kind == kind)
+    }
+
+    /// Returns true if the node is the head of a rule or inside it
+    pub fn in_rule_head(&self) -> bool {
+        self.steps.iter().any(|s| s.kind == NodeKind::Rule && s.index == 0)
+    }
+
+    /// Returns true if the node is one of the literals in the body of a rule or inside one
+    pub fn in_rule_body(&self) -> bool {
+        self.steps.iter().any(|s| s.kind == NodeKind::Rule && s.index > 0)
+    }
+}
+
+// Returns from the enclosing function unless `flow`, from entering its node, is `Continue`
+macro_rules! enter {
+    ($flow:expr) => {
+        match $flow {
+            Flow::Continue => {}
+            Flow::Skip => return Flow::Continue,
+            Flow::Stop => return Flow::Stop
+        }
+    }
+}
+
+// Returns `Flow::Stop` from the enclosing function if traversing a child stopped
+macro_rules! child {
+    ($flow:expr) => {
+        if $flow == Flow::Stop {
+            return Flow::Stop;
+        }
+    }
+}
+
+/// A visitor for the AST that can change it. Each `enter` function will get called when that
+/// respective AST node is reached, before its children, and decides whether the traversal goes on,
+/// so visitors can keep track of what they're inside of, like the rule whose variables they're
+/// seeing. Each `visit` function will get called after the node's children have been visited.
 pub trait Visitor {
+    /// Returns the path that the traversal keeps up to date with where the node being entered or
+    /// visited is, or `None`, the default, if the visitor doesn't need it
+    fn path_mut(&mut self) -> Option<&mut Path> {
+        None
+    }
+
+    fn enter_clause(&mut self, _: &mut Clause) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_rule(&mut self, _: &mut Rule) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_sentence(&mut self, _: &mut Sentence) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_proposition(&mut self, _: &mut Proposition) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_relation(&mut self, _: &mut Relation) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_literal(&mut self, _: &mut Literal) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_term(&mut self, _: &mut Term) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_constant(&mut self, _: &mut Constant) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_or(&mut self, _: &mut Or) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_not(&mut self, _: &mut Not) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_distinct(&mut self, _: &mut Distinct) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_variable(&mut self, _: &mut Variable) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_function(&mut self, _: &mut Function) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_unknown(&mut self, _: &mut Unknown) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_knows(&mut self, _: &mut Knows) -> Flow {
+        Flow::Continue
+    }
+
     fn visit_clause(&mut self, _: &mut Clause) {}
 
     fn visit_rule(&mut self, _: &mut Rule) {}
@@ -32,116 +239,708 @@ pub trait Visitor {
     fn visit_variable(&mut self, _: &mut Variable) {}
 
     fn visit_function(&mut self, _: &mut Function) {}
+
+    fn visit_unknown(&mut self, _: &mut Unknown) {}
+
+    fn visit_knows(&mut self, _: &mut Knows) {}
 }
 
-/// Performs a post-order traversal of a GDL description
-pub fn visit<V: Visitor>(desc: &mut Description, visitor: &mut V) {
-    for clause in desc.clauses.iter_mut() {
-        visit_clause(clause, visitor);
+/// Performs a post-order traversal of a GDL description. Returns `Flow::Stop` if the visitor
+/// stopped it, or `Flow::Continue` otherwise, and likewise for the other visit functions.
+pub fn visit<V: Visitor>(desc: &mut Description, visitor: &mut V) -> Flow {
+    for (i, clause) in desc.clauses.iter_mut().enumerate() {
+        child!(visit_step(visitor, NodeKind::Description, i, |v| visit_clause(clause, v)));
     }
+    Flow::Continue
 }
 
 /// Performs a post-order traversal of a GDL clause
-pub fn visit_clause<V: Visitor>(clause: &mut Clause, visitor: &mut V) {
-    match clause {
-        &mut RuleClause(ref mut r) => visit_rule(r, visitor),
-        &mut SentenceClause(ref mut s) => visit_sentence(s, visitor)
-    }
+pub fn visit_clause<V: Visitor>(clause: &mut Clause, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_clause(clause));
+    child!(visit_step(visitor, NodeKind::Clause, 0, |v| {
+        match clause {
+            &mut RuleClause(ref mut r) => visit_rule(r, v),
+            &mut SentenceClause(ref mut s) => visit_sentence(s, v),
+            &mut UnknownClause(ref mut u) => visit_unknown(u, v)
+        }
+    }));
     visitor.visit_clause(clause);
+    Flow::Continue
 }
 
 /// Performs a post-order traversal of a GDL rule
-pub fn visit_rule<V: Visitor>(rule: &mut Rule, visitor: &mut V) {
-    visit_sentence(&mut rule.head, visitor);
-    for l in rule.body.iter_mut() {
-        visit_literal(l, visitor);
+pub fn visit_rule<V: Visitor>(rule: &mut Rule, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_rule(rule));
+    child!(visit_step(visitor, NodeKind::Rule, 0, |v| visit_sentence(&mut rule.head, v)));
+    for (i, l) in rule.body.iter_mut().enumerate() {
+        child!(visit_step(visitor, NodeKind::Rule, i + 1, |v| visit_literal(l, v)));
     }
     visitor.visit_rule(rule);
+    Flow::Continue
 }
 
 /// Performs a post-order traversal of a GDL sentence
-pub fn visit_sentence<V: Visitor>(sentence: &mut Sentence, visitor: &mut V) {
-    match sentence {
-        &mut PropSentence(ref mut p) => visit_proposition(p, visitor),
-        &mut RelSentence(ref mut r) => visit_relation(r, visitor)
-    }
+pub fn visit_sentence<V: Visitor>(sentence: &mut Sentence, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_sentence(sentence));
+    child!(visit_step(visitor, NodeKind::Sentence, 0, |v| {
+        match sentence {
+            &mut PropSentence(ref mut p) => visit_proposition(p, v),
+            &mut RelSentence(ref mut r) => visit_relation(r, v)
+        }
+    }));
     visitor.visit_sentence(sentence);
+    Flow::Continue
 }
 
 /// Performs a post-order traversal of a GDL proposition
-pub fn visit_proposition<V: Visitor>(proposition: &mut Proposition, visitor: &mut V) {
-    visit_constant(&mut proposition.name, visitor);
-    visitor.visit_proposition(proposition)
+pub fn visit_proposition<V: Visitor>(proposition: &mut Proposition, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_proposition(proposition));
+    child!(visit_step(visitor, NodeKind::Proposition, 0, |v| {
+        visit_constant(&mut proposition.name, v)
+    }));
+    visitor.visit_proposition(proposition);
+    Flow::Continue
 }
 
 /// Performs a post-order traversal of a GDL relation
-pub fn visit_relation<V: Visitor>(relation: &mut Relation, visitor: &mut V) {
-    visit_constant(&mut relation.name, visitor);
-    for t in relation.args.iter_mut() {
-        visit_term(t, visitor)
+pub fn visit_relation<V: Visitor>(relation: &mut Relation, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_relation(relation));
+    child!(visit_step(visitor, NodeKind::Relation, 0, |v| visit_constant(&mut relation.name, v)));
+    for (i, t) in relation.args.iter_mut().enumerate() {
+        child!(visit_step(visitor, NodeKind::Relation, i + 1, |v| visit_term(t, v)));
     }
-    visitor.visit_relation(relation)
+    visitor.visit_relation(relation);
+    Flow::Continue
 }
 
 /// Performs a post-order traversal of a GDL literal
-pub fn visit_literal<V: Visitor>(literal: &mut Literal, visitor: &mut V) {
-    match literal {
-        &mut OrLit(ref mut or) => visit_or(or, visitor),
-        &mut NotLit(ref mut not) => visit_not(not, visitor),
-        &mut DistinctLit(ref mut distinct) => visit_distinct(distinct, visitor),
-        &mut RelLit(ref mut rel) => visit_relation(rel, visitor),
-        &mut PropLit(ref mut prop) => visit_proposition(prop, visitor)
-    }
+pub fn visit_literal<V: Visitor>(literal: &mut Literal, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_literal(literal));
+    child!(visit_step(visitor, NodeKind::Literal, 0, |v| {
+        match literal {
+            &mut OrLit(ref mut or) => visit_or(or, v),
+            &mut NotLit(ref mut not) => visit_not(not, v),
+            &mut DistinctLit(ref mut distinct) => visit_distinct(distinct, v),
+            &mut RelLit(ref mut rel) => visit_relation(rel, v),
+            &mut PropLit(ref mut prop) => visit_proposition(prop, v),
+            &mut KnowsLit(ref mut knows) => visit_knows(knows, v)
+        }
+    }));
     visitor.visit_literal(literal);
+    Flow::Continue
 }
 
 /// Performs a post-order traversal of a GDL term
-pub fn visit_term<V: Visitor>(term: &mut Term, visitor: &mut V) {
-    match term {
-        &mut ConstTerm(ref mut c) => visit_constant(c, visitor),
-        &mut FuncTerm(ref mut f) => visit_function(f, visitor),
-        &mut VarTerm(ref mut v) => visit_variable(v, visitor)
-    }
+pub fn visit_term<V: Visitor>(term: &mut Term, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_term(term));
+    child!(visit_step(visitor, NodeKind::Term, 0, |v| {
+        match term {
+            &mut ConstTerm(ref mut c) => visit_constant(c, v),
+            &mut FuncTerm(ref mut f) => visit_function(f, v),
+            &mut VarTerm(ref mut var) => visit_variable(var, v)
+        }
+    }));
     visitor.visit_term(term);
+    Flow::Continue
 }
 
 /// Performs a post-order traversal of a GDL constant
-pub fn visit_constant<V: Visitor>(constant: &mut Constant, visitor: &mut V) {
+pub fn visit_constant<V: Visitor>(constant: &mut Constant, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_constant(constant));
     visitor.visit_constant(constant);
+    Flow::Continue
 }
 
 /// Performs a post-order traversal of a GDL or literal
-pub fn visit_or<V: Visitor>(or: &mut Or, visitor: &mut V) {
-    for l in or.lits.iter_mut() {
-        visit_literal(l, visitor);
+pub fn visit_or<V: Visitor>(or: &mut Or, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_or(or));
+    for (i, l) in or.lits.iter_mut().enumerate() {
+        child!(visit_step(visitor, NodeKind::Or, i, |v| visit_literal(l, v)));
     }
     visitor.visit_or(or);
+    Flow::Continue
 }
 
 /// Performs a post-order traversal of a GDL not literal
-pub fn visit_not<V: Visitor>(not: &mut Not, visitor: &mut V) {
-    visit_literal(&mut not.lit, visitor);
+pub fn visit_not<V: Visitor>(not: &mut Not, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_not(not));
+    child!(visit_step(visitor, NodeKind::Not, 0, |v| visit_literal(&mut not.lit, v)));
     visitor.visit_not(not);
+    Flow::Continue
 }
 
 /// Performs a post-order traversal of a GDL distinct literal
-pub fn visit_distinct<V: Visitor>(distinct: &mut Distinct, visitor: &mut V) {
-    visit_term(&mut distinct.term1, visitor);
-    visit_term(&mut distinct.term2, visitor);
+pub fn visit_distinct<V: Visitor>(distinct: &mut Distinct, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_distinct(distinct));
+    child!(visit_step(visitor, NodeKind::Distinct, 0, |v| visit_term(&mut distinct.term1, v)));
+    child!(visit_step(visitor, NodeKind::Distinct, 1, |v| visit_term(&mut distinct.term2, v)));
     visitor.visit_distinct(distinct);
+    Flow::Continue
 }
 
 /// Performs a post-order traversal of a GDL variable
-pub fn visit_variable<V: Visitor>(variable: &mut Variable, visitor: &mut V) {
-    visit_constant(&mut variable.name, visitor);
+pub fn visit_variable<V: Visitor>(variable: &mut Variable, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_variable(variable));
+    child!(visit_step(visitor, NodeKind::Variable, 0, |v| visit_constant(&mut variable.name, v)));
     visitor.visit_variable(variable);
+    Flow::Continue
 }
 
 /// Performs a post-order traversal of a GDL function
-pub fn visit_function<V: Visitor>(function: &mut Function, visitor: &mut V) {
-    visit_constant(&mut function.name, visitor);
-    for t in function.args.iter_mut() {
-        visit_term(t, visitor)
+pub fn visit_function<V: Visitor>(function: &mut Function, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_function(function));
+    child!(visit_step(visitor, NodeKind::Function, 0, |v| visit_constant(&mut function.name, v)));
+    for (i, t) in function.args.iter_mut().enumerate() {
+        child!(visit_step(visitor, NodeKind::Function, i + 1, |v| visit_term(t, v)));
+    }
+    visitor.visit_function(function);
+    Flow::Continue
+}
+
+/// Visits a top-level form that isn't a valid clause
+pub fn visit_unknown<V: Visitor>(unknown: &mut Unknown, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_unknown(unknown));
+    visitor.visit_unknown(unknown);
+    Flow::Continue
+}
+
+/// Performs a post-order traversal of a GDL-III knows literal
+pub fn visit_knows<V: Visitor>(knows: &mut Knows, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_knows(knows));
+    if let Some(ref mut role) = knows.role {
+        child!(visit_step(visitor, NodeKind::Knows, 0, |v| visit_term(role, v)));
+    }
+    child!(visit_step(visitor, NodeKind::Knows, 1, |v| visit_term(&mut knows.prop, v)));
+    visitor.visit_knows(knows);
+    Flow::Continue
+}
+
+// Visits a child of a node with `visit`, with the step to it on the visitor's path while it does
+fn visit_step<V, F>(visitor: &mut V, kind: NodeKind, index: usize, visit: F) -> Flow
+    where V: Visitor, F: FnOnce(&mut V) -> Flow
+{
+    if let Some(path) = visitor.path_mut() {
+        path.steps.push(Step { kind: kind, index: index });
+    }
+    let flow = visit(visitor);
+    if let Some(path) = visitor.path_mut() {
+        path.steps.pop();
+    }
+    flow
+}
+
+/// A visitor for the AST that only reads it, so it can walk a description it doesn't own. Each
+/// `enter` function will get called when that respective AST node is reached, before its children,
+/// and decides whether the walk goes on. Each `visit` function will get called after the node's
+/// children have been walked. For example
+///
+/// ```
+/// use gdl_parser::{parse, Relation};
+/// use gdl_parser::visitor::{self, Flow, Visit};
+///
+/// // Finds the first `does` relation
+/// struct FirstDoes(Option<String>);
+///
+/// impl Visit for FirstDoes {
+///     fn enter_relation(&mut self, relation: &Relation) -> Flow {
+///         if relation.name.name == "does" {
+///             self.0 = Some(relation.to_string());
+///             return Flow::Stop;
+///         }
+///         Flow::Continue
+///     }
+/// }
+///
+/// let desc = parse("(<= (next (cell ?x)) (does white (mark ?x))) (<= p (does black noop))");
+/// let mut first = FirstDoes(None);
+/// assert_eq!(visitor::walk(&desc, &mut first), Flow::Stop);
+/// assert_eq!(first.0.unwrap(), "(does white (mark ?x))");
+/// ```
+pub trait Visit {
+    /// Returns the path that the traversal keeps up to date with where the node being entered or
+    /// visited is, or `None`, the default, if the visitor doesn't need it
+    fn path_mut(&mut self) -> Option<&mut Path> {
+        None
+    }
+
+    fn enter_clause(&mut self, _: &Clause) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_rule(&mut self, _: &Rule) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_sentence(&mut self, _: &Sentence) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_proposition(&mut self, _: &Proposition) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_relation(&mut self, _: &Relation) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_literal(&mut self, _: &Literal) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_term(&mut self, _: &Term) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_constant(&mut self, _: &Constant) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_or(&mut self, _: &Or) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_not(&mut self, _: &Not) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_distinct(&mut self, _: &Distinct) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_variable(&mut self, _: &Variable) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_function(&mut self, _: &Function) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_unknown(&mut self, _: &Unknown) -> Flow {
+        Flow::Continue
+    }
+
+    fn enter_knows(&mut self, _: &Knows) -> Flow {
+        Flow::Continue
+    }
+
+    fn visit_clause(&mut self, _: &Clause) {}
+
+    fn visit_rule(&mut self, _: &Rule) {}
+
+    fn visit_sentence(&mut self, _: &Sentence) {}
+
+    fn visit_proposition(&mut self, _: &Proposition) {}
+
+    fn visit_relation(&mut self, _: &Relation) {}
+
+    fn visit_literal(&mut self, _: &Literal) {}
+
+    fn visit_term(&mut self, _: &Term) {}
+
+    fn visit_constant(&mut self, _: &Constant) {}
+
+    fn visit_or(&mut self, _: &Or) {}
+
+    fn visit_not(&mut self, _: &Not) {}
+
+    fn visit_distinct(&mut self, _: &Distinct) {}
+
+    fn visit_variable(&mut self, _: &Variable) {}
+
+    fn visit_function(&mut self, _: &Function) {}
+
+    fn visit_unknown(&mut self, _: &Unknown) {}
+
+    fn visit_knows(&mut self, _: &Knows) {}
+}
+
+/// Performs a post-order traversal of a GDL description without changing it. Returns
+/// `Flow::Stop` if the visitor stopped it, or `Flow::Continue` otherwise, and likewise for the
+/// other walk functions.
+pub fn walk<V: Visit>(desc: &Description, visitor: &mut V) -> Flow {
+    for (i, clause) in desc.clauses.iter().enumerate() {
+        child!(walk_step(visitor, NodeKind::Description, i, |v| walk_clause(clause, v)));
+    }
+    Flow::Continue
+}
+
+/// Performs a post-order traversal of a GDL clause without changing it
+pub fn walk_clause<V: Visit>(clause: &Clause, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_clause(clause));
+    child!(walk_step(visitor, NodeKind::Clause, 0, |v| {
+        match clause {
+            &RuleClause(ref r) => walk_rule(r, v),
+            &SentenceClause(ref s) => walk_sentence(s, v),
+            &UnknownClause(ref u) => walk_unknown(u, v)
+        }
+    }));
+    visitor.visit_clause(clause);
+    Flow::Continue
+}
+
+/// Performs a post-order traversal of a GDL rule without changing it
+pub fn walk_rule<V: Visit>(rule: &Rule, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_rule(rule));
+    child!(walk_step(visitor, NodeKind::Rule, 0, |v| walk_sentence(&rule.head, v)));
+    for (i, l) in rule.body.iter().enumerate() {
+        child!(walk_step(visitor, NodeKind::Rule, i + 1, |v| walk_literal(l, v)));
+    }
+    visitor.visit_rule(rule);
+    Flow::Continue
+}
+
+/// Performs a post-order traversal of a GDL sentence without changing it
+pub fn walk_sentence<V: Visit>(sentence: &Sentence, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_sentence(sentence));
+    child!(walk_step(visitor, NodeKind::Sentence, 0, |v| {
+        match sentence {
+            &PropSentence(ref p) => walk_proposition(p, v),
+            &RelSentence(ref r) => walk_relation(r, v)
+        }
+    }));
+    visitor.visit_sentence(sentence);
+    Flow::Continue
+}
+
+/// Performs a post-order traversal of a GDL proposition without changing it
+pub fn walk_proposition<V: Visit>(proposition: &Proposition, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_proposition(proposition));
+    child!(walk_step(visitor, NodeKind::Proposition, 0, |v| walk_constant(&proposition.name, v)));
+    visitor.visit_proposition(proposition);
+    Flow::Continue
+}
+
+/// Performs a post-order traversal of a GDL relation without changing it
+pub fn walk_relation<V: Visit>(relation: &Relation, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_relation(relation));
+    child!(walk_step(visitor, NodeKind::Relation, 0, |v| walk_constant(&relation.name, v)));
+    for (i, t) in relation.args.iter().enumerate() {
+        child!(walk_step(visitor, NodeKind::Relation, i + 1, |v| walk_term(t, v)));
+    }
+    visitor.visit_relation(relation);
+    Flow::Continue
+}
+
+/// Performs a post-order traversal of a GDL literal without changing it
+pub fn walk_literal<V: Visit>(literal: &Literal, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_literal(literal));
+    child!(walk_step(visitor, NodeKind::Literal, 0, |v| {
+        match literal {
+            &OrLit(ref or) => walk_or(or, v),
+            &NotLit(ref not) => walk_not(not, v),
+            &DistinctLit(ref distinct) => walk_distinct(distinct, v),
+            &RelLit(ref rel) => walk_relation(rel, v),
+            &PropLit(ref prop) => walk_proposition(prop, v),
+            &KnowsLit(ref knows) => walk_knows(knows, v)
+        }
+    }));
+    visitor.visit_literal(literal);
+    Flow::Continue
+}
+
+/// Performs a post-order traversal of a GDL term without changing it
+pub fn walk_term<V: Visit>(term: &Term, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_term(term));
+    child!(walk_step(visitor, NodeKind::Term, 0, |v| {
+        match term {
+            &ConstTerm(ref c) => walk_constant(c, v),
+            &FuncTerm(ref f) => walk_function(f, v),
+            &VarTerm(ref var) => walk_variable(var, v)
+        }
+    }));
+    visitor.visit_term(term);
+    Flow::Continue
+}
+
+/// Performs a post-order traversal of a GDL constant without changing it
+pub fn walk_constant<V: Visit>(constant: &Constant, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_constant(constant));
+    visitor.visit_constant(constant);
+    Flow::Continue
+}
+
+/// Performs a post-order traversal of a GDL or literal without changing it
+pub fn walk_or<V: Visit>(or: &Or, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_or(or));
+    for (i, l) in or.lits.iter().enumerate() {
+        child!(walk_step(visitor, NodeKind::Or, i, |v| walk_literal(l, v)));
+    }
+    visitor.visit_or(or);
+    Flow::Continue
+}
+
+/// Performs a post-order traversal of a GDL not literal without changing it
+pub fn walk_not<V: Visit>(not: &Not, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_not(not));
+    child!(walk_step(visitor, NodeKind::Not, 0, |v| walk_literal(&not.lit, v)));
+    visitor.visit_not(not);
+    Flow::Continue
+}
+
+/// Performs a post-order traversal of a GDL distinct literal without changing it
+pub fn walk_distinct<V: Visit>(distinct: &Distinct, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_distinct(distinct));
+    child!(walk_step(visitor, NodeKind::Distinct, 0, |v| walk_term(&distinct.term1, v)));
+    child!(walk_step(visitor, NodeKind::Distinct, 1, |v| walk_term(&distinct.term2, v)));
+    visitor.visit_distinct(distinct);
+    Flow::Continue
+}
+
+/// Performs a post-order traversal of a GDL variable without changing it
+pub fn walk_variable<V: Visit>(variable: &Variable, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_variable(variable));
+    child!(walk_step(visitor, NodeKind::Variable, 0, |v| walk_constant(&variable.name, v)));
+    visitor.visit_variable(variable);
+    Flow::Continue
+}
+
+/// Performs a post-order traversal of a GDL function without changing it
+pub fn walk_function<V: Visit>(function: &Function, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_function(function));
+    child!(walk_step(visitor, NodeKind::Function, 0, |v| walk_constant(&function.name, v)));
+    for (i, t) in function.args.iter().enumerate() {
+        child!(walk_step(visitor, NodeKind::Function, i + 1, |v| walk_term(t, v)));
+    }
+    visitor.visit_function(function);
+    Flow::Continue
+}
+
+/// Walks a top-level form that isn't a valid clause
+pub fn walk_unknown<V: Visit>(unknown: &Unknown, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_unknown(unknown));
+    visitor.visit_unknown(unknown);
+    Flow::Continue
+}
+
+/// Performs a post-order traversal of a GDL-III knows literal without changing it
+pub fn walk_knows<V: Visit>(knows: &Knows, visitor: &mut V) -> Flow {
+    enter!(visitor.enter_knows(knows));
+    if let Some(ref role) = knows.role {
+        child!(walk_step(visitor, NodeKind::Knows, 0, |v| walk_term(role, v)));
+    }
+    child!(walk_step(visitor, NodeKind::Knows, 1, |v| walk_term(&knows.prop, v)));
+    visitor.visit_knows(knows);
+    Flow::Continue
+}
+
+// Walks a child of a node with `walk`, with the step to it on the visitor's path while it does
+fn walk_step<V, F>(visitor: &mut V, kind: NodeKind, index: usize, walk: F) -> Flow
+    where V: Visit, F: FnOnce(&mut V) -> Flow
+{
+    if let Some(path) = visitor.path_mut() {
+        path.steps.push(Step { kind: kind, index: index });
+    }
+    let flow = walk(visitor);
+    if let Some(path) = visitor.path_mut() {
+        path.steps.pop();
+    }
+    flow
+}
+
+/// A transformer for the AST that takes each node and returns the node to replace it with, which
+/// can be a different kind of the same node, like an `or` literal in place of a relation. Each
+/// function will get called when that respective AST node is folded, after its children have been,
+/// and returns the node unchanged by default.
+pub trait Folder {
+    fn fold_clause(&mut self, clause: Clause) -> Clause {
+        clause
     }
-    visitor.visit_function(function)
+
+    fn fold_rule(&mut self, rule: Rule) -> Rule {
+        rule
+    }
+
+    fn fold_sentence(&mut self, sentence: Sentence) -> Sentence {
+        sentence
+    }
+
+    fn fold_proposition(&mut self, proposition: Proposition) -> Proposition {
+        proposition
+    }
+
+    fn fold_relation(&mut self, relation: Relation) -> Relation {
+        relation
+    }
+
+    fn fold_literal(&mut self, literal: Literal) -> Literal {
+        literal
+    }
+
+    fn fold_term(&mut self, term: Term) -> Term {
+        term
+    }
+
+    fn fold_constant(&mut self, constant: Constant) -> Constant {
+        constant
+    }
+
+    fn fold_or(&mut self, or: Or) -> Or {
+        or
+    }
+
+    fn fold_not(&mut self, not: Not) -> Not {
+        not
+    }
+
+    fn fold_distinct(&mut self, distinct: Distinct) -> Distinct {
+        distinct
+    }
+
+    fn fold_variable(&mut self, variable: Variable) -> Variable {
+        variable
+    }
+
+    fn fold_function(&mut self, function: Function) -> Function {
+        function
+    }
+
+    fn fold_unknown(&mut self, unknown: Unknown) -> Unknown {
+        unknown
+    }
+
+    fn fold_knows(&mut self, knows: Knows) -> Knows {
+        knows
+    }
+}
+
+/// Performs a post-order fold of a GDL description, returning the description made of the folded
+/// clauses. For example
+///
+/// ```
+/// use gdl_parser::{parse, Literal, Not};
+/// use gdl_parser::Literal::NotLit;
+/// use gdl_parser::visitor::{self, Folder};
+///
+/// // Removes double negations
+/// struct NotNot;
+///
+/// impl Folder for NotNot {
+///     fn fold_literal(&mut self, literal: Literal) -> Literal {
+///         match literal {
+///             NotLit(not) => match *not.lit {
+///                 NotLit(inner) => *inner.lit,
+///                 lit => NotLit(Not { lit: Box::new(lit), ..not })
+///             },
+///             l => l
+///         }
+///     }
+/// }
+///
+/// let desc = visitor::fold(parse("(<= p (not (not q)) (not r))"), &mut NotNot);
+/// assert_eq!(desc.to_string(), "(<= p q (not r))");
+/// ```
+pub fn fold<F: Folder>(desc: Description, folder: &mut F) -> Description {
+    let clauses = desc.clauses.into_iter().map(|c| fold_clause(c, folder)).collect();
+    Description::new(clauses)
+}
+
+/// Performs a post-order fold of a GDL clause
+pub fn fold_clause<F: Folder>(clause: Clause, folder: &mut F) -> Clause {
+    let clause = match clause {
+        RuleClause(r) => RuleClause(fold_rule(r, folder)),
+        SentenceClause(s) => SentenceClause(fold_sentence(s, folder)),
+        UnknownClause(u) => UnknownClause(fold_unknown(u, folder))
+    };
+    folder.fold_clause(clause)
+}
+
+/// Performs a post-order fold of a GDL rule
+pub fn fold_rule<F: Folder>(rule: Rule, folder: &mut F) -> Rule {
+    let head = fold_sentence(rule.head, folder);
+    let body = rule.body.into_iter().map(|l| fold_literal(l, folder)).collect();
+    folder.fold_rule(Rule { head: head, body: body, ..rule })
+}
+
+/// Performs a post-order fold of a GDL sentence
+pub fn fold_sentence<F: Folder>(sentence: Sentence, folder: &mut F) -> Sentence {
+    let sentence = match sentence {
+        PropSentence(p) => PropSentence(fold_proposition(p, folder)),
+        RelSentence(r) => RelSentence(fold_relation(r, folder))
+    };
+    folder.fold_sentence(sentence)
+}
+
+/// Performs a post-order fold of a GDL proposition
+pub fn fold_proposition<F: Folder>(proposition: Proposition, folder: &mut F) -> Proposition {
+    let name = fold_constant(proposition.name, folder);
+    folder.fold_proposition(Proposition { name: name, ..proposition })
+}
+
+/// Performs a post-order fold of a GDL relation
+pub fn fold_relation<F: Folder>(relation: Relation, folder: &mut F) -> Relation {
+    let name = fold_constant(relation.name, folder);
+    let args = relation.args.into_iter().map(|t| fold_term(t, folder)).collect();
+    folder.fold_relation(Relation { name: name, args: args, ..relation })
+}
+
+/// Performs a post-order fold of a GDL literal
+pub fn fold_literal<F: Folder>(literal: Literal, folder: &mut F) -> Literal {
+    let literal = match literal {
+        OrLit(or) => OrLit(fold_or(or, folder)),
+        NotLit(not) => NotLit(fold_not(not, folder)),
+        DistinctLit(distinct) => DistinctLit(fold_distinct(distinct, folder)),
+        RelLit(rel) => RelLit(fold_relation(rel, folder)),
+        PropLit(prop) => PropLit(fold_proposition(prop, folder)),
+        KnowsLit(knows) => KnowsLit(fold_knows(knows, folder))
+    };
+    folder.fold_literal(literal)
+}
+
+/// Performs a post-order fold of a GDL term
+pub fn fold_term<F: Folder>(term: Term, folder: &mut F) -> Term {
+    let term = match term {
+        ConstTerm(c) => ConstTerm(fold_constant(c, folder)),
+        FuncTerm(f) => FuncTerm(fold_function(f, folder)),
+        VarTerm(v) => VarTerm(fold_variable(v, folder))
+    };
+    folder.fold_term(term)
+}
+
+/// Performs a post-order fold of a GDL constant
+pub fn fold_constant<F: Folder>(constant: Constant, folder: &mut F) -> Constant {
+    folder.fold_constant(constant)
+}
+
+/// Performs a post-order fold of a GDL or literal
+pub fn fold_or<F: Folder>(or: Or, folder: &mut F) -> Or {
+    let lits = or.lits.into_iter().map(|l| fold_literal(l, folder)).collect();
+    folder.fold_or(Or { lits: lits, ..or })
+}
+
+/// Performs a post-order fold of a GDL not literal
+pub fn fold_not<F: Folder>(not: Not, folder: &mut F) -> Not {
+    let lit = Box::new(fold_literal(*not.lit, folder));
+    folder.fold_not(Not { lit: lit, ..not })
+}
+
+/// Performs a post-order fold of a GDL distinct literal
+pub fn fold_distinct<F: Folder>(distinct: Distinct, folder: &mut F) -> Distinct {
+    let term1 = fold_term(distinct.term1, folder);
+    let term2 = fold_term(distinct.term2, folder);
+    folder.fold_distinct(Distinct { term1: term1, term2: term2, ..distinct })
+}
+
+/// Performs a post-order fold of a GDL variable
+pub fn fold_variable<F: Folder>(variable: Variable, folder: &mut F) -> Variable {
+    let name = fold_constant(variable.name, folder);
+    folder.fold_variable(Variable { name: name, ..variable })
+}
+
+/// Performs a post-order fold of a GDL function
+pub fn fold_function<F: Folder>(function: Function, folder: &mut F) -> Function {
+    let name = fold_constant(function.name, folder);
+    let args = function.args.into_iter().map(|t| fold_term(t, folder)).collect();
+    folder.fold_function(Function { name: name, args: args, ..function })
+}
+
+/// Folds a top-level form that isn't a valid clause
+pub fn fold_unknown<F: Folder>(unknown: Unknown, folder: &mut F) -> Unknown {
+    folder.fold_unknown(unknown)
+}
+
+/// Performs a post-order fold of a GDL-III knows literal
+pub fn fold_knows<F: Folder>(knows: Knows, folder: &mut F) -> Knows {
+    let role = knows.role.map(|r| fold_term(r, folder));
+    let prop = fold_term(knows.prop, folder);
+    folder.fold_knows(Knows { role: role, prop: prop, ..knows })
 }
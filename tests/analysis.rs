@@ -0,0 +1,346 @@
+extern crate gdl_parser;
+
+use gdl_parser::parse;
+use gdl_parser::analysis::{dead_clauses, duplicate_clauses, inconsistent_arities,
+                           reachable_relations, static_relations, stratify, trivial_literals,
+                           unbounded_rules, undefined_relations, unused_rules, Dependency,
+                           DependencyGraph, Domain, DomainModel, Reference, Symbol, SymbolKind,
+                           SymbolTable};
+
+use std::fs::File;
+use std::io::Read;
+
+#[test]
+fn test_stratify() {
+    let gdl = "(role white) (succ 1 2)\n\
+               (<= (legal ?r noop) (role ?r) (not (true (control ?r))))\n\
+               (<= (reach ?x ?y) (succ ?x ?y))\n\
+               (<= (reach ?x ?z) (succ ?x ?y) (reach ?y ?z))\n\
+               (<= (unreachable ?x ?y) (succ ?x ?a) (succ ?y ?b) (not (reach ?x ?y)))\n\
+               (<= terminal (not (legal white noop)) (or (unreachable 1 2) (not open)))";
+    let strata = stratify(&parse(gdl)).unwrap();
+    assert_eq!(strata.strata().to_vec(),
+               vec![vec!["role", "succ", "true", "reach", "open"],
+                    vec!["legal", "unreachable"],
+                    vec!["terminal"]]);
+    assert_eq!(strata.stratum("reach"), Some(0));
+    assert_eq!(strata.stratum("terminal"), Some(2));
+    assert_eq!(strata.stratum("goal"), None);
+
+    // Double negation is positive
+    let strata = stratify(&parse("(<= p (not (not p)))")).unwrap();
+    assert_eq!(strata.strata().to_vec(), vec![vec!["p"]]);
+    assert!(stratify(&parse("")).unwrap().strata().is_empty());
+}
+
+#[test]
+fn test_negation_cycle() {
+    let gdl = "(<= p (q ?x) (r ?x)) (<= (q ?x) (s ?x)) (<= (s ?x) (t ?x) (not p))";
+    let cycle = stratify(&parse(gdl)).unwrap_err();
+    assert_eq!(cycle.relations, vec!["s", "p", "q"]);
+    assert_eq!((cycle.span.lo, cycle.span.hi), (63, 64));
+    assert_eq!(cycle.to_string(), "cycle through negation at byte 63: s -> not p -> q -> s");
+
+    let cycle = stratify(&parse("(<= p q) (<= p (not p))")).unwrap_err();
+    assert_eq!(cycle.relations, vec!["p"]);
+    assert_eq!(cycle.to_string(), "cycle through negation at byte 20: p -> not p");
+
+    // The first negative edge in source order is reported
+    let cycle = stratify(&parse("(<= a (not b)) (<= b (not a))")).unwrap_err();
+    assert_eq!(cycle.relations, vec!["a", "b"]);
+}
+
+#[test]
+fn test_undefined_relations() {
+    let gdl = "(role white) (init (cell 1 1 b)) (succ 1 2)\n\
+               (<= (legal ?r (mark ?x)) (role ?r) (true (cell ?x ?x b)) (not (true (cel 1 1 b))))\n\
+               (<= (next (cell 1 1 x)) (does white (mrak 1)) (sucs 1 ?y) (not (succ 1 2 3)))\n\
+               (<= terminal (or opne (does ?r noop)) (true control))";
+    let found = undefined_relations(&parse(gdl)).into_iter().map(|d| {
+        assert_eq!(d.code, "undefined-relation");
+        (d.span.snippet(gdl).to_string(), d.message, d.fix.map(|f| f.apply(gdl)).is_some())
+    }).collect::<Vec<_>>();
+    let expected = [("(cel 1 1 b)", "fluent `cel/3` is never defined by `init`, `next`, or \
+                                      `base`; did you mean `cell`?", true),
+                    ("(mrak 1)", "move `mrak/1` is never defined by `legal` or `input`; did you \
+                                  mean `mark`?", true),
+                    ("(sucs 1 ?y)", "relation `sucs/2` is never defined by any rule or fact; did \
+                                     you mean `succ`?", true),
+                    ("(succ 1 2 3)", "relation `succ/3` is never defined by any rule or fact",
+                     false),
+                    ("opne", "relation `opne/0` is never defined by any rule or fact", false),
+                    ("noop", "move `noop/0` is never defined by `legal` or `input`", false),
+                    ("control", "fluent `control/0` is never defined by `init`, `next`, or \
+                                 `base`", false)];
+    assert_eq!(found, expected.iter().map(|&(s, m, f)| (s.to_string(), m.to_string(), f))
+                              .collect::<Vec<_>>());
+
+    let diagnostics = undefined_relations(&parse(gdl));
+    let fixed = diagnostics[0].fix.as_ref().unwrap().apply(gdl);
+    assert!(fixed.contains("(not (true (cell 1 1 b)))"));
+
+    // A variable fluent could be anything
+    let gdl = "(<= (next ?x) (true ?x)) (<= p (true (anything 1)))";
+    assert_eq!(undefined_relations(&parse(gdl)), vec![]);
+}
+
+#[test]
+fn test_inconsistent_arities() {
+    let gdl = "(init (cell 1 1 b)) (init control)\n\
+               (<= (next (cell ?x ?y)) (true (cell ?x ?y b)) (true (control white)))\n\
+               (<= open (true (cell 1 1 b)) (not (open 1)) (or (succ 1) (succ 1 2)))";
+    let found = inconsistent_arities(&parse(gdl)).into_iter().map(|d| {
+        assert_eq!(d.code, "inconsistent-arity");
+        (d.span.snippet(gdl).to_string(), d.message)
+    }).collect::<Vec<_>>();
+    let expected = [("(cell 1 1 b)", "function `cell` has 3 arguments here but 2 elsewhere"),
+                    ("control", "function `control` has 0 arguments here but 1 elsewhere"),
+                    ("(cell ?x ?y)", "function `cell` has 2 arguments here but 3 elsewhere"),
+                    ("(cell ?x ?y b)", "function `cell` has 3 arguments here but 2 elsewhere"),
+                    ("(control white)", "function `control` has 1 arguments here but 0 elsewhere"),
+                    ("open", "relation `open` has 0 arguments here but 1 elsewhere"),
+                    ("(cell 1 1 b)", "function `cell` has 3 arguments here but 2 elsewhere"),
+                    ("(open 1)", "relation `open` has 1 arguments here but 0 elsewhere"),
+                    ("(succ 1)", "relation `succ` has 1 arguments here but 2 elsewhere"),
+                    ("(succ 1 2)", "relation `succ` has 2 arguments here but 1 elsewhere")];
+    assert_eq!(found, expected.iter().map(|&(s, m)| (s.to_string(), m.to_string()))
+                              .collect::<Vec<_>>());
+
+    let found = inconsistent_arities(&parse("(p 1) (p 1 2) (<= q (p 1 2 3))"));
+    assert_eq!(found[0].message, "relation `p` has 1 arguments here but 2 or 3 elsewhere");
+    assert_eq!(inconsistent_arities(&parse("(p 1) (q (p 1 2)) (r 1)")), vec![]);
+}
+
+#[test]
+fn test_unused_rules() {
+    let gdl = "(role white) (init (cell 1)) (succ 1 2) (helper 1)\n\
+               (<= (next (cell ?y)) (true (cell ?x)) (adjacent ?x ?y))\n\
+               (<= (adjacent ?x ?y) (succ ?x ?y))\n\
+               (<= (legal white noop) (not (blocked white)))\n\
+               (<= (old ?x) (helper ?x) (succ ?x ?y))\n\
+               (<= (older ?x) (old ?x))";
+    assert_eq!(reachable_relations(&parse(gdl)),
+               vec!["role", "init", "succ", "next", "true", "adjacent", "legal", "blocked"]);
+    let found = unused_rules(&parse(gdl)).into_iter().map(|d| {
+        assert_eq!(d.code, "unused-rule");
+        d.span.snippet(gdl).to_string()
+    }).collect::<Vec<_>>();
+    assert_eq!(found, vec!["(helper 1)", "(<= (old ?x) (helper ?x) (succ ?x ?y))",
+                           "(<= (older ?x) (old ?x))"]);
+    assert_eq!(unused_rules(&parse("(helper 1)"))[0].message,
+               "`helper` is never used by the game's rules, so this can't affect play");
+}
+
+#[test]
+fn test_static_relations() {
+    let gdl = "(role white) (succ 1 2)\n\
+               (<= (lt ?x ?y) (succ ?x ?y)) (<= (lt ?x ?z) (lt ?x ?y) (lt ?y ?z))\n\
+               (<= (next (step ?y)) (true (step ?x)) (succ ?x ?y))\n\
+               (<= (legal white (mark ?x)) (open ?x) (lt ?x 2))\n\
+               (<= (open ?x) (not (true (c ?x))))\n\
+               (<= (moved ?x) (does white ?x)) (<= (after ?x) (moved ?x))";
+    assert_eq!(static_relations(&parse(gdl)), vec!["role", "succ", "lt"]);
+}
+
+#[test]
+fn test_dependency_graph() {
+    let gdl = "(role white) (succ 1 2)\n\
+               (<= (reach ?x ?y) (succ ?x ?y))\n\
+               (<= (reach ?x ?z) (succ ?x ?y) (reach ?y ?z))\n\
+               (<= (legal ?r noop) (role ?r) (not (true (control ?r))) (true (control ?r)))\n\
+               (<= terminal (or (not (reach 1 2)) (true done)))";
+    let graph = DependencyGraph::new(&parse(gdl));
+    assert_eq!(graph.relations(), ["role", "succ", "reach", "legal", "true", "terminal"]);
+    let edges = |deps: Vec<&Dependency>| {
+        deps.into_iter().map(|d| (d.from.clone(), d.to.clone(), d.negative)).collect::<Vec<_>>()
+    };
+    let edge = |from: &str, to: &str, negative| (from.to_string(), to.to_string(), negative);
+    assert_eq!(edges(graph.dependencies().iter().collect()),
+               vec![edge("reach", "succ", false), edge("reach", "reach", false),
+                    edge("legal", "role", false), edge("legal", "true", true),
+                    edge("legal", "true", false), edge("terminal", "reach", true),
+                    edge("terminal", "true", false)]);
+    assert_eq!(edges(graph.successors("legal")),
+               vec![edge("legal", "role", false), edge("legal", "true", true),
+                    edge("legal", "true", false)]);
+    assert_eq!(edges(graph.predecessors("reach")),
+               vec![edge("reach", "reach", false), edge("terminal", "reach", true)]);
+    assert_eq!(graph.successors("missing"), Vec::<&Dependency>::new());
+
+    // The span is the first literal that makes the dependency
+    let span = graph.successors("legal")[1].span;
+    assert_eq!(&gdl[span.lo..span.hi], "(true (control ?r))");
+    assert!(gdl[..span.lo].ends_with("(not "));
+
+    assert_eq!(graph.topological_order(),
+               ["role", "succ", "reach", "true", "legal", "terminal"]);
+    assert!(graph.mutually_recursive("reach", "reach"));
+    assert!(!graph.mutually_recursive("reach", "succ"));
+    assert!(!graph.mutually_recursive("missing", "missing"));
+
+    let graph = DependencyGraph::new(&parse("(<= a b) (<= b c) (<= c a) (<= d a) (<= c e)"));
+    assert_eq!(graph.topological_order(), ["e", "a", "b", "c", "d"]);
+    assert!(graph.mutually_recursive("a", "c"));
+    assert!(!graph.mutually_recursive("a", "d"));
+}
+
+#[test]
+fn test_components() {
+    let gdl = "(<= a b) (<= b c) (<= c a) (<= d a) (<= c e) (<= (f ?x) (g ?x) (f ?x))";
+    let graph = DependencyGraph::new(&parse(gdl));
+    assert_eq!(graph.components().to_vec(),
+               vec![vec!["e"], vec!["a", "b", "c"], vec!["d"], vec!["g"], vec!["f"]]);
+    assert_eq!((graph.component("b"), graph.component("d"), graph.component("x")),
+               (Some(1), Some(2), None));
+    assert_eq!(graph.recursive_groups(), [&["a", "b", "c"][..], &["f"][..]]);
+    assert!(graph.is_recursive("b"));
+    assert!(graph.is_recursive("f"));
+    assert!(!graph.is_recursive("d"));
+    assert!(!graph.is_recursive("x"));
+
+    // Long chains don't overflow the stack
+    let gdl = (0..100000).map(|i| format!("(<= r{} r{})", i, i + 1)).collect::<String>();
+    let graph = DependencyGraph::new(&parse(&gdl));
+    assert_eq!(graph.components().len(), 100001);
+    assert_eq!(graph.components()[0], ["r100000"]);
+}
+
+#[test]
+fn test_domain_model() {
+    let gdl = "(role x) (role o) (succ 1 2) (succ 2 3)\n\
+               (init (cell 1 1 b)) (init (cell 2 1 b)) (init (control x))\n\
+               (<= (next (cell ?m ?n ?p)) (does ?p (mark ?m ?n)) (true (cell ?m ?n b)))\n\
+               (<= (next (control o)) (true (control x)))\n\
+               (<= (legal ?p (mark ?m ?n)) (true (cell ?m ?n b)) (true (control ?p)))\n\
+               (<= (legal ?p noop) (role ?p) (not (blocked ?p ?q)))\n\
+               (<= (higher ?y) (succ ?x ?y) (higher ?x)) (higher 1)";
+    let domains = DomainModel::new(&parse(gdl));
+    let constants = |d: Option<&Domain>| {
+        d.unwrap().constants.iter().map(|c| c.name.clone()).collect::<Vec<_>>()
+    };
+    let functions = |d: Option<&Domain>| {
+        d.unwrap().functions.iter().map(|&(ref f, n)| format!("{}/{}", f, n)).collect::<Vec<_>>()
+    };
+    assert_eq!(constants(domains.domain_of("role", 1, 0)), ["o", "x"]);
+    assert_eq!(functions(domains.domain_of("init", 1, 0)), ["cell/3", "control/1"]);
+    assert_eq!(functions(domains.domain_of("true", 1, 0)), ["cell/3", "control/1"]);
+    assert_eq!(constants(domains.function_domain("cell", 3, 0)), ["1", "2"]);
+    assert_eq!(constants(domains.function_domain("cell", 3, 2)), ["b", "o", "x"]);
+    assert_eq!(constants(domains.function_domain("control", 1, 0)), ["o", "x"]);
+    assert_eq!(constants(domains.domain_of("legal", 2, 0)), ["o", "x"]);
+    assert_eq!(constants(domains.domain_of("does", 2, 1)), ["noop"]);
+    assert_eq!(functions(domains.domain_of("does", 2, 1)), ["mark/2"]);
+    assert_eq!(constants(domains.function_domain("mark", 2, 1)), ["1"]);
+
+    // Recursive rules reach a fixed point
+    assert_eq!(constants(domains.domain_of("higher", 1, 0)), ["1", "2", "3"]);
+
+    // Negated literals don't bind variables, but their positions still exist
+    assert_eq!(domains.domain_of("blocked", 2, 1), Some(&Domain::default()));
+    assert_eq!(domains.domain_of("role", 2, 0), None);
+    assert_eq!(domains.domain_of("role", 1, 1), None);
+    assert_eq!(domains.function_domain("role", 1, 0), None);
+}
+
+#[test]
+fn test_unbounded_rules() {
+    let gdl = "(num 0) (<= (num (s ?x)) (num ?x))\n\
+               (init (step 0)) (<= (next (step (s ?x))) (true (step ?x)))\n\
+               (<= (next (cell ?x)) (true (cell ?x)))\n\
+               (succ 0 1) (<= (count (s ?x)) (count ?x) (succ ?x ?y))\n\
+               (<= (wrapped (box ?x)) (num ?x))";
+    let unbounded = unbounded_rules(&parse(gdl));
+    let found = unbounded.iter().map(|u| {
+        (&u.relation[..], &u.function.0[..], &u.variable[..], u.span.lo, u.span.hi)
+    }).collect::<Vec<_>>();
+    assert_eq!(found, [("num", "s", "x", 17, 23), ("next", "s", "x", 67, 73)]);
+    assert_eq!(unbounded[0].to_string(),
+               "the rule for `num` at byte 8 can nest `s` without limit, since `?x` is only bound \
+                by literals that depend on the rule");
+
+    assert!(unbounded_rules(&parse("(succ 0 1) (<= (num (s ?y)) (succ ?x ?y))")).is_empty());
+
+    // Moving a variable into a fluent doesn't nest it any deeper from one step to the next
+    let gdl = "(<= (next (cell ?x ?y ?role)) (does ?role (move ?u ?v ?x ?y)))";
+    assert!(unbounded_rules(&parse(gdl)).is_empty());
+    let mut gdl = String::new();
+    let f = File::open("tests/test-alquerque.gdl");
+    f.unwrap().read_to_string(&mut gdl).ok().expect("Unable to read GDL file");
+    assert!(unbounded_rules(&parse(&gdl)).is_empty());
+}
+
+#[test]
+fn test_duplicate_clauses() {
+    let gdl = "(succ 1 2) (<= (p ?x) (q ?x ?y))\n\
+               (succ 1 2) (<= (p ?a) (q ?a ?b)) (<= (p ?x) (q ?y ?x))";
+    let desc = parse(gdl);
+    let duplicates = duplicate_clauses(&desc);
+    let found = duplicates.iter().map(|d| (d.code, d.span.lo, d.span.hi)).collect::<Vec<_>>();
+    assert_eq!(found, [("duplicate-clause", 33, 43), ("duplicate-clause", 44, 65)]);
+    assert_eq!(duplicates[0].message, "this clause is the same as the one at byte 0");
+    assert_eq!(duplicates[1].message,
+               "this clause is the same as the one at byte 11 except for its variable names");
+    let fix = duplicates[0].fix.as_ref().unwrap();
+    assert_eq!((fix.span.lo, fix.span.hi, &fix.text[..]), (33, 43, ""));
+
+    // Constants are case-sensitive, so clauses that differ only in case aren't duplicates
+    assert!(duplicate_clauses(&parse("(cell 1 1 B) (cell 1 1 b) (<= (P ?x) (q ?x))")).is_empty());
+}
+
+#[test]
+fn test_trivial_literals() {
+    let gdl = "(<= (p ?x) (q ?x) (distinct a b) (not (distinct ?x ?x)))\n\
+               (<= (r ?x) (q ?x) (distinct (f ?x) (f ?x)))\n\
+               (<= (s ?x) (q ?x) (distinct ?x a) (not (distinct a b)))";
+    let trivial = trivial_literals(&parse(gdl));
+    let found = trivial.iter().map(|d| {
+        let fix = d.fix.as_ref().unwrap();
+        (d.span.lo, d.span.hi, fix.span.lo, fix.span.hi)
+    }).collect::<Vec<_>>();
+    assert_eq!(found, [(18, 32, 17, 32), (33, 55, 32, 55), (75, 99, 57, 100),
+                       (135, 155, 101, 156)]);
+    let messages = trivial.iter().map(|d| &d.message[..]).collect::<Vec<_>>();
+    assert_eq!(messages, ["`(distinct a b)` is always true, so it can be removed",
+                          "`(not (distinct ?x ?x))` is always true, so it can be removed",
+                          "`(distinct (f ?x) (f ?x))` is always false, so this rule never applies",
+                          "`(not (distinct a b))` is always false, so this rule never applies"]);
+}
+
+#[test]
+fn test_symbol_table() {
+    let gdl = "(role white) (init (cell 1))\n\
+               (<= (next (cell ?x)) (true (cell ?x)) (succ ?x ?y))\n\
+               (succ 1 2) (<= terminal (true (cell 2)))";
+    let desc = parse(gdl);
+    let table = SymbolTable::new(&desc);
+    let cell = Symbol::new("cell", 1, SymbolKind::Function);
+    let spans = |refs: Vec<&Reference>| {
+        refs.into_iter().map(|r| (r.span.lo, r.span.hi, r.clause)).collect::<Vec<_>>()
+    };
+    assert_eq!(spans(table.definitions(&cell)), [(20, 24, 1), (40, 44, 2)]);
+    assert_eq!(spans(table.usages(&cell)), [(57, 61, 2), (112, 116, 4)]);
+    assert_eq!(table.defining_clauses(&Symbol::new("succ", 2, SymbolKind::Relation)), [3]);
+    assert_eq!(table.references(&Symbol::new("cell", 2, SymbolKind::Function)), []);
+
+    let symbols = table.symbols().iter().map(|s| (&s.name[..], s.arity)).take(6)
+                       .collect::<Vec<_>>();
+    assert_eq!(symbols, [("role", 1), ("white", 0), ("init", 1), ("cell", 1), ("1", 0),
+                         ("next", 1)]);
+
+    let (symbol, reference) = table.symbol_at(57).unwrap();
+    assert_eq!((symbol, reference.definition), (&cell, false));
+    assert_eq!(table.symbol_at(53).unwrap().0, &Symbol::new("true", 1, SymbolKind::Relation));
+    assert!(table.symbol_at(5).is_none());
+}
+
+#[test]
+fn test_dead_clauses() {
+    let gdl = "(role white) (init (cell 1))\n\
+               (<= (legal white noop) (true (cell 1)) (distinct a b))\n\
+               (<= (legal white wait) (ready white))\n\
+               (<= (goal white 100) (does white noop) (or (missing) (not (distinct a b))))\n\
+               (<= terminal (does white noop) (distinct a a))\n\
+               (<= (ready ?r) (missing ?r))";
+    assert_eq!(dead_clauses(&parse(gdl)), [3, 4, 5, 6]);
+    assert_eq!(dead_clauses(&parse("(<= p q) (<= q (not r)) (<= r (or s q))")), Vec::<usize>::new());
+}
@@ -0,0 +1,82 @@
+extern crate gdl_parser;
+
+use std::fs::File;
+use std::io::Read;
+
+use gdl_parser::{from_binary, parse, to_binary, BinaryError, Clause, Constant, Description, Knows,
+                 Proposition, Rule, Sentence, BINARY_VERSION};
+
+fn read_alquerque() -> String {
+    let mut gdl = String::new();
+    File::open("tests/test-alquerque.gdl").unwrap().read_to_string(&mut gdl).unwrap();
+    gdl
+}
+
+#[test]
+fn test_binary_round_trip() {
+    let gdl = read_alquerque();
+    let desc = parse(&gdl);
+    let bytes = to_binary(&desc);
+    assert!(bytes.starts_with(b"GDLB"));
+    assert_eq!(bytes[4], BINARY_VERSION);
+    assert_eq!(from_binary(&bytes).unwrap(), desc);
+
+    assert_eq!(from_binary(&to_binary(&Description::new(vec![]))).unwrap(),
+               Description::new(vec![]));
+
+    let desc = gdl_parser::parse_lenient("(role |white player|)\n(foo)\n(<= p (or q (not r)))").0;
+    assert_eq!(from_binary(&to_binary(&desc)).unwrap(), desc);
+
+    let desc = parse("(name \"a b\" |a b| a \"a\")");
+    let decoded = from_binary(&to_binary(&desc)).unwrap();
+    assert_eq!(decoded, desc);
+    assert_eq!(decoded.to_string(), "(name \"a b\" |a b| a \"a\")");
+
+    // Knows literals can be built and encoded without the `gdl-iii` feature
+    let knows = Knows::new(None, Constant::new("secret").into());
+    let rule = Rule::new(Proposition::new("p").into(), vec![knows.into()]);
+    let desc = Description::new(vec![rule.into()]);
+    assert_eq!(desc.to_string(), "(<= p (knows secret))");
+    assert_eq!(from_binary(&to_binary(&desc)).unwrap(), desc);
+}
+
+#[test]
+fn test_binary_spans() {
+    let desc = from_binary(&to_binary(&parse("(role white) (init (cell 1 1 b))"))).unwrap();
+    match &desc.clauses[1] {
+        &Clause::SentenceClause(Sentence::RelSentence(ref r)) => {
+            assert_eq!((r.span.lo, r.span.hi), (13, 32));
+            assert_eq!((r.name.span.lo, r.name.span.hi), (14, 18));
+            let span = r.args[0].span();
+            assert_eq!((span.lo, span.hi), (19, 31));
+        }
+        c => panic!("Unexpected clause {:?}", c)
+    }
+}
+
+#[test]
+fn test_binary_errors() {
+    let bytes = to_binary(&parse("(role white) (<= (legal ?r noop) (role ?r))"));
+    assert_eq!(from_binary(b"(role white)"), Err(BinaryError::BadMagic));
+    assert_eq!(from_binary(b"GDLB\xff"), Err(BinaryError::UnsupportedVersion(0xff)));
+    for i in 0..bytes.len() {
+        assert!(from_binary(&bytes[..i]).is_err(), "Truncated at {} decoded", i);
+    }
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    match from_binary(&trailing) {
+        Err(BinaryError::Invalid(_)) => {}
+        r => panic!("Unexpected result {:?}", r)
+    }
+
+    // A clause tag that doesn't exist
+    let mut bad_tag = to_binary(&parse("p"));
+    let pos = bad_tag.len() - 7;
+    assert_eq!(bad_tag[pos], 1);
+    bad_tag[pos] = 9;
+    match from_binary(&bad_tag) {
+        Err(BinaryError::Invalid(_)) => {}
+        r => panic!("Unexpected result {:?}", r)
+    }
+}
@@ -0,0 +1,82 @@
+extern crate gdl_parser;
+
+use gdl_parser::{parse, parse_checked, parse_iter, parse_lenient, parse_reader, parse_term,
+                 parse_with_comments, parse_with_trivia, reparse, Comment, ParseError,
+                 ParserOptions, Span, TextEdit};
+
+#[test]
+fn test_parse_with_comments() {
+    let gdl = ";; Roles\n(role red) ; first\n(role black)\n\n; Rules\n(<= (legal ?r noop) ; inner\n    (role ?r))\n; end\n";
+    let (desc, comments) = parse_with_comments(gdl).unwrap();
+    assert_eq!(desc, parse(gdl));
+
+    let text = |c: &Vec<Comment>| c.iter().map(|c| c.text.clone()).collect::<Vec<_>>();
+    assert_eq!(text(&comments.clauses[0].leading), vec![";; Roles"]);
+    assert_eq!(text(&comments.clauses[0].trailing), vec!["; first"]);
+    assert!(comments.clauses[1].leading.is_empty());
+    assert_eq!(text(&comments.clauses[2].leading), vec!["; Rules"]);
+    assert_eq!(text(&comments.clauses[2].inner), vec!["; inner"]);
+    assert_eq!(text(&comments.dangling), vec!["; end"]);
+}
+
+#[test]
+fn test_keep_comments() {
+    let gdl = "; Roles\n(ROLE red) ; first\n(role black)\n; end";
+    let options = ParserOptions { fold_case: true, keep_comments: true, ..ParserOptions::new() };
+    let (desc, comments) = parse_with_trivia(gdl, &options).unwrap();
+    assert_eq!(desc, parse("(role red) (role black)"));
+    assert_eq!(comments.clauses[0].leading, vec![Comment::new("; Roles", Span::new(0, 7))]);
+    assert_eq!(comments.clauses[0].trailing, vec![Comment::new("; first", Span::new(19, 26))]);
+    assert_eq!(comments.dangling, vec![Comment::new("; end", Span::new(40, 45))]);
+
+    let (_, comments) = parse_with_trivia(gdl, &ParserOptions::new()).unwrap();
+    assert_eq!(comments.clauses.len(), 2);
+    assert!(comments.clauses[0].leading.is_empty() && comments.dangling.is_empty());
+}
+
+#[test]
+fn test_block_comments() {
+    let gdl = "#| Tic-tac-toe\n   #| nested |# (role x) |#\n(role x) #| |x| ; |# (role o)";
+    let desc = parse(gdl);
+    assert_eq!(desc, parse("(role x) (role o)"));
+    let span = desc.clauses[0].span();
+    assert_eq!((span.lo, span.hi), (43, 51));
+
+    let (_, comments) = parse_with_comments(gdl).unwrap();
+    assert_eq!(comments.clauses[0].leading,
+               vec![Comment::new("#| Tic-tac-toe\n   #| nested |# (role x) |#", Span::new(0, 42))]);
+    assert_eq!(comments.clauses[0].trailing,
+               vec![Comment::new("#| |x| ; |#", Span::new(52, 63))]);
+    assert_eq!(parse_iter(gdl).count(), 2);
+
+    match parse_checked("(role x) #| #| |#") {
+        Err(ParseError::UnexpectedEof { expected, .. }) => assert_eq!(expected, vec!["|#"]),
+        r => panic!("Unexpected result {:?}", r)
+    }
+
+    // A comment that's never closed is an error instead of hiding the clauses after it
+    let gdl = "(role x) #| (role o)\n(role y)";
+    let results: Vec<_> = parse_iter(gdl).collect();
+    assert_eq!(results.len(), 2);
+    match results[1] {
+        Err(ParseError::UnexpectedEof { ref expected, .. }) => assert_eq!(*expected, vec!["|#"]),
+        ref r => panic!("Unexpected result {:?}", r)
+    }
+    assert!(parse_reader(gdl.as_bytes()).is_err());
+    let (desc, diagnostics) = parse_lenient(gdl);
+    assert_eq!(desc, parse("(role x)"));
+    assert_eq!(diagnostics.len(), 1);
+    assert!(parse_term("#| x").is_err());
+
+    let options = gdl_parser::ParserOptions { keep_unknown: true,
+                                              ..gdl_parser::ParserOptions::default() };
+    let desc = gdl_parser::parse_with(gdl, &options).unwrap();
+    assert_eq!(desc.clauses.len(), 2);
+    assert_eq!(desc.clauses[1].to_string(), "#| (role o)\n(role y)");
+
+    let src = "(role x)\n(role o)";
+    let mut desc = parse(src);
+    let edit = TextEdit::new(Span::new(9, 9), "#| ");
+    assert!(reparse(&mut desc, &edit, &edit.apply(src)).is_err());
+    assert_eq!(desc, parse(src));
+}
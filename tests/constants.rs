@@ -0,0 +1,60 @@
+extern crate gdl_parser;
+
+use gdl_parser::{parse, parse_checked, parse_sentence, parse_with_comments, Comment, Constant,
+                 Description, ParseError, Relation, Span};
+use gdl_parser::Sentence::RelSentence;
+
+#[test]
+fn test_quoted_atoms() {
+    let gdl = "(name \"foo bar\" |weird (atom)| \"a\\\"b;c\") ; comment";
+    let desc = parse(gdl);
+    let rel = Relation::new("name", vec![Constant::string("foo bar").into(),
+                                         Constant::new("weird (atom)").into(),
+                                         Constant::string("a\"b;c").into()]);
+    assert_eq!(desc, Description::new(vec![RelSentence(rel).into()]));
+
+    // Strings and quoted atoms are printed the way they were written
+    let printed = desc.to_string();
+    assert_eq!(printed, "(name \"foo bar\" |weird (atom)| \"a\\\"b;c\")");
+    assert_eq!(parse(&printed), desc);
+    assert_eq!(Constant::new("a|b\\c").to_string(), "|a\\|b\\\\c|");
+    assert_eq!(Constant::string("a|b\\c").to_string(), "\"a|b\\\\c\"");
+
+    // A string is a different constant from the atom with the same name
+    for gdl in ["(p \"a b\")", "(p |a b|)", "(p \"7\")", "(p \"x\")"].iter() {
+        assert_eq!(parse(gdl).to_string(), *gdl);
+    }
+    assert!(parse("(p \"a b\")") != parse("(p |a b|)"));
+    assert!(parse("(p \"x\")") != parse("(p x)"));
+    assert!(!Constant::string("7").is_int());
+
+    let span = desc.clauses[0].span();
+    assert_eq!((span.lo, span.hi), (0, 40));
+    let (_, comments) = parse_with_comments(gdl).unwrap();
+    assert_eq!(comments.clauses[0].trailing, vec![Comment::new("; comment", Span::new(41, 50))]);
+
+    match parse_checked("(role \"white)") {
+        Err(ParseError::UnexpectedEof { expected, .. }) => assert_eq!(expected, vec!["\""]),
+        r => panic!("Unexpected result {:?}", r)
+    }
+}
+
+#[test]
+fn test_numeric_constants() {
+    assert_eq!(Constant::new("100").as_int(), Some(100));
+    assert_eq!(Constant::new("007").as_int(), Some(7));
+    assert_eq!(Constant::new("x1").as_int(), None);
+    assert_eq!(Constant::new("99999999999999999999").as_int(), None);
+    assert!(!Constant::new("").is_int());
+
+    let mut constants: Vec<Constant> = ["b", "10", "a", "9", "07", "7"].iter()
+        .map(|&c| Constant::new(c)).collect();
+    constants.sort();
+    let names: Vec<&str> = constants.iter().map(|c| &*c.name).collect();
+    assert_eq!(names, vec!["07", "7", "9", "10", "a", "b"]);
+
+    let mut goals = vec![parse_sentence("(goal white 100)").unwrap(),
+                         parse_sentence("(goal white 50)").unwrap()];
+    goals.sort();
+    assert_eq!(goals[0].to_string(), "(goal white 50)");
+}
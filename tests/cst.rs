@@ -0,0 +1,56 @@
+extern crate gdl_parser;
+
+use gdl_parser::{parse, Description};
+use gdl_parser::cst::{Kind, Node, SyntaxTree};
+
+const GDL: &'static str = ";; Roles\n(role red)  (role black) ; two players\n\n\
+                           (<= (legal ?r noop)\n    (role ?r))\n";
+
+#[test]
+fn test_round_trip() {
+    let tree = SyntaxTree::parse(GDL);
+    assert_eq!(tree.to_string(), GDL);
+    assert_eq!(tree.to_description().unwrap(), parse(GDL));
+
+    match tree.children[0] {
+        Node::Leaf(ref t) => assert_eq!((t.kind, &*t.text), (Kind::Comment, ";; Roles")),
+        _ => panic!("Expected a comment")
+    }
+    assert_eq!(tree.children[2].span().snippet(GDL), "(role red)");
+
+    let broken = "(role red)) (<= (legal ?r";
+    assert_eq!(SyntaxTree::parse(broken).to_string(), broken);
+}
+
+#[test]
+fn test_display_deep_nesting() {
+    let deep = format!("{}a", "(".repeat(100000));
+    let mut tree = SyntaxTree::parse(&deep);
+    assert_eq!(tree.to_string(), deep);
+
+    // Dropping the tree recurses once per level, so it's taken apart one level at a time
+    let mut node = tree.children.pop().unwrap();
+    while let Node::List(mut children, _) = node {
+        node = children.pop().unwrap();
+    }
+}
+
+#[test]
+fn test_update() {
+    let tree = SyntaxTree::parse(GDL);
+    let mut desc = parse(GDL);
+
+    assert_eq!(tree.update(&desc).to_string(), GDL);
+
+    desc.clauses[1] = parse("(role blue)").clauses.pop().unwrap();
+    let updated = tree.update(&desc).to_string();
+    assert_eq!(updated, GDL.replace("(role black)", "(role blue)"));
+
+    desc.clauses.remove(0);
+    desc.clauses.push(parse("(init (step 1))").clauses.pop().unwrap());
+    let updated = tree.update(&desc).to_string();
+    assert_eq!(updated, ";; Roles\n(role blue) ; two players\n\n\
+                         (<= (legal ?r noop)\n    (role ?r))\n(init (step 1))\n");
+
+    assert_eq!(SyntaxTree::from_description(&Description::new(vec![])).to_string(), "");
+}
@@ -0,0 +1,52 @@
+extern crate gdl_parser;
+
+use gdl_parser::{parse_checked, parse_iter, parse_term, ParseError};
+
+use std::thread;
+
+#[test]
+fn test_deep_nesting() {
+    let depth = 100000;
+    let gdl = format!("(p {}x{})", "(f ".repeat(depth), ")".repeat(depth));
+    match parse_checked(&gdl) {
+        Err(ParseError::TooDeep { span, max_depth }) => {
+            assert_eq!(max_depth, gdl_parser::DEFAULT_MAX_DEPTH);
+            assert_eq!(span.lo, 3 * max_depth);
+        }
+        r => panic!("Unexpected result {:?}", r.map(|_| ()))
+    }
+    assert!(parse_iter(&gdl).next().unwrap().is_err());
+    assert!(parse_term(&gdl[3..]).is_err());
+
+    let hrf = format!("p({}x{})", "f(".repeat(depth), ")".repeat(depth));
+    assert!(gdl_parser::parse_hrf(&hrf).is_err());
+    let hrf = format!("p :- {}q", "~".repeat(depth));
+    assert!(gdl_parser::parse_hrf(&hrf).is_err());
+    let hrf = format!("p :- {}q{}", "(".repeat(depth), ")".repeat(depth));
+    assert!(gdl_parser::parse_hrf(&hrf).is_err());
+}
+
+// Input nested as deeply as the default limit allows can be parsed, printed, and encoded on a
+// thread with the default 2 MiB stack
+#[test]
+fn test_max_depth_stack() {
+    let thread = thread::Builder::new().stack_size(2 * 1024 * 1024).spawn(|| {
+        let depth = gdl_parser::DEFAULT_MAX_DEPTH - 1;
+        let gdls = [format!("(p {}x{})", "(f ".repeat(depth), ")".repeat(depth)),
+                    format!("(<= p {}q{})", "(not ".repeat(depth), ")".repeat(depth)),
+                    format!("(<= p {}q{})", "(or ".repeat(depth), ")".repeat(depth))];
+        for gdl in gdls.iter() {
+            let desc = parse_checked(gdl).unwrap();
+            assert_eq!(desc.to_string(), *gdl);
+            assert_eq!(gdl_parser::from_binary(&gdl_parser::to_binary(&desc)).unwrap(), desc);
+        }
+
+        let hrfs = [format!("p({}x{})", "f(".repeat(depth), ")".repeat(depth)),
+                    format!("p :- {}q", "~".repeat(depth)),
+                    format!("p :- {}q{}", "(".repeat(depth), ")".repeat(depth))];
+        for hrf in hrfs.iter() {
+            gdl_parser::parse_hrf(hrf).unwrap();
+        }
+    });
+    thread.unwrap().join().unwrap();
+}
@@ -0,0 +1,72 @@
+extern crate gdl_parser;
+extern crate serde_json;
+
+use gdl_parser::{diagnostics_to_json, parse, parse_checked, parse_lenient, parse_with_diagnostics,
+                 Severity, Span, TextEdit};
+
+use serde_json::Value;
+
+#[test]
+fn test_parse_lenient() {
+    let gdl = "(role red)\n(<= (legal ?r) (role ? r))\n(role black))\n(init (cell 1 1 b))";
+    let (desc, diagnostics) = parse_lenient(gdl);
+    assert_eq!(desc, parse("(role red) (role black) (init (cell 1 1 b))"));
+    assert_eq!(desc.clauses[2].span().snippet(gdl), "(init (cell 1 1 b))");
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].span.start_line_col(gdl), (2, 22));
+    assert_eq!(diagnostics[1].span.snippet(gdl), ")");
+}
+
+#[test]
+fn test_diagnostics() {
+    let gdl = "(<= (legal ?r noop) (role ?player))\n\
+               (<= (true ?x) (p ?x ?_y))\n\
+               (role ?)";
+    let (desc, diagnostics) = parse_with_diagnostics(gdl);
+    assert_eq!(desc.clauses.len(), 2);
+    let found: Vec<_> = diagnostics.iter().map(|d| (d.severity, d.code, d.span.snippet(gdl)))
+        .collect();
+    assert_eq!(found, [(Severity::Warning, "singleton-variable", "?r"),
+                       (Severity::Warning, "singleton-variable", "?player"),
+                       (Severity::Warning, "shadowed-keyword", "(true ?x)"),
+                       (Severity::Error, "invalid-variable", "?")]);
+    assert_eq!(diagnostics[0].to_string(),
+               "warning[singleton-variable]: variable `?r` only appears once");
+}
+
+#[test]
+fn test_fixes() {
+    let fixed = |gdl: &str| {
+        let (_, diagnostics) = parse_lenient(gdl);
+        diagnostics[0].fix.as_ref().map(|fix| fix.apply(gdl))
+    };
+    assert_eq!(fixed("(role a))\n(role b)").unwrap(), "(role a)\n(role b)");
+    assert_eq!(fixed("(role a)\n(<= (p ?x) (q (f ?x) ; done\n").unwrap(),
+               "(role a)\n(<= (p ?x) (q (f ?x))) ; done\n");
+    assert_eq!(fixed("(role ?)").unwrap(), "(role )");
+    assert_eq!(fixed("(role ?-x)"), None);
+
+    let err = parse_checked("(role (f a)").unwrap_err();
+    assert_eq!(err.fix("(role (f a)"), Some(TextEdit::new(Span::new(11, 11), ")")));
+}
+
+#[test]
+fn test_diagnostics_to_json() {
+    let gdl = "(role white)\n(role ?)";
+    let (_, diagnostics) = parse_lenient(gdl);
+    let json: Value = serde_json::from_str(&diagnostics_to_json(&diagnostics, gdl)).unwrap();
+    let d = &json.as_array().unwrap()[0];
+    assert_eq!(d.get("severity").and_then(|s| s.as_str()), Some("error"));
+    assert_eq!(d.get("code").and_then(|s| s.as_str()), Some("invalid-variable"));
+    assert_eq!(d.get("message").and_then(|s| s.as_str()),
+               Some(&diagnostics[0].message[..]));
+    let start = d.get("range").and_then(|r| r.get("start")).unwrap();
+    assert_eq!(start.get("offset").and_then(|n| n.as_u64()), Some(19));
+    assert_eq!(start.get("line").and_then(|n| n.as_u64()), Some(2));
+    assert_eq!(start.get("column").and_then(|n| n.as_u64()), Some(7));
+    let fix = d.get("fix").unwrap();
+    assert_eq!(fix.get("text").and_then(|s| s.as_str()), Some(""));
+
+    assert_eq!(serde_json::from_str::<Value>(&diagnostics[0].to_json(gdl)).unwrap(), *d);
+}
@@ -0,0 +1,131 @@
+extern crate gdl_parser;
+
+use std::fs::File;
+use std::io::Read;
+
+use gdl_parser::{parse, parse_with, Limit, ParseError, ParserOptions, Unknown};
+
+const GDL_II: &'static str = "(role random) (role player)\n\
+                              (<= (sees player (card ?c)) (does random (deal ?c)))\n\
+                              (<= (legal random (deal ?c)) (not (sees player (card ?c))))";
+
+#[test]
+fn test_is_gdl_ii() {
+    assert!(parse(GDL_II).is_gdl_ii());
+    assert!(parse("(<= (sees ?r ?p) (true (seen ?r ?p)))").is_gdl_ii());
+    assert!(!parse("(role white) (<= (legal ?r noop) (role ?r))").is_gdl_ii());
+    assert!(!parse("(random 1 2)").is_gdl_ii());
+}
+
+#[test]
+fn test_parse_gdl_ii() {
+    let options = ParserOptions { allow_gdl_ii: true, ..ParserOptions::default() };
+    assert_eq!(parse_with(GDL_II, &options).unwrap(), parse(GDL_II));
+
+    let gdl = "(role random)\n(<= terminal (not (sees random)))";
+    let e = parse_with(gdl, &options).unwrap_err();
+    match e {
+        ParseError::InvalidArity { ref name, ref expected, found, .. } => {
+            assert_eq!((&**name, &expected[..], found), ("sees", &[2][..], 1))
+        }
+        ref e => panic!("Unexpected error {:?}", e)
+    }
+    assert_eq!(&gdl[e.span().lo..e.span().hi], "(sees random)");
+
+    // Without the option `sees` is an ordinary relation
+    assert!(parse_with(gdl, &ParserOptions::new()).is_ok());
+}
+
+#[test]
+fn test_fold_case() {
+    let gdl = "(ROLE White)\n(<= (Legal ?R Noop) (NOT (True (Control ?r))))";
+    let options = ParserOptions { fold_case: true, ..ParserOptions::default() };
+    let desc = parse_with(gdl, &options).unwrap();
+    assert_eq!(desc, parse("(role white)\n(<= (legal ?r noop) (not (true (control ?r))))"));
+
+    let span = desc.clauses[1].span();
+    assert_eq!(&gdl[span.lo..span.hi], "(<= (Legal ?R Noop) (NOT (True (Control ?r))))");
+
+    // Case is preserved by default
+    assert_eq!(parse_with(gdl, &ParserOptions::new()).unwrap(), parse(gdl));
+}
+
+#[test]
+fn test_max_depth() {
+    let gdl = "(<= (next (cell ?x)) (true (cell ?x)))";
+    let options = ParserOptions { max_depth: Some(3), ..ParserOptions::default() };
+    assert_eq!(parse_with(gdl, &options).unwrap(), parse(gdl));
+
+    let options = ParserOptions { max_depth: Some(2), ..ParserOptions::default() };
+    match parse_with(gdl, &options) {
+        Err(ParseError::TooDeep { span, max_depth: 2 }) => assert_eq!((span.lo, span.hi), (10, 11)),
+        r => panic!("Unexpected result {:?}", r)
+    }
+}
+
+#[test]
+fn test_limits() {
+    let gdl = "(role white) (role black) (<= (legal ?player noop) (role ?player))";
+    assert_eq!(parse_with(gdl, &ParserOptions::untrusted()).unwrap(), parse(gdl));
+
+    let limit = |options: ParserOptions| match parse_with(gdl, &options) {
+        Err(ParseError::LimitExceeded { span, limit, max }) => (span.lo, span.hi, limit, max),
+        r => panic!("Unexpected result {:?}", r)
+    };
+    let options = ParserOptions { max_input_size: Some(10), ..ParserOptions::default() };
+    assert_eq!(limit(options), (0, gdl.len(), Limit::InputSize, 10));
+    let options = ParserOptions { max_clauses: Some(2), ..ParserOptions::default() };
+    assert_eq!(limit(options), (26, 27, Limit::Clauses, 2));
+    let options = ParserOptions { max_clauses: Some(2), keep_unknown: true,
+                                  ..ParserOptions::default() };
+    assert_eq!(limit(options), (26, 27, Limit::Clauses, 2));
+    let options = ParserOptions { max_atom_len: Some(6), ..ParserOptions::default() };
+    assert_eq!(limit(options), (37, 44, Limit::AtomLength, 6));
+}
+
+#[test]
+fn test_strict_arity() {
+    let options = ParserOptions { strict_arity: true, ..ParserOptions::default() };
+    let mut gdl = String::new();
+    let f = File::open("tests/test-alquerque.gdl");
+    f.unwrap().read_to_string(&mut gdl).ok().expect("Unable to read GDL file");
+    assert!(parse_with(&gdl, &options).is_ok());
+
+    let gdl = "(role white)\n(<= (legal white noop) (true (control white)) (not (does white)))";
+    let e = parse_with(gdl, &options).unwrap_err();
+    match e {
+        ParseError::InvalidArity { ref name, ref expected, found, .. } => {
+            assert_eq!((&**name, &expected[..], found), ("does", &[2][..], 1))
+        }
+        ref e => panic!("Unexpected error {:?}", e)
+    }
+    assert_eq!(&gdl[e.span().lo..e.span().hi], "(does white)");
+
+    match parse_with("role (terminal x)", &options) {
+        Err(ParseError::InvalidArity { ref name, found: 0, .. }) => assert_eq!(name, "role"),
+        r => panic!("Unexpected result {:?}", r)
+    }
+    assert!(parse_with("(role white) (terminal)", &options).is_ok());
+    assert!(parse_with("(goal white)", &ParserOptions::new()).is_ok());
+}
+
+#[test]
+fn test_keep_unknown() {
+    let gdl = "#stylesheet tictactoe.xsl\n(role x) (role o\n";
+    assert!(parse_with(gdl, &ParserOptions::new()).is_err());
+
+    let options = ParserOptions { keep_unknown: true, ..ParserOptions::default() };
+    let desc = parse_with(gdl, &options).unwrap();
+    assert_eq!(desc.clauses.len(), 3);
+    assert_eq!(desc.clauses[0], Unknown::new("#stylesheet tictactoe.xsl").into());
+    assert_eq!(desc.clauses[1], parse("(role x)").clauses[0]);
+    assert_eq!(desc.clauses[2], Unknown::new("(role o").into());
+    let span = desc.clauses[2].span();
+    assert_eq!((span.lo, span.hi), (35, 42));
+    assert_eq!(desc.to_string(), "#stylesheet tictactoe.xsl (role x) (role o");
+
+    let options = ParserOptions { fold_case: true, ..options };
+    let desc = parse_with("@Meta Data\n(ROLE x)", &options).unwrap();
+    assert_eq!(desc.clauses[0], Unknown::new("@Meta Data").into());
+    assert_eq!(desc.clauses[1].to_string(), "(role x)");
+}
@@ -0,0 +1,27 @@
+extern crate gdl_parser;
+
+use gdl_parser::{parse_bytes, ParseError};
+
+#[test]
+fn test_parse_bytes() {
+    let (desc, diagnostics) = parse_bytes(b"\xEF\xBB\xBF(role white)").unwrap();
+    assert_eq!(desc.to_string(), "(role white)");
+    assert!(diagnostics.is_empty());
+
+    let utf16: Vec<u8> = "(role black)".encode_utf16().flat_map(|u| vec![u as u8, (u >> 8) as u8])
+        .collect();
+    let (desc, diagnostics) = parse_bytes(&[&[0xFF, 0xFE][..], &utf16].concat()).unwrap();
+    assert_eq!(desc.to_string(), "(role black)");
+    assert!(diagnostics.is_empty());
+
+    // Latin-1 files from older repositories
+    let (desc, diagnostics) = parse_bytes(b"; caf\xE9\n(role white)").unwrap();
+    assert_eq!(desc.to_string(), "(role white)");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!((diagnostics[0].span.lo, diagnostics[0].span.hi), (5, 7));
+
+    match parse_bytes(b"; \xE9\n(role white) (") {
+        Err(ParseError::UnbalancedParen { span }) => assert_eq!((span.lo, span.hi), (18, 19)),
+        r => panic!("{:?}", r)
+    }
+}
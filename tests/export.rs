@@ -0,0 +1,147 @@
+extern crate gdl_parser;
+
+use gdl_parser::{parse, parse_with, ParserOptions};
+use gdl_parser::export::{dependency_graphml, domain_graphml, to_asp, to_souffle, to_tptp};
+
+#[test]
+fn test_to_asp() {
+    let gdl = "(role white) (init (cell 1 1 b)) terminal\n\
+               (<= (legal ?r noop) (role ?r) (not (true (control ?r))))\n\
+               (<= (line ?x) (or (row ?x) (col ?x)) (or (diag ?x) open))\n\
+               (<= p (not (or q (distinct ?x a))) (r ?x))\n\
+               (<= (next done) (not (not q)))";
+    assert_eq!(to_asp(&parse(gdl)),
+               "role(white).\n\
+                init(cell(1,1,b)).\n\
+                terminal.\n\
+                legal(R,noop) :- role(R), not true(control(R)).\n\
+                line(X) :- row(X), diag(X).\n\
+                line(X) :- row(X), open.\n\
+                line(X) :- col(X), diag(X).\n\
+                line(X) :- col(X), open.\n\
+                p :- not q, X = a, r(X).\n\
+                next(done) :- q.");
+
+    // Names that aren't ASP identifiers
+    let gdl = "(<= (|Cell Value| ?_x |Red Piece| 007) (Foo ?_x))";
+    assert_eq!(to_asp(&parse(gdl)),
+               "c_Cell_20_Value(V___x,\"Red Piece\",\"007\") :- c_Foo(V___x).");
+
+    // Names that differ only in case, or that look like escaped names, stay different
+    let gdl = "(<= (foo ?x ?X ?v_x) (Foo ?x) (c_Foo ?X) (p ?v_X ?V_x))";
+    assert_eq!(to_asp(&parse(gdl)),
+               "foo(X,V_X,V_v__x) :- c_Foo(X), c_c__Foo(V_X), p(V_v__X,V_V__x).");
+
+    // An empty disjunction is never true, so the rule is dropped
+    assert_eq!(to_asp(&parse("(<= p (or)) (<= q)")), "q.");
+
+    let options = ParserOptions { keep_unknown: true, ..ParserOptions::new() };
+    let desc = parse_with("(role white) (<= p (not))\n(role black)", &options).unwrap();
+    assert_eq!(to_asp(&desc), "role(white).\n% (<= p (not))\nrole(black).");
+}
+
+#[test]
+fn test_to_souffle() {
+    let gdl = "(role white) (init (cell 1 b)) (init open) terminal\n\
+               (<= (legal ?r (mark ?x)) (true (cell ?x b)) (role ?r) (not (true (control ?r))))\n\
+               (<= (adjacent ?x ?y) (succ ?x ?y)) (succ 1 2)\n\
+               (<= (row ?x) (true (cell ?x ?y)) (or (distinct ?y b) (distinct ?x 1)))";
+    assert_eq!(to_souffle(&parse(gdl)),
+               ".type Term = Sym {s: symbol} | cell {a0: symbol, a1: symbol} | mark {a0: symbol} | \
+                control {a0: symbol}\n\
+                \n\
+                .decl role(name:symbol)\n\
+                .decl init(prop:Term)\n\
+                .decl terminal()\n\
+                .decl legal(role:symbol, move:Term)\n\
+                .decl true_(prop:Term)\n\
+                .decl adjacent(a0:symbol, a1:symbol)\n\
+                .decl succ(a0:symbol, a1:symbol)\n\
+                .decl row(a0:symbol)\n\
+                \n\
+                role(\"white\").\n\
+                init($cell(\"1\", \"b\")).\n\
+                init($Sym(\"open\")).\n\
+                terminal().\n\
+                legal(R, $mark(X)) :- true_($cell(X, \"b\")), role(R), !true_($control(R)).\n\
+                adjacent(X, Y) :- succ(X, Y).\n\
+                succ(\"1\", \"2\").\n\
+                row(X) :- true_($cell(X, Y)), Y != \"b\".\n\
+                row(X) :- true_($cell(X, Y)), X != \"1\".");
+}
+
+#[test]
+fn test_to_tptp() {
+    let gdl = "(role white) (init (cell 1 b))\n\
+               (<= (legal ?r (mark ?x)) (true (cell ?x b)) (role ?r) (not (true (control ?r))))\n\
+               (<= (goal ?r 100) (role ?r) (or (line ?r) (not (distinct ?r white))))\n\
+               (<= p (or)) (<= (|Big Cell| ?x) (q ?x |it's|))";
+    assert_eq!(to_tptp(&parse(gdl)),
+               "fof(clause_1, axiom, role(white)).\n\
+                fof(clause_2, axiom, init(cell('1',b))).\n\
+                fof(clause_3, axiom, ![R,X]: ((true(cell(X,b)) & role(R) & ~true(control(R))) => \
+                legal(R,mark(X)))).\n\
+                fof(clause_4, axiom, ![R]: ((role(R) & (line(R) | ~(R != white))) => \
+                goal(R,'100'))).\n\
+                fof(clause_5, axiom, ($false => p)).\n\
+                fof(clause_6, axiom, ![X]: (q(X,'it\\'s') => 'Big Cell'(X))).");
+
+    let options = ParserOptions { keep_unknown: true, ..ParserOptions::new() };
+    let desc = parse_with("(role white)\n(<= p (not))", &options).unwrap();
+    assert_eq!(to_tptp(&desc), "fof(clause_1, axiom, role(white)).\n% (<= p (not))");
+}
+
+#[test]
+fn test_dependency_graphml() {
+    let gdl = "(role white) (<= (legal ?r noop) (role ?r) (not (true (control ?r))))\n\
+               (<= terminal (or (true done) (not (line |a&b|))) (distinct 1 2))";
+    assert_eq!(dependency_graphml(&parse(gdl)),
+               "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n  \
+                <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n  \
+                <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n  \
+                <key id=\"negative\" for=\"edge\" attr.name=\"negative\" \
+                attr.type=\"boolean\"/>\n  \
+                <graph id=\"dependencies\" edgedefault=\"directed\">\n    \
+                <node id=\"n0\"><data key=\"label\">role</data>\
+                <data key=\"kind\">relation</data></node>\n    \
+                <node id=\"n1\"><data key=\"label\">legal</data>\
+                <data key=\"kind\">relation</data></node>\n    \
+                <node id=\"n2\"><data key=\"label\">true</data>\
+                <data key=\"kind\">relation</data></node>\n    \
+                <node id=\"n3\"><data key=\"label\">terminal</data>\
+                <data key=\"kind\">relation</data></node>\n    \
+                <node id=\"n4\"><data key=\"label\">line</data>\
+                <data key=\"kind\">relation</data></node>\n    \
+                <edge source=\"n1\" target=\"n0\"><data key=\"negative\">false</data></edge>\n    \
+                <edge source=\"n1\" target=\"n2\"><data key=\"negative\">true</data></edge>\n    \
+                <edge source=\"n3\" target=\"n2\"><data key=\"negative\">false</data></edge>\n    \
+                <edge source=\"n3\" target=\"n4\"><data key=\"negative\">true</data></edge>\n  \
+                </graph>\n\
+                </graphml>\n");
+}
+
+#[test]
+fn test_domain_graphml() {
+    let gdl = "(init (cell 1 |a<b|)) (succ 1 2)\n\
+               (<= (next (cell ?y ?m)) (true (cell ?x ?m)) (succ ?x ?y) (not (blocked ?m)))";
+    let graphml = domain_graphml(&parse(gdl));
+    let nodes = graphml.lines().filter(|l| l.contains("<node")).collect::<Vec<_>>();
+    let labels = nodes.iter().map(|l| {
+        let start = l.find("\"label\">").unwrap() + 8;
+        &l[start..start + l[start..].find('<').unwrap()]
+    }).collect::<Vec<_>>();
+    assert_eq!(labels, vec!["init[0]", "cell/2", "cell[0]", "1", "cell[1]", "a&lt;b", "succ[0]",
+                            "succ[1]", "2", "true[0]", "next[0]"]);
+    assert!(nodes[1].contains("<data key=\"kind\">function</data>"));
+    assert!(nodes[3].contains("<data key=\"kind\">constant</data>"));
+
+    let edges = graphml.lines().filter(|l| l.contains("<edge")).map(|l| {
+        let ids = l.split('"').filter(|s| s.starts_with('n'))
+                   .filter_map(|s| s[1..].parse::<usize>().ok()).collect::<Vec<_>>();
+        (labels[ids[0]], labels[ids[1]])
+    }).collect::<Vec<_>>();
+    assert_eq!(edges, vec![("cell/2", "init[0]"), ("1", "cell[0]"), ("a&lt;b", "cell[1]"),
+                           ("1", "succ[0]"), ("2", "succ[1]"), ("cell/2", "next[0]"),
+                           ("succ[1]", "cell[0]"), ("cell[1]", "cell[1]")]);
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "gdl-iii")]
+
+extern crate gdl_parser;
+
+use gdl_parser::{from_binary, parse, parse_checked, parse_literal, to_binary, validate, Constant,
+                 Description, Function, Knows, Not, ParseError, Relation, Rule, Term, Variable};
+use gdl_parser::Literal::KnowsLit;
+use gdl_parser::analysis::DependencyGraph;
+
+#[test]
+fn test_parse_knows() {
+    let gdl = "(<= (goal ?r 100) (knows ?r (secret ?x)) (not (knows (secret ?x))))";
+    let secret: Term = Function::new("secret", vec![var("x")]).into();
+    let head = Relation::new("goal", vec![var("r"), Constant::new("100").into()]);
+    let body = vec![Knows::new(Some(var("r")), secret.clone()).into(),
+                    Not::new(Box::new(Knows::new(None, secret).into())).into()];
+    let desc = parse(gdl);
+    assert_eq!(desc, Description::new(vec![Rule::new(head.into(), body).into()]));
+    assert_eq!(desc.to_string(), gdl);
+    assert_eq!(from_binary(&to_binary(&desc)).unwrap(), desc);
+
+    match parse_literal("  (knows white (cell 1 1 x))").unwrap() {
+        KnowsLit(k) => assert_eq!((k.span.lo, k.span.hi), (2, 28)),
+        l => panic!("Expected a knows literal, found {}", l)
+    }
+
+    // Rule heads are left alone since only literals can be knows literals
+    assert_eq!(parse("(knows a b)").clauses[0].to_string(), "(knows a b)");
+}
+
+#[test]
+fn test_knows_arity() {
+    let e = parse_checked("(<= p (knows a b c))").unwrap_err();
+    match e {
+        ParseError::InvalidArity { span, ref expected, found: 3, .. } => {
+            assert_eq!((span.lo, span.hi, &expected[..]), (6, 19, &[1, 2][..]))
+        }
+        ref e => panic!("Unexpected error {:?}", e)
+    }
+    assert_eq!(e.to_string(), "`knows` takes 1 or 2 arguments but has 3 at byte 6");
+    match parse_literal("knows") {
+        Err(ParseError::InvalidArity { found: 0, .. }) => {}
+        r => panic!("Unexpected result {:?}", r)
+    }
+}
+
+fn var(name: &str) -> Term {
+    Variable::new(name).into()
+}
+
+#[test]
+fn test_knows_dependencies() {
+    let gdl = "(<= (goal ?r 100) (role ?r) (knows ?r (secret ?x)) (not (knows (secret ?x))))";
+    let graph = DependencyGraph::new(&parse(gdl));
+    assert_eq!(graph.relations(), ["goal", "role", "knows"]);
+    let edges = graph.successors("goal").into_iter().map(|d| (&d.to[..], d.negative))
+                     .collect::<Vec<_>>();
+    assert_eq!(edges, [("role", false), ("knows", false), ("knows", true)]);
+}
+
+#[test]
+fn test_knows_reserved() {
+    let report = validate(&parse("(<= (knows white p) p)"));
+    let errors = report.errors().iter().map(|d| (d.code, d.span.lo, d.span.hi))
+                       .collect::<Vec<_>>();
+    assert_eq!(errors, [("keyword-redefinition", 4, 19)]);
+}
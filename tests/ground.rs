@@ -0,0 +1,102 @@
+extern crate gdl_parser;
+
+use gdl_parser::parse;
+use gdl_parser::ground::{add_base_and_input, ground, ground_checked, ground_with, GroundError,
+                         GroundOptions};
+
+use std::fs::File;
+use std::io::Read;
+
+#[test]
+fn test_ground() {
+    let gdl = "(role white) (index 1) (index 2) (init (marked 1))\n\
+               (<= (legal white (mark ?i)) (index ?i) (not (true (marked ?i))))\n\
+               (<= (next (marked ?i)) (does white (mark ?i)))\n\
+               (<= (next (marked ?i)) (true (marked ?i)))\n\
+               (<= (either ?i) (or (index ?i) (true (marked ?i))))\n\
+               (<= (pair ?i ?j) (index ?i) (index ?j) (not (distinct ?i ?j)))";
+    let grounded = ground(&parse(gdl));
+    let clauses = grounded.clauses.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+    assert_eq!(clauses, ["(role white)", "(index 1)", "(index 2)", "(init (marked 1))",
+                         "(<= (legal white (mark 1)) (index 1) (not (true (marked 1))))",
+                         "(<= (legal white (mark 2)) (index 2) (not (true (marked 2))))",
+                         "(<= (next (marked 1)) (does white (mark 1)))",
+                         "(<= (next (marked 2)) (does white (mark 2)))",
+                         "(<= (next (marked 1)) (true (marked 1)))",
+                         "(<= (next (marked 2)) (true (marked 2)))",
+                         "(<= (either 1) (or (index 1) (true (marked 1))))",
+                         "(<= (either 2) (or (index 2) (true (marked 2))))",
+                         "(<= (pair 1 1) (index 1) (index 1))",
+                         "(<= (pair 2 2) (index 2) (index 2))"]);
+    let span = grounded.clauses[4].span();
+    assert_eq!((span.lo, span.hi), (51, 115));
+
+    // Duplicate instances are removed
+    assert_eq!(ground(&parse("(p 1) (<= q (p ?x)) (<= q (p ?y))")).to_string(),
+               "(p 1) (<= q (p 1))");
+}
+
+#[test]
+fn test_ground_relevant_only() {
+    let desc = parse("(e 1 2) (e 2 1) (<= (f ?x ?y) (e ?x ?y)) (<= (g ?x) (f ?x ?x))");
+    assert_eq!(ground(&desc).clauses.len(), 8);
+    let options = GroundOptions { relevant_only: true, ..GroundOptions::new() };
+    assert_eq!(ground_with(&desc, &options).unwrap().to_string(),
+               "(e 1 2) (e 2 1) (<= (f 1 2) (e 1 2)) (<= (f 2 1) (e 2 1))");
+}
+
+#[test]
+fn test_ground_errors() {
+    match ground_checked(&parse("(q 1) (<= (p ?x) (not (q ?x)))")) {
+        Err(GroundError::UnsafeVariable { ref name, span, clause }) => {
+            assert_eq!((&name[..], span.lo, clause.lo), ("x", 13, 6))
+        }
+        r => panic!("Unexpected result {:?}", r)
+    }
+    match ground_checked(&parse("(num 0) (<= (num (s ?x)) (num ?x))")) {
+        Err(GroundError::Unbounded(ref u)) => assert_eq!(u.relation, "num"),
+        r => panic!("Unexpected result {:?}", r)
+    }
+    let e = ground_checked(&parse("(num 0) (small 0) (<= (num (s ?x)) (num ?x) (small ?x))"))
+                .unwrap_err();
+    assert_eq!(e, GroundError::InfiniteDomain { function: ("s".to_string(), 1) });
+    assert_eq!(e.to_string(), "the function `s/1` can be nested in itself without limit");
+
+    let options = GroundOptions { max_instances: Some(3), ..GroundOptions::new() };
+    let e = ground_with(&parse("(e 1 2) (e 2 1) (<= (f ?x ?y) (e ?x ?y))"), &options).unwrap_err();
+    assert_eq!(e.to_string(), "grounding makes more than 3 rule instances");
+
+    // A finite game isn't refused as unbounded
+    let mut gdl = String::new();
+    let f = File::open("tests/test-alquerque.gdl");
+    f.unwrap().read_to_string(&mut gdl).ok().expect("Unable to read GDL file");
+    let options = GroundOptions { max_instances: Some(1000), ..GroundOptions::new() };
+    let e = ground_with(&parse(&gdl), &options).unwrap_err();
+    assert_eq!(e, GroundError::TooManyInstances { limit: 1000 });
+}
+
+#[test]
+fn test_add_base_and_input() {
+    let gdl = "(role x) (role o) (index 1) (index 2) (init (cell 1 b))\n\
+               (<= (next (cell ?i ?r)) (does ?r (mark ?i)))\n\
+               (<= (legal ?r (mark ?i)) (role ?r) (index ?i) (true (cell ?i b)))\n\
+               (<= (legal ?r noop) (role ?r))";
+    let desc = parse(gdl);
+    let added = add_base_and_input(&desc).unwrap();
+    let added = added.clauses[desc.clauses.len()..].iter().map(|c| c.to_string())
+                     .collect::<Vec<_>>();
+    assert_eq!(added, ["(base (cell 1 b))", "(base (cell 1 o))", "(base (cell 1 x))",
+                       "(base (cell 2 b))", "(base (cell 2 o))", "(base (cell 2 x))",
+                       "(input o noop)", "(input o (mark 1))", "(input o (mark 2))",
+                       "(input x noop)", "(input x (mark 1))", "(input x (mark 2))"]);
+
+    // Existing base and input clauses are kept as they are
+    let desc = parse("(role x) (init a) (base a) (<= (input ?r noop) (role ?r)) (legal x noop)");
+    assert_eq!(add_base_and_input(&desc).unwrap(), desc);
+    let desc = parse("(role x) (init a) (<= (input ?r noop) (role ?r)) (legal x noop)");
+    assert_eq!(add_base_and_input(&desc).unwrap().clauses.last().unwrap().to_string(), "(base a)");
+
+    let desc = parse("(init (n 0)) (<= (next (n (s ?x))) (true (n ?x)))");
+    assert_eq!(add_base_and_input(&desc).unwrap_err(),
+               GroundError::InfiniteDomain { function: ("s".to_string(), 1) });
+}
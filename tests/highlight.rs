@@ -0,0 +1,26 @@
+#![cfg(feature = "highlight")]
+
+extern crate gdl_parser;
+
+use gdl_parser::{parse, to_ansi, to_html};
+
+#[test]
+fn test_to_html() {
+    let desc = parse("(<= (legal ?r noop) (role ?r) (distinct ?r \"a<b\"))");
+    assert_eq!(to_html(&desc),
+               "(<span class=\"gdl-keyword\">&lt;=</span> \
+                (<span class=\"gdl-keyword\">legal</span> <span class=\"gdl-variable\">?r</span> \
+                <span class=\"gdl-constant\">noop</span>)\n    \
+                (<span class=\"gdl-keyword\">role</span> \
+                <span class=\"gdl-variable\">?r</span>)\n    \
+                (<span class=\"gdl-keyword\">distinct</span> \
+                <span class=\"gdl-variable\">?r</span> \
+                <span class=\"gdl-constant\">&quot;a&lt;b&quot;</span>))");
+}
+
+#[test]
+fn test_to_ansi() {
+    let desc = parse("(role white) terminal");
+    assert_eq!(to_ansi(&desc),
+               "(\x1b[1;35mrole\x1b[0m \x1b[32mwhite\x1b[0m)\n\x1b[1;35mterminal\x1b[0m");
+}
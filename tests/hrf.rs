@@ -0,0 +1,62 @@
+extern crate gdl_parser;
+
+use std::fs::File;
+use std::io::Read;
+
+use gdl_parser::{parse, parse_hrf, to_hrf, ParseError};
+
+#[test]
+fn test_parse_hrf() {
+    let hrf = "% Legal moves\nlegal(W,mark(X,Y)) :- true(cell(X,Y,b)) & true(control(W))\n\
+               role(xplayer)\nterminal :- ~open, (line(x) | line(o)), distinct(X,Y)";
+    let gdl = "(<= (legal ?w (mark ?x ?y)) (true (cell ?x ?y b)) (true (control ?w)))\n\
+               (role xplayer)\n\
+               (<= terminal (not open) (or (line x) (line o)) (distinct ?x ?y))";
+    let desc = parse_hrf(hrf).unwrap();
+    assert_eq!(desc, parse(gdl));
+
+    let span = desc.clauses[1].span();
+    assert_eq!(&hrf[span.lo..span.hi], "role(xplayer)");
+    assert_eq!(to_hrf(&desc), "legal(W,mark(X,Y)) :- true(cell(X,Y,b)) & true(control(W))\n\
+                               role(xplayer)\n\
+                               terminal :- ~open & (line(x) | line(o)) & distinct(X,Y)");
+}
+
+#[test]
+fn test_hrf_round_trip() {
+    let mut gdl = String::new();
+    let f = File::open("tests/test-alquerque.gdl");
+    f.unwrap().read_to_string(&mut gdl).ok().expect("Unable to read GDL file");
+
+    // Rules without a body are printed as facts, so compare the printed text rather than the ASTs
+    let hrf = to_hrf(&parse(&gdl));
+    assert_eq!(to_hrf(&parse_hrf(&hrf).unwrap()), hrf);
+
+    // Names that aren't identifiers or that start with an uppercase letter are quoted, and
+    // variables are only uppercased if that doesn't lose their case
+    let desc = parse("(p |a b|) (q |x,y| Foo foo ?x ?X ?_1)\n\
+                      (<= (r ?X (f |it's|)) (s ?X ?x) (not (t Bar)))");
+    let hrf = to_hrf(&desc);
+    assert_eq!(hrf, "p('a b')\n\
+                     q('x,y','Foo',foo,X,?X,?_1)\n\
+                     r(?X,f('it\\'s')) :- s(?X,X) & ~t('Bar')");
+    assert_eq!(parse_hrf(&hrf).unwrap(), desc);
+
+    // HRF has no string literals, so strings are printed as quoted constants
+    assert_eq!(to_hrf(&parse("(p \"a b\")")), "p('a b')");
+}
+
+#[test]
+fn test_hrf_errors() {
+    match parse_hrf("p(X) :- q(X") {
+        Err(ParseError::UnexpectedEof { expected, .. }) => assert_eq!(expected, vec![")"]),
+        r => panic!("Unexpected result {:?}", r)
+    }
+    match parse_hrf("P(x)") {
+        Err(e @ ParseError::UnexpectedToken { .. }) => {
+            assert_eq!((e.span().lo, e.span().hi), (0, 1));
+            assert_eq!(e.expected(), &["constant"]);
+        }
+        r => panic!("Unexpected result {:?}", r)
+    }
+}
@@ -0,0 +1,62 @@
+extern crate gdl_parser;
+
+use gdl_parser::{parse, parse_checked, reparse, Span, TextEdit};
+
+use std::cmp;
+
+#[test]
+fn test_reparse() {
+    let src = "(role red)\n(role black)\n; comment\n(init (cell 1 1 b))\n(<= (legal ?r noop) (role ?r))";
+    let mut desc = parse(src);
+
+    // Each edit replaces the first occurrence of a string
+    let edits = vec![("black", "white"), ("", "(role blue) "), ("; comment", "(init (step 1))"),
+                     ("(init (cell", "; (init (cell"), ("role red)", "roles red)"), ("noop", "")];
+    let mut src = src.to_string();
+    for (old, new) in edits {
+        let lo = src.find(old).unwrap();
+        let edit = TextEdit::new(Span::new(lo, lo + old.len()), new);
+        let new_src = edit.apply(&src);
+        reparse(&mut desc, &edit, &new_src).unwrap();
+        assert_eq!(desc, parse(&new_src));
+        for (c, expected) in desc.clauses.iter().zip(parse(&new_src).clauses.iter()) {
+            assert_eq!(c.span().snippet(&new_src), expected.span().snippet(&new_src));
+        }
+        src = new_src;
+    }
+
+    let edit = TextEdit::new(Span::new(0, 1), "");
+    let before = desc.clone();
+    assert!(reparse(&mut desc, &edit, &edit.apply(&src)).is_err());
+    assert_eq!(desc, before);
+}
+
+#[test]
+fn test_reparse_comments() {
+    // Every edit that adds or removes part of a block comment delimiter, checked against parsing
+    // the edited text from scratch
+    let srcs = ["(a) (b) #| x |# (c) (d)", "(a) #| (b) |# (c)\n; |#\n(d)", "(a) #| #| n |# |# (b)",
+                "(a) (b) \"|#\" x#| (c) |# |q|"];
+    for src in srcs.iter() {
+        let desc = parse_checked(src).unwrap();
+        for lo in 0..src.len() + 1 {
+            for hi in lo..cmp::min(lo + 2, src.len()) + 1 {
+                for text in ["", "#", "|", "#|", "|#", "#| (e) ", " |# (f)"].iter() {
+                    let edit = TextEdit::new(Span::new(lo, hi), *text);
+                    let new_src = edit.apply(src);
+                    let mut reparsed = desc.clone();
+                    match (reparse(&mut reparsed, &edit, &new_src), parse_checked(&new_src)) {
+                        (Ok(()), Ok(expected)) => {
+                            assert_eq!(reparsed, expected, "{:?}", new_src);
+                            for (c, e) in reparsed.clauses.iter().zip(expected.clauses.iter()) {
+                                assert_eq!(c.span(), e.span(), "{:?}", new_src);
+                            }
+                        }
+                        (Err(_), Err(_)) => {}
+                        (r, e) => panic!("{:?}: {:?} {:?}", new_src, r, e.map(|_| ()))
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+extern crate gdl_parser;
+
+use gdl_parser::{parse, parse_file, parse_iter, parse_reader, GdlError, ParseError};
+
+use std::{cmp, env};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+
+// A reader that returns at most `n` bytes per read, to exercise clauses split across reads
+struct SlowReader<'a>(&'a [u8], usize);
+
+impl<'a> Read for SlowReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = cmp::min(cmp::min(self.1, buf.len()), self.0.len());
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_parse_reader() {
+    let mut gdl = String::new();
+    let f = File::open("tests/test-alquerque.gdl");
+    f.unwrap().read_to_string(&mut gdl).ok().expect("Unable to read GDL file");
+
+    let desc = parse_reader(SlowReader(gdl.as_bytes(), 7)).unwrap();
+    assert_eq!(desc, parse(&gdl));
+    assert_eq!(desc.clauses[5].span().snippet(&gdl), parse(&gdl).clauses[5].span().snippet(&gdl));
+
+    let gdl = "(role red) ; réd\n(role black)\n(role";
+    match parse_reader(SlowReader(gdl.as_bytes(), 2)).unwrap_err() {
+        ParseError::UnbalancedParen { span } => assert_eq!(span.lo, 31),
+        e => panic!("Unexpected error {:?}", e)
+    }
+
+    match parse_reader(SlowReader(b"(role red) (role \xff)", 4)).unwrap_err() {
+        ParseError::Io { span, kind, .. } => {
+            assert_eq!(span.lo, 17);
+            assert_eq!(kind, io::ErrorKind::InvalidData);
+        }
+        e => panic!("Unexpected error {:?}", e)
+    }
+}
+
+#[test]
+fn test_parse_iter() {
+    let gdl = "(role red) (role black) (init (cell 1 1 b)) (<= (legal ?r noop) (role ?r)) (oops";
+    let roles: Vec<_> = parse_iter(gdl)
+        .map(|c| c.unwrap())
+        .take_while(|c| c.to_string().starts_with("(role "))
+        .collect();
+    assert_eq!(roles, parse("(role red) (role black)").clauses);
+
+    let results: Vec<_> = parse_iter(gdl).collect();
+    assert_eq!(results.len(), 5);
+    assert_eq!(results[3].as_ref().unwrap().span().snippet(gdl), "(<= (legal ?r noop) (role ?r))");
+    assert!(results[4].is_err());
+}
+
+#[test]
+fn test_parse_file() {
+    let mut gdl = String::new();
+    let f = File::open("tests/test-alquerque.gdl");
+    f.unwrap().read_to_string(&mut gdl).ok().expect("Unable to read GDL file");
+    assert_eq!(parse_file("tests/test-alquerque.gdl").unwrap(), parse(&gdl));
+
+    match parse_file("tests/missing.gdl") {
+        Err(e @ GdlError::Io { .. }) => {
+            assert!(e.to_string().starts_with("tests/missing.gdl: "));
+        }
+        r => panic!("Unexpected result {:?}", r)
+    }
+
+    let path = env::temp_dir().join("gdl-parser-test-parse-file.gdl");
+    File::create(&path).unwrap().write_all(b"(role x)\n  (role o))").unwrap();
+    let result = parse_file(&path);
+    fs::remove_file(&path).unwrap();
+    match result {
+        Err(e @ GdlError::Parse { line: 2, column: 11, .. }) => {
+            assert_eq!(e.path(), &path);
+            assert_eq!(e.to_string(),
+                       format!("{}:2:11: unbalanced parenthesis at byte 19", path.display()));
+        }
+        r => panic!("Unexpected result {:?}", r)
+    }
+}
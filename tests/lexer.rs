@@ -0,0 +1,46 @@
+extern crate gdl_parser;
+
+use gdl_parser::lexer::{Lexer, Token};
+use gdl_parser::lexer::Token::{LParen, RParen, Atom, Variable, Comment, Whitespace, Unknown};
+
+#[test]
+fn test_tokens() {
+    let gdl = "(<= (legal ?r noop) ; move\n  (role ?r)) ? -";
+    let tokens: Vec<Token> = Lexer::new(gdl).filter(|&(t, _)| !t.is_trivia()).map(|(t, _)| t)
+        .collect();
+    assert_eq!(tokens, vec![LParen, Atom("<="), LParen, Atom("legal"), Variable("r"), Atom("noop"),
+                            RParen, LParen, Atom("role"), Variable("r"), RParen, RParen,
+                            Unknown("?"), Unknown("-")]);
+
+    let comments: Vec<_> = Lexer::new(gdl).filter_map(|(t, span)| match t {
+        Comment(c) => Some((c, span.start_line_col(gdl))),
+        _ => None
+    }).collect();
+    assert_eq!(comments, vec![("; move", (1, 21))]);
+}
+
+#[test]
+fn test_lossless() {
+    let gdl = ";; header\r\n(role red)\t(role ?)\n";
+    let text: String = Lexer::new(gdl).map(|(_, span)| span.snippet(gdl)).collect();
+    assert_eq!(text, gdl);
+    assert_eq!(Lexer::new(gdl).next(), Some((Comment(";; header\r"), gdl_parser::Span::new(0, 10))));
+    assert!(Lexer::new(gdl).any(|(t, _)| t == Whitespace("\t")));
+}
+
+#[test]
+fn test_quoted() {
+    let gdl = "(name \"a \\\" (b\" |c;d|) \"e";
+    let tokens: Vec<Token> = Lexer::new(gdl).filter(|&(t, _)| !t.is_trivia()).map(|(t, _)| t)
+        .collect();
+    assert_eq!(tokens, vec![LParen, Atom("name"), Atom("\"a \\\" (b\""), Atom("|c;d|"), RParen,
+                            Unknown("\""), Atom("e")]);
+}
+
+#[test]
+fn test_block_comments() {
+    let gdl = "#| a #| b |# c |#(role)#| d";
+    let tokens: Vec<Token> = Lexer::new(gdl).map(|(t, _)| t).collect();
+    assert_eq!(tokens, vec![Comment("#| a #| b |# c |#"), LParen, Atom("role"), RParen,
+                            Unknown("#|"), Whitespace(" "), Atom("d")]);
+}
@@ -0,0 +1,130 @@
+extern crate gdl_parser;
+
+use gdl_parser::{lint, parse, Description, Diagnostic, Lint, LintLevel, LintRegistry, NestedMoves,
+                 Severity};
+
+// Flags facts with more than two arguments
+struct WideFacts;
+
+impl Lint for WideFacts {
+    fn code(&self) -> &'static str {
+        "wide-fact"
+    }
+
+    fn default_level(&self) -> LintLevel {
+        LintLevel::Allow
+    }
+
+    fn check(&self, desc: &Description) -> Vec<Diagnostic> {
+        desc.clauses.iter().filter(|c| c.to_string().split(' ').count() > 3).map(|c| {
+            Diagnostic::warning(self.code(), c.span(), "this fact is wide")
+        }).collect()
+    }
+}
+
+#[test]
+fn test_lint_registry() {
+    let gdl = "(init (cell 1 1 b)) (cell 1 2 3)\n\
+               (<= (legal ?player noop) (role ?p))\n\
+               (<= (true ?x) (true (cel 1 1 b)) (open ?x))";
+    let desc = parse(gdl);
+    let mut registry = LintRegistry::new();
+    assert_eq!(registry.codes(), ["singleton-variable", "shadowed-keyword", "undefined-relation",
+                                  "inconsistent-arity", "unused-rule", "duplicate-clause",
+                                  "trivial-literal", "nested-move"]);
+    let found = |registry: &LintRegistry| {
+        registry.run(&desc).into_iter().map(|d| (d.severity, d.code, d.span.lo))
+                .collect::<Vec<_>>()
+    };
+    assert_eq!(found(&registry),
+               [(Severity::Warning, "unused-rule", 20),
+                (Severity::Warning, "singleton-variable", 44),
+                (Severity::Warning, "undefined-relation", 58),
+                (Severity::Warning, "singleton-variable", 64),
+                (Severity::Warning, "unused-rule", 69),
+                (Severity::Warning, "shadowed-keyword", 73),
+                (Severity::Warning, "undefined-relation", 89),
+                (Severity::Warning, "undefined-relation", 102)]);
+
+    registry.set_level("undefined-relation", LintLevel::Allow);
+    registry.set_level("shadowed-keyword", LintLevel::Deny);
+    registry.set_level("unused-rule", LintLevel::Allow);
+    registry.register(Box::new(WideFacts));
+    assert_eq!(registry.level("wide-fact"), Some(LintLevel::Allow));
+    assert_eq!(registry.level("missing"), None);
+    assert_eq!(found(&registry),
+               [(Severity::Warning, "singleton-variable", 44),
+                (Severity::Warning, "singleton-variable", 64),
+                (Severity::Error, "shadowed-keyword", 73)]);
+
+    registry.set_level("wide-fact", LintLevel::Warn);
+    assert_eq!(found(&registry)[0], (Severity::Warning, "wide-fact", 0));
+
+    let mut registry = LintRegistry::empty();
+    registry.register(Box::new(WideFacts));
+    assert_eq!(registry.run(&desc), vec![]);
+}
+
+#[test]
+fn test_lint_builtin() {
+    // `lint` is the same as running the clause lints
+    let gdl = "(<= (legal ?player noop) (role ?p)) (<= (true ?x) (q ?x))";
+    let mut registry = LintRegistry::new();
+    for &code in ["undefined-relation", "inconsistent-arity", "unused-rule"].iter() {
+        registry.set_level(code, LintLevel::Allow);
+    }
+    assert_eq!(registry.run(&parse(gdl)), lint(&parse(gdl)));
+    assert_eq!(lint(&parse(gdl)).len(), 3);
+}
+
+#[test]
+fn test_lint_level() {
+    assert_eq!("deny".parse::<LintLevel>(), Ok(LintLevel::Deny));
+    assert_eq!(LintLevel::Warn.to_string(), "warn");
+    assert_eq!("forbid".parse::<LintLevel>(),
+               Err("unknown lint level `forbid`, expected `allow`, `warn`, or `deny`".to_string()));
+}
+
+#[test]
+fn test_nested_moves() {
+    let gdl = "(<= (legal white (move (cell ?x 1) (cell ?x 2))) (true (cell ?x 1)))\n\
+               (<= (legal white (mark ?x)) (index ?x)) (<= p (does white noop))\n\
+               (<= q (not (does black (jump (to (cell 1 1))))))";
+    let desc = parse(gdl);
+    let nested = NestedMoves::default().check(&desc);
+    let found = nested.iter().map(|d| (d.span.lo, d.span.hi)).collect::<Vec<_>>();
+    assert_eq!(found, [(17, 47), (157, 179)]);
+    assert_eq!(nested[0].message,
+               "the move `(move (cell ?x 1) (cell ?x 2))` is nested 2 functions deep, but some \
+                players only handle moves nested 1 deep");
+
+    assert_eq!(NestedMoves::new(2).check(&desc).len(), 1);
+    assert_eq!(NestedMoves::new(0).check(&desc).len(), 3);
+
+    let mut registry = LintRegistry::new();
+    registry.register(Box::new(NestedMoves::new(3)));
+    assert!(registry.run(&desc).iter().all(|d| d.code != "nested-move"));
+}
+
+#[test]
+fn test_lint_similar_variables() {
+    let gdl = "(<= (legal ?player (move ?piece)) (role ?player) (owns ?player ?peice))";
+    let diagnostics = lint(&parse(gdl));
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].message, "variable `?piece` only appears once");
+    // Neither is used twice, so neither is suggested
+    assert_eq!(diagnostics[1].message, "variable `?peice` only appears once");
+
+    let gdl = "(<= (next (cell ?x ?piece)) (true (cell ?x ?piece)) (not (moved ?X ?peice)))";
+    let diagnostics = lint(&parse(gdl));
+    assert_eq!(diagnostics.iter().map(|d| &d.message[..]).collect::<Vec<_>>(),
+               ["variable `?X` only appears once; did you mean `?x`?",
+                "variable `?peice` only appears once; did you mean `?piece`?"]);
+    assert_eq!(diagnostics[1].fix.as_ref().unwrap().apply(gdl),
+               "(<= (next (cell ?x ?piece)) (true (cell ?x ?piece)) (not (moved ?X ?piece)))");
+
+    // Short names that differ aren't suggested
+    let diagnostics = lint(&parse("(<= (p ?x ?x) (q ?y))"));
+    assert_eq!(diagnostics[0].message, "variable `?y` only appears once");
+    assert_eq!(diagnostics[0].fix, None);
+}
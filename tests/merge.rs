@@ -0,0 +1,40 @@
+extern crate gdl_parser;
+
+use gdl_parser::{parse, MergeError, MergePolicy};
+
+#[test]
+fn test_merge() {
+    let game = parse("(role white) (<= (next (step ?y)) (true (step ?x)) (succ ?x ?y))");
+    let library = parse("(succ 1 2) (<= (lt ?x ?y) (succ ?x ?y)) (<= (legal ?r noop) (role ?r))");
+    let merged = game.merge(&library, &MergePolicy::Strict).unwrap();
+    assert_eq!(merged.to_string(),
+               "(role white) (<= (next (step ?y)) (true (step ?x)) (succ ?x ?y)) (succ 1 2) \
+                (<= (lt ?x ?y) (succ ?x ?y)) (<= (legal ?r noop) (role ?r))");
+    let span = merged.clauses[2].span();
+    assert_eq!((span.lo, span.hi), (0, 10));
+
+    let prefix = MergePolicy::PrefixOther("lib_".to_string());
+    assert_eq!(game.merge(&library, &prefix).unwrap().to_string(),
+               "(role white) (<= (next (step ?y)) (true (step ?x)) (succ ?x ?y)) (lib_succ 1 2) \
+                (<= (lib_lt ?x ?y) (lib_succ ?x ?y)) (<= (legal ?r noop) (role ?r))");
+}
+
+#[test]
+fn test_merge_clash() {
+    let game = parse("(cell 1 1) (<= p (cell ?x ?y))");
+    let library = parse("(<= q (cell ?x)) (cell 1)");
+    let e = game.merge(&library, &MergePolicy::Strict).unwrap_err();
+    assert_eq!(e, MergeError { name: "cell".to_string(), arities: (2, 1), span: e.span });
+    assert_eq!((e.span.lo, e.span.hi), (7, 11));
+    assert_eq!(e.to_string(),
+               "relation `cell` has 2 arguments in the first description, but 1 at byte 7 of the \
+                second");
+
+    let merged = game.merge(&library, &MergePolicy::PrefixOther("lib_".to_string())).unwrap();
+    assert_eq!(merged.clauses.len(), 4);
+
+    // Prefixed names can still clash
+    let game = parse("(lib_cell 1 1)");
+    let e = game.merge(&library, &MergePolicy::PrefixOther("lib_".to_string())).unwrap_err();
+    assert_eq!((&e.name[..], e.arities), ("lib_cell", (2, 1)));
+}
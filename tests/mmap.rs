@@ -0,0 +1,28 @@
+#![cfg(feature = "mmap")]
+
+extern crate gdl_parser;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+
+use gdl_parser::{parse, parse_mmap, GdlError};
+
+#[test]
+fn test_parse_mmap() {
+    let mut gdl = String::new();
+    let f = File::open("tests/test-alquerque.gdl");
+    f.unwrap().read_to_string(&mut gdl).ok().expect("Unable to read GDL file");
+    assert_eq!(parse_mmap("tests/test-alquerque.gdl").unwrap(), parse(&gdl));
+
+    let path = env::temp_dir().join("gdl-parser-test-parse-mmap.gdl");
+    File::create(&path).unwrap();
+    assert!(parse_mmap(&path).unwrap().clauses.is_empty());
+    File::create(&path).unwrap().write_all(b"(role \xff)").unwrap();
+    let result = parse_mmap(&path);
+    fs::remove_file(&path).unwrap();
+    match result {
+        Err(GdlError::Io { error, .. }) => assert_eq!(error.kind(), io::ErrorKind::InvalidData),
+        r => panic!("Unexpected result {:?}", r)
+    }
+}
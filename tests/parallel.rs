@@ -0,0 +1,28 @@
+#![cfg(feature = "parallel")]
+
+extern crate gdl_parser;
+
+use std::fs::File;
+use std::io::Read;
+
+use gdl_parser::{parse, parse_parallel, ParseError};
+
+#[test]
+fn test_parse_parallel() {
+    let mut gdl = String::new();
+    let f = File::open("tests/test-alquerque.gdl");
+    f.unwrap().read_to_string(&mut gdl).ok().expect("Unable to read GDL file");
+    let desc = parse_parallel(&gdl).unwrap();
+    assert_eq!(desc, parse(&gdl));
+    for (c1, c2) in desc.clauses.iter().zip(parse(&gdl).clauses.iter()) {
+        assert_eq!((c1.span().lo, c1.span().hi), (c2.span().lo, c2.span().hi));
+    }
+}
+
+#[test]
+fn test_parse_parallel_errors() {
+    match parse_parallel("(role x) (role ?) (role o) ()") {
+        Err(ParseError::InvalidVariable { span }) => assert_eq!(span.lo, 15),
+        r => panic!("Unexpected result {:?}", r)
+    }
+}
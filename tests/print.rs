@@ -0,0 +1,61 @@
+extern crate gdl_parser;
+
+use gdl_parser::{parse, Constant, Description, Function, Term};
+
+use std::io;
+
+#[test]
+fn test_display_deep_nesting() {
+    let depth = 100000;
+    let mut term: Term = Constant::new("a").into();
+    for _ in 0..depth {
+        term = Function::new(Constant::new("f"), vec![term]).into();
+    }
+    let s = term.to_string();
+    assert_eq!(s.len(), depth * 4 + 1);
+    assert!(s.starts_with("(f (f (f") && s.contains(" (f a))") && s.ends_with(")))"));
+    // Dropping the term recurses once per level, so it's taken apart one level at a time
+    while let Term::FuncTerm(mut f) = term {
+        term = f.args.pop().unwrap();
+    }
+}
+
+#[test]
+fn test_canonical_string() {
+    let a = parse("(ROLE White)\n\
+                   (<= (Legal ?Player noop)\n    (role ?player))\n\
+                   (init |Mixed Case|)");
+    let b = parse("(init |Mixed Case|) (<= (legal ?r noop) (role ?r)) (role white)");
+    assert_eq!(a.to_canonical_string(), b.to_canonical_string());
+    assert_eq!(a.to_canonical_string(),
+               "(<= (legal ?v0 noop) (role ?v0))\n(init |Mixed Case|)\n(role white)");
+    assert!(parse("(init |mixed case|)").to_canonical_string() != b.to_canonical_string());
+}
+
+#[test]
+fn test_write_to() {
+    let gdl = "(role white) (<= (legal ?r noop) (role ?r))";
+    let desc = parse(gdl);
+    let mut out = Vec::new();
+    desc.write_to(&mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), gdl);
+
+    let mut out = io::Cursor::new(Vec::new());
+    desc.clauses[1].write_to(&mut out).unwrap();
+    assert_eq!(out.into_inner(), b"(<= (legal ?r noop) (role ?r))");
+
+    // Errors from the writer are passed on
+    let mut buf = [0; 4];
+    let err = desc.write_to(&mut &mut buf[..]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+}
+
+#[test]
+fn test_to_compact_string() {
+    let gdl = "(role white)  (<= (legal ?r noop)\n    (role ?r) (distinct |a b| ?r))\nterminal";
+    let desc = parse(gdl);
+    let compact = desc.to_compact_string();
+    assert_eq!(compact, "(role white)(<=(legal ?r noop)(role ?r)(distinct |a b| ?r))terminal");
+    assert_eq!(parse(&compact), desc);
+    assert_eq!(Description::new(vec![]).to_compact_string(), "");
+}
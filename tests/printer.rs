@@ -0,0 +1,77 @@
+extern crate gdl_parser;
+
+use gdl_parser::parse;
+use gdl_parser::{parse_with, ParserOptions};
+use gdl_parser::printer::{format, reformat, FormatStyle, PrettyPrinter};
+
+#[test]
+fn test_pretty_print() {
+    let gdl = "(role white) (role black) (<= (legal ?r noop) (role ?r) (not (true (control ?r)))) \
+               (<= terminal (or (line x) (line o) (not open))) (init (control white))";
+    let desc = parse(gdl);
+    let printed = PrettyPrinter::new().print(&desc);
+    assert_eq!(printed, "(role white)\n\
+                         (role black)\n\
+                         \n\
+                         (<= (legal ?r noop)\n    \
+                             (role ?r)\n    \
+                             (not (true (control ?r))))\n\
+                         \n\
+                         (<= terminal\n    \
+                             (or (line x) (line o) (not open)))\n\
+                         \n\
+                         (init (control white))");
+    assert_eq!(parse(&printed), desc);
+
+    let printed = PrettyPrinter::new().indent(2).max_width(30).print(&desc);
+    assert!(printed.contains("(<= terminal\n  (or\n    (line x)\n    (line o)\n    (not open)))"));
+    assert_eq!(parse(&printed), desc);
+
+    let desc = parse("(<= (legal ?r noop) (role ?r) (not (true (control ?r))))");
+    assert_eq!(PrettyPrinter::new().indent(2).max_width(80).print(&desc),
+               "(<= (legal ?r noop)\n  (role ?r)\n  (not (true (control ?r))))");
+    assert_eq!(PrettyPrinter::new().print(&parse("(<= p)")), "(<= p)");
+}
+
+#[test]
+fn test_format_style() {
+    let gdl = "(role white) (<= terminal (or (true (control white)) (not (true (cell 1 1 b)))))";
+    let desc = parse(gdl);
+
+    let style = FormatStyle { compact: true, trailing_newline: true, ..FormatStyle::new() };
+    assert_eq!(format(&desc, &style), format!("{}\n", gdl));
+
+    let style = FormatStyle { uppercase_keywords: true, align_body: true, indent: 2, max_width: 40,
+                              ..FormatStyle::new() };
+    let formatted = format(&desc, &style);
+    assert_eq!(formatted, "(ROLE white)\n\
+                           \n\
+                           (<= TERMINAL\n    \
+                               (OR (TRUE (control white))\n        \
+                                   (NOT (TRUE (cell 1 1 b)))))");
+    let options = ParserOptions { fold_case: true, ..ParserOptions::new() };
+    assert_eq!(parse_with(&formatted, &options).unwrap(), desc);
+
+    // Constants named like keywords keep their case
+    let style = FormatStyle { uppercase_keywords: true, compact: true, ..FormatStyle::new() };
+    assert_eq!(format(&parse("(init (cell role)) (role not)"), &style),
+               "(INIT (cell role)) (ROLE not)");
+}
+
+#[test]
+fn test_reformat() {
+    let gdl = "; Roles\n( role  white )   ; first\n\n\n\n(<=  (legal ?r noop)\n\
+               \t\t( role ?r )  ; who\n  #| block |#\n   (not (true (control ?r)))\n  )\n";
+    let style = FormatStyle::new();
+    let formatted = reformat(gdl, &style).unwrap();
+    assert_eq!(formatted, "; Roles\n(role white) ; first\n\n(<= (legal ?r noop)\n    \
+                           (role ?r) ; who\n    #| block |#\n    (not (true (control ?r)))\n\
+                           )\n");
+    assert_eq!(reformat(&formatted, &style).unwrap(), formatted);
+    assert_eq!(parse(&formatted), parse(gdl));
+
+    let style = FormatStyle { uppercase_keywords: true, trailing_newline: true,
+                              ..FormatStyle::new() };
+    assert_eq!(reformat("(role  white)", &style).unwrap(), "(ROLE white)\n");
+    assert!(reformat("(role white", &style).is_err());
+}
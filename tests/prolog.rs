@@ -0,0 +1,82 @@
+extern crate gdl_parser;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+
+use gdl_parser::{parse, parse_prolog, parse_prolog_file, GdlError, ParseError};
+
+#[test]
+fn test_parse_prolog() {
+    let prolog = "% Tic-tac-toe\n\
+                  role(x). role('o player').\n\
+                  next(cell(X,Y,o)) :- does(o, mark(X,Y)), \\+ true(cell(X,Y,x)).\n\
+                  /* multi-line\n   comment */\n\
+                  bad :- (line(x) ; not(line(o))), X \\= Y, true(step(_, _)).";
+    let gdl = "(role x) (role |o player|)\n\
+               (<= (next (cell ?x ?y o)) (does o (mark ?x ?y)) (not (true (cell ?x ?y x))))\n\
+               (<= bad (or (line x) (not (line o))) (distinct ?x ?y) (true (step ?_1 ?_2)))";
+    let desc = parse_prolog(prolog).unwrap();
+    assert_eq!(desc, parse(gdl));
+
+    let span = desc.clauses[2].span();
+    assert_eq!(&prolog[span.lo..span.hi],
+               "next(cell(X,Y,o)) :- does(o, mark(X,Y)), \\+ true(cell(X,Y,x))");
+
+    // Names given to `_` don't clash with the other variables in the clause
+    let desc = parse_prolog("p(_1, _) :- q(_, ?_2, _1). r(_).").unwrap();
+    assert_eq!(desc, parse("(<= (p ?_1 ?_3) (q ?_4 ?_2 ?_1)) (r ?_1)"));
+}
+
+#[test]
+fn test_prolog_errors() {
+    match parse_prolog("role(x)") {
+        Err(ParseError::UnexpectedEof { expected, .. }) => assert_eq!(expected, vec!["."]),
+        r => panic!("Unexpected result {:?}", r)
+    }
+    match parse_prolog("p :- q & r.") {
+        Err(e @ ParseError::UnexpectedToken { .. }) => assert_eq!(e.span().lo, 7),
+        r => panic!("Unexpected result {:?}", r)
+    }
+    assert!(parse_prolog("p :- 'unterminated.").is_err());
+}
+
+#[test]
+fn test_prolog_directives() {
+    let prolog = ":- dynamic true/1, does/2.\n\
+                  :- discontiguous(cell/3).\n\
+                  role(x). :- ensure_loaded('lib.pl').\n\
+                  succ(1,2).";
+    assert_eq!(parse_prolog(prolog).unwrap(), parse("(role x) (succ 1 2)"));
+
+    // Only a `:-` where a clause would start is a directive
+    match parse_prolog("p :- :- q.") {
+        Err(e @ ParseError::UnexpectedToken { .. }) => assert_eq!(e.span().lo, 5),
+        r => panic!("Unexpected result {:?}", r)
+    }
+    match parse_prolog("role(x). :- dynamic cell/3") {
+        Err(ParseError::UnexpectedEof { expected, .. }) => assert_eq!(expected, vec!["."]),
+        r => panic!("Unexpected result {:?}", r)
+    }
+}
+
+#[test]
+fn test_parse_prolog_file() {
+    let path = env::temp_dir().join("gdl-parser-test-parse-prolog-file.pl");
+    File::create(&path).unwrap().write_all(b":- dynamic true/1.\nrole(x).\nrole(o)").unwrap();
+    let result = parse_prolog_file(&path);
+    match result {
+        Err(e @ GdlError::Parse { line: 3, column: 8, .. }) => assert_eq!(e.path(), &path),
+        r => panic!("Unexpected result {:?}", r)
+    }
+
+    File::create(&path).unwrap().write_all(b":- dynamic true/1.\nrole(x).\nrole(o).").unwrap();
+    let result = parse_prolog_file(&path);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(result.unwrap(), parse("(role x) (role o)"));
+
+    match parse_prolog_file("tests/missing.pl") {
+        Err(GdlError::Io { .. }) => {}
+        r => panic!("Unexpected result {:?}", r)
+    }
+}
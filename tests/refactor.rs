@@ -0,0 +1,67 @@
+extern crate gdl_parser;
+
+use gdl_parser::parse;
+use gdl_parser::refactor::{rename_relation, RenameError};
+
+#[test]
+fn test_rename_relation() {
+    let gdl = "(succ 1 2) (cell) (<= (next (succ ?y)) (true (succ ?x)) (succ ?x ?y))\n\
+               (<= (lt ?x ?y) (or (succ ?x ?y) (not (succ ?y ?x)))) (<= p cell)";
+    let mut desc = parse(gdl);
+    rename_relation(&mut desc, "succ", "plus").unwrap();
+    rename_relation(&mut desc, "cell", "board").unwrap();
+    assert_eq!(desc.to_string(),
+               "(plus 1 2) (board) (<= (next (succ ?y)) (true (succ ?x)) (plus ?x ?y)) \
+                (<= (lt ?x ?y) (or (plus ?x ?y) (not (plus ?y ?x)))) (<= p board)");
+    let span = desc.clauses[0].span();
+    assert_eq!((span.lo, span.hi), (0, 10));
+
+    let before = desc.clone();
+    rename_relation(&mut desc, "lt", "lt").unwrap();
+    assert_eq!(desc, before);
+}
+
+#[test]
+fn test_rename_relation_errors() {
+    let mut desc = parse("(role white) (succ 1 2) (<= (lt ?x ?y) (succ ?x ?y))");
+    let before = desc.clone();
+    let keyword = |name: &str| Err(RenameError::Keyword { name: name.to_string() });
+    let invalid = |name: &str| Err(RenameError::InvalidName { name: name.to_string() });
+
+    // Keywords can't be renamed or be the new name, including the GDL-II ones
+    assert_eq!(rename_relation(&mut desc, "legal", "allowed"), keyword("legal"));
+    assert_eq!(rename_relation(&mut desc, "succ", "does"), keyword("does"));
+    assert_eq!(rename_relation(&mut desc, "succ", "sees"), keyword("sees"));
+
+    // `white` is only a constant, so there's no relation to rename
+    assert_eq!(rename_relation(&mut desc, "plus", "succ"),
+               Err(RenameError::Undefined { name: "plus".to_string() }));
+    assert_eq!(rename_relation(&mut desc, "white", "black"),
+               Err(RenameError::Undefined { name: "white".to_string() }));
+
+    assert_eq!(rename_relation(&mut desc, "succ", "?x"), invalid("?x"));
+    assert_eq!(rename_relation(&mut desc, "succ", "a b"), invalid("a b"));
+    assert_eq!(rename_relation(&mut desc, "succ", "<="), invalid("<="));
+
+    let e = rename_relation(&mut desc, "succ", "lt").unwrap_err();
+    match e {
+        RenameError::Conflict { ref name, span } => {
+            assert_eq!((&name[..], span.lo, span.hi), ("lt", 29, 31))
+        }
+        ref e => panic!("Unexpected error {:?}", e)
+    }
+    assert_eq!(e.to_string(), "there's already a relation named `lt` at byte 29");
+
+    // None of the refused renames changed anything
+    assert_eq!(desc, before);
+    assert_eq!(desc.to_string(), "(role white) (succ 1 2) (<= (lt ?x ?y) (succ ?x ?y))");
+
+    // Constants and functions with the new name aren't relations
+    assert_eq!(rename_relation(&mut desc, "succ", "white"), Ok(()));
+    match rename_relation(&mut desc, "lt", "white") {
+        Err(RenameError::Conflict { ref name, span }) => {
+            assert_eq!((&name[..], span.lo, span.hi), ("white", 14, 18))
+        }
+        r => panic!("Unexpected result {:?}", r)
+    }
+}
@@ -0,0 +1,28 @@
+#![cfg(feature = "pretty")]
+
+extern crate gdl_parser;
+
+use gdl_parser::{lint, parse, parse_lenient, render};
+
+#[test]
+fn test_render() {
+    let gdl = "(role white)\n(<= (legal ?r noop)\n    (role ?r)\n";
+    let (_, diagnostics) = parse_lenient(gdl);
+    assert_eq!(render(&diagnostics[0], gdl, "game.gdl"),
+               "error[unbalanced-paren]: unbalanced parenthesis at byte 13\n \
+                --> game.gdl:2:1\n  \
+                |\n\
+                2 | (<= (legal ?r noop)\n  \
+                | ^\n  \
+                = help: insert `)`\n");
+
+    let gdl = "(<= (legal ?r noop)\n\t(role ?player))";
+    let diagnostics = lint(&parse(gdl));
+    assert_eq!(render(&diagnostics[1], gdl, "game.gdl"),
+               "warning[singleton-variable]: variable `?player` only appears once\n \
+                --> game.gdl:2:8\n  \
+                |\n\
+                1 | (<= (legal ?r noop)\n\
+                2 | \t(role ?player))\n  \
+                | \t      ^^^^^^^\n");
+}
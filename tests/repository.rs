@@ -0,0 +1,76 @@
+extern crate gdl_parser;
+
+use gdl_parser::{from_ggp_json, parse, parse_game_json, to_ggp_json, ParseError, RepositoryError};
+
+#[test]
+fn test_parse_game_json() {
+    let json = r#"{
+        "gameName": "Tic-Tac-Toe",
+        "description": "A simple game",
+        "version": 2,
+        "numRoles": 2,
+        "roleNames": ["xplayer", "oplayer"],
+        "rulesheet": "(role xplayer) (role oplayer)\n(<= terminal (true (step 10)))"
+    }"#;
+    let game = parse_game_json(json).unwrap();
+    assert_eq!(game.desc, parse("(role xplayer) (role oplayer) (<= terminal (true (step 10)))"));
+    assert_eq!(game.metadata.name, Some("Tic-Tac-Toe".to_string()));
+    assert_eq!(game.metadata.description, Some("A simple game".to_string()));
+    assert_eq!(game.metadata.version, Some(2));
+    assert_eq!(game.num_roles(), 2);
+    assert_eq!(game.role_names(), vec!["xplayer", "oplayer"]);
+
+    // Serialized by GGP Base, without role metadata
+    let json = r#"{"theName": "Chicken", "theRulesheet": "(role white) (role black) (role red)"}"#;
+    let game = parse_game_json(json).unwrap();
+    assert_eq!(game.metadata.name, Some("Chicken".to_string()));
+    assert_eq!(game.metadata.num_roles, None);
+    assert_eq!(game.num_roles(), 3);
+    assert_eq!(game.role_names(), vec!["white", "black", "red"]);
+}
+
+#[test]
+fn test_game_json_errors() {
+    match parse_game_json("{\"gameName\": ") {
+        Err(RepositoryError::InvalidJson(_)) => {}
+        r => panic!("Unexpected result {:?}", r)
+    }
+    match parse_game_json("[]") {
+        Err(RepositoryError::InvalidJson(_)) => {}
+        r => panic!("Unexpected result {:?}", r)
+    }
+    // A repository's METADATA names the rulesheet file rather than including it
+    match parse_game_json(r#"{"gameName": "Tic-Tac-Toe", "rulesheet": "rulesheet.kif"}"#) {
+        Err(RepositoryError::MissingRulesheet) => {}
+        r => panic!("Unexpected result {:?}", r)
+    }
+    match parse_game_json(r#"{"rulesheet": "(role x"}"#) {
+        Err(RepositoryError::Parse(ParseError::UnbalancedParen { span })) => {
+            assert_eq!((span.lo, span.hi), (0, 1))
+        }
+        r => panic!("Unexpected result {:?}", r)
+    }
+}
+
+#[test]
+fn test_ggp_json() {
+    let gdl = "(role white) (<= (legal ?r noop) (role ?r) (not (true (control ?r)))) terminal \
+               (init |a b|)";
+    let desc = parse(gdl);
+    let json = to_ggp_json(&desc);
+    assert!(json.starts_with(r#"[["role","white"],["<=",["legal","?r","noop"],["role","?r"],"#));
+    assert_eq!(from_ggp_json(&json).unwrap(), desc);
+
+    match from_ggp_json(r#"[["role", 1]]"#) {
+        Err(RepositoryError::InvalidJson(_)) => {}
+        r => panic!("Unexpected result {:?}", r)
+    }
+    match from_ggp_json(r#"{"role": "white"}"#) {
+        Err(RepositoryError::InvalidJson(_)) => {}
+        r => panic!("Unexpected result {:?}", r)
+    }
+    match from_ggp_json(r#"[["<=", "p", ["not"]]]"#) {
+        Err(RepositoryError::Parse(_)) => {}
+        r => panic!("Unexpected result {:?}", r)
+    }
+}
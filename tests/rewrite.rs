@@ -0,0 +1,60 @@
+extern crate gdl_parser;
+
+use gdl_parser::{parse, parse_term};
+use gdl_parser::rewrite::{RewriteError, Rewriter, DEFAULT_MAX_STEPS};
+
+fn rule(rewriter: &mut Rewriter, pattern: &str, replacement: &str) {
+    rewriter.register(parse_term(pattern).unwrap(), parse_term(replacement).unwrap()).unwrap();
+}
+
+#[test]
+fn test_rewrite() {
+    let mut rewriter = Rewriter::new();
+    rule(&mut rewriter, "(plus ?x 0)", "?x");
+    rule(&mut rewriter, "(pair ?x ?x)", "(same ?x)");
+    rule(&mut rewriter, "open", "(closed no)");
+    rule(&mut rewriter, "(cell ?x ?y)", "(square (at ?x ?y))");
+
+    let desc = parse("(cell 1 (plus (plus 2 0) 0)) (<= (goal ?r 100) open (not (cell ?r ?r)))\n\
+                      (<= (next (pair ?x ?x)) (true (pair a ?x)) (distinct (pair b b) open))");
+    let rewritten = rewriter.rewrite(&desc).unwrap();
+    assert_eq!(rewritten.to_string(),
+               "(square (at 1 2)) (<= (goal ?r 100) (closed no) (not (square (at ?r ?r)))) \
+                (<= (next (same ?x)) (true (pair a ?x)) (distinct (same b) (closed no)))");
+    let span = rewritten.clauses[0].span();
+    assert_eq!((span.lo, span.hi), (0, 28));
+    assert_eq!(rewriter.rewrite_term(&parse_term("(plus (pair 0 0) 0)").unwrap()).unwrap(),
+               parse_term("(same 0)").unwrap());
+
+    let unchanged = parse("(role white) (<= (legal white noop) (true (control white)))");
+    assert_eq!(rewriter.rewrite(&unchanged).unwrap(), unchanged);
+}
+
+#[test]
+fn test_rewrite_errors() {
+    let mut rewriter = Rewriter::new();
+    assert_eq!(rewriter.register(parse_term("(f ?x)").unwrap(), parse_term("(g ?y)").unwrap()),
+               Err(RewriteError::UnboundVariable { name: "y".to_string() }));
+
+    rule(&mut rewriter, "(flip ?x)", "(flop ?x)");
+    rule(&mut rewriter, "(flop ?x)", "(flip ?x)");
+    let mut rewriter = rewriter.with_max_steps(10);
+    assert_eq!(rewriter.rewrite(&parse("(flip 1)")),
+               Err(RewriteError::NoFixpoint { limit: 10 }));
+
+    rewriter = Rewriter::new();
+    rule(&mut rewriter, "(wrap ?x)", "?x");
+    match rewriter.rewrite(&parse("(role a) (wrap ?y)")) {
+        Err(RewriteError::NotASentence { span }) => assert_eq!((span.lo, span.hi), (9, 18)),
+        r => panic!("unexpected result {:?}", r)
+    }
+    assert_eq!(rewriter.rewrite(&parse("(role (wrap a))")).unwrap(), parse("(role a)"));
+
+    // A rule that nests terms deeper every time runs out of steps instead of stack
+    rewriter = Rewriter::new();
+    rule(&mut rewriter, "(f ?x)", "(f (f ?x))");
+    assert_eq!(rewriter.rewrite(&parse("(p (f a))")),
+               Err(RewriteError::NoFixpoint { limit: DEFAULT_MAX_STEPS }));
+    assert_eq!(rewriter.rewrite_term(&parse_term("(p (f a))").unwrap()),
+               Err(RewriteError::NoFixpoint { limit: DEFAULT_MAX_STEPS }));
+}
@@ -0,0 +1,35 @@
+extern crate gdl_parser;
+
+use gdl_parser::{parse, Function, Term};
+use gdl_parser::scramble::Scrambler;
+
+#[test]
+fn test_scramble() {
+    let gdl = "(role white) (role random) (init (control white))\n\
+               (<= (legal white (mark ?x)) (true (control white)) (index ?x))\n\
+               (<= (goal ?r 100) (role ?r) (not (distinct ?r white)))\n\
+               (<= (sees ?r ?m) (does ?r ?m))";
+    let desc = parse(gdl);
+    let mut scrambler = Scrambler::with_seed(7);
+    let scrambled = scrambler.scramble(&desc);
+    let printed = scrambled.to_string();
+    let kept = ["role", "random", "init", "legal", "true", "goal", "100", "distinct", "sees"];
+    for name in kept.iter() {
+        assert!(printed.contains(name), "{}", name);
+    }
+    for name in ["white", "control", "mark", "index", "?r", "?x"].iter() {
+        assert!(!printed.contains(name), "{}", name);
+    }
+    assert_eq!(scrambler.unscramble(&scrambled), desc);
+
+    // Words are consistent across the description and terms, and picked the same for a seed
+    let word = scrambler.word("white").unwrap().to_string();
+    assert_eq!(scrambler.name(&word), Some("white"));
+    assert!(printed.contains(&format!("(role {})", word)));
+    assert_eq!(Scrambler::with_seed(7).scramble(&desc), scrambled);
+
+    let mark = Term::FuncTerm(Function::new("mark", vec![Term::ConstTerm("1".into())]));
+    let scrambled = scrambler.scramble_term(&mark);
+    assert_eq!(scrambled.to_string(), format!("({} 1)", scrambler.word("mark").unwrap()));
+    assert_eq!(scrambler.unscramble_term(&scrambled), mark);
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "serde")]
+
+extern crate gdl_parser;
+extern crate serde_json;
+
+use std::fs::File;
+use std::io::Read;
+
+use gdl_parser::{parse, Description};
+#[cfg(feature = "legacy-json")]
+use gdl_parser::{from_legacy_json, LegacyJsonError};
+
+#[test]
+fn test_serde_round_trip() {
+    let mut gdl = String::new();
+    let f = File::open("tests/test-alquerque.gdl");
+    f.unwrap().read_to_string(&mut gdl).ok().expect("Unable to read GDL file");
+
+    let desc = parse(&gdl);
+    let json = serde_json::to_string(&desc).unwrap();
+    assert_eq!(serde_json::from_str::<Description>(&json).unwrap(), desc);
+}
+
+#[test]
+fn test_serde_enum_representation() {
+    let json = serde_json::to_value(&parse("(role white)")).unwrap();
+    let name = &json["clauses"][0]["SentenceClause"]["RelSentence"]["name"];
+    assert_eq!(name["name"], "role");
+    assert_eq!(name["span"]["lo"], 1);
+
+    // Spans can be left out, like in JSON written before spans existed
+    let json = r#"{"clauses": [{"SentenceClause": {"PropSentence": {"name": {"name": "p"}}}}]}"#;
+    assert_eq!(serde_json::from_str::<Description>(json).unwrap(), parse("p"));
+}
+
+#[cfg(feature = "legacy-json")]
+#[test]
+fn test_legacy_json() {
+    let mut gdl = String::new();
+    let f = File::open("tests/test-alquerque.gdl");
+    f.unwrap().read_to_string(&mut gdl).ok().expect("Unable to read GDL file");
+
+    let mut json = String::new();
+    let f = File::open("tests/test-alquerque.json");
+    f.unwrap().read_to_string(&mut json).ok().expect("Unable to read JSON file");
+
+    // A fixture written by rustc-serialize can be converted to serde's representation
+    let desc = from_legacy_json(&json).unwrap();
+    assert_eq!(desc, parse(&gdl));
+    let converted = serde_json::to_string(&desc).unwrap();
+    assert_eq!(serde_json::from_str::<Description>(&converted).unwrap(), parse(&gdl));
+
+    let json = r#"{"clauses": [{"variant": "SentenceClause", "fields": [{"variant": "PropSentence",
+                  "fields": [{"name": {"name": "p", "span": {"lo": 0, "hi": 1}}}]}]}]}"#;
+    let desc = from_legacy_json(json).unwrap();
+    assert_eq!(desc, parse("p"));
+    assert_eq!(desc.clauses[0].span().hi, 0);
+
+    match from_legacy_json(r#"{"clauses": [{"variant": "Nope", "fields": []}]}"#) {
+        Err(LegacyJsonError::Malformed(message)) => {
+            assert_eq!(message, "unknown Clause variant `Nope`")
+        }
+        r => panic!("expected a malformed JSON error, found {:?}", r)
+    }
+    assert!(from_legacy_json("{").is_err());
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn test_cbor_round_trip() {
+    let mut gdl = String::new();
+    let f = File::open("tests/test-alquerque.gdl");
+    f.unwrap().read_to_string(&mut gdl).ok().expect("Unable to read GDL file");
+
+    let desc = parse(&gdl);
+    let bytes = gdl_parser::to_cbor(&desc);
+    let decoded = gdl_parser::from_cbor(&bytes).unwrap();
+    assert_eq!(decoded, desc);
+    let span = decoded.clauses[0].span();
+    assert_eq!((span.lo, span.hi), (desc.clauses[0].span().lo, desc.clauses[0].span().hi));
+
+    assert!(gdl_parser::from_cbor(&bytes[..bytes.len() / 2]).is_err());
+    assert!(gdl_parser::from_cbor(b"(role white)").is_err());
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn test_msgpack_round_trip() {
+    let mut gdl = String::new();
+    let f = File::open("tests/test-alquerque.gdl");
+    f.unwrap().read_to_string(&mut gdl).ok().expect("Unable to read GDL file");
+
+    let desc = parse(&gdl);
+    let bytes = gdl_parser::to_msgpack(&desc);
+    let decoded = gdl_parser::from_msgpack(&bytes).unwrap();
+    assert_eq!(decoded, desc);
+    let span = decoded.clauses[0].span();
+    assert_eq!((span.lo, span.hi), (desc.clauses[0].span().lo, desc.clauses[0].span().hi));
+
+    assert!(gdl_parser::from_msgpack(&bytes[..bytes.len() / 2]).is_err());
+    assert!(gdl_parser::from_msgpack(b"(role white)").is_err());
+}
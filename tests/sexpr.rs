@@ -0,0 +1,71 @@
+extern crate gdl_parser;
+
+use std::convert::TryFrom;
+
+use gdl_parser::{parse, parse_lenient, parse_sexprs, Description, ParseError, SExpr};
+use gdl_parser::SExpr::{Atom, List};
+
+#[test]
+fn test_parse_sexprs() {
+    let exprs = parse_sexprs("(defrelation ?x |a b|) ; comment\nfoo (()) #| block |#").unwrap();
+    assert_eq!(exprs, vec![List(vec![SExpr::atom("defrelation"), SExpr::atom("?x"),
+                                     SExpr::atom("|a b|")]),
+                           SExpr::atom("foo"),
+                           List(vec![List(vec![])])]);
+    assert_eq!(exprs[0].as_list().unwrap()[1].as_atom(), Some("?x"));
+    assert_eq!(exprs[1].as_list(), None);
+    assert_eq!(exprs.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+               vec!["(defrelation ?x |a b|)", "foo", "(())"]);
+
+    match parse_sexprs("(a (b)") {
+        Err(ParseError::UnbalancedParen { span }) => assert_eq!((span.lo, span.hi), (0, 1)),
+        r => panic!("Unexpected result {:?}", r)
+    }
+    match parse_sexprs("a)") {
+        Err(ParseError::UnbalancedParen { span }) => assert_eq!((span.lo, span.hi), (1, 2)),
+        r => panic!("Unexpected result {:?}", r)
+    }
+
+    assert_eq!("(a b)".parse::<SExpr>().unwrap(), List(vec![Atom("a".to_string()),
+                                                             Atom("b".to_string())]));
+    match "(a) (b)".parse::<SExpr>() {
+        Err(ParseError::UnexpectedToken { span, .. }) => {
+            assert_eq!((span.lo, span.hi), (4, 5))
+        }
+        r => panic!("Unexpected result {:?}", r)
+    }
+    match " ".parse::<SExpr>() {
+        Err(ParseError::UnexpectedEof { .. }) => {}
+        r => panic!("Unexpected result {:?}", r)
+    }
+}
+
+#[test]
+fn test_sexpr_description() {
+    let gdl = "(role white) (<= (legal ?r noop) (role ?r) (not (true (control ?r)))) terminal \
+               (<= p (or q (distinct ?x a)))";
+    let desc = parse(gdl);
+    let expr: SExpr = desc.clone().into();
+    assert_eq!(expr.to_string(), format!("({})", gdl));
+    assert_eq!(Description::try_from(expr.clone()).unwrap(), desc);
+
+    // Splice a computed clause into the description
+    let mut clauses = expr.as_list().unwrap().to_vec();
+    clauses.push(List(vec![SExpr::atom("role"), SExpr::atom("black")]));
+    let desc = Description::try_from(List(clauses)).unwrap();
+    assert_eq!(desc, parse(&format!("{} (role black)", gdl)));
+
+    let (desc, _) = parse_lenient("(role white) (not)");
+    let expr: SExpr = desc.into();
+    assert_eq!(expr.as_list().unwrap()[1], List(vec![SExpr::atom("not")]));
+
+    match Description::try_from(SExpr::atom("role")) {
+        Err(ParseError::UnexpectedToken { .. }) => {}
+        r => panic!("Unexpected result {:?}", r)
+    }
+    let rule = List(vec![SExpr::atom("<="), SExpr::atom("p"), List(vec![SExpr::atom("not")])]);
+    match Description::try_from(List(vec![rule])) {
+        Err(ParseError::UnexpectedToken { span, .. }) => assert_eq!((span.lo, span.hi), (10, 11)),
+        r => panic!("Unexpected result {:?}", r)
+    }
+}
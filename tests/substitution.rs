@@ -0,0 +1,49 @@
+extern crate gdl_parser;
+
+use gdl_parser::{parse, parse_literal, Constant, Function, Substitution, Term, Variable};
+use gdl_parser::Clause::RuleClause;
+
+fn var(name: &str) -> Term {
+    Variable::new(name).into()
+}
+
+fn constant(name: &str) -> Term {
+    Constant::new(name).into()
+}
+
+#[test]
+fn test_apply() {
+    let mut subst = Substitution::new();
+    assert!(subst.is_empty());
+    subst.insert(Variable::new("x"), constant("1"));
+    subst.insert(Variable::new("y"), Function::new("f", vec![var("x")]).into());
+    assert_eq!(subst.len(), 2);
+    assert_eq!(subst.get(&Variable::new("x")), Some(&constant("1")));
+
+    let term: Term = Function::new("cell", vec![var("x"), var("y"), var("z")]).into();
+    assert_eq!(subst.apply(&term).to_string(), "(cell 1 (f ?x) ?z)");
+
+    let rule = match parse("(<= (next (cell ?x ?y)) (true (cell ?y ?x)) (not (distinct ?x ?z)))")
+                        .clauses.remove(0) {
+        RuleClause(r) => r,
+        c => panic!("Expected a rule, found {}", c)
+    };
+    assert_eq!(subst.apply_rule(&rule).to_string(),
+               "(<= (next (cell 1 (f ?x))) (true (cell (f ?x) 1)) (not (distinct 1 ?z)))");
+    assert_eq!(subst.apply_sentence(&rule.head).to_string(), "(next (cell 1 (f ?x)))");
+    let lit = parse_literal("(or (p ?x) (q ?w))").unwrap();
+    assert_eq!(subst.apply_literal(&lit).to_string(), "(or (p 1) (q ?w))");
+}
+
+#[test]
+fn test_compose() {
+    let first = vec![(Variable::new("x"), Function::new("f", vec![var("y")]).into())]
+                    .into_iter().collect::<Substitution>();
+    let second = vec![(Variable::new("y"), constant("a")), (Variable::new("x"), constant("b"))]
+                     .into_iter().collect::<Substitution>();
+    let composed = first.compose(&second);
+    let term: Term = Function::new("g", vec![var("x"), var("y")]).into();
+    assert_eq!(composed.apply(&term), second.apply(&first.apply(&term)));
+    assert_eq!(composed.apply(&term).to_string(), "(g (f a) a)");
+    assert_eq!(composed.len(), 2);
+}
@@ -1,14 +1,17 @@
 extern crate gdl_parser;
-extern crate rustc_serialize;
 
-use gdl_parser::{parse, Constant, Proposition, Relation};
+use gdl_parser::{parse, parse_checked, parse_literal, parse_rule, parse_sentence,
+                 parse_term, Constant, Description, Function, ParseError, Proposition, Relation,
+                 Rule, Sentence, Span, Term};
+use gdl_parser::Clause::RuleClause;
 use gdl_parser::Sentence::{PropSentence, RelSentence};
-
-use rustc_serialize::json;
+#[cfg(feature = "legacy-json")]
+use gdl_parser::from_legacy_json;
 
 use std::fs::File;
 use std::io::Read;
 
+#[cfg(feature = "legacy-json")]
 #[test]
 fn test() {
     let mut gdl = String::new();
@@ -19,7 +22,7 @@ fn test() {
     let f = File::open("tests/test-alquerque.json");
     f.unwrap().read_to_string(&mut json).ok().expect("Unable to read JSON file");
 
-    assert_eq!(parse(&gdl), json::decode(&json).unwrap());
+    assert_eq!(parse(&gdl), from_legacy_json(&json).unwrap());
 }
 
 #[test]
@@ -41,4 +44,148 @@ fn test_to_string() {
                       vec![Constant::new("a").into(),
                            Constant::new("b").into()]));
     assert_eq!(sentence.to_string(), "(p a b)".to_string());
+
+    assert_eq!(Description::new(vec![]).to_string(), "");
+    let gdl = "(<= (p ?x) (or (q ?x) (r |a b|)) (or))";
+    assert_eq!(parse(gdl).to_string(), gdl);
+}
+
+#[test]
+fn test_parse_checked() {
+    assert!(parse_checked("(role red) (role black)").is_ok());
+
+    let gdl = "(role red) (role black";
+    let err = parse_checked(gdl).unwrap_err();
+    assert_eq!(err, ParseError::UnbalancedParen { span: Span::new(11, 12) });
+    assert_eq!(err.line_col(gdl), (1, 12));
+}
+
+#[test]
+fn test_parse_error_kinds() {
+    match parse_checked("(role red))").unwrap_err() {
+        ParseError::UnbalancedParen { span } => assert_eq!((span.lo, span.hi), (10, 11)),
+        e => panic!("Unexpected error {:?}", e)
+    }
+
+    match parse_checked("(role red) ()").unwrap_err() {
+        ParseError::EmptyClause { span } => assert_eq!((span.lo, span.hi), (11, 13)),
+        e => panic!("Unexpected error {:?}", e)
+    }
+
+    match parse_checked("(<= (legal ?r noop) (role ? r))").unwrap_err() {
+        ParseError::InvalidVariable { span } => assert_eq!((span.lo, span.hi), (26, 27)),
+        e => panic!("Unexpected error {:?}", e)
+    }
+
+    let gdl = "(<= (legal ?r noop)\n    (role ?r) (not (a) (b)))";
+    match parse_checked(gdl).unwrap_err() {
+        e @ ParseError::UnexpectedToken { .. } => {
+            assert_eq!(e.span().snippet(gdl), "(");
+            assert_eq!(e.line_col(gdl), (2, 24));
+            assert_eq!(e.expected(), &[")"]);
+        }
+        e => panic!("Unexpected error {:?}", e)
+    }
+}
+
+#[test]
+fn test_spans() {
+    let gdl = "(role red) ; a comment\n(<= (legal ?r noop)\n    (role ?r)\n    (not (terminal)))";
+    let desc = parse(gdl);
+
+    let role = desc.clauses[0].span();
+    assert_eq!(role.snippet(gdl), "(role red)");
+
+    let rule = desc.clauses[1].span();
+    assert_eq!(rule.snippet(gdl), &gdl[23..]);
+    assert_eq!(rule.start_line_col(gdl), (2, 1));
+    assert_eq!(rule.end_line_col(gdl), (4, 22));
+
+    match desc.clauses[1] {
+        RuleClause(ref r) => {
+            assert_eq!(r.head.span().snippet(gdl), "(legal ?r noop)");
+            assert_eq!(r.body[1].span().snippet(gdl), "(not (terminal))");
+            assert_eq!(r.body[1].span().start_line_col(gdl), (4, 5));
+            match r.head {
+                RelSentence(ref rel) => assert_eq!(rel.args[0].span().snippet(gdl), "?r"),
+                _ => panic!("Expected a relation")
+            }
+        }
+        _ => panic!("Expected a rule")
+    }
+
+    // Spans don't affect equality
+    assert_eq!(desc, parse(&gdl.replace(" ; a comment\n", "  ")));
+}
+
+#[test]
+fn test_parse_fragments() {
+    let term = parse_term("  (move 2 3) ; white's move\n").unwrap();
+    assert_eq!(term, Function::new("move", vec![Constant::new("2").into(),
+                                                Constant::new("3").into()]).into());
+    assert_eq!((term.span().lo, term.span().hi), (2, 12));
+
+    assert_eq!(parse_sentence("(cell 1 1 b)").unwrap().to_string(), "(cell 1 1 b)");
+    assert_eq!(parse_sentence("terminal").unwrap().to_string(), "terminal");
+    assert_eq!(parse_literal("(not (true (control white)))").unwrap().to_string(),
+               "(not (true (control white)))");
+    assert_eq!(parse_rule("(<= (legal ?r noop) (role ?r))").unwrap(),
+               match parse("(<= (legal ?r noop) (role ?r))").clauses.pop().unwrap() {
+                   RuleClause(r) => r,
+                   _ => panic!("Expected a rule")
+               });
+
+    assert!(parse_term("(move 2 3) (move 3 4)").is_err());
+    assert!(parse_sentence("?x").is_err());
+    match parse_literal(" (cell 1 ?)").unwrap_err() {
+        ParseError::InvalidVariable { span } => assert_eq!(span.lo, 9),
+        e => panic!("Unexpected error {:?}", e)
+    }
+}
+
+#[test]
+fn test_from_str() {
+    let sentence: Sentence = "(cell 1 1 b)".parse().unwrap();
+    assert_eq!(sentence, RelSentence(Relation::new("cell", vec![Constant::new("1").into(),
+                                                                Constant::new("1").into(),
+                                                                Constant::new("b").into()])));
+    assert_eq!("(role red) (role black)".parse::<Description>().unwrap().clauses.len(), 2);
+    assert_eq!("(<= terminal (true (step 10)))".parse::<Rule>().unwrap().body.len(), 1);
+    assert_eq!("?x".parse::<Term>().unwrap().to_string(), "?x");
+    assert_eq!("white".parse::<Constant>().unwrap(), Constant::new("white"));
+
+    match "(f a)".parse::<Constant>().unwrap_err() {
+        ParseError::UnexpectedToken { found, expected, .. } => {
+            assert_eq!(found, "(f a)");
+            assert_eq!(expected, vec!["constant"]);
+        }
+        e => panic!("Unexpected error {:?}", e)
+    }
+}
+
+#[test]
+fn test_keyword_errors() {
+    assert_eq!(parse_literal("(or)").unwrap(), gdl_parser::Or::new(Vec::new()).into());
+
+    let gdl = "(<= p (distinct ?x ?y ?z))";
+    match parse_checked(gdl).unwrap_err() {
+        e @ ParseError::UnexpectedToken { .. } => {
+            assert_eq!(e.span().snippet(gdl), "?z");
+            assert_eq!(e.expected(), &[")"]);
+        }
+        e => panic!("Unexpected error {:?}", e)
+    }
+    match parse_checked("(<= p (not))").unwrap_err() {
+        ParseError::UnexpectedToken { found, expected, .. } => {
+            assert_eq!(found, ")");
+            assert_eq!(expected, vec!["(", "constant"]);
+        }
+        e => panic!("Unexpected error {:?}", e)
+    }
+    match parse_checked("(<= p (true -x))").unwrap_err() {
+        ParseError::UnexpectedToken { span, found, .. } => {
+            assert_eq!((span.lo, span.hi, &*found), (12, 14, "-x"))
+        }
+        e => panic!("Unexpected error {:?}", e)
+    }
 }
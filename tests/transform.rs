@@ -0,0 +1,238 @@
+extern crate gdl_parser;
+
+use gdl_parser::parse;
+use gdl_parser::Clause::RuleClause;
+use gdl_parser::transform::{canonicalize_variables, condense, dedup_clauses, deor,
+                            eliminate_dead_rules, evaluate_static, flatten_ors,
+                            inline_relations, negation_normal_form,
+                            rename_apart, rename_canonically, sort_by_dependencies,
+                            standardize_apart, VariableGenerator};
+
+use std::fs::File;
+use std::io::Read;
+
+#[test]
+fn test_dedup_clauses() {
+    let gdl = "(succ 1 2) (<= (p ?x) (q ?x ?y)) (SUCC 1 2) (succ 2 3)\n\
+               (<= (p ?a) (q ?a ?b)) (<= (p ?x) (q ?y ?x))";
+    assert_eq!(dedup_clauses(&parse(gdl)).to_string(),
+               "(succ 1 2) (<= (p ?x) (q ?x ?y)) (SUCC 1 2) (succ 2 3) (<= (p ?x) (q ?y ?x))");
+
+    let desc = parse("(cell 1 1 B) (cell 1 1 b)");
+    assert_eq!(dedup_clauses(&desc), desc);
+
+    let desc = parse("(role white) (init (cell 1))");
+    assert_eq!(dedup_clauses(&desc), desc);
+}
+
+#[test]
+fn test_eliminate_dead_rules() {
+    let gdl = "(role white) (init (cell 1)) (succ 1 2) (helper 1)\n\
+               (<= (next (cell ?y)) (true (cell ?x)) (succ ?x ?y))\n\
+               (<= (old ?x) (helper ?x) (succ ?x ?y)) (<= (older ?x) (old ?x))\n\
+               (<= (legal white noop) (not (blocked white)))";
+    let (pruned, removed) = eliminate_dead_rules(&parse(gdl));
+    assert_eq!(pruned.to_string(),
+               "(role white) (init (cell 1)) (succ 1 2) \
+                (<= (next (cell ?y)) (true (cell ?x)) (succ ?x ?y)) \
+                (<= (legal white noop) (not (blocked white)))");
+    let removed = removed.iter().map(|c| c.span().snippet(gdl)).collect::<Vec<_>>();
+    assert_eq!(removed, ["(helper 1)", "(<= (old ?x) (helper ?x) (succ ?x ?y))",
+                         "(<= (older ?x) (old ?x))"]);
+
+    let desc = parse("(role white) (<= terminal (true done))");
+    assert_eq!(eliminate_dead_rules(&desc), (desc, vec![]));
+}
+
+#[test]
+fn test_inline_relations() {
+    let gdl = "(<= (p (f ?a) ?b) (q ?a ?c) (r ?c ?b))\n\
+               (<= (s ?y) (p ?y 1) (t ?y)) (<= (s2 ?y) (p (g ?y) 1))\n\
+               (<= s3 (not (p 1 2)) (p ?z ?z))\n\
+               (<= (a ?x) (b ?x)) (<= (b ?x) (c ?x ?_v0)) (<= terminal (a 1))";
+    let inlined = inline_relations(&parse(gdl));
+    assert_eq!(inlined.to_string(),
+               "(<= (p (f ?a) ?b) (q ?a ?c) (r ?c ?b)) \
+                (<= (s (f ?_v1)) (q ?_v1 ?_v3) (r ?_v3 1) (t (f ?_v1))) \
+                (<= s3 (not (p 1 2)) (q ?_v7 ?_v9) (r ?_v9 (f ?_v7))) \
+                (<= terminal (c 1 ?_v14))");
+    match inlined.clauses[1] {
+        RuleClause(ref r) => {
+            let spans = r.body.iter().map(|l| l.span().lo).collect::<Vec<_>>();
+            assert_eq!((r.span.lo, spans), (39, vec![18, 28, 59]));
+        }
+        _ => panic!("Expected a rule")
+    }
+
+    // Recursive relations, relations with several rules, and keywords aren't inlined
+    let gdl = "(<= (lt ?x ?y) (succ ?x ?y)) (<= (lt ?x ?y) (succ ?x ?z) (lt ?z ?y))\n\
+               (<= (r ?x) (r2 ?x)) (<= (r2 ?x) (r ?x)) (<= (legal ?r noop) (role ?r))\n\
+               (<= goal (lt 1 2) (r 1) (legal white noop))";
+    let desc = parse(gdl);
+    assert_eq!(inline_relations(&desc), desc);
+}
+
+#[test]
+fn test_variable_generator() {
+    let desc = parse("(<= (p ?_v0 ?x) (q ?_v2))");
+    let mut generator = VariableGenerator::for_description(&desc);
+    generator.reserve("_v3");
+    let names = (0..3).map(|_| generator.fresh().to_string()).collect::<Vec<_>>();
+    assert_eq!(names, ["?_v1", "?_v4", "?_v5"]);
+}
+
+#[test]
+fn test_standardize_apart() {
+    let desc = parse("(<= (p ?x ?y) (q ?y ?x)) (<= (r ?x) (p ?x ?_v0)) (s ?z)");
+    assert_eq!(standardize_apart(&desc).to_string(),
+               "(<= (p ?_v1 ?_v2) (q ?_v2 ?_v1)) (<= (r ?_v3) (p ?_v3 ?_v4)) (s ?_v5)");
+
+    let rule = match desc.clauses[0] {
+        RuleClause(ref r) => r,
+        ref c => panic!("Expected a rule, found {}", c)
+    };
+    let mut generator = VariableGenerator::new();
+    assert_eq!(rename_apart(rule, &mut generator).to_string(), "(<= (p ?_v0 ?_v1) (q ?_v1 ?_v0))");
+    assert_eq!(rename_apart(rule, &mut generator).to_string(), "(<= (p ?_v2 ?_v3) (q ?_v3 ?_v2))");
+}
+
+#[test]
+fn test_rename_canonically() {
+    let gdl = "(<= (p ?x ?v0) (q ?v0 ?y) (not (r ?x))) (<= (p ?a ?b) (q ?b ?c) (not (r ?a)))\n\
+               (f ?z) (<= (legal ?r noop) (role ?r))";
+    let desc = canonicalize_variables(&parse(gdl));
+    assert_eq!(desc.to_string(),
+               "(<= (p ?v0 ?v1) (q ?v1 ?v2) (not (r ?v0))) \
+                (<= (p ?v0 ?v1) (q ?v1 ?v2) (not (r ?v0))) (f ?v0) \
+                (<= (legal ?v0 noop) (role ?v0))");
+    assert_eq!(desc.clauses[0], desc.clauses[1]);
+    let span = desc.clauses[1].span();
+    assert_eq!((span.lo, span.hi), (40, 77));
+
+    match desc.clauses[3] {
+        RuleClause(ref r) => assert_eq!(rename_canonically(r), *r),
+        _ => panic!("Expected a rule")
+    }
+}
+
+#[test]
+fn test_deor() {
+    let desc = parse("(<= (p ?x) (or (q ?x) (r ?x)) (or (s ?x) (not (not (t ?x)))))\n\
+                      (<= w (not (or a (not (or b c))))) (<= z (not y)) (fact 1)");
+    assert_eq!(deor(&desc).to_string(),
+               "(<= (p ?x) (q ?x) (s ?x)) (<= (p ?x) (q ?x) (t ?x)) (<= (p ?x) (r ?x) (s ?x)) \
+                (<= (p ?x) (r ?x) (t ?x)) (<= w (not a) b) (<= w (not a) c) (<= z (not y)) \
+                (fact 1)");
+    let rule = &deor(&desc).clauses[0];
+    assert_eq!((rule.span().lo, rule.span().hi), (0, 61));
+
+    let desc = parse("(<= p (q ?x) (not (r ?x)))");
+    assert_eq!(deor(&desc), desc);
+}
+
+#[test]
+fn test_flatten_ors() {
+    let gdl = "(<= p (or (or a (q ?x)) a (or (r ?x) (or (q ?x) (s ?x)))) (not (or b)))\n\
+               (<= q (or (not (or c (or d))) (or c)) (or e)) (r 1)";
+    let flattened = flatten_ors(&parse(gdl));
+    assert_eq!(flattened.to_string(),
+               "(<= p (or a (q ?x) (r ?x) (s ?x)) (not b)) (<= q (or (not (or c d)) c) e) (r 1)");
+    match flattened.clauses[1] {
+        RuleClause(ref r) => {
+            let spans = r.body.iter().map(|l| (l.span().lo, l.span().hi)).collect::<Vec<_>>();
+            assert_eq!(spans, [(78, 109), (114, 115)]);
+        }
+        _ => panic!("Expected a rule")
+    }
+
+    let desc = parse("(<= p (or a b) (not c))");
+    assert_eq!(flatten_ors(&desc), desc);
+}
+
+#[test]
+fn test_negation_normal_form() {
+    let desc = parse("(<= (p ?x) (q ?x) (not (or (r ?x) (not (s ?x)) (distinct ?x 1))))\n\
+                      (<= w (or a (not (or b c)))) (<= z (or (or x y) (not (not v)))) (fact 1)");
+    assert_eq!(negation_normal_form(&desc).to_string(),
+               "(<= (p ?x) (q ?x) (not (r ?x)) (s ?x) (not (distinct ?x 1))) \
+                (<= w (or a (not b)) (or a (not c))) (<= z (or x y v)) (fact 1)");
+
+    let desc = parse("(<= p (q ?x) (not (r ?x)) (or (s ?x) (t ?x)))");
+    assert_eq!(negation_normal_form(&desc), desc);
+}
+
+#[test]
+fn test_evaluate_static() {
+    let gdl = "(role white) (index 1) (index 2) (succ 1 2) (succ 2 3)\n\
+               (<= (lt ?x ?y) (succ ?x ?y)) (<= (lt ?x ?z) (succ ?x ?y) (lt ?y ?z))\n\
+               (<= (init (cell ?x)) (index ?x))\n\
+               (<= (next (step ?y)) (true (step ?x)) (succ ?x ?y))\n\
+               (<= (legal white (mark ?x)) (true (cell ?x)) (not (lt ?x 2)))\n\
+               (<= terminal (or (lt 3 1) (true (step 3))) (distinct 1 2))";
+    let evaluated = evaluate_static(&parse(gdl));
+    assert_eq!(evaluated.to_string(),
+               "(role white) (lt 1 2) (lt 2 3) (lt 1 3) (init (cell 1)) (init (cell 2)) \
+                (<= (next (step 2)) (true (step 1))) (<= (next (step 3)) (true (step 2))) \
+                (<= (legal white (mark ?x)) (true (cell ?x)) (not (lt ?x 2))) \
+                (<= terminal (true (step 3)))");
+    let spans = evaluated.clauses.iter().map(|c| c.span().lo).collect::<Vec<_>>();
+    assert_eq!(spans, vec![0, 55, 55, 84, 124, 124, 157, 157, 209, 271]);
+
+    // Relations that build unbounded terms can't be evaluated
+    let desc = parse("(num 0) (<= (num (s ?x)) (num ?x)) (<= (ok ?x) (num ?x))\n\
+                      (<= terminal (true (n ?x)) (ok ?x))");
+    assert_eq!(evaluate_static(&desc), desc);
+
+    let desc = parse("(<= p (not q)) (<= q (not p))");
+    assert_eq!(evaluate_static(&desc), desc);
+
+    // Only one literal of a rule is inlined for more than one fact, so the number of instances
+    // doesn't multiply
+    let gdl = "(d 1) (d 2) (e 1) (e 2) (<= (next (p ?x ?y)) (true q) (d ?x) (e ?y))";
+    assert_eq!(evaluate_static(&parse(gdl)).to_string(),
+               "(e 1) (e 2) (<= (next (p 1 ?y)) (true q) (e ?y)) \
+                (<= (next (p 2 ?y)) (true q) (e ?y))");
+
+    let mut gdl = String::new();
+    let f = File::open("tests/test-alquerque.gdl");
+    f.unwrap().read_to_string(&mut gdl).ok().expect("Unable to read GDL file");
+    assert!(evaluate_static(&parse(&gdl)).clauses.len() < 3000);
+}
+
+#[test]
+fn test_condense() {
+    let gdl = "(<= (p ?x ?y) (q ?x) (r ?x ?z) (r ?x 1) (not (distinct ?y ?x)) (q ?x))\n\
+               (<= (p ?x) (q ?x ?y) (q ?x ?z)) (<= (p ?x) (q ?x ?y) (q ?y ?x))\n\
+               (<= (p ?x) (q (f ?a) ?x) (q (f 1) ?x) (not (distinct 2 ?a)))\n\
+               (<= (p ?x) (not (distinct ?x (f ?x))) (q ?x)) (q 1 2)";
+    let condensed = condense(&parse(gdl));
+    assert_eq!(condensed.to_string(),
+               "(<= (p ?x ?x) (q ?x) (r ?x 1)) (<= (p ?x) (q ?x ?z)) \
+                (<= (p ?x) (q ?x ?y) (q ?y ?x)) (<= (p ?x) (q (f 2) ?x) (q (f 1) ?x)) \
+                (<= (p ?x) (not (distinct ?x (f ?x))) (q ?x)) (q 1 2)");
+    match condensed.clauses[0] {
+        RuleClause(ref r) => assert_eq!((r.span.lo, r.body[1].span().lo), (0, 31)),
+        _ => panic!("Expected a rule")
+    }
+}
+
+#[test]
+fn test_sort_by_dependencies() {
+    let gdl = "(<= (next (cell ?x)) (does ?r (mark ?x)) (not (occupied ?x)))\n\
+               (<= (lt ?x ?y) (succ ?x ?y)) (<= (lt ?x ?z) (succ ?x ?y) (lt ?y ?z))\n\
+               (<= (occupied ?x) (true (cell ?x))) (succ 1 2) (succ 2 3) (role x)\n\
+               (<= (legal ?r (mark ?x)) (role ?r) (not (occupied ?x)))";
+    let desc = parse(gdl);
+    let sorted = sort_by_dependencies(&desc);
+    assert_eq!(sorted.to_string(),
+               "(<= (occupied ?x) (true (cell ?x))) (succ 1 2) (succ 2 3) \
+                (<= (lt ?x ?y) (succ ?x ?y)) (<= (lt ?x ?z) (succ ?x ?y) (lt ?y ?z)) (role x) \
+                (<= (next (cell ?x)) (does ?r (mark ?x)) (not (occupied ?x))) \
+                (<= (legal ?r (mark ?x)) (role ?r) (not (occupied ?x)))");
+    let span = sorted.clauses[1].span();
+    assert_eq!((span.lo, span.hi), (167, 177));
+    assert_eq!(sort_by_dependencies(&sorted), sorted);
+
+    let desc = parse("(<= p (not q)) (<= q p)");
+    assert_eq!(sort_by_dependencies(&desc), desc);
+}
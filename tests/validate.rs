@@ -0,0 +1,238 @@
+extern crate gdl_parser;
+
+use gdl_parser::{check_recursion, check_roles, check_safety, parse, parse_with, validate, Constant,
+                 Description, ParseError, ParserOptions, Relation, Severity};
+use gdl_parser::Clause::SentenceClause;
+use gdl_parser::Term::ConstTerm;
+
+fn codes(desc: &Description) -> Vec<(&'static str, usize, usize)> {
+    validate(desc).diagnostics.iter().map(|d| (d.code, d.span.lo, d.span.hi)).collect()
+}
+
+#[test]
+fn test_validate_valid() {
+    let gdl = "(role white) (init (cell 1 b)) (succ 1 2)\n\
+               (<= (legal ?r noop) (role ?r) (not (true (control ?r))))\n\
+               (<= (next (cell ?y b)) (true (cell ?x b)) (succ ?x ?y) (distinct ?x ?y))\n\
+               (<= (reach ?x ?y) (succ ?x ?y))\n\
+               (<= (reach ?x ?z) (succ ?x ?y) (reach ?y ?z))";
+    let report = validate(&parse(gdl));
+    assert!(report.is_valid());
+    assert_eq!(report.diagnostics, vec![]);
+    assert_eq!(report.to_string(), "");
+}
+
+#[test]
+fn test_validate_safety() {
+    let gdl = "(<= (p ?x ?y) (q ?x) (not (r ?z)) (distinct ?w ?x))";
+    let report = validate(&parse(gdl));
+    let errors = report.errors().iter().map(|d| (d.code, d.span.lo, d.span.hi))
+                       .collect::<Vec<_>>();
+    assert_eq!(errors, vec![("unsafe-variable", 10, 12), ("unsafe-variable", 29, 31),
+                            ("unsafe-variable", 44, 46)]);
+    assert_eq!(report.errors()[0].message,
+               "variable `?y` in the rule for `p` doesn't appear in a positive literal in its \
+                body");
+
+    // Positive literals in an `or` bind variables too, but only if every disjunct has them
+    assert!(validate(&parse("(<= (p ?x) (or (q ?x) (r ?x)))")).is_valid());
+    let report = validate(&parse("(<= (p ?x ?y) (or (q ?x ?y) (r ?x)))"));
+    let errors = report.errors().iter().map(|d| (d.code, d.span.lo, d.span.hi))
+                       .collect::<Vec<_>>();
+    assert_eq!(errors, vec![("unsafe-variable", 10, 12)]);
+}
+
+#[test]
+fn test_validate_stratification() {
+    let gdl = "(<= p (not q)) (<= q (r ?x) (not (s ?x))) (<= (s ?x) (r ?x) p)";
+    let report = validate(&parse(gdl));
+    assert_eq!(codes(&parse(gdl)), vec![("unstratified", 11, 12), ("unstratified", 33, 39)]);
+    assert_eq!(report.errors()[0].message,
+               "`p` depends on the negation of `q`, which depends on `p`");
+
+    assert_eq!(codes(&parse("(<= p (not p))")), vec![("unstratified", 11, 12)]);
+    assert!(validate(&parse("(<= p (not (not p)))")).is_valid());
+    assert!(validate(&parse("(<= p (not q)) (<= q r)")).is_valid());
+}
+
+#[test]
+fn test_validate_recursion_restriction() {
+    let gdl = "(<= (reach ?x ?y) (succ ?x ?y))\n\
+               (<= (reach ?x ?z) (reach ?x ?y) (reach ?y ?z))";
+    assert_eq!(codes(&parse(gdl)), vec![("recursion-restriction", 60, 62),
+                                        ("recursion-restriction", 71, 73)]);
+
+    let gdl = "(<= (p ?x) (q ?x)) (<= (p ?x) (q ?x) (p (f ?y)) (r ?y))";
+    assert_eq!(codes(&parse(gdl)), vec![("recursion-restriction", 40, 46)]);
+    assert_eq!(validate(&parse(gdl)).errors()[0].message,
+               "argument 1 of `p`, `(f ?y)`, must be ground, an argument of the head, or a \
+                variable from a literal that isn't recursive, since `p` is recursive with `p`");
+}
+
+#[test]
+fn test_check_recursion() {
+    let gdl = "(<= (num 0))
+(<= (num (s ?x)) (num ?x))
+(<= (big ?x) (num ?x) (big (s ?x)))";
+    assert!(check_recursion(&parse("(<= (num 0)) (<= (num ?y) (succ ?x ?y) (num ?x))")).is_ok());
+    assert!(check_recursion(&parse("(<= (p ?x) (q ?x) (p ?x))")).is_ok());
+    let e = check_recursion(&parse(gdl)).unwrap_err();
+    match e {
+        ParseError::RecursionRestriction { span, ref name, position, rule } => {
+            assert_eq!((span.lo, span.hi, &**name, position), (35, 37, "num", 0));
+            assert_eq!((rule.lo, rule.hi), (13, 39));
+        }
+        ref e => panic!("Unexpected error {:?}", e)
+    }
+    assert_eq!(e.to_string(), "argument 1 of recursive `num` at byte 35 breaks the recursion \
+                               restriction in the rule at byte 13");
+    assert_eq!(codes(&parse(gdl)), vec![("recursion-restriction", 35, 37),
+                                        ("recursion-restriction", 67, 73)]);
+
+    let options = ParserOptions { strict_recursion: true, ..ParserOptions::default() };
+    assert!(parse_with(gdl, &options).is_err());
+    assert!(parse_with(gdl, &ParserOptions::new()).is_ok());
+}
+
+#[test]
+fn test_validate_arity_and_unknown() {
+    let role = Relation::new("role", vec![ConstTerm(Constant::new("black")),
+                                          ConstTerm(Constant::new("white"))]);
+    let desc = Description::new(vec![SentenceClause(role.into())]);
+    let report = validate(&desc);
+    assert_eq!(report.errors()[0].code, "invalid-arity");
+    assert_eq!(report.errors()[0].message, "`role` takes 1 arguments but has 2");
+
+    let options = ParserOptions { keep_unknown: true, ..ParserOptions::new() };
+    let desc = parse_with("(role white)\n(<= p (not))", &options).unwrap();
+    assert_eq!(codes(&desc), vec![("invalid-clause", 13, 25)]);
+}
+
+#[test]
+fn test_validate_warnings() {
+    let report = validate(&parse("(<= (p ?x) (q ?x ?y)) (<= r (not s) (t ?z))"));
+    assert!(report.is_valid());
+    assert_eq!(report.warnings().len(), 2);
+    assert!(report.diagnostics.iter().all(|d| d.severity == Severity::Warning));
+    assert_eq!(report.to_string().lines().count(), 2);
+}
+
+#[test]
+fn test_check_safety() {
+    let gdl = "(role white)\n(<= (legal ?r (mark ?x)) (role ?r) (not (taken ?y)))";
+    let e = check_safety(&parse(gdl)).unwrap_err();
+    match e {
+        ParseError::UnsafeVariable { span, ref name, rule } => {
+            assert_eq!((span.lo, span.hi, &**name), (33, 35, "x"));
+            assert_eq!((rule.lo, rule.hi), (13, 65));
+        }
+        ref e => panic!("Unexpected error {:?}", e)
+    }
+    assert_eq!(e.to_string(), "unsafe variable `?x` at byte 33 in the rule at byte 13");
+    assert_eq!(e.code(), "unsafe-variable");
+
+    // Facts can't have variables either
+    assert!(check_safety(&parse("(succ ?x 1)")).is_err());
+    assert!(check_safety(&parse("(<= (p ?x) (q ?x) (not (r ?x)) (distinct ?x a))")).is_ok());
+    assert!(check_safety(&parse("(<= (p ?x) (or (q ?x) r))")).is_err());
+    assert!(check_safety(&parse("(<= (p ?x) (or (q ?x) (s ?x)) (not (or (r ?x) t)))")).is_ok());
+
+    let options = ParserOptions { strict_safety: true, ..ParserOptions::default() };
+    match parse_with("(<= (p ?x) (distinct ?x a))", &options) {
+        Err(ParseError::UnsafeVariable { ref name, .. }) => assert_eq!(name, "x"),
+        r => panic!("Unexpected result {:?}", r)
+    }
+    assert!(parse_with("(<= (p ?x) (distinct ?x a))", &ParserOptions::new()).is_ok());
+}
+
+#[test]
+fn test_check_roles() {
+    let roles = |gdl| {
+        check_roles(&parse(gdl)).iter().map(|d| (d.code, d.span.lo, d.span.hi)).collect::<Vec<_>>()
+    };
+    let gdl = "(role white) (role black) (role white)\n\
+               (<= (legal ?r noop) (role ?r)) (goal white 100) terminal";
+    assert_eq!(roles(gdl), vec![("missing-goal", 13, 25)]);
+    assert_eq!(check_roles(&parse(gdl))[0].message, "role `black` doesn't have any goals");
+
+    let gdl = "(role white) (role ?r) (<= (role black) p) (legal (f) noop)";
+    assert_eq!(roles(gdl), vec![("missing-legal", 0, 12), ("missing-goal", 0, 12),
+                                ("missing-terminal", 0, 0), ("invalid-role", 13, 22),
+                                ("invalid-role", 23, 42)]);
+    assert_eq!(check_roles(&parse(gdl))[3].message,
+               "`role` must be defined by facts like `(role white)`");
+
+    assert_eq!(roles("terminal"), vec![("no-roles", 0, 0)]);
+}
+
+#[test]
+fn test_validate_keywords() {
+    let gdl = "(<= (init (cell 1)) (next (cell 1)))\n\
+               (<= (true p) (q ?x) (does ?x a))";
+    assert_eq!(codes(&parse(gdl)), vec![("keyword-position", 20, 35),
+                                        ("keyword-dependency", 20, 35),
+                                        ("keyword-redefinition", 41, 49)]);
+    assert_eq!(validate(&parse(gdl)).errors()[0].message,
+               "`next` can only be in the head of a rule");
+
+    let gdl = "(<= (moved ?r) (does ?r a))\n\
+               (<= (legal ?r noop) (role ?r) (not (moved ?r)))\n\
+               (<= terminal (does white a))";
+    assert_eq!(codes(&parse(gdl)), vec![("keyword-dependency", 63, 73),
+                                        ("keyword-dependency", 89, 103)]);
+    let report = validate(&parse(gdl));
+    assert_eq!(report.errors()[0].message,
+               "`legal` can't depend on `does`, but `moved` does through moved -> does");
+    assert_eq!(report.errors()[1].message, "`terminal` can't depend on `does`");
+}
+
+#[test]
+fn test_validate_keyword_redefinition() {
+    let gdl = "(<= (distinct ?x ?y) (p ?x) (p ?y)) (does white a) (or a b)";
+    assert_eq!(codes(&parse(gdl)), vec![("keyword-redefinition", 4, 20),
+                                        ("keyword-redefinition", 36, 50),
+                                        ("keyword-redefinition", 51, 59)]);
+    assert_eq!(validate(&parse(gdl)).errors()[0].message,
+               "`distinct` is defined by GDL, so the game can't define it too");
+}
+
+#[test]
+fn test_validate_gdl_ii() {
+    let gdl = "(role random) (role player) (card 1)\n\
+               (<= (sees player (card ?c)) (does random (deal ?c)))\n\
+               (<= (legal random (deal ?c)) (card ?c) (not (true (dealt ?c))))";
+    assert!(validate(&parse(gdl)).is_valid());
+
+    let gdl = "(role player) (<= (legal random (deal ?c)) (card ?c))\n\
+               (<= (sees dealer (card ?c)) (card ?c))\n\
+               (<= (heard ?x) (sees player ?x))\n\
+               (<= (sees player ?x) (heard ?x))";
+    assert_eq!(codes(&parse(gdl)), vec![("undeclared-random", 25, 31),
+                                        ("sees-role", 64, 70),
+                                        ("percept-dependency", 147, 157)]);
+    let errors = validate(&parse(gdl)).errors().iter().map(|d| d.message.clone())
+                                      .collect::<Vec<_>>();
+    assert_eq!(errors, ["`random` is used as a role, but `(role random)` isn't declared",
+                        "`sees` is for `dealer`, which isn't a role",
+                        "percepts can't depend on `sees`, but `heard` does through heard -> sees"]);
+
+    // Without `sees` or `random` the game isn't GDL-II
+    assert!(validate(&parse("(<= (legal dealer noop) (card ?c))")).is_valid());
+}
+
+#[test]
+fn test_check_roles_dead_rules() {
+    let gdl = "(role white) (role black) (<= (legal ?r noop) (role ?r) (ready ?r))\n\
+               (<= (goal white 100) (true done)) (goal black 0) (<= terminal (distinct a a))";
+    let roles = check_roles(&parse(gdl));
+    let found = roles.iter().map(|d| (d.code, d.span.lo, &d.message[..])).collect::<Vec<_>>();
+    assert_eq!(found,
+               [("missing-legal", 0, "role `white` doesn't have any legal moves, since none of \
+                                      its rules can ever apply"),
+                ("missing-goal", 0, "role `white` doesn't have any goals, since none of its \
+                                     rules can ever apply"),
+                ("missing-legal", 13, "role `black` doesn't have any legal moves, since none of \
+                                       its rules can ever apply"),
+                ("missing-terminal", 117, "`terminal` can never be true, since none of its rules \
+                                           can ever apply, so the game never ends")]);
+}
@@ -0,0 +1,312 @@
+extern crate gdl_parser;
+
+use gdl_parser::{parse, parse_literal, parse_rule, parse_term, Constant, Literal, Not, Relation,
+                 Rule, Term, Variable};
+use gdl_parser::visitor::{self, Flow, Folder, NodeKind, Path, Step, Visit, Visitor};
+use gdl_parser::Clause::RuleClause;
+use gdl_parser::Literal::{NotLit, RelLit};
+
+#[test]
+fn test_map_constants() {
+    let gdl = "(ROLE White) (<= (Legal ?Role (Mark ?X)) (role ?Role) (not (Cell ?X B))) Terminal";
+    let desc = parse(gdl);
+    let mut seen = Vec::new();
+    let lowercased = desc.map_constants(|c| {
+        seen.push(c.name.clone());
+        Constant::new(c.name.to_lowercase()).with_span(c.span)
+    });
+    assert_eq!(lowercased.to_string(),
+               "(role white) (<= (legal ?Role (mark ?X)) (role ?Role) (not (cell ?X b))) terminal");
+    assert_eq!(seen, ["White", "ROLE", "Mark", "Legal", "role", "B", "Cell", "Terminal"]);
+    assert_eq!(lowercased.clauses[0].span(), desc.clauses[0].span());
+    assert_eq!(parse("(<= p (q ?x))").map_constants(|c| c.clone()), parse("(<= p (q ?x))"));
+
+    let renamed = desc.map_variables(|v| {
+        Variable::new(Constant::new(format!("v_{}", v.name.name))).with_span(v.span)
+    });
+    assert_eq!(renamed.to_string(),
+               "(ROLE White) (<= (Legal ?v_Role (Mark ?v_X)) (role ?v_Role) (not (Cell ?v_X B))) \
+                Terminal");
+}
+
+// Records the names of the relations and variables it's shown, in order
+#[derive(Default)]
+struct Names {
+    names: Vec<String>
+}
+
+impl Visit for Names {
+    fn visit_relation(&mut self, r: &Relation) {
+        self.names.push(r.name.name.clone());
+    }
+
+    fn visit_variable(&mut self, v: &Variable) {
+        self.names.push(format!("?{}", v.name.name));
+    }
+}
+
+impl Visitor for Names {
+    fn visit_relation(&mut self, r: &mut Relation) {
+        Visit::visit_relation(self, r);
+    }
+
+    fn visit_variable(&mut self, v: &mut Variable) {
+        Visit::visit_variable(self, v);
+    }
+}
+
+#[test]
+fn test_walk() {
+    let desc = parse("(role white) (<= (legal ?r (mark ?x)) (role ?r) (or p (not (cell ?x b))))");
+    let mut walked = Names::default();
+    visitor::walk(&desc, &mut walked);
+    assert_eq!(walked.names, ["role", "?r", "?x", "legal", "?r", "role", "?x", "cell"]);
+
+    let mut visited = Names::default();
+    visitor::visit(&mut desc.clone(), &mut visited);
+    assert_eq!(visited.names, walked.names);
+
+    let mut walked = Names::default();
+    visitor::walk_term(&parse_term("(f ?y (g ?z))").unwrap(), &mut walked);
+    assert_eq!(walked.names, ["?y", "?z"]);
+}
+
+// Increments numbers and negates `q` literals
+struct Increment;
+
+impl Folder for Increment {
+    fn fold_constant(&mut self, c: Constant) -> Constant {
+        match c.name.parse::<u32>() {
+            Ok(n) => Constant::new((n + 1).to_string()).with_span(c.span),
+            Err(_) => c
+        }
+    }
+
+    fn fold_literal(&mut self, l: Literal) -> Literal {
+        match l {
+            RelLit(r) if r.name.name == "q" => Not::new(Box::new(RelLit(r))).into(),
+            l => l
+        }
+    }
+}
+
+#[test]
+fn test_fold() {
+    let desc = parse("(succ 1 2) (<= (p ?x) (q ?x 3) (not (q (f 4) ?x)))");
+    let folded = visitor::fold(desc.clone(), &mut Increment);
+    assert_eq!(folded.to_string(),
+               "(succ 2 3) (<= (p ?x) (not (q ?x 4)) (not (not (q (f 5) ?x))))");
+    let span = folded.clauses[1].span();
+    assert_eq!((span.lo, span.hi), (11, 50));
+    match folded.clauses[1] {
+        RuleClause(ref r) => match r.body[1] {
+            NotLit(ref n) => assert_eq!((n.span.lo, n.span.hi), (31, 49)),
+            ref l => panic!("unexpected literal {}", l)
+        },
+        ref c => panic!("unexpected clause {}", c)
+    }
+
+    struct Identity;
+    impl Folder for Identity {}
+    assert_eq!(visitor::fold(desc.clone(), &mut Identity), desc);
+    assert_eq!(visitor::fold_term(parse_term("(f 1 ?x)").unwrap(), &mut Increment),
+               parse_term("(f 2 ?x)").unwrap());
+}
+
+// Records relations and variables like `Names`, but leaves out what's under `not` and stops at
+// the first `does`
+#[derive(Default)]
+struct Positive {
+    names: Names
+}
+
+impl Visit for Positive {
+    fn enter_not(&mut self, _: &Not) -> Flow {
+        Flow::Skip
+    }
+
+    fn enter_relation(&mut self, r: &Relation) -> Flow {
+        if r.name.name == "does" { Flow::Stop } else { Flow::Continue }
+    }
+
+    fn visit_relation(&mut self, r: &Relation) {
+        Visit::visit_relation(&mut self.names, r);
+    }
+
+    fn visit_variable(&mut self, v: &Variable) {
+        Visit::visit_variable(&mut self.names, v);
+    }
+}
+
+#[test]
+fn test_walk_flow() {
+    let desc = parse("(<= (p ?x) (not (q ?x)) (r ?y) (or (not s) (t ?z)))\n\
+                      (<= u (does ?r v) (w ?r))");
+    let mut walked = Positive::default();
+    assert_eq!(visitor::walk(&desc, &mut walked), Flow::Stop);
+    assert_eq!(walked.names.names, ["?x", "p", "?y", "r", "?z", "t"]);
+
+    let mut walked = Positive::default();
+    assert_eq!(visitor::walk_clause(&desc.clauses[0], &mut walked), Flow::Continue);
+    assert_eq!(walked.names.names.len(), 6);
+    let mut walked = Positive::default();
+    let not = parse_literal("(not (q ?x))").unwrap();
+    assert_eq!(visitor::walk_literal(&not, &mut walked), Flow::Continue);
+    assert!(walked.names.names.is_empty());
+}
+
+// Prefixes each variable with the name of the rule it's in, leaving out the ones under `not` and
+// stopping at a rule for `terminal`
+#[derive(Default)]
+struct Scope {
+    rule: Option<String>,
+    order: Vec<String>
+}
+
+impl Visitor for Scope {
+    fn enter_rule(&mut self, r: &mut Rule) -> Flow {
+        let name = r.head.name().name.clone();
+        self.order.push(format!("enter {}", name));
+        if name == "terminal" {
+            return Flow::Stop;
+        }
+        self.rule = Some(name);
+        Flow::Continue
+    }
+
+    fn enter_not(&mut self, _: &mut Not) -> Flow {
+        Flow::Skip
+    }
+
+    fn visit_rule(&mut self, r: &mut Rule) {
+        self.order.push(format!("exit {}", r.head.name().name));
+        self.rule = None;
+    }
+
+    fn visit_variable(&mut self, v: &mut Variable) {
+        if let Some(ref rule) = self.rule {
+            v.name.name = format!("{}_{}", rule, v.name.name);
+        }
+    }
+}
+
+#[test]
+fn test_visit_enter() {
+    let mut desc = parse("(<= (p ?x) (q ?x) (not (r ?x))) (s ?y) (<= (t ?x) (q ?x))\n\
+                          (<= terminal (q ?x)) (<= (u ?x) (q ?x))");
+    let mut scope = Scope::default();
+    assert_eq!(visitor::visit(&mut desc, &mut scope), Flow::Stop);
+    assert_eq!(desc.to_string(),
+               "(<= (p ?p_x) (q ?p_x) (not (r ?x))) (s ?y) (<= (t ?t_x) (q ?t_x)) \
+                (<= terminal (q ?x)) (<= (u ?x) (q ?x))");
+    assert_eq!(scope.order, ["enter p", "exit p", "enter t", "exit t", "enter terminal"]);
+
+    let mut rule = parse_rule("(<= (p ?x) (q ?x))").unwrap();
+    assert_eq!(visitor::visit_rule(&mut rule, &mut Scope::default()), Flow::Continue);
+    assert_eq!(rule.to_string(), "(<= (p ?p_x) (q ?p_x))");
+}
+
+// Describes where each relation and constant is
+#[derive(Default)]
+struct Places {
+    path: Path,
+    places: Vec<String>
+}
+
+impl Places {
+    fn place(&self, name: &str) -> String {
+        let place = if self.path.in_rule_head() {
+            "head"
+        } else if self.path.is_within(NodeKind::Not) {
+            "negated"
+        } else if self.path.in_rule_body() {
+            "body"
+        } else {
+            "fact"
+        };
+        let depth = self.path.steps().iter().filter(|s| s.kind == NodeKind::Function).count();
+        format!("{} {} {}", name, place, depth)
+    }
+}
+
+impl Visit for Places {
+    fn path_mut(&mut self) -> Option<&mut Path> {
+        Some(&mut self.path)
+    }
+
+    fn visit_relation(&mut self, r: &Relation) {
+        let place = self.place(&r.name.name);
+        self.places.push(place);
+    }
+
+    fn visit_term(&mut self, t: &Term) {
+        if let &Term::ConstTerm(ref c) = t {
+            let place = self.place(&c.name);
+            self.places.push(place);
+        }
+    }
+}
+
+impl Visitor for Places {
+    fn path_mut(&mut self) -> Option<&mut Path> {
+        Some(&mut self.path)
+    }
+
+    fn visit_constant(&mut self, c: &mut Constant) {
+        if self.path.parent() == Some(&Step { kind: NodeKind::Relation, index: 0 }) {
+            c.name = c.name.to_uppercase();
+        }
+    }
+}
+
+#[test]
+fn test_walk_path() {
+    let desc = parse("(succ 1 2) (<= (next (cell (f a))) (true (cell b)) (not (or c (g (h d)))))");
+    let mut places = Places::default();
+    visitor::walk(&desc, &mut places);
+    assert_eq!(places.places,
+               ["1 fact 0", "2 fact 0", "succ fact 0", "a head 2", "next head 0", "b body 1",
+                "true body 0", "d negated 1", "g negated 0"]);
+    assert!(places.path.steps().is_empty());
+
+    let mut places = Places::default();
+    let clause = &desc.clauses[1];
+    visitor::walk_clause(clause, &mut places);
+    assert_eq!(places.places.len(), 6);
+    let mut places = Places::default();
+    visitor::walk(&parse("(<= p (distinct ?x (f a)))"), &mut places);
+    assert_eq!(places.places, ["a body 1"]);
+
+    let mut desc = desc;
+    let mut places = Places::default();
+    visitor::visit(&mut desc, &mut places);
+    assert_eq!(desc.to_string(),
+               "(SUCC 1 2) (<= (NEXT (cell (f a))) (TRUE (cell b)) (not (or c (G (h d)))))");
+    assert!(places.path.steps().is_empty());
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+}